@@ -100,4 +100,8 @@ pub enum Error {
     RecoveryNotInitiated = 73,
     /// Stream amount or flow rate exceeds maximum allowed limit
     AmountOverflow = 78,
+    /// Stream position is soulbound and cannot be transferred or approved
+    StreamNotTransferable = 79,
+    /// Stream is not insolvent and cannot be settled
+    StreamNotInsolvent = 80,
 }
@@ -1,6 +1,6 @@
 #![no_std]
 #![allow(clippy::too_many_arguments)]
-use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::xdr::{FromXdr, ToXdr};
 use soroban_sdk::{
     contract, contractimpl, symbol_short, Address, Bytes, Env, IntoVal, Symbol, Vec,
 };
@@ -13,24 +13,41 @@ mod v1_interface;
 
 use contracterror::Error;
 pub use types::{
-    AdminTransferredEvent, BatchStreamsCreatedEvent, BeneficiaryTransferredV2Event, BpsRecipient,
+    AdminTransferredEvent, AllowanceFunding, AllowanceStreamArgs, BatchStreamsCreatedEvent,
+    BeneficiaryTransferredV2Event, BpsRecipient, BridgeDeposit, BridgeReceipt, ClassicAsset,
     ClawbackRebalanceEvent, ContractPausedEvent, ContractState, ContractTerminatedEvent,
-    ContractUnpausedEvent, DexPoolInfo, DustAccumulatedEvent, FeesWithdrawnEvent, LedgerFootprint,
-    MigrationEvent, MultiAssetRecipient, NebulaEvent, Operation, OperationExecutedEvent,
-    OperationScheduledEvent, PendingRateUpdate, PermitArgs, PermitPayload,
-    PermitStreamCreatedEvent, ProtocolHealthV2, Recipient, SignatureStreamCreatedEvent,
-    SimulationCheck, SimulationReport, SimulationResult, SplitExecutedEvent, StreamArgs,
-    StreamBatchEntry, StreamCancelledV2Event, StreamClaimV2Event, StreamCreatedV2Event,
-    StreamMigratedEvent, StreamParams, StreamRefilledEvent, StreamRequestApprovedEvent,
-    StreamRequestExecutedEvent, StreamRequestInitiatedEvent, StreamSplitUpdatedEvent, StreamStatus,
-    StreamToppedUpEvent, StreamV2, SwapResult, SwapStreamArgs, SwapStreamCreatedEvent,
-    MAX_MEMO_LENGTH,
+    ContractUnpausedEvent, CurvePoint, CurveType, DexPoolInfo, DustAccumulatedEvent, FeeShare,
+    FeesWithdrawnEvent, FundingStatus, GovernanceConfig, HeldEntry, KeeperTask, LedgerFootprint,
+    MetaAction, MetaIntent, MetaWithdrawal, MigrationEvent, MultiAssetRecipient, NebulaEvent,
+    Operation, OperationExecutedEvent, OperationScheduledEvent, PendingAmendment,
+    PendingRateUpdate, PendingSplitInfo, Permit, PermitArgs, PermitDomain, PermitPayload,
+    PermitSignature, PermitStreamCreatedEvent, ProposalStatus, ProtocolHealthV2, Recipient,
+    RecoveryState, RouteHop, ScheduledOperation, SignatureStreamCreatedEvent, SimulationCheck,
+    SimulationReport, SimulationResult, SolvencyReport, SplitExecutedEvent, SplitVetoTally,
+    StreamAmendment, StreamArgs, StreamBatchEntry, StreamCancelledV2Event, StreamClaimV2Event,
+    StreamCreatedV2Event, StreamHealth, StreamMigratedEvent, StreamParams, StreamRefilledEvent,
+    StreamRequestApprovedEvent, StreamRequestExecutedEvent, StreamRequestInitiatedEvent,
+    StreamSplitUpdatedEvent, StreamStatus, StreamTemplate, StreamTemplateRef, StreamToppedUpEvent,
+    StreamV2, SwapResult, SwapRoute, SwapSchedule, SwapStreamArgs, SwapStreamCreatedEvent,
+    TokenLimits, TreasuryProposal, VaultConfig, VaultExitReport, VaultKind, VaultPosition,
+    VoteChoice, VoterLock, BRIDGE_METADATA_VERSION, MAX_CURVE_POINTS, MAX_DEX_PAIRS,
+    MAX_FEE_SHARES, MAX_MEMO_LENGTH, MAX_META_FEE_BPS, MAX_PAGE_SIZE, MAX_ROUTE_BRANCHES,
+    MAX_ROUTE_HOPS, MAX_SOLVENCY_AGE, MAX_SOLVENCY_PAGE, MAX_SWAP_ROUTES, PERMIT_MESSAGE_PREFIX,
 };
 use v1_interface::Client as V1Client;
 
 #[contract]
 pub struct Contract;
 
+/// Decoded bridge instruction metadata (see `on_token_receive`).
+struct BridgeInstruction {
+    receiver: Address,
+    duration: u64,
+    cliff: u64,
+    curve_type: CurveType,
+    memo: Option<Symbol>,
+}
+
 const CONTRACT_VERSION: u32 = 2;
 /// SHA-256 of the contract-metadata.json file, stored as raw bytes.
 /// Avoids embedding a long URL string in the WASM binary.
@@ -40,21 +57,39 @@ const CONTRACT_METADATA_HASH: [u8; 32] = [
 ];
 
 /// Gas buffer fee per `split_multi_asset` execution (1 XLM = 10_000_000 stroops).
+/// Normalized to the configured fee token's decimals.
 const GAS_FEE_PER_SPLIT_STROOPS: i128 = 10_000_000;
 
 /// Maximum protocol fee (5%) - protects users from admin abuse (Issue #415)
 pub const MAX_FEE_BPS: u32 = 500;
 
-/// Maximum stream amount (1 Billion XLM = 1,000,000,000 * 10^7 stroops)
+/// Maximum stream amount (1 Billion XLM = 1,000,000,000 * 10^7 stroops).
+/// Normalized to each token's decimals unless overridden via `Operation::SetTokenLimits`.
 pub const MAX_STREAM_AMOUNT: i128 = 1_000_000_000 * 10_000_000;
-/// Maximum stream flow rate (1 Million XLM/s = 1,000,000 * 10^7 stroops/s)
+/// Maximum stream flow rate (1 Million XLM/s = 1,000,000 * 10^7 stroops/s).
+/// Normalized to each token's decimals unless overridden via `Operation::SetTokenLimits`.
 pub const MAX_FLOW_RATE: i128 = 1_000_000 * 10_000_000;
 
+/// Average ledger close time in seconds, used to convert between the
+/// timestamp and ledger-sequence accrual clocks.
+pub const LEDGER_CLOSE_SECS: u64 = 5;
+
+/// Network write fee per KB (`fee_write_1kb`, stroops) used for rent quotes.
+/// Fee settings are not readable from a contract, so this is a conservative
+/// approximation of the mainnet value.
+const RENT_FEE_PER_1KB: i128 = 12_000;
+/// Mainnet `persistent_rent_rate_denominator`.
+const PERSISTENT_RENT_RATE_DENOMINATOR: i128 = 2_103;
+
+/// Scale of `SwapTrait::get_spot_price` quotes and of the realized slice
+/// prices reported in `swap_px` events (token_out per token_in, 7 decimals).
+const SPOT_PRICE_SCALE: i128 = math::SCALE;
+
 /// Tiered fee configuration for "Whale" discounts.
 #[soroban_sdk::contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct FeeTier {
-    pub threshold: i128, // Amount in stroops to qualify for this tier
+    pub threshold: i128, // Amount in stroops (7 decimals), normalized to the stream token
     pub fee_bps: u32,    // The fee percentage in basis points
 }
 
@@ -66,6 +101,20 @@ pub trait VaultTrait {
     fn get_accrued_interest(env: Env, principal: i128) -> i128;
 }
 
+/// Share-based (ERC-4626-style) vault: deposits mint shares whose value grows
+/// with the vault's yield.
+#[soroban_sdk::contractclient(name = "ShareVaultClient")]
+pub trait ShareVaultTrait {
+    /// Pull `assets` from `from` (pre-approved) and mint shares to it.
+    /// Returns the shares minted.
+    fn deposit(env: Env, from: Address, assets: i128) -> i128;
+    /// Burn `shares` held by `owner` and send it the underlying assets.
+    /// Returns the assets sent.
+    fn redeem(env: Env, owner: Address, shares: i128) -> i128;
+    /// Current value of `shares` in the underlying asset.
+    fn convert_to_assets(env: Env, shares: i128) -> i128;
+}
+
 /// Compliance oracle interface (Issue #412).
 /// The oracle must implement `is_allowed(addr) -> bool`.
 /// Returning `false` means the address is on the deny-list.
@@ -184,7 +233,7 @@ impl Contract {
     /// # Returns
     /// - `SimulationReport` with detailed check results
     pub fn simulate_stream_creation(env: Env, args: StreamArgs) -> SimulationReport {
-        let now = env.ledger().timestamp();
+        let now = Self::clock_now(&env, args.accrual_clock);
 
         // Check 1: Parameter validation
         let params_check = Self::simulate_validate_params(&env, &args, now);
@@ -258,6 +307,22 @@ impl Contract {
             };
         }
 
+        if Self::validate_accrual_clock(args.accrual_clock).is_err() {
+            return SimulationCheck {
+                passed: false,
+                error_code: 2, // InvalidTimeRange
+                error_message: String::from_str(env, "Unknown accrual clock"),
+            };
+        }
+
+        if Self::validate_curve(&args.curve_type, args.start_time, args.end_time).is_err() {
+            return SimulationCheck {
+                passed: false,
+                error_code: 2, // InvalidTimeRange
+                error_message: String::from_str(env, "Invalid curve schedule"),
+            };
+        }
+
         // Validate penalty
         if args.penalty_bps > 10_000 {
             return SimulationCheck {
@@ -276,7 +341,8 @@ impl Contract {
             };
         }
 
-        if args.total_amount > MAX_STREAM_AMOUNT {
+        let limits = Self::token_limits(env, &args.token);
+        if args.total_amount > limits.max_stream_amount {
             return SimulationCheck {
                 passed: false,
                 error_code: 78, // AmountOverflow
@@ -284,9 +350,9 @@ impl Contract {
             };
         }
 
-        let duration = (args.end_time - args.start_time) as i128;
+        let duration = Self::duration_secs(args.accrual_clock, args.start_time, args.end_time);
         if duration > 0 {
-            if args.total_amount > MAX_FLOW_RATE.saturating_mul(duration) {
+            if args.total_amount > limits.max_flow_rate.saturating_mul(duration) {
                 return SimulationCheck {
                     passed: false,
                     error_code: 78, // AmountOverflow
@@ -375,130 +441,432 @@ impl Contract {
         }
     }
 
-    /// Estimate the ledger footprint for creating a stream.
+    /// Footprint and rent of `create_batch_streams` for `streams`.
+    pub fn estimate_batch_footprint(env: Env, streams: Vec<StreamArgs>) -> LedgerFootprint {
+        let mut fp = Self::empty_footprint();
+        let mut fee_keys = Vec::new(&env);
+        for args in streams.iter() {
+            let fee_key = (args.token.clone(), args.affiliate.clone());
+            if !fee_keys.contains(&fee_key) {
+                Self::add_fee_footprint(&env, &mut fp, &args.token, &args.affiliate);
+                fee_keys.push_back(fee_key);
+            }
+        }
+        // The batch pulls every stream's funds in a single transfer.
+        if !streams.is_empty() {
+            Self::add_transfer_footprint(&mut fp, 2);
+            Self::add_compliance_footprint(&env, &mut fp);
+        }
+        Self::add_streams_footprint(&env, &mut fp, &streams, false);
+        // Batch summary event
+        fp.event_bytes += Self::event_size(
+            &env,
+            symbol_short!("batch"),
+            soroban_sdk::vec![&env, streams.len().into_val(&env), 0i128.into_val(&env)],
+        );
+        Self::finish_footprint(fp)
+    }
+
+    /// Footprint and rent of `create_swap_stream` for `args`.
+    pub fn estimate_swap_stream_footprint(env: Env, args: SwapStreamArgs) -> LedgerFootprint {
+        let (token, total_amount) = if args.swap_on_withdraw {
+            (args.asset_in.clone(), args.amount_in)
+        } else {
+            (args.asset_out.clone(), args.min_amount_out)
+        };
+        let stream_args = StreamArgs {
+            sender: args.sender.clone(),
+            receiver: args.receiver.clone(),
+            token: token.clone(),
+            total_amount,
+            start_time: args.start_time,
+            cliff_time: args.cliff_time,
+            end_time: args.end_time,
+            step_duration: 0,
+            multiplier_bps: 0,
+            penalty_bps: 0,
+            vault_address: args.vault_address.clone(),
+            yield_enabled: args.yield_enabled,
+            is_recurrent: false,
+            cycle_duration: 0,
+            cancellation_type: args.cancellation_type,
+            affiliate: args.affiliate.clone(),
+            memo: None,
+            yield_recipient: args.yield_recipient,
+            split_address: args.split_address.clone(),
+            split_bps: args.split_bps,
+            curve_type: CurveType::Linear,
+            is_soulbound: false,
+            accrual_clock: 0,
+        };
+
+        let mut fp = Self::empty_footprint();
+        Self::add_fee_footprint(&env, &mut fp, &token, &args.affiliate);
+        if args.swap_on_withdraw {
+            // asset_in: sender -> contract, plus the per-withdrawal swap settings.
+            Self::add_transfer_footprint(&mut fp, 2);
+            let schedule = SwapSchedule {
+                asset_out: args.asset_out.clone(),
+                slippage_tolerance_bps: args.slippage_tolerance_bps,
+                max_price_impact_bps: args.max_price_impact_bps,
+            };
+            fp.persistent_bytes += storage::swap_schedule_entry_size(
+                &env,
+                storage::get_health(&env).total_v2_streams,
+                &schedule,
+            );
+            fp.estimated_writes += 1;
+        } else {
+            // asset_in: sender -> contract -> DEX; asset_out: DEX -> contract.
+            Self::add_transfer_footprint(&mut fp, 3);
+            Self::add_transfer_footprint(&mut fp, 2);
+            // DEX contract instance and its pool state
+            fp.estimated_reads += 1;
+            fp.estimated_writes += 1;
+        }
+        Self::add_compliance_footprint(&env, &mut fp);
+        Self::add_streams_footprint(&env, &mut fp, &soroban_sdk::vec![&env, stream_args], true);
+        Self::finish_footprint(fp)
+    }
+
+    /// Footprint and rent of `withdraw` on an existing stream. Rent is an
+    /// upper bound: `withdraw` re-extends the stream TTL to `STREAM_TTL_BUMP`.
+    pub fn estimate_withdraw_footprint(env: Env, stream_id: u64) -> Result<LedgerFootprint, Error> {
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        let mut fp = Self::empty_footprint();
+        fp.instance_bytes += storage::stats_entry_size(&env);
+        fp.estimated_writes += 1;
+
+        let stream_bytes = storage::stream_entry_size(&env, stream_id, &stream);
+        fp.persistent_bytes += stream_bytes;
+        fp.estimated_writes += 1;
+        // update_stats checks both parties' UserSeen flags
+        fp.estimated_reads += 2;
+
+        let mut recipients = 1;
+        if stream.split_bps > 0 && stream.split_address.is_some() {
+            recipients += 1;
+        }
+        if stream.vault_address.is_some() {
+            // Vault instance and position, plus the interest recipient's balance
+            fp.estimated_reads += 1;
+            fp.estimated_writes += 1;
+            recipients += 1;
+        }
+        Self::add_transfer_footprint(&mut fp, recipients + 1);
+        if storage::get_compliance_oracle(&env).is_some() {
+            fp.estimated_reads += 1;
+        }
+        if storage::get_oracle_address(&env).is_some() {
+            fp.estimated_reads += 1;
+        }
+
+        fp.event_bytes += Self::event_size(
+            &env,
+            symbol_short!("claim"),
+            soroban_sdk::vec![
+                &env,
+                stream_id.into_val(&env),
+                stream.beneficiary.clone().into_val(&env),
+                stream.total_amount.into_val(&env),
+                stream.total_amount.into_val(&env),
+                u64::MAX.into_val(&env),
+            ],
+        );
+        Ok(Self::finish_footprint(fp))
+    }
+
+    /// Estimate the ledger footprint, event size and rent of `create_stream`.
     fn estimate_ledger_footprint(env: &Env, args: &StreamArgs) -> LedgerFootprint {
-        // StreamV2 struct size estimation
-        // - Address: 32 bytes each (sender, receiver, beneficiary, token) = 128 bytes
-        // - i128 values: 16 bytes each (total_amount, withdrawn_amount, etc.) = ~80 bytes
-        // - u64 timestamps: 8 bytes each = ~40 bytes
-        // - bool flags: 1 byte each = ~4 bytes
-        // - Option<Address>: 33 bytes each (discriminant + address) = ~66 bytes
-        // - u32 values: 4 bytes each = ~16 bytes
-        // Total estimated: ~350 bytes per stream
-
-        let persistent_bytes: u32 = 350;
-
-        // Instance storage (admin list, fee config, etc.)
-        // Approximately 500-1000 bytes depending on configuration
-        let instance_bytes: u32 = 800;
-
-        // Estimated operations
-        // - 2 reads: get_admin, get_fee_bps (if set)
-        // - 3 writes: set_stream, update_stats, bump_instance
-        // - 1 event emit
-        let estimated_reads: u32 = 5;
-        let estimated_writes: u32 = 4;
-
-        // Event size: ~200-300 bytes for the event data
-        let event_bytes: u32 = 250;
+        let mut fp = Self::empty_footprint();
+        Self::add_fee_footprint(env, &mut fp, &args.token, &args.affiliate);
+        Self::add_transfer_footprint(&mut fp, 2);
+        Self::add_compliance_footprint(env, &mut fp);
+        Self::add_streams_footprint(env, &mut fp, &soroban_sdk::vec![env, args.clone()], true);
+        Self::finish_footprint(fp)
+    }
 
+    fn empty_footprint() -> LedgerFootprint {
         LedgerFootprint {
-            instance_bytes,
-            persistent_bytes,
-            estimated_reads,
-            estimated_writes,
-            event_bytes,
+            instance_bytes: 0,
+            persistent_bytes: 0,
+            estimated_reads: 0,
+            estimated_writes: 0,
+            event_bytes: 0,
+            rent_ledgers: 0,
+            rent_fee: 0,
+        }
+    }
+
+    /// Contract instance entry with the stats counters and fee balances, plus
+    /// the approved affiliate's fee entry.
+    fn add_fee_footprint(
+        env: &Env,
+        fp: &mut LedgerFootprint,
+        token: &Address,
+        affiliate: &Option<Address>,
+    ) {
+        if fp.estimated_writes == 0 {
+            // The instance entry itself, rewritten once per invocation.
+            fp.estimated_writes += 1;
+            fp.instance_bytes += storage::stats_entry_size(env);
+        }
+        if storage::get_fee_bps(env) > 0 {
+            if let Some(treasury) = storage::get_treasury(env) {
+                fp.instance_bytes += storage::pending_fees_entry_size(env, &treasury, token);
+            }
+            if let Some(affiliate) = affiliate {
+                if storage::get_affiliate(env, affiliate) == Some(true) {
+                    fp.estimated_reads += 1;
+                    fp.estimated_writes += 1;
+                    fp.persistent_bytes +=
+                        storage::affiliate_fees_entry_size(env, affiliate, token);
+                }
+            }
+        }
+    }
+
+    /// One token transfer fan-out: the token contract instance is read and
+    /// `balances` balance entries (payer and payees) are written.
+    fn add_transfer_footprint(fp: &mut LedgerFootprint, balances: u32) {
+        fp.estimated_reads += 1;
+        fp.estimated_writes += balances;
+    }
+
+    /// Sender and receiver screening reads the compliance oracle's instance.
+    fn add_compliance_footprint(env: &Env, fp: &mut LedgerFootprint) {
+        if storage::get_compliance_oracle(env).is_some() {
+            fp.estimated_reads += 1;
+        }
+    }
+
+    /// Stream entries, beneficiary position indexes, first-seen user flags,
+    /// vault deposits and creation events for `streams`.
+    fn add_streams_footprint(
+        env: &Env,
+        fp: &mut LedgerFootprint,
+        streams: &Vec<StreamArgs>,
+        allow_vault: bool,
+    ) {
+        let first_id = storage::get_health(env).total_v2_streams;
+        let mut owners: soroban_sdk::Map<Address, u32> = soroban_sdk::Map::new(env);
+        let mut users = Vec::new(env);
+
+        for (next_id, args) in (first_id..).zip(streams.iter()) {
+            let vault_used = allow_vault && args.yield_enabled && args.vault_address.is_some();
+            let stream = StreamV2 {
+                sender: args.sender.clone(),
+                receiver: args.receiver.clone(),
+                beneficiary: args.receiver.clone(),
+                token: args.token.clone(),
+                total_amount: args.total_amount,
+                start_time: args.start_time,
+                end_time: args.end_time,
+                cliff_time: args.cliff_time,
+                withdrawn_amount: 0,
+                cancelled: false,
+                migrated_from_v1: false,
+                v1_stream_id: 0,
+                step_duration: args.step_duration,
+                multiplier_bps: args.multiplier_bps,
+                penalty_bps: args.penalty_bps,
+                vault_address: if vault_used {
+                    args.vault_address.clone()
+                } else {
+                    None
+                },
+                yield_enabled: args.yield_enabled,
+                is_pending: false,
+                is_recurrent: args.is_recurrent,
+                cycle_duration: args.cycle_duration,
+                cancellation_type: args.cancellation_type,
+                yield_recipient: args.yield_recipient,
+                split_address: args.split_address.clone(),
+                split_bps: args.split_bps,
+                curve_type: args.curve_type.clone(),
+                is_soulbound: args.is_soulbound,
+                accrual_clock: args.accrual_clock,
+            };
+            fp.persistent_bytes += storage::stream_entry_size(env, next_id, &stream);
+            fp.estimated_writes += 1;
+            if let CurveType::Piecewise(points) = &args.curve_type {
+                fp.persistent_bytes += storage::curve_schedule_entry_size(env, next_id, points);
+                fp.estimated_writes += 1;
+            }
+
+            let added = owners.get(args.receiver.clone()).unwrap_or(0);
+            owners.set(args.receiver.clone(), added + 1);
+            let slot = storage::get_owner_position_count(env, &args.receiver) + added;
+            fp.persistent_bytes +=
+                storage::owner_position_entry_size(env, &args.receiver, slot, next_id);
+            fp.estimated_writes += 2;
+
+            for user in [args.sender.clone(), args.receiver.clone()] {
+                if !users.contains(&user) {
+                    users.push_back(user);
+                }
+            }
+
+            if vault_used {
+                // Vault contract instance and its deposit record, plus the
+                // stream's position and the vault's deposit total
+                fp.estimated_reads += 1;
+                fp.estimated_writes += 3;
+            }
+
+            let mut data = Vec::new(env);
+            data.push_back(next_id.into_val(env));
+            data.push_back(args.sender.clone().into_val(env));
+            data.push_back(args.receiver.clone().into_val(env));
+            data.push_back(args.token.clone().into_val(env));
+            data.push_back(args.total_amount.into_val(env));
+            data.push_back(args.start_time.into_val(env));
+            data.push_back(args.cliff_time.into_val(env));
+            data.push_back(args.end_time.into_val(env));
+            data.push_back(u64::MAX.into_val(env));
+            data.push_back(args.memo.clone().into_val(env));
+            fp.event_bytes += Self::event_size(env, symbol_short!("create_v2"), data);
+        }
+
+        for owner in owners.keys().iter() {
+            fp.persistent_bytes += storage::owner_position_count_entry_size(env, &owner);
+            fp.estimated_writes += 1;
+        }
+        for user in users.iter() {
+            if storage::is_user_seen(env, &user) {
+                fp.estimated_reads += 1;
+            } else {
+                fp.persistent_bytes += storage::user_seen_entry_size(env, &user);
+                fp.estimated_writes += 1;
+            }
         }
     }
 
+    /// XDR size of a `NebulaEvent` published under `(u64, action)` topics.
+    fn event_size(env: &Env, action: Symbol, data: Vec<soroban_sdk::Val>) -> u32 {
+        let topics = (u64::MAX, action.clone());
+        let event = NebulaEvent {
+            version: 2,
+            timestamp: u64::MAX,
+            action,
+            data,
+        };
+        topics.to_xdr(env).len() + event.to_xdr(env).len()
+    }
+
+    /// Quote rent for the persistent bytes over the stream TTL bump.
+    fn finish_footprint(mut fp: LedgerFootprint) -> LedgerFootprint {
+        fp.rent_ledgers = storage::STREAM_TTL_BUMP;
+        let numerator =
+            fp.persistent_bytes as i128 * RENT_FEE_PER_1KB * storage::STREAM_TTL_BUMP as i128;
+        let denominator = 1024 * PERSISTENT_RENT_RATE_DENOMINATOR;
+        fp.rent_fee = (numerator + denominator - 1) / denominator;
+        fp
+    }
+
     // ----------------------------------------------------------------
     // Issue #407 — Bridge-In Receiver Hook
     // ----------------------------------------------------------------
 
-    /// Handle incoming tokens from another chain (e.g., Ethereum via Allbridge).
+    /// Receive bridged tokens (e.g. from Ethereum via Allbridge) and open a
+    /// stream from the attached instruction metadata in the same transaction.
     ///
-    /// When assets arrive with "Instruction Metadata", this function parses the
-    /// metadata to extract receiver_address and duration, then automatically
-    /// creates a stream without requiring a second transaction from the user.
+    /// Only bridges allowlisted via `Operation::SetBridgeAllowed` may call this,
+    /// and the bridge must authorize the call. The contract pulls `amount` of
+    /// `token` from the bridge and checks that its own balance grew by exactly
+    /// `amount` before anything is recorded.
     ///
-    /// Metadata format (40 bytes total):
-    /// - First 32 bytes: receiver address (as Bytes)
-    /// - Next 8 bytes: duration in seconds (u64, big-endian)
+    /// Metadata format v1 (integers big-endian):
+    /// - 1 byte: version (`BRIDGE_METADATA_VERSION`)
+    /// - 2 bytes: receiver length `n`, then `n` bytes of the receiver `Address` XDR
+    /// - 8 bytes: duration in seconds (1 ..= 10 years)
+    /// - 8 bytes: cliff offset from the start in seconds (<= duration)
+    /// - 1 byte: curve (0 = linear, 1 = exponential, 2 = cubic, 3 = square root,
+    ///   4 = logarithmic; piecewise schedules cannot be bridged)
+    /// - 1 byte: memo length `m` (0 = no memo, max 32), then `m` bytes of
+    ///   `[A-Za-z0-9_]` memo characters
     ///
-    /// # Parameters
-    /// - `from`: The address that sent the tokens (bridge contract)
-    /// - `amount`: The amount of tokens received
-    /// - `metadata`: Bytes containing receiver address and duration
-    ///
-    /// # Returns
-    /// - `Ok(stream_id)` if stream was created successfully
-    /// - `Ok(0)` if no valid metadata was provided (funds still received but no stream created)
-    /// - `Err(Error)` if metadata was invalid
+    /// Funds that arrive with metadata that does not decode are held as a
+    /// bridge deposit, which the bridge can return via `refund_bridge_deposit`.
     pub fn on_token_receive(
         env: Env,
-        from: Address,
+        bridge: Address,
+        token: Address,
         amount: i128,
         metadata: Bytes,
-    ) -> Result<u64, Error> {
-        // Don't require auth for bridge callbacks - the bridge should be authorized
-        // by having sent the tokens to this contract
+    ) -> Result<BridgeReceipt, Error> {
+        Self::require_not_paused(&env)?;
+        if !storage::is_bridge_allowed(&env, &bridge) {
+            return Err(Error::UnauthorizedSender);
+        }
+        bridge.require_auth();
 
-        // Validate minimum amount
         if amount <= 0 {
             return Err(Error::InvalidBridgeMetadata);
         }
+        Self::require_asset_whitelisted(&env, &token)?;
 
-        // If no metadata or too short, just accept funds without creating stream
-        // Stellar addresses encoded as strings are typically 56 characters, plus 8 bytes duration = 64 bytes
-        if metadata.is_empty() || metadata.len() < 64 {
-            // Just accept the funds - no auto-stream created
-            return Ok(0);
-        }
-
-        // Parse metadata: first 56 bytes = receiver address, next 8 bytes = duration
-        let receiver_address_bytes = metadata.slice(0..56);
-        let duration_bytes = metadata.slice(56..64);
-
-        // Parse duration from 8 bytes (big-endian u64)
-        let mut arr = [0u8; 8];
-        for i in 0u32..8 {
-            arr[i as usize] = duration_bytes.get(i).unwrap_or(0);
-        }
-        let duration = u64::from_be_bytes(arr);
-
-        // Validate duration (must be at least 1 second and not exceed 10 years)
-        if duration == 0 || duration > 315_360_000 {
-            return Err(Error::InvalidBridgeMetadata);
+        // Pull the funds and verify what actually arrived.
+        let token_client = soroban_sdk::token::TokenClient::new(&env, &token);
+        let contract = env.current_contract_address();
+        let balance_before = token_client.balance(&contract);
+        token_client.transfer(&bridge, &contract, &amount);
+        let received = token_client
+            .balance(&contract)
+            .checked_sub(balance_before)
+            .ok_or(Error::Overflow)?;
+        if received != amount {
+            return Err(Error::InsufficientBalance);
         }
 
-        // Convert receiver address bytes to Address
-        // For Stellar addresses, we need to handle them properly
-        // Try to parse as a valid address from the bytes
-        let receiver_address = Self::parse_address_from_bytes(&receiver_address_bytes)?;
+        let now = env.ledger().timestamp();
 
-        // Get the token address - this will be the token that was transferred
-        // We need to determine which token was sent. In a bridge scenario,
-        // the token contract that called this function is the token being received.
-        // We'll need to get this from the environment.
-        let token_address = Self::get_received_token(&env, &from)?;
+        let instruction = match Self::parse_bridge_metadata(&env, &metadata) {
+            Some(instruction) => instruction,
+            None => {
+                let deposit_id = storage::add_bridge_deposit(
+                    &env,
+                    &BridgeDeposit {
+                        bridge: bridge.clone(),
+                        token: token.clone(),
+                        amount,
+                        received_at: now,
+                    },
+                );
 
-        // Validate asset is whitelisted
-        Self::require_asset_whitelisted(&env, &token_address)?;
+                let mut data = Vec::new(&env);
+                data.push_back(deposit_id.into_val(&env));
+                data.push_back(bridge.into_val(&env));
+                data.push_back(token.into_val(&env));
+                data.push_back(amount.into_val(&env));
+                data.push_back(now.into_val(&env));
+
+                env.events().publish(
+                    (deposit_id, symbol_short!("brdg_hold")),
+                    NebulaEvent {
+                        version: 2,
+                        timestamp: now,
+                        action: symbol_short!("brdg_hold"),
+                        data,
+                    },
+                );
 
-        // Calculate stream times
-        let now = env.ledger().timestamp();
-        let start_time = now;
-        let end_time = now.saturating_add(duration);
+                return Ok(BridgeReceipt {
+                    stream_id: None,
+                    deposit_id: Some(deposit_id),
+                });
+            }
+        };
 
-        // Create StreamArgs
         let stream_args = StreamArgs {
-            sender: from.clone(), // Bridge acts as sender (has the funds)
-            receiver: receiver_address.clone(),
-            token: token_address,
+            sender: bridge.clone(), // Bridge acts as sender (has the funds)
+            receiver: instruction.receiver.clone(),
+            token,
             total_amount: amount,
-            start_time,
-            cliff_time: start_time, // No cliff for bridge-in streams
-            end_time,
+            start_time: now,
+            cliff_time: now.saturating_add(instruction.cliff),
+            end_time: now.saturating_add(instruction.duration),
             step_duration: 0,      // Default linear stream
             multiplier_bps: 10000, // 1.0x multiplier (no escalation)
             penalty_bps: 0,        // No penalty for bridge streams
@@ -508,23 +876,24 @@ impl Contract {
             cycle_duration: 0,
             cancellation_type: 0, // Unilateral cancellation
             affiliate: None,
-            memo: None,
+            memo: instruction.memo.clone(),
             yield_recipient: 0,
             split_address: None,
             split_bps: 0,
-            curve_type: 0,
+            curve_type: instruction.curve_type.clone(),
+            is_soulbound: false,
+            accrual_clock: 0,
         };
 
-        // Create the stream
         let stream_id = Self::create_stream_internal(env.clone(), stream_args)?;
 
-        // Emit event for bridge-in stream creation
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
-        data.push_back(from.into_val(&env));
-        data.push_back(receiver_address.into_val(&env));
+        data.push_back(bridge.into_val(&env));
+        data.push_back(instruction.receiver.into_val(&env));
         data.push_back(amount.into_val(&env));
-        data.push_back(duration.into_val(&env));
+        data.push_back(instruction.duration.into_val(&env));
+        data.push_back(instruction.memo.into_val(&env));
         data.push_back(now.into_val(&env));
 
         env.events().publish(
@@ -537,77 +906,140 @@ impl Contract {
             },
         );
 
-        Ok(stream_id)
+        Ok(BridgeReceipt {
+            stream_id: Some(stream_id),
+            deposit_id: None,
+        })
     }
 
-    /// Parse address from bytes slice.
-    /// The bytes should contain a valid Stellar address string (e.g., "G...").
-    fn parse_address_from_bytes(bytes: &soroban_sdk::Bytes) -> Result<Address, Error> {
-        // The metadata should contain a string like "GABC123..."
-        // We'll try to extract it as a string
+    /// Return a held bridge deposit to `refund_to`. Only the bridge that
+    /// delivered the funds can refund them.
+    pub fn refund_bridge_deposit(
+        env: Env,
+        deposit_id: u64,
+        refund_to: Address,
+    ) -> Result<i128, Error> {
+        let deposit =
+            storage::get_bridge_deposit(&env, deposit_id).ok_or(Error::NothingToWithdraw)?;
+        deposit.bridge.require_auth();
 
-        // Check minimum length for a Stellar address
-        if bytes.len() < 56 {
-            return Err(Error::InvalidBridgeMetadata);
-        }
+        storage::remove_bridge_deposit(&env, deposit_id);
 
-        // For Stellar addresses encoded as strings, they're typically 56 characters
-        // Starting with 'G' and containing base32 characters
-        // Since we can't easily convert Bytes to String in Soroban, we need a workaround
+        let token_client = soroban_sdk::token::TokenClient::new(&env, &deposit.token);
+        token_client.transfer(&env.current_contract_address(), &refund_to, &deposit.amount);
 
-        // Try to find a 'G' or 'C' in the bytes to locate the address start
-        let mut start_idx: Option<u32> = None;
-        for i in 0u32..bytes.len() {
-            let b = bytes.get(i).unwrap_or(0);
-            if b == b'G' || b == b'C' {
-                start_idx = Some(i);
-                break;
-            }
-        }
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(deposit_id.into_val(&env));
+        data.push_back(deposit.bridge.into_val(&env));
+        data.push_back(refund_to.into_val(&env));
+        data.push_back(deposit.amount.into_val(&env));
+        data.push_back(now.into_val(&env));
 
-        let start_idx = start_idx.ok_or(Error::InvalidBridgeMetadata)?;
+        env.events().publish(
+            (deposit_id, symbol_short!("brdg_rfnd")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("brdg_rfnd"),
+                data,
+            },
+        );
 
-        // Extract up to 56 characters after 'G'
-        let mut addr_str_arr = [0u8; 56];
-        let mut found_end = false;
-        let mut char_count = 0usize;
+        Ok(deposit.amount)
+    }
 
-        for j in 0usize..56 {
-            let idx = start_idx as usize + j;
-            if idx >= bytes.len() as usize {
-                found_end = true;
-                break;
-            }
-            let b = bytes.get(idx as u32).unwrap_or(0);
-            if b == 0 {
-                found_end = true;
-                break;
-            }
-            addr_str_arr[j] = b;
-            char_count += 1;
-        }
+    pub fn get_bridge_deposit(env: Env, deposit_id: u64) -> Option<BridgeDeposit> {
+        storage::get_bridge_deposit(&env, deposit_id)
+    }
 
-        if char_count < 56 {
-            return Err(Error::InvalidBridgeMetadata);
+    pub fn is_bridge_allowed(env: Env, bridge: Address) -> bool {
+        storage::is_bridge_allowed(&env, &bridge)
+    }
+
+    /// Decode v1 bridge instruction metadata. Returns `None` for anything
+    /// malformed so the funds can be held instead of lost.
+    fn parse_bridge_metadata(env: &Env, metadata: &Bytes) -> Option<BridgeInstruction> {
+        if metadata.get(0)? != BRIDGE_METADATA_VERSION {
+            return None;
+        }
+
+        let receiver_len = Self::read_be(metadata, 1, 2)? as u32;
+        let mut offset = 3u32;
+        let receiver_end = offset.checked_add(receiver_len)?;
+        if receiver_end > metadata.len() {
+            return None;
+        }
+        let receiver = Self::parse_address_xdr(env, &metadata.slice(offset..receiver_end))?;
+        offset = receiver_end;
+
+        let duration = Self::read_be(metadata, offset, 8)?;
+        let cliff = Self::read_be(metadata, offset + 8, 8)?;
+        let curve_type = match Self::read_be(metadata, offset + 16, 1)? {
+            0 => CurveType::Linear,
+            1 => CurveType::Exponential,
+            2 => CurveType::Cubic,
+            3 => CurveType::SquareRoot,
+            4 => CurveType::Logarithmic,
+            _ => return None,
+        };
+        let memo_len = Self::read_be(metadata, offset + 17, 1)? as u32;
+        offset += 18;
+
+        if duration == 0 || duration > 315_360_000 || cliff > duration {
+            return None;
+        }
+        if memo_len > MAX_MEMO_LENGTH || offset + memo_len != metadata.len() {
+            return None;
         }
 
-        // Create string from the array
-        let addr_str = soroban_sdk::String::from_str(
-            &bytes.env(),
-            core::str::from_utf8(&addr_str_arr[..56]).unwrap_or(""),
-        );
+        let memo = if memo_len == 0 {
+            None
+        } else {
+            let mut buf = [0u8; MAX_MEMO_LENGTH as usize];
+            for i in 0..memo_len {
+                let c = metadata.get(offset + i)?;
+                if !(c.is_ascii_alphanumeric() || c == b'_') {
+                    return None;
+                }
+                buf[i as usize] = c;
+            }
+            let memo = core::str::from_utf8(&buf[..memo_len as usize]).ok()?;
+            Some(Symbol::new(env, memo))
+        };
+
+        Some(BridgeInstruction {
+            receiver,
+            duration,
+            cliff,
+            curve_type,
+            memo,
+        })
+    }
 
-        Ok(Address::from_string(&addr_str))
+    /// Read a big-endian unsigned integer of `len` (<= 8) bytes at `offset`.
+    fn read_be(bytes: &Bytes, offset: u32, len: u32) -> Option<u64> {
+        let mut value = 0u64;
+        for i in offset..offset.checked_add(len)? {
+            value = (value << 8) | bytes.get(i)? as u64;
+        }
+        Some(value)
     }
 
-    /// Get the token address that was received.
-    /// The bridge should include the token address in the metadata.
-    /// For now, we use the 'from' address as the token if it's a valid token contract.
-    fn get_received_token(_env: &Env, from: &Address) -> Result<Address, Error> {
-        // In many bridge scenarios, the 'from' address is the token contract
-        // This is a reasonable default - the bridge can pass the token as the sender
-        // and we'll treat the first 32 bytes of metadata as the actual sender
-        Ok(from.clone())
+    /// Decode an `ScVal::Address` XDR blob. The layout is checked up front
+    /// because the host traps, rather than erroring, on malformed XDR.
+    fn parse_address_xdr(env: &Env, xdr: &Bytes) -> Option<Address> {
+        // SCV_ADDRESS, then SC_ADDRESS_TYPE_ACCOUNT + PUBLIC_KEY_TYPE_ED25519
+        // + 32-byte key, or SC_ADDRESS_TYPE_CONTRACT + 32-byte hash.
+        let header_len = match (xdr.len(), Self::read_be(xdr, 0, 8)?) {
+            (44, 0x0000_0012_0000_0000) => 12,
+            (40, 0x0000_0012_0000_0001) => 8,
+            _ => return None,
+        };
+        if header_len == 12 && Self::read_be(xdr, 8, 4)? != 0 {
+            return None;
+        }
+        Address::from_xdr(env, xdr).ok()
     }
 
     /// Internal create_stream logic without auth check (used by bridge)
@@ -621,21 +1053,32 @@ impl Contract {
         {
             return Err(Error::InvalidTimeRange);
         }
+        Self::validate_accrual_clock(args.accrual_clock)?;
+        Self::validate_curve(&args.curve_type, args.start_time, args.end_time)?;
 
-        Self::validate_limits(args.total_amount, args.start_time, args.end_time)?;
+        Self::validate_limits(
+            &env,
+            &args.token,
+            args.total_amount,
+            args.start_time,
+            args.end_time,
+            args.accrual_clock,
+        )?;
 
         if args.penalty_bps > 10_000 {
             return Err(Error::InvalidPenalty);
         }
+        Self::validate_cancellation_type(args.cancellation_type)?;
 
         if args.total_amount < storage::get_min_value(&env, &args.token) {
             return Err(Error::BelowDustThreshold);
         }
 
-        // Note: Funds already transferred to contract via token callback
+        // Note: Funds were already pulled and verified by on_token_receive
         // No need to do another transfer
 
-        let stream_amount = Self::apply_protocol_fee(&env, &args.token, args.total_amount)?;
+        let stream_amount =
+            Self::apply_protocol_fee(&env, &args.token, args.total_amount, &args.affiliate)?;
 
         let stream_id = storage::next_stream_id(&env);
 
@@ -664,10 +1107,12 @@ impl Contract {
             yield_recipient: 0,
             split_address: None,
             split_bps: 0,
-            curve_type: args.curve_type,
+            curve_type: args.curve_type.clone(),
+            is_soulbound: args.is_soulbound,
+            accrual_clock: args.accrual_clock,
         };
 
-        storage::set_stream(&env, stream_id, &stream);
+        Self::insert_new_stream(&env, stream_id, &stream);
         storage::update_stats(&env, stream_amount, &args.sender, &args.receiver);
 
         let now = env.ledger().timestamp();
@@ -730,10 +1175,9 @@ impl Contract {
 
     /// Transfer admin rights to a new address (e.g. a multisig or DAO contract).
     ///
-    /// The current admin must authorise this call. The new admin becomes the
-    /// sole admin with threshold = 1, ready to be promoted to a full multisig
-    /// via `set_admins` if desired.
-    /// Internal helper for transfer_admin.
+    /// Applied by `Operation::TransferAdmin`. The new admin becomes the sole
+    /// admin with threshold = 1, ready to be promoted to a full multisig via
+    /// `Operation::SetAdmins` if desired.
     fn transfer_admin_internal(env: Env, new_admin: Address) -> Result<(), Error> {
         let previous_admin = storage::try_get_admin(&env)?;
         storage::set_admin(&env, &new_admin);
@@ -756,82 +1200,72 @@ impl Contract {
         Ok(())
     }
 
-    pub fn transfer_admin(env: Env, new_admin: Address) -> Result<(), Error> {
-        let admin = storage::try_get_admin(&env)?;
-        admin.require_auth();
-        Self::transfer_admin_internal(env, new_admin)
-    }
-
     // ----------------------------------------------------------------
     // Issue #396 — Dust Threshold
     // ----------------------------------------------------------------
 
-    /// Return the minimum stream amount for `asset` (default: 10 XLM).
+    /// Return the minimum stream amount for `asset` (default: 10 XLM
+    /// equivalent in the asset's decimals).
     pub fn get_min_value(env: Env, asset: Address) -> i128 {
         storage::get_min_value(&env, &asset)
     }
 
-    /// Override the minimum for a specific asset. Admin-only.
-    /// Internal helper for set_min_value.
+    /// Override the minimum for a specific asset.
+    /// Applied by `Operation::SetMinValue`.
     fn set_min_value_internal(env: Env, asset: Address, min: i128) -> Result<(), Error> {
         storage::set_min_value(&env, &asset, min);
         Ok(())
     }
 
-    pub fn set_min_value(env: Env, asset: Address, min: i128) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        Self::set_min_value_internal(env, asset, min)
+    /// Return the effective per-stream caps for `asset`, in its own decimals.
+    pub fn get_token_limits(env: Env, asset: Address) -> TokenLimits {
+        Self::token_limits(&env, &asset)
     }
 
     // ----------------------------------------------------------------
     // Issue #378 — DEX Configuration for Streaming Swap
     // ----------------------------------------------------------------
 
-    /// Set the default DEX contract address for swap operations.
-    /// Admin-only.
-    pub fn set_dex_address(env: Env, dex_address: Address) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::set_dex_address(&env, &dex_address);
-        Ok(())
-    }
-
     /// Get the configured DEX contract address.
     pub fn get_dex_address(env: Env) -> Option<Address> {
         storage::get_dex_address(&env)
     }
 
-    /// Enable or disable swap streaming globally.
-    /// Admin-only.
-    pub fn set_swap_enabled(env: Env, enabled: bool) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::set_swap_enabled(&env, enabled);
-        Ok(())
-    }
-
     /// Check if swap streaming is enabled globally.
     pub fn is_swap_enabled(env: Env) -> bool {
         storage::is_swap_enabled(&env)
     }
 
-    /// Set a specific DEX pool configuration for an asset pair.
-    /// This allows routing through different pools for different asset pairs.
-    /// Admin-only.
-    pub fn set_dex_pool(
-        env: Env,
-        token_in: Address,
-        token_out: Address,
-        pool_info: DexPoolInfo,
-    ) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::set_dex_pool(&env, &token_in, &token_out, &pool_info);
-        Ok(())
-    }
-
     /// Get the DEX pool configuration for an asset pair.
     pub fn get_dex_pool(env: Env, token_in: Address, token_out: Address) -> Option<DexPoolInfo> {
         storage::get_dex_pool(&env, &token_in, &token_out)
     }
 
+    /// DEX adapters enabled for routing via `Operation::SetDexAdapter`.
+    pub fn get_dex_adapters(env: Env) -> Vec<Address> {
+        storage::get_dex_adapters(&env)
+    }
+
+    /// Pool pairs registered via `Operation::SetDexPool`.
+    pub fn get_dex_pairs(env: Env) -> Vec<(Address, Address)> {
+        storage::get_dex_pairs(&env)
+    }
+
+    /// Allowlist entry of `vault` (`Operation::SetVault`), if any.
+    pub fn get_vault_config(env: Env, vault: Address) -> Option<VaultConfig> {
+        storage::get_vault_config(&env, &vault)
+    }
+
+    /// Principal all streams currently hold in `vault`.
+    pub fn get_vault_deposits(env: Env, vault: Address) -> i128 {
+        storage::get_vault_deposits(&env, &vault)
+    }
+
+    /// A stream's shares and remaining principal in its vault.
+    pub fn get_vault_position(env: Env, stream_id: u64) -> Option<VaultPosition> {
+        storage::get_vault_position(&env, stream_id)
+    }
+
     // ----------------------------------------------------------------
     // Issue #359 — Migration Bridge
     // ----------------------------------------------------------------
@@ -921,10 +1355,12 @@ impl Contract {
             yield_recipient: 0,
             split_address: None,
             split_bps: 0,
-            curve_type: 0,
+            curve_type: CurveType::Linear,
+            is_soulbound: false,
+            accrual_clock: 0,
         };
 
-        storage::set_stream(&env, v2_stream_id, &v2_stream);
+        Self::insert_new_stream(&env, v2_stream_id, &v2_stream);
         storage::update_stats(&env, remaining, &v1_stream.sender, &caller);
         // Issue #399 — record migration so the same V1 stream cannot be migrated again
         storage::mark_v1_migrated(&env, v1_stream_id);
@@ -1032,10 +1468,12 @@ impl Contract {
             yield_recipient: 0,
             split_address: None,
             split_bps: 0,
-            curve_type: 0,
+            curve_type: CurveType::Linear,
+            is_soulbound: false,
+            accrual_clock: 0,
         };
 
-        storage::set_stream(&env, v2_stream_id, &v2_stream);
+        Self::insert_new_stream(&env, v2_stream_id, &v2_stream);
         storage::update_stats(&env, remaining_balance, &v1_stream.sender, &caller);
         // Issue #399 — record migration so the same V1 stream cannot be migrated again
         storage::mark_v1_migrated(&env, v1_stream_id);
@@ -1078,12 +1516,14 @@ impl Contract {
         }
 
         let mut results = Vec::new(&env);
-        let now_nanos = Self::ledger_timestamp_nanos(&env);
-        let now = env.ledger().timestamp();
 
         for id in ids.iter() {
             if let Some(stream) = storage::get_stream(&env, id) {
-                let unlocked = Self::calculate_unlocked_internal(&stream, now_nanos);
+                let now = Self::stream_now(&env, &stream);
+                let unlocked = Self::calculate_unlocked_internal(
+                    &stream,
+                    Self::stream_clock_nanos(&env, &stream),
+                );
                 let remaining_unlocked = unlocked.saturating_sub(stream.withdrawn_amount);
 
                 let status = if stream.cancelled {
@@ -1118,8 +1558,8 @@ impl Contract {
     /// Predict the exact balance at a future timestamp for a given stream.
     ///
     /// This function is useful for "Stellar Glass" projection charts that need to
-    /// visualize future stream balances. It accounts for cliffs and exponential curves
-    /// defined in the stream configuration.
+    /// visualize future stream balances. It accounts for cliffs and for every
+    /// unlock curve, piecewise schedules included, defined in the stream.
     ///
     /// # Parameters
     /// - `stream_id`: The ID of the stream to predict
@@ -1139,9 +1579,35 @@ impl Contract {
         future_timestamp: u64,
     ) -> Result<i128, Error> {
         let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        // Ledger-clock streams: estimate the ledger current at that timestamp.
+        let at = if stream.accrual_clock == 1 {
+            Self::estimate_ledger_at(&env, future_timestamp)
+        } else {
+            future_timestamp
+        };
+        Ok(Self::calculate_unlocked_internal(
+            &stream,
+            at * math::NANOS_PER_SEC,
+        ))
+    }
+
+    /// Same as `predict_balance_at`, keyed on a future ledger sequence.
+    /// Exact for ledger-clock streams; timestamp streams use an estimate
+    /// based on `LEDGER_CLOSE_SECS`.
+    pub fn predict_balance_at_ledger(
+        env: Env,
+        stream_id: u64,
+        future_ledger: u32,
+    ) -> Result<i128, Error> {
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        let at = if stream.accrual_clock == 1 {
+            future_ledger as u64
+        } else {
+            Self::estimate_timestamp_at(&env, future_ledger as u64)
+        };
         Ok(Self::calculate_unlocked_internal(
             &stream,
-            future_timestamp * math::NANOS_PER_SEC,
+            at * math::NANOS_PER_SEC,
         ))
     }
 
@@ -1180,16 +1646,89 @@ impl Contract {
     /// - `AlreadyCancelled`: If the stream has been cancelled
     /// - `NothingToWithdraw`: If no funds are unlocked
     pub fn withdraw(env: Env, stream_id: u64, beneficiary: Address) -> Result<i128, Error> {
-        Self::require_not_paused_only(&env)?;
         beneficiary.require_auth();
+        Self::withdraw_internal(env, stream_id, beneficiary, None).map(|(amount, _)| amount)
+    }
 
-        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+    /// Withdraw unlocked funds, paid out in `asset_out` instead of the stream
+    /// token. The withdrawable amount is swapped through the configured DEX
+    /// (`Operation::SetDexAddress`) before the usual split routing and transfer.
+    ///
+    /// `asset_out` must be whitelisted and swaps must be enabled. Passing the
+    /// stream token itself pays out without a swap.
+    ///
+    /// # Returns
+    /// - `Ok(amount_out)`: The amount of `asset_out` paid, before any split
+    ///
+    /// # Errors
+    /// Those of `withdraw`, plus:
+    /// - `AssetNotWhitelisted`: If `asset_out` is not whitelisted
+    /// - `DexNotConfigured`: If swaps are disabled or no DEX is set
+    /// - `SwapSlippageExceeded`: If the swap returns less than `min_out`
+    pub fn withdraw_as(
+        env: Env,
+        stream_id: u64,
+        beneficiary: Address,
+        asset_out: Address,
+        min_out: i128,
+    ) -> Result<i128, Error> {
+        Self::require_asset_whitelisted(&env, &asset_out)?;
+        beneficiary.require_auth();
+        Self::withdraw_internal(env, stream_id, beneficiary, Some((asset_out, min_out)))
+            .map(|(_, amount_out)| amount_out)
+    }
 
-        if stream.beneficiary != beneficiary {
-            return Err(Error::UnauthorizedSender);
+    /// Retry a stream left pending by a failed vault withdrawal, paying the
+    /// beneficiary everything unlocked so far. Anyone may call this; funds
+    /// only ever go to the beneficiary.
+    ///
+    /// A share vault position now worth less than its principal cannot be
+    /// redeemed in full, so it is exited at a loss first: the stream's
+    /// shares are redeemed and the missing principal is written off its
+    /// `total_amount`, as in `emergency_exit_vault`.
+    ///
+    /// # Returns
+    /// - `Ok(amount)`: The amount withdrawn, or 0 if the vault is still
+    ///   failing and the stream stays pending
+    ///
+    /// # Errors
+    /// Those of `withdraw`, plus:
+    /// - `NoPendingUpdate`: If the stream is not pending
+    pub fn resolve_pending_stream(env: Env, stream_id: u64) -> Result<i128, Error> {
+        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        if !stream.is_pending {
+            return Err(Error::NoPendingUpdate);
+        }
+        if let Some(vault) = stream.vault_address.clone() {
+            if Self::vault_position_at_loss(&env, stream_id, &vault) {
+                Self::exit_vault_stream(&env, stream_id, &mut stream, &vault);
+            }
         }
+        Self::withdraw_internal(env, stream_id, stream.beneficiary, None).map(|(amount, _)| amount)
+    }
 
-        if stream.cancelled {
+    /// Shared body of `withdraw`, `withdraw_as` and `resolve_pending_stream`;
+    /// callers authorize. Returns the amount taken from the stream and the
+    /// amount paid out in the payout asset.
+    fn withdraw_internal(
+        env: Env,
+        stream_id: u64,
+        beneficiary: Address,
+        payout_as: Option<(Address, i128)>,
+    ) -> Result<(i128, i128), Error> {
+        Self::require_not_paused_only(&env)?;
+
+        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        if stream.beneficiary != beneficiary {
+            return Err(Error::UnauthorizedSender);
+        }
+
+        // A cancelled allowance stream stays withdrawable for the vested
+        // balance its sender's allowance did not cover at cancellation.
+        let funding = storage::get_allowance_funding(&env, stream_id);
+        if stream.cancelled && (funding.is_none() || stream.withdrawn_amount >= stream.total_amount)
+        {
             return Err(Error::AlreadyCancelled);
         }
 
@@ -1197,37 +1736,73 @@ impl Contract {
         Self::require_compliant(&env, &stream.beneficiary)?;
 
         let now = env.ledger().timestamp();
-        let to_withdraw =
-            Self::calculate_unlocked_internal(&stream, Self::ledger_timestamp_nanos(&env))
-                .saturating_sub(stream.withdrawn_amount);
+        let mut to_withdraw = if stream.cancelled {
+            stream.total_amount - stream.withdrawn_amount
+        } else {
+            Self::calculate_unlocked_internal(&stream, Self::stream_clock_nanos(&env, &stream))
+                .saturating_sub(stream.withdrawn_amount)
+        };
 
         if to_withdraw <= 0 {
             return Err(Error::NothingToWithdraw);
         }
 
-        // If Yield-Bearing, withdraw principal from Vault
+        // Allowance streams pull the accrual from the sender now. A shortfall
+        // pays what is covered and starts the grace period; one still unpaid
+        // once it has run out defaults the stream.
+        let mut payable = to_withdraw;
+        let escrowed = funding.is_none();
+        if let Some(mut funding) = funding {
+            let (pulled, net) = Self::pull_allowance(&env, &stream, &funding, to_withdraw)?;
+            if pulled < to_withdraw {
+                if funding.delinquent_since == 0 {
+                    funding.delinquent_since = now;
+                    Self::emit_allowance_event(
+                        &env,
+                        stream_id,
+                        &stream,
+                        symbol_short!("delinqnt"),
+                        to_withdraw - pulled,
+                    );
+                } else if now
+                    > funding
+                        .delinquent_since
+                        .saturating_add(funding.grace_period)
+                {
+                    stream.cancelled = true;
+                    stream.total_amount = stream.withdrawn_amount + pulled;
+                    storage::remove_owner_position(&env, &stream.beneficiary, stream_id);
+                    Self::emit_allowance_event(
+                        &env,
+                        stream_id,
+                        &stream,
+                        symbol_short!("default"),
+                        to_withdraw - pulled,
+                    );
+                }
+            } else {
+                funding.delinquent_since = 0;
+            }
+            storage::set_allowance_funding(&env, stream_id, &funding);
+            if pulled == 0 {
+                // Return Ok(0) to persist the delinquency, as for pending vaults.
+                storage::set_stream(&env, stream_id, &stream);
+                return Ok((0, 0));
+            }
+            to_withdraw = pulled;
+            payable = net;
+        }
+
+        // If Yield-Bearing, redeem principal (and its yield) from the Vault
         if let Some(vault_addr) = &stream.vault_address {
-            let vault_client = VaultClient::new(&env, vault_addr);
-            let result = vault_client.try_withdraw(&to_withdraw);
-            if result.is_err() {
-                // Vault withdrawal failed — mark stream as pending for manual resolution
+            if !Self::vault_redeem(&env, stream_id, &stream, vault_addr, to_withdraw)? {
+                // Vault withdrawal failed — mark stream as pending until a
+                // retry (`withdraw` or `resolve_pending_stream`) succeeds.
                 stream.is_pending = true;
                 storage::set_stream(&env, stream_id, &stream);
                 // Return Ok(0) to persist the 'is_pending' state change.
                 // Returning Err automatically rolls back state in Soroban.
-                return Ok(0);
-            }
-
-            // Route accrued interest to the designated yield_recipient (Issue #410)
-            let interest = vault_client.get_accrued_interest(&to_withdraw);
-            if interest > 0 {
-                let interest_dest = match stream.yield_recipient {
-                    0 => stream.sender.clone(),
-                    2 => storage::get_treasury(&env).unwrap_or(stream.sender.clone()),
-                    _ => stream.beneficiary.clone(), // 1 = Receiver (default)
-                };
-                let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
-                token_client.transfer(&env.current_contract_address(), &interest_dest, &interest);
+                return Ok((0, 0));
             }
         }
 
@@ -1241,13 +1816,377 @@ impl Contract {
                 return Err(Error::ExpiredDeadline);
             }
         }
-        let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
+        // Swap-on-withdraw streams pay out the slice swapped at today's price;
+        // `withdraw_as` picks the payout asset and its own minimum instead.
+        let schedule = storage::get_swap_schedule(&env, stream_id);
+        let (slippage_bps, max_impact_bps) = match &schedule {
+            Some(schedule) => (
+                schedule.slippage_tolerance_bps,
+                schedule.max_price_impact_bps,
+            ),
+            None => (10_000, 10_000),
+        };
+        let (payout_token, min_out) = match payout_as {
+            Some(payout) => payout,
+            None => match schedule {
+                Some(schedule) => (schedule.asset_out, 0),
+                None => (stream.token.clone(), 0),
+            },
+        };
+        let paid = if payout_token == stream.token {
+            payable
+        } else {
+            Self::swap_slice(
+                &env,
+                stream_id,
+                &stream.token,
+                &payout_token,
+                payable,
+                slippage_bps,
+                max_impact_bps,
+                min_out,
+            )?
+        };
+        if paid < min_out {
+            return Err(Error::SwapSlippageExceeded);
+        }
+        Self::pay_beneficiary(&env, stream_id, &stream, &payout_token, paid);
+
+        // Update state
+        stream.withdrawn_amount += to_withdraw;
+        stream.is_pending = false; // Successfully withdrawn, any previous pending status cleared
+        storage::set_stream(&env, stream_id, &stream);
+
+        // Update analytics (TVL decreased)
+        if escrowed {
+            storage::update_stats(&env, -to_withdraw, &stream.sender, &stream.receiver);
+        }
+
+        let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(stream.beneficiary.clone().into_val(&env));
+        data.push_back(to_withdraw.into_val(&env));
+        data.push_back(stream.withdrawn_amount.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (stream_id, symbol_short!("claim")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("claim"),
+                data,
+            },
+        );
+
+        Ok((to_withdraw, paid))
+    }
+
+    /// Deposit a new stream's escrow into an allowlisted vault, within its
+    /// deposit cap, and record the stream's position.
+    fn vault_deposit(
+        env: &Env,
+        stream_id: u64,
+        vault: &Address,
+        token: &Address,
+        amount: i128,
+    ) -> Result<(), Error> {
+        let config = storage::get_vault_config(env, vault).ok_or(Error::AssetNotWhitelisted)?;
+        let deposits = storage::get_vault_deposits(env, vault)
+            .checked_add(amount)
+            .ok_or(Error::Overflow)?;
+        if config.deposit_cap > 0 && deposits > config.deposit_cap {
+            return Err(Error::AmountOverflow);
+        }
+
+        let shares = match config.kind {
+            VaultKind::Principal => {
+                VaultClient::new(env, vault).deposit(&amount);
+                amount
+            }
+            VaultKind::Shares => {
+                let contract = env.current_contract_address();
+                soroban_sdk::token::TokenClient::new(env, token).approve(
+                    &contract,
+                    vault,
+                    &amount,
+                    &env.ledger().sequence().saturating_add(1),
+                );
+                ShareVaultClient::new(env, vault).deposit(&contract, &amount)
+            }
+        };
+        storage::set_vault_deposits(env, vault, deposits);
+        storage::add_vault_stream(env, vault, stream_id);
+        storage::set_vault_position(
+            env,
+            stream_id,
+            &VaultPosition {
+                kind: config.kind,
+                shares,
+                principal: amount,
+            },
+        );
+        Ok(())
+    }
+
+    /// Take `amount` of a stream's principal out of its vault and pay the
+    /// yield earned on it to the stream's yield recipient. Share vaults
+    /// redeem the stream's shares pro rata to the principal taken.
+    ///
+    /// Returns `Ok(false)`, with nothing changed, when the vault call fails
+    /// or the shares are worth less than the principal. A redemption that
+    /// still comes back short fails with `InsufficientBalance`.
+    fn vault_redeem(
+        env: &Env,
+        stream_id: u64,
+        stream: &StreamV2,
+        vault: &Address,
+        amount: i128,
+    ) -> Result<bool, Error> {
+        let contract = env.current_contract_address();
+        let token_client = soroban_sdk::token::TokenClient::new(env, &stream.token);
+        let position = storage::get_vault_position(env, stream_id);
+
+        let (shares, earned) = match &position {
+            Some(position) if position.kind == VaultKind::Shares => {
+                let shares = if amount >= position.principal {
+                    position.shares
+                } else {
+                    math::FixedPoint::mul_div(position.shares, amount, position.principal)?
+                };
+                let vault_client = ShareVaultClient::new(env, vault);
+                if !matches!(vault_client.try_convert_to_assets(&shares), Ok(Ok(value)) if value >= amount)
+                {
+                    return Ok(false);
+                }
+                let balance_before = token_client.balance(&contract);
+                if !matches!(vault_client.try_redeem(&contract, &shares), Ok(Ok(_))) {
+                    return Ok(false);
+                }
+                let assets = token_client.balance(&contract) - balance_before;
+                if assets < amount {
+                    return Err(Error::InsufficientBalance);
+                }
+                (shares, assets - amount)
+            }
+            // Principal vaults, including streams deposited before positions
+            // were tracked.
+            _ => {
+                let vault_client = VaultClient::new(env, vault);
+                if !matches!(vault_client.try_withdraw(&amount), Ok(Ok(_))) {
+                    return Ok(false);
+                }
+                (amount, vault_client.get_accrued_interest(&amount))
+            }
+        };
+
+        if let Some(mut position) = position {
+            let principal = amount.min(position.principal);
+            position.shares -= shares.min(position.shares);
+            position.principal -= principal;
+            storage::set_vault_position(env, stream_id, &position);
+            if position.principal == 0 {
+                storage::remove_vault_stream(env, vault, stream_id);
+            }
+            let deposits = storage::get_vault_deposits(env, vault);
+            storage::set_vault_deposits(env, vault, (deposits - principal).max(0));
+        }
+
+        Self::pay_vault_yield(env, stream, earned);
+        Ok(true)
+    }
+
+    /// Route vault yield to the designated yield_recipient (Issue #410).
+    fn pay_vault_yield(env: &Env, stream: &StreamV2, earned: i128) {
+        if earned <= 0 {
+            return;
+        }
+        let yield_dest = match stream.yield_recipient {
+            0 => stream.sender.clone(),
+            2 => storage::get_treasury(env).unwrap_or(stream.sender.clone()),
+            _ => stream.beneficiary.clone(), // 1 = Receiver (default)
+        };
+        soroban_sdk::token::TokenClient::new(env, &stream.token).transfer(
+            &env.current_contract_address(),
+            &yield_dest,
+            &earned,
+        );
+    }
+
+    /// Principal a stream still holds in its vault. Streams deposited before
+    /// positions were tracked hold everything not yet withdrawn.
+    fn vault_principal(env: &Env, stream_id: u64, stream: &StreamV2) -> i128 {
+        match storage::get_vault_position(env, stream_id) {
+            Some(position) => position.principal,
+            None if stream.cancelled => 0,
+            None => stream.total_amount.saturating_sub(stream.withdrawn_amount),
+        }
+    }
+
+    /// Whether a stream's share vault position is worth less than the
+    /// principal it holds.
+    fn vault_position_at_loss(env: &Env, stream_id: u64, vault: &Address) -> bool {
+        match storage::get_vault_position(env, stream_id) {
+            Some(position) if position.kind == VaultKind::Shares && position.shares > 0 => {
+                matches!(
+                    ShareVaultClient::new(env, vault).try_convert_to_assets(&position.shares),
+                    Ok(Ok(value)) if value < position.principal
+                )
+            }
+            _ => false,
+        }
+    }
+
+    /// Redeem everything a stream holds in `vault` back into the contract
+    /// and clear its `vault_address`, so it keeps streaming from the
+    /// contract's own balance. Yield above the principal goes to the yield
+    /// recipient; principal the vault could not return is written off the
+    /// stream's `total_amount`.
+    ///
+    /// Returns the principal, the assets recovered and the shortfall, or
+    /// `None`, with nothing changed, when the redemption fails outright.
+    fn exit_vault_stream(
+        env: &Env,
+        stream_id: u64,
+        stream: &mut StreamV2,
+        vault: &Address,
+    ) -> Option<(i128, i128, i128)> {
+        let contract = env.current_contract_address();
+        let position = storage::get_vault_position(env, stream_id);
+        let principal = Self::vault_principal(env, stream_id, stream);
+        let token_client = soroban_sdk::token::TokenClient::new(env, &stream.token);
+        let balance_before = token_client.balance(&contract);
+
+        let redeemed = match &position {
+            Some(position) if position.shares == 0 => true,
+            Some(position) if position.kind == VaultKind::Shares => matches!(
+                ShareVaultClient::new(env, vault).try_redeem(&contract, &position.shares),
+                Ok(Ok(_))
+            ),
+            _ => {
+                principal == 0
+                    || matches!(
+                        VaultClient::new(env, vault).try_withdraw(&principal),
+                        Ok(Ok(_))
+                    )
+            }
+        };
+        if !redeemed {
+            return None;
+        }
+
+        let recovered = token_client.balance(&contract) - balance_before;
+        let shortfall = (principal - recovered).max(0);
+        Self::pay_vault_yield(env, stream, recovered - principal);
+        if shortfall > 0 {
+            let written_off =
+                shortfall.min(stream.total_amount.saturating_sub(stream.withdrawn_amount));
+            stream.total_amount -= written_off;
+            storage::update_stats(env, -written_off, &stream.sender, &stream.receiver);
+        }
+        stream.vault_address = None;
+        stream.is_pending = false;
+        storage::set_stream(env, stream_id, stream);
+        storage::remove_vault_position(env, stream_id);
+        storage::remove_vault_stream(env, vault, stream_id);
+        let deposits = storage::get_vault_deposits(env, vault);
+        storage::set_vault_deposits(env, vault, (deposits - principal).max(0));
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(env);
+        data.push_back(stream_id.into_val(env));
+        data.push_back(vault.clone().into_val(env));
+        data.push_back(principal.into_val(env));
+        data.push_back(recovered.into_val(env));
+        data.push_back(shortfall.into_val(env));
+        env.events().publish(
+            (stream_id, symbol_short!("vlt_out")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("vlt_out"),
+                data,
+            },
+        );
+        Some((principal, recovered, shortfall))
+    }
+
+    /// Pull up to `amount` of an allowance stream's accrual from the sender
+    /// into the contract, as far as the sender's allowance and balance cover
+    /// it, and take the protocol fee from what was pulled. Returns the amount
+    /// pulled and what is left to pay out.
+    fn pull_allowance(
+        env: &Env,
+        stream: &StreamV2,
+        funding: &AllowanceFunding,
+        amount: i128,
+    ) -> Result<(i128, i128), Error> {
+        let pulled = amount.min(Self::allowance_available(env, stream));
+        if pulled <= 0 {
+            return Ok((0, 0));
+        }
+        let contract = env.current_contract_address();
+        soroban_sdk::token::TokenClient::new(env, &stream.token).transfer_from(
+            &contract,
+            &stream.sender,
+            &contract,
+            &pulled,
+        );
+        let net = Self::apply_protocol_fee(env, &stream.token, pulled, &funding.affiliate)?;
+        Ok((pulled, net))
+    }
+
+    /// What an allowance stream can currently pull: the lesser of the
+    /// sender's allowance to this contract and its balance.
+    fn allowance_available(env: &Env, stream: &StreamV2) -> i128 {
+        let token_client = soroban_sdk::token::TokenClient::new(env, &stream.token);
+        token_client
+            .allowance(&stream.sender, &env.current_contract_address())
+            .min(token_client.balance(&stream.sender))
+            .max(0)
+    }
+
+    fn emit_allowance_event(
+        env: &Env,
+        stream_id: u64,
+        stream: &StreamV2,
+        action: Symbol,
+        shortfall: i128,
+    ) {
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(env);
+        data.push_back(stream_id.into_val(env));
+        data.push_back(stream.sender.clone().into_val(env));
+        data.push_back(shortfall.into_val(env));
+        data.push_back(now.into_val(env));
+        env.events().publish(
+            (stream_id, action.clone()),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action,
+                data,
+            },
+        );
+    }
+
+    /// Transfer `amount` of `token` (the stream token, or the asset an
+    /// unlocked slice was swapped into) to the stream's beneficiary, routing
+    /// the configured split (Issue #411) and reporting any dust.
+    fn pay_beneficiary(
+        env: &Env,
+        stream_id: u64,
+        stream: &StreamV2,
+        token: &Address,
+        amount: i128,
+    ) {
+        let token_client = soroban_sdk::token::TokenClient::new(env, token);
         let to_beneficiary = if stream.split_bps > 0 {
             if let Some(ref split_addr) = stream.split_address.clone() {
-                let calc_numerator = to_withdraw * stream.split_bps as i128;
+                let calc_numerator = amount * stream.split_bps as i128;
                 let split_amount = calc_numerator / 10_000;
                 let dust_amount = calc_numerator % 10_000;
-                let remainder = to_withdraw - split_amount;
+                let remainder = amount - split_amount;
 
                 if split_amount > 0 {
                     token_client.transfer(
@@ -1259,23 +2198,23 @@ impl Contract {
 
                 if dust_amount > 0 {
                     let now = env.ledger().timestamp();
-                    let mut dust_data: Vec<soroban_sdk::Val> = Vec::new(&env);
-                    dust_data.push_back(stream_id.into_val(&env));
-                    dust_data.push_back(stream.token.clone().into_val(&env));
-                    dust_data.push_back(split_addr.clone().into_val(&env));
-                    dust_data.push_back(stream.split_bps.into_val(&env));
-                    dust_data.push_back(to_withdraw.into_val(&env));
-                    dust_data.push_back(split_amount.into_val(&env));
-                    dust_data.push_back(dust_amount.into_val(&env));
-                    dust_data.push_back(now.into_val(&env));
+                    let mut dust_data: Vec<soroban_sdk::Val> = Vec::new(env);
+                    dust_data.push_back(stream_id.into_val(env));
+                    dust_data.push_back(token.clone().into_val(env));
+                    dust_data.push_back(split_addr.clone().into_val(env));
+                    dust_data.push_back(stream.split_bps.into_val(env));
+                    dust_data.push_back(amount.into_val(env));
+                    dust_data.push_back(split_amount.into_val(env));
+                    dust_data.push_back(dust_amount.into_val(env));
+                    dust_data.push_back(now.into_val(env));
                     env.events().publish(
                         (stream_id, symbol_short!("dust")),
                         DustAccumulatedEvent {
                             stream_id,
-                            token: stream.token.clone(),
+                            token: token.clone(),
                             split_address: split_addr.clone(),
                             split_bps: stream.split_bps,
-                            to_withdraw,
+                            to_withdraw: amount,
                             split_amount,
                             dust_amount,
                             timestamp: now,
@@ -1285,44 +2224,41 @@ impl Contract {
 
                 remainder
             } else {
-                to_withdraw
+                amount
             }
         } else {
-            to_withdraw
+            amount
         };
 
-        token_client.transfer(
+        Self::deliver_to_beneficiary(env, stream_id, stream, token, to_beneficiary);
+    }
+
+    /// Transfer `amount` of `token` to a stream's beneficiary, or credit it
+    /// to the beneficiary's claimable balance (see `claim_balance`) if the
+    /// stream's receiver could not hold its token at creation and the
+    /// beneficiary still cannot.
+    fn deliver_to_beneficiary(
+        env: &Env,
+        stream_id: u64,
+        stream: &StreamV2,
+        token: &Address,
+        amount: i128,
+    ) {
+        if amount <= 0 {
+            return;
+        }
+        if storage::has_claimable_payouts(env, stream_id)
+            && !Self::can_hold(env, token, &stream.beneficiary)
+        {
+            let claimable = storage::get_claimable(env, &stream.beneficiary, token);
+            storage::set_claimable(env, &stream.beneficiary, token, claimable + amount);
+            return;
+        }
+        soroban_sdk::token::TokenClient::new(env, token).transfer(
             &env.current_contract_address(),
             &stream.beneficiary,
-            &to_beneficiary,
-        );
-
-        // Update state
-        stream.withdrawn_amount += to_withdraw;
-        stream.is_pending = false; // Successfully withdrawn, any previous pending status cleared
-        storage::set_stream(&env, stream_id, &stream);
-
-        // Update analytics (TVL decreased)
-        storage::update_stats(&env, -to_withdraw, &stream.sender, &stream.receiver);
-
-        let mut data = Vec::new(&env);
-        data.push_back(stream_id.into_val(&env));
-        data.push_back(stream.beneficiary.clone().into_val(&env));
-        data.push_back(to_withdraw.into_val(&env));
-        data.push_back(stream.withdrawn_amount.into_val(&env));
-        data.push_back(now.into_val(&env));
-
-        env.events().publish(
-            (stream_id, symbol_short!("claim")),
-            NebulaEvent {
-                version: 2,
-                timestamp: now,
-                action: symbol_short!("claim"),
-                data,
-            },
+            &amount,
         );
-
-        Ok(to_withdraw)
     }
 
     // ----------------------------------------------------------------
@@ -1332,29 +2268,29 @@ impl Contract {
     /// Withdraw from a stream using a signed message, allowing a relayer to
     /// pay the gas fee in exchange for a small fee in the streamed asset.
     ///
-    /// The receiver signs a "Withdrawal Intent" off-chain containing:
-    /// - stream_id, withdrawal_amount, relayer_fee, nonce, deadline
+    /// The beneficiary signs a typed `Permit` for `withdraw_meta` whose
+    /// arguments are a `MetaWithdrawal` (stream_id, withdrawal_amount,
+    /// relayer_fee, relayer).
     ///
-    /// The relayer (env.invoker()) submits the transaction and receives
-    /// `relayer_fee` from the withdrawn amount. The receiver gets the rest.
+    /// The relayer submits the transaction and receives `relayer_fee` from
+    /// the withdrawn amount. The beneficiary gets the rest.
     ///
     /// # Parameters
     /// - `stream_id`: The stream to withdraw from
     /// - `withdrawal_amount`: Total amount to withdraw (must be <= unlocked)
     /// - `relayer_fee`: Fee paid to the relayer (must be < withdrawal_amount)
-    /// - `nonce`: Unique nonce to prevent replay attacks
-    /// - `deadline`: Unix timestamp after which the signature expires
-    /// - `signature`: Ed25519 signature from the receiver's public key
+    /// - `nonce`: The beneficiary's permit nonce
+    /// - `deadline`: Unix timestamp after which the permit expires
+    /// - `signature`: The beneficiary's authorization of the permit
     pub fn withdraw_meta(
         env: Env,
         stream_id: u64,
         withdrawal_amount: i128,
         relayer_fee: i128,
         relayer: Address,
-        beneficiary_pubkey: soroban_sdk::BytesN<32>,
         nonce: u64,
         deadline: u64,
-        signature: soroban_sdk::BytesN<64>,
+        signature: PermitSignature,
     ) -> Result<i128, Error> {
         Self::require_not_paused_only(&env)?;
 
@@ -1381,21 +2317,9 @@ impl Contract {
             return Err(Error::AlreadyCancelled);
         }
 
-        // Verify nonce to prevent replay attacks
-        let nonce_key = (
-            symbol_short!("W_NONCE"),
-            stream.beneficiary.clone(),
-            stream_id,
-        );
-        let stored_nonce: u64 = env.storage().instance().get(&nonce_key).unwrap_or(0u64);
-
-        if nonce != stored_nonce {
-            return Err(Error::InvalidNonce);
-        }
-
         // Calculate unlocked amount (Issue #403 — nanosecond domain)
         let unlocked =
-            Self::calculate_unlocked_internal(&stream, Self::ledger_timestamp_nanos(&env));
+            Self::calculate_unlocked_internal(&stream, Self::stream_clock_nanos(&env, &stream));
         let available = unlocked.saturating_sub(stream.withdrawn_amount);
 
         if withdrawal_amount > available {
@@ -1406,37 +2330,26 @@ impl Contract {
             return Err(Error::NothingToWithdraw);
         }
 
-        // Construct the message that was signed
-        let mut msg = soroban_sdk::Bytes::new(&env);
-        msg.extend_from_slice(b"STELLARSTREAM_WITHDRAW_META_V2");
-        msg.append(&env.current_contract_address().to_xdr(&env));
-        msg.extend_from_slice(&stream_id.to_be_bytes());
-        msg.extend_from_slice(&withdrawal_amount.to_be_bytes());
-        msg.extend_from_slice(&relayer_fee.to_be_bytes());
-        msg.extend_from_slice(&nonce.to_be_bytes());
-        msg.extend_from_slice(&deadline.to_be_bytes());
-
-        let msg_hash: soroban_sdk::BytesN<32> = env.crypto().sha256(&msg).into();
-
-        // Use the provided beneficiary public key for signature verification
-        let beneficiary_pubkey = beneficiary_pubkey.clone();
-
-        // Verify the signature
-        env.crypto()
-            .ed25519_verify(&beneficiary_pubkey, &msg_hash.into(), &signature);
-
-        // Increment nonce to prevent replay
-        env.storage()
-            .instance()
-            .set(&nonce_key, &(stored_nonce + 1));
+        let intent = MetaWithdrawal {
+            stream_id,
+            withdrawal_amount,
+            relayer_fee,
+            relayer: relayer.clone(),
+        };
+        Self::verify_permit(
+            &env,
+            Symbol::new(&env, "withdraw_meta"),
+            &stream.beneficiary,
+            nonce,
+            deadline,
+            intent.to_xdr(&env),
+            &signature,
+        )?;
 
         // If Yield-Bearing, withdraw principal from Vault
         if stream.yield_enabled {
             if let Some(vault_addr) = &stream.vault_address {
-                let vault_client = VaultClient::new(&env, vault_addr);
-                let result = vault_client.try_withdraw(&withdrawal_amount);
-
-                if result.is_err() {
+                if !Self::vault_redeem(&env, stream_id, &stream, vault_addr, withdrawal_amount)? {
                     stream.is_pending = true;
                     storage::set_stream(&env, stream_id, &stream);
                     return Ok(0);
@@ -1444,19 +2357,29 @@ impl Contract {
             }
         }
 
+        // Allowance streams must cover a relayed withdrawal in full.
+        let funding = storage::get_allowance_funding(&env, stream_id);
+        let payable = match &funding {
+            Some(funding) => {
+                let (pulled, net) =
+                    Self::pull_allowance(&env, &stream, funding, withdrawal_amount)?;
+                if pulled < withdrawal_amount {
+                    return Err(Error::InsufficientBalance);
+                }
+                net
+            }
+            None => withdrawal_amount,
+        };
+
         // Calculate amounts
-        let to_receiver = withdrawal_amount - relayer_fee;
+        let to_receiver = payable - relayer_fee;
+        if to_receiver < 0 {
+            return Err(Error::InvalidRelayerFee);
+        }
 
         // Perform transfers
         let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
-
-        if to_receiver > 0 {
-            token_client.transfer(
-                &env.current_contract_address(),
-                &stream.beneficiary,
-                &to_receiver,
-            );
-        }
+        Self::deliver_to_beneficiary(&env, stream_id, &stream, &stream.token, to_receiver);
 
         if relayer_fee > 0 {
             token_client.transfer(&env.current_contract_address(), &relayer, &relayer_fee);
@@ -1468,7 +2391,9 @@ impl Contract {
         storage::set_stream(&env, stream_id, &stream);
 
         // Update analytics (TVL decreased)
-        storage::update_stats(&env, -withdrawal_amount, &stream.sender, &stream.receiver);
+        if funding.is_none() {
+            storage::update_stats(&env, -withdrawal_amount, &stream.sender, &stream.receiver);
+        }
 
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
@@ -1522,7 +2447,7 @@ impl Contract {
     pub fn cancel(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
         Self::require_not_paused(&env)?;
 
-        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
 
         if stream.cancelled {
             return Err(Error::AlreadyCancelled);
@@ -1540,30 +2465,59 @@ impl Contract {
             caller.require_auth();
         }
 
+        Self::cancel_internal(&env, stream_id, stream, &caller, 0).map(|_| ())
+    }
+
+    /// Body of `cancel` once the caller is authorized. `relayer_fee` is held
+    /// back from the caller's share for the relayer of a meta-transaction.
+    /// Returns the amounts owed to the beneficiary and to the sender, before
+    /// that fee.
+    fn cancel_internal(
+        env: &Env,
+        stream_id: u64,
+        mut stream: StreamV2,
+        caller: &Address,
+        relayer_fee: i128,
+    ) -> Result<(i128, i128), Error> {
+        let env = env.clone();
         let now = env.ledger().timestamp();
         let unlocked =
-            Self::calculate_unlocked_internal(&stream, Self::ledger_timestamp_nanos(&env));
+            Self::calculate_unlocked_internal(&stream, Self::stream_clock_nanos(&env, &stream));
         let earned = unlocked.saturating_sub(stream.withdrawn_amount);
         let sender_remaining = stream.total_amount.saturating_sub(unlocked);
 
         // Apply breakup penalty if the sender is cancelling and penalty_bps > 0.
-        let penalty = if caller == stream.sender && stream.penalty_bps > 0 {
+        let penalty = if *caller == stream.sender && stream.penalty_bps > 0 {
             (sender_remaining * stream.penalty_bps as i128) / 10_000
         } else {
             0
         };
 
-        let to_receiver = earned + penalty;
-        let to_sender = sender_remaining.saturating_sub(penalty);
+        let funding = storage::get_allowance_funding(&env, stream_id);
+        let mut pulled = 0;
+        let (to_receiver, to_sender) = match &funding {
+            // Nothing is escrowed: the beneficiary gets what the sender still covers.
+            Some(funding) => {
+                let (paid, net) = Self::pull_allowance(&env, &stream, funding, earned + penalty)?;
+                pulled = paid;
+                (net, 0)
+            }
+            None => (earned + penalty, sender_remaining.saturating_sub(penalty)),
+        };
         let total_remaining = to_receiver + to_sender;
+        let (paid_receiver, paid_sender) = if *caller == stream.sender {
+            (to_receiver, to_sender - relayer_fee)
+        } else {
+            (to_receiver - relayer_fee, to_sender)
+        };
+        if paid_receiver < 0 || paid_sender < 0 {
+            return Err(Error::InvalidRelayerFee);
+        }
 
         // If Yield-Bearing, withdraw total remaining from Vault
         if stream.yield_enabled {
             if let Some(vault_addr) = &stream.vault_address {
-                let vault_client = VaultClient::new(&env, vault_addr);
-                let result = vault_client.try_withdraw(&total_remaining);
-
-                if result.is_err() {
+                if !Self::vault_redeem(&env, stream_id, &stream, vault_addr, total_remaining)? {
                     stream.is_pending = true;
                     storage::set_stream(&env, stream_id, &stream);
                     return Err(Error::ContractPaused);
@@ -1571,31 +2525,50 @@ impl Contract {
             }
         }
 
-        stream.withdrawn_amount = unlocked;
+        match funding {
+            // The sender stays liable for what its allowance did not cover:
+            // it remains withdrawable, and the grace period starts now.
+            Some(mut funding) => {
+                let owed = earned + penalty;
+                stream.total_amount = stream.withdrawn_amount + owed;
+                stream.withdrawn_amount += pulled;
+                if pulled < owed && funding.delinquent_since == 0 {
+                    funding.delinquent_since = now;
+                    storage::set_allowance_funding(&env, stream_id, &funding);
+                    Self::emit_allowance_event(
+                        &env,
+                        stream_id,
+                        &stream,
+                        symbol_short!("delinqnt"),
+                        owed - pulled,
+                    );
+                }
+            }
+            None => stream.withdrawn_amount = unlocked,
+        }
         stream.cancelled = true;
         storage::set_stream(&env, stream_id, &stream);
+        storage::remove_owner_position(&env, &stream.beneficiary, stream_id);
 
         // Sanctions check (#937): query oracle for each recipient before transferring.
         Self::check_not_sanctioned(&env, &stream.beneficiary)?;
         Self::check_not_sanctioned(&env, &stream.sender)?;
 
         let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
-        if to_receiver > 0 {
+        Self::deliver_to_beneficiary(&env, stream_id, &stream, &stream.token, paid_receiver);
+        if paid_sender > 0 {
             token_client.transfer(
                 &env.current_contract_address(),
-                &stream.beneficiary,
-                &to_receiver,
+                &stream.sender,
+                &paid_sender,
             );
         }
-        if to_sender > 0 {
-            token_client.transfer(&env.current_contract_address(), &stream.sender, &to_sender);
-        }
 
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
         data.push_back(caller.clone().into_val(&env));
-        data.push_back(to_receiver.into_val(&env));
-        data.push_back(to_sender.into_val(&env));
+        data.push_back(paid_receiver.into_val(&env));
+        data.push_back(paid_sender.into_val(&env));
         data.push_back(now.into_val(&env));
 
         env.events().publish(
@@ -1608,7 +2581,7 @@ impl Contract {
             },
         );
 
-        Ok(())
+        Ok((to_receiver, to_sender))
     }
 
     /// Archive and recover storage rent for a fully-withdrawn stream (Issue #380).
@@ -1624,19 +2597,46 @@ impl Contract {
         }
         caller.require_auth();
 
-        if stream.withdrawn_amount != stream.total_amount {
-            return Err(Error::StreamNotFullyWithdrawn);
-        }
+        Self::archive_stream(&env, stream_id, &stream, &caller)
+    }
+
+    /// Remove a fully withdrawn stream and its position bookkeeping, returning
+    /// any unused keeper buffer to the sender.
+    fn archive_stream(
+        env: &Env,
+        stream_id: u64,
+        stream: &StreamV2,
+        caller: &Address,
+    ) -> Result<(), Error> {
+        if stream.withdrawn_amount != stream.total_amount {
+            return Err(Error::StreamNotFullyWithdrawn);
+        }
 
+        let env = env.clone();
         let key = storage::DataKeyV2::Stream(stream_id);
         env.storage().persistent().remove(&key);
+        storage::remove_curve_schedule(&env, stream_id);
+        storage::remove_swap_schedule(&env, stream_id);
+        storage::remove_vault_position(&env, stream_id);
+        storage::remove_allowance_funding(&env, stream_id);
+        storage::remove_claimable_payouts(&env, stream_id);
+        storage::set_position_approval(&env, stream_id, &None);
+        storage::remove_owner_position(&env, &stream.beneficiary, stream_id);
+
+        // Return any unused keeper buffer to the sender.
+        let leftover = storage::get_keeper_buffer(&env, stream_id);
+        if leftover > 0 {
+            let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
+            token_client.transfer(&env.current_contract_address(), &stream.sender, &leftover);
+        }
+        storage::clear_keeper_stream(&env, stream_id);
 
         let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
         data.push_back(stream.total_amount.into_val(&env));
         data.push_back(stream.withdrawn_amount.into_val(&env));
-        data.push_back(caller.into_val(&env));
+        data.push_back(caller.clone().into_val(&env));
         data.push_back(now.into_val(&env));
 
         env.events().publish(
@@ -1652,6 +2652,8 @@ impl Contract {
         Ok(())
     }
 
+    /// Move the stream position to `new_beneficiary`. Only the current
+    /// beneficiary may call this; operators use `transfer_from`.
     pub fn transfer_beneficiary(
         env: Env,
         stream_id: u64,
@@ -1659,22 +2661,203 @@ impl Contract {
     ) -> Result<(), Error> {
         Self::require_not_paused(&env)?;
 
-        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        stream.beneficiary.require_auth();
+
+        Self::transfer_position(&env, stream_id, stream, new_beneficiary)
+    }
+
+    // ----------------------------------------------------------------
+    // Transferable Stream Positions (NFT-style ownership)
+    // ----------------------------------------------------------------
+
+    /// Current owner of a stream position, i.e. its beneficiary.
+    pub fn owner_of(env: Env, stream_id: u64) -> Result<Address, Error> {
+        storage::get_stream(&env, stream_id)
+            .map(|stream| stream.beneficiary)
+            .ok_or(Error::StreamNotFound)
+    }
+
+    /// Up to `limit` (at most `MAX_PAGE_SIZE`) stream IDs owned by `owner`,
+    /// starting at position `start`. Closing a stream moves the owner's last
+    /// position into its place, so the order is not stable. Streams created
+    /// before positions were indexed are listed once `track_position` has
+    /// been called for them.
+    pub fn positions_of(env: Env, owner: Address, start: u32, limit: u32) -> Vec<u64> {
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(storage::get_owner_position_count(&env, &owner));
+        let mut positions = Vec::new(&env);
+        for slot in start..end {
+            if let Some(stream_id) = storage::get_owner_position(&env, &owner, slot) {
+                positions.push_back(stream_id);
+            }
+        }
+        positions
+    }
+
+    /// Number of indexed stream positions owned by `owner` (see
+    /// `positions_of`).
+    pub fn balance_of(env: Env, owner: Address) -> u32 {
+        storage::get_owner_position_count(&env, &owner)
+    }
+
+    /// Index a stream created before positions were indexed under its
+    /// beneficiary, so `positions_of` and `balance_of` see it. Anyone may
+    /// call this; it only records who already owns the stream.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`: If the stream does not exist
+    /// - `StreamNotMigratable`: If the stream is cancelled or already indexed
+    pub fn track_position(env: Env, stream_id: u64) -> Result<(), Error> {
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        if stream.cancelled || storage::has_owner_position(&env, &stream.beneficiary, stream_id) {
+            return Err(Error::StreamNotMigratable);
+        }
+        storage::add_owner_position(&env, &stream.beneficiary, stream_id);
+        Ok(())
+    }
+
+    /// Approve `operator` to transfer a single position, or clear the approval
+    /// with `None`. The approval is cleared automatically on transfer.
+    pub fn approve(env: Env, stream_id: u64, operator: Option<Address>) -> Result<(), Error> {
+        Self::require_not_paused(&env)?;
 
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
         stream.beneficiary.require_auth();
 
-        let previous_beneficiary = stream.beneficiary.clone();
-        stream.beneficiary = new_beneficiary.clone();
+        if stream.is_soulbound {
+            return Err(Error::StreamNotTransferable);
+        }
 
-        storage::set_stream(&env, stream_id, &stream);
+        storage::set_position_approval(&env, stream_id, &operator);
 
         let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
-        data.push_back(previous_beneficiary.into_val(&env));
-        data.push_back(new_beneficiary.into_val(&env));
+        data.push_back(stream.beneficiary.into_val(&env));
+        data.push_back(operator.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (stream_id, symbol_short!("approve")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("approve"),
+                data,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Operator approved for a single position, if any.
+    pub fn get_approved(env: Env, stream_id: u64) -> Option<Address> {
+        storage::get_position_approval(&env, stream_id)
+    }
+
+    /// Grant or revoke `operator` the right to transfer every position
+    /// owned by `owner`, including ones received later.
+    pub fn set_approval_for_all(
+        env: Env,
+        owner: Address,
+        operator: Address,
+        approved: bool,
+    ) -> Result<(), Error> {
+        Self::require_not_paused(&env)?;
+        owner.require_auth();
+
+        storage::set_operator_approval(&env, &owner, &operator, approved);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(owner.clone().into_val(&env));
+        data.push_back(operator.into_val(&env));
+        data.push_back(approved.into_val(&env));
         data.push_back(now.into_val(&env));
 
+        env.events().publish(
+            (owner, symbol_short!("appr_all")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("appr_all"),
+                data,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn is_approved_for_all(env: Env, owner: Address, operator: Address) -> bool {
+        storage::is_operator_approved(&env, &owner, &operator)
+    }
+
+    /// Transfer a position from `from` to `to` on behalf of `spender`, who must
+    /// be the owner, the position's approved operator, or an operator for all
+    /// of the owner's positions.
+    pub fn transfer_from(
+        env: Env,
+        spender: Address,
+        from: Address,
+        to: Address,
+        stream_id: u64,
+    ) -> Result<(), Error> {
+        Self::require_not_paused(&env)?;
+        spender.require_auth();
+
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        if stream.beneficiary != from {
+            return Err(Error::UnauthorizedSender);
+        }
+
+        let authorized = spender == from
+            || storage::get_position_approval(&env, stream_id) == Some(spender.clone())
+            || storage::is_operator_approved(&env, &from, &spender);
+        if !authorized {
+            return Err(Error::UnauthorizedSender);
+        }
+
+        Self::transfer_position(&env, stream_id, stream, to)
+    }
+
+    /// Shared transfer path: enforces the soulbound flag and sanctions and
+    /// compliance checks, clears the single-position approval and moves the
+    /// owner index.
+    fn transfer_position(
+        env: &Env,
+        stream_id: u64,
+        mut stream: StreamV2,
+        new_beneficiary: Address,
+    ) -> Result<(), Error> {
+        if stream.is_soulbound {
+            return Err(Error::StreamNotTransferable);
+        }
+        // Closed streams have left the owner index; there is nothing to move.
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+
+        Self::check_not_sanctioned(env, &stream.beneficiary)?;
+        Self::check_not_sanctioned(env, &new_beneficiary)?;
+        Self::require_compliant(env, &new_beneficiary)?;
+
+        let previous_beneficiary = stream.beneficiary.clone();
+        stream.beneficiary = new_beneficiary.clone();
+
+        storage::set_stream(env, stream_id, &stream);
+        storage::set_position_approval(env, stream_id, &None);
+        storage::remove_owner_position(env, &previous_beneficiary, stream_id);
+        storage::add_owner_position(env, &new_beneficiary, stream_id);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(env);
+        data.push_back(stream_id.into_val(env));
+        data.push_back(previous_beneficiary.into_val(env));
+        data.push_back(new_beneficiary.into_val(env));
+        data.push_back(now.into_val(env));
+
         env.events().publish(
             (stream_id, symbol_short!("benefic")),
             NebulaEvent {
@@ -1707,7 +2890,7 @@ impl Contract {
             return Err(Error::InvalidPenalty);
         }
 
-        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
 
         if stream.cancelled {
             return Err(Error::AlreadyCancelled);
@@ -1715,9 +2898,21 @@ impl Contract {
 
         stream.beneficiary.require_auth();
 
+        Self::set_split_internal(&env, stream_id, stream, split_address, split_bps);
+        Ok(())
+    }
+
+    /// Body of `split_stream` once the beneficiary is authorized.
+    fn set_split_internal(
+        env: &Env,
+        stream_id: u64,
+        mut stream: StreamV2,
+        split_address: Option<Address>,
+        split_bps: u32,
+    ) {
         stream.split_address = split_address.clone();
         stream.split_bps = split_bps;
-        storage::set_stream(&env, stream_id, &stream);
+        storage::set_stream(env, stream_id, &stream);
 
         let now = env.ledger().timestamp();
         env.events().publish(
@@ -1730,8 +2925,6 @@ impl Contract {
                 timestamp: now,
             },
         );
-
-        Ok(())
     }
 
     /// Calculate the unlocked token amount for a stream at time `now_nanos`.
@@ -1789,13 +2982,17 @@ impl Contract {
             }
 
             numerator / denominator
-        } else if stream.curve_type == 1 {
-            // Exponential (back-loaded): unlocked = total * (elapsed / duration)^2
-            math::calculate_exponential_unlocked(stream.total_amount, duration, elapsed)
         } else {
-            // Issue #403 — Smooth-Flow: use calculate_flow (backed by mul_div)
-            // for overflow-safe, precision-preserving linear unlocking.
-            math::calculate_flow(stream.total_amount, duration, elapsed)
+            // Issue #403 — Smooth-Flow: every curve goes through the mul_div
+            // helpers for overflow-safe, precision-preserving unlocking.
+            // Piecewise offsets are in seconds (or ledgers), elapsed in nanos.
+            math::calculate_curve_unlocked(
+                &stream.curve_type,
+                stream.total_amount,
+                duration,
+                elapsed,
+                nps as i128,
+            )
         }
     }
 
@@ -1844,6 +3041,7 @@ impl Contract {
     /// - `NotStreamOwner`: If the caller is not the stream sender
     /// - `AlreadyCancelled`: If the stream has been cancelled
     /// - `BelowDustThreshold`: If the extra amount is <= 0
+    /// - `InvalidTimeRange`: If the stream follows a piecewise schedule
     pub fn top_up(
         env: Env,
         stream_id: u64,
@@ -1851,8 +3049,23 @@ impl Contract {
         extra_amount: i128,
     ) -> Result<(), Error> {
         Self::require_not_paused(&env)?;
-        Self::require_not_emergency(&env)?;
         sender.require_auth();
+        Self::top_up_internal(&env, stream_id, &sender, extra_amount, None)
+    }
+
+    /// Body of `top_up` once the sender is authorized. With `relayer_fee`
+    /// the funds are pulled from the sender's allowance to this contract
+    /// instead of a direct transfer, and the fee is held back from them for
+    /// the relayer of a meta-transaction.
+    fn top_up_internal(
+        env: &Env,
+        stream_id: u64,
+        sender: &Address,
+        extra_amount: i128,
+        relayer_fee: Option<i128>,
+    ) -> Result<(), Error> {
+        let env = env.clone();
+        Self::require_not_emergency(&env)?;
 
         if extra_amount <= 0 {
             return Err(Error::BelowDustThreshold);
@@ -1860,7 +3073,7 @@ impl Contract {
 
         let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
 
-        if stream.sender != sender {
+        if stream.sender != *sender {
             return Err(Error::UnauthorizedSender);
         }
 
@@ -1868,24 +3081,38 @@ impl Contract {
             .total_amount
             .checked_add(extra_amount)
             .ok_or(Error::Overflow)?;
-        if new_total_amount > MAX_STREAM_AMOUNT {
+        if new_total_amount > Self::token_limits(&env, &stream.token).max_stream_amount {
             return Err(Error::AmountOverflow);
         }
 
         if stream.cancelled {
             return Err(Error::AlreadyCancelled);
         }
+        Self::require_reshapeable(&stream)?;
 
         let now = env.ledger().timestamp();
 
         // Checkpoint: calculate what's already unlocked so the rate stays consistent.
         let unlocked_at_now =
-            Self::calculate_unlocked_internal(&stream, Self::ledger_timestamp_nanos(&env));
+            Self::calculate_unlocked_internal(&stream, Self::stream_clock_nanos(&env, &stream));
         let remaining = stream.total_amount.saturating_sub(unlocked_at_now);
 
-        // Pull the new funds into the contract.
+        // Pull the new funds into the contract; allowance streams only raise their cap.
+        let escrowed = storage::get_allowance_funding(&env, stream_id).is_none();
         let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
-        token_client.transfer(&sender, &env.current_contract_address(), &extra_amount);
+        let contract = env.current_contract_address();
+        match relayer_fee {
+            Some(fee) if fee > 0 && !escrowed => return Err(Error::InvalidRelayerFee),
+            Some(_) if escrowed => {
+                token_client.transfer_from(&contract, sender, &contract, &extra_amount)
+            }
+            None if escrowed => token_client.transfer(sender, &contract, &extra_amount),
+            _ => {}
+        }
+        let extra_amount = extra_amount - relayer_fee.unwrap_or(0);
+        if extra_amount <= 0 {
+            return Err(Error::InvalidRelayerFee);
+        }
 
         // Extend end_time proportionally: keep the same rate over the new remaining balance.
         let duration = (stream.end_time - stream.start_time) as i128;
@@ -1893,14 +3120,16 @@ impl Contract {
         let rate = stream.total_amount; // tokens per `duration` seconds
                                         // new_end_time = now + (new_remaining * duration / rate)
         let extra_seconds = (new_remaining * duration) / rate;
-        let new_end_time = now + extra_seconds as u64;
+        let new_end_time = Self::stream_now(&env, &stream) + extra_seconds as u64;
 
         stream.total_amount += extra_amount;
         stream.end_time = new_end_time;
         storage::set_stream(&env, stream_id, &stream);
 
         // Update TVL.
-        storage::update_stats(&env, extra_amount, &stream.sender, &stream.receiver);
+        if escrowed {
+            storage::update_stats(&env, extra_amount, &stream.sender, &stream.receiver);
+        }
 
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
@@ -2032,14 +3261,6 @@ impl Contract {
     // Issue #937 — Sanctions Oracle Interface
     // ----------------------------------------------------------------
 
-    /// Set the address of the external sanctions oracle. Admin-only.
-    pub fn set_oracle_address(env: Env, oracle: Address) -> Result<(), Error> {
-        let admin = storage::try_get_admin(&env)?;
-        admin.require_auth();
-        storage::set_oracle_address(&env, &oracle);
-        Ok(())
-    }
-
     pub fn get_oracle_address(env: Env) -> Option<Address> {
         storage::get_oracle_address(&env)
     }
@@ -2149,7 +3370,9 @@ impl Contract {
     ///
     /// # Returns
     /// - `(contract_balance, sum_remaining)`: Tuple of actual contract balance
-    ///   and sum of all active stream remaining balances
+    ///   and sum of all active stream remaining balances plus prepaid keeper
+    ///   buffers, claimable payouts and held balances (pending fees, bridge
+    ///   refunds, voter locks and gas buffers)
     ///
     /// # Usage
     /// ```rust
@@ -2164,20 +3387,73 @@ impl Contract {
 
         for i in 0..total_streams {
             if let Some(stream) = storage::get_stream(&env, i) {
-                if !stream.cancelled && stream.token == token {
+                // Allowance streams hold nothing in the contract.
+                if !stream.cancelled
+                    && stream.token == token
+                    && storage::get_allowance_funding(&env, i).is_none()
+                {
                     let remaining = stream.total_amount.saturating_sub(stream.withdrawn_amount);
                     sum_remaining = sum_remaining.saturating_add(remaining);
                 }
+                if stream.token == token {
+                    // Prepaid keeper bounties are owed to keepers or the sender.
+                    sum_remaining =
+                        sum_remaining.saturating_add(storage::get_keeper_buffer(&env, i));
+                }
             }
         }
 
+        // Payouts held for receivers that could not take them.
+        sum_remaining = sum_remaining.saturating_add(storage::get_claimable_total(&env, &token));
+        // Fees, bridge refunds, voter locks and gas buffers are owed too.
+        sum_remaining = sum_remaining.saturating_add(storage::get_held_balance(&env, &token));
+
         let token_client = soroban_sdk::token::TokenClient::new(&env, &token);
         let contract_balance = token_client.balance(&env.current_contract_address());
         (contract_balance, sum_remaining)
     }
 
+    /// Count a balance credited before held balances were tracked — pending
+    /// fees, a bridge deposit, a voter lock or a gas buffer — as committed,
+    /// so recovery leaves it in place. Anyone may call this; it only records
+    /// what the entry already holds. Returns the amount now counted.
+    ///
+    /// # Errors
+    /// - `StreamNotMigratable`: If the entry holds nothing or is already
+    ///   counted in full
+    pub fn track_held_balance(env: Env, entry: HeldEntry) -> Result<i128, Error> {
+        let held = match &entry {
+            HeldEntry::PendingFees(recipient, token) => Some((
+                token.clone(),
+                storage::get_pending_fees(&env, recipient, token),
+            )),
+            HeldEntry::AffiliateFees(affiliate, token) => Some((
+                token.clone(),
+                storage::get_affiliate_fees(&env, affiliate, token),
+            )),
+            HeldEntry::BridgeDeposit(deposit_id) => storage::get_bridge_deposit(&env, *deposit_id)
+                .map(|deposit| (deposit.token, deposit.amount)),
+            HeldEntry::VoterLock(holder) => storage::get_dao_token(&env)
+                .zip(storage::get_voter_lock(&env, holder))
+                .map(|(dao_token, lock)| (dao_token, lock.amount)),
+            HeldEntry::GasBuffer(sender, token) => {
+                Some((token.clone(), storage::get_gas_buffer(&env, sender, token)))
+            }
+        };
+        let (token, amount) = match held {
+            Some((token, amount)) if amount > 0 => (token, amount),
+            _ => return Err(Error::StreamNotMigratable),
+        };
+        if storage::get_held_tracked(&env, &entry) == Some((token.clone(), amount)) {
+            return Err(Error::StreamNotMigratable);
+        }
+        storage::track_held(&env, &entry, &token, amount);
+        Ok(amount)
+    }
+
     /// Proportionally reduce all active streams for a token if the contract
-    /// balance is less than the total committed amount.
+    /// balance is less than the total committed amount. `check_solvency` and
+    /// `settle_insolvent_stream` instead protect earned balances first.
     pub fn rebalance_after_clawback(env: Env, token: Address) -> Result<(), Error> {
         let admin = storage::try_get_admin(&env)?;
         admin.require_auth();
@@ -2187,11 +3463,34 @@ impl Contract {
             return Ok(());
         }
 
-        let reduction_factor_bps = (balance * 10000) / sum_remaining;
+        // Only stream escrow is cut; every other committed balance is owed in full.
         let total_streams = storage::get_health(&env).total_v2_streams;
+        let mut streams_remaining: i128 = 0;
+        for i in 0..total_streams {
+            if let Some(stream) = storage::get_stream(&env, i) {
+                if !stream.cancelled
+                    && stream.token == token
+                    && storage::get_allowance_funding(&env, i).is_none()
+                {
+                    streams_remaining = streams_remaining.saturating_add(
+                        stream.total_amount.saturating_sub(stream.withdrawn_amount),
+                    );
+                }
+            }
+        }
+        if streams_remaining == 0 {
+            return Ok(());
+        }
+        let available = balance
+            .saturating_sub(sum_remaining - streams_remaining)
+            .max(0);
+        let reduction_factor_bps = (available * 10000) / streams_remaining;
         for i in 0..total_streams {
             if let Some(mut stream) = storage::get_stream(&env, i) {
-                if !stream.cancelled && stream.token == token {
+                if !stream.cancelled
+                    && stream.token == token
+                    && storage::get_allowance_funding(&env, i).is_none()
+                {
                     let old_remaining = stream.total_amount.saturating_sub(stream.withdrawn_amount);
                     let new_remaining = (old_remaining * reduction_factor_bps) / 10000;
                     stream.total_amount = stream.withdrawn_amount + new_remaining;
@@ -2257,6 +3556,116 @@ impl Contract {
         env.ledger().timestamp() * math::NANOS_PER_SEC
     }
 
+    /// Current reading of an accrual clock: the ledger timestamp in seconds
+    /// (clock 0) or the ledger sequence number (clock 1).
+    fn clock_now(env: &Env, accrual_clock: u32) -> u64 {
+        if accrual_clock == 1 {
+            env.ledger().sequence() as u64
+        } else {
+            env.ledger().timestamp()
+        }
+    }
+
+    /// Current reading of `stream`'s accrual clock, in the units of its
+    /// start/cliff/end times.
+    fn stream_now(env: &Env, stream: &StreamV2) -> u64 {
+        Self::clock_now(env, stream.accrual_clock)
+    }
+
+    /// `stream_now` in the nanosecond domain used by `calculate_unlocked_internal`.
+    /// Ledger-sequence streams accrue one "second" per ledger, so validators
+    /// cannot shift accrual by nudging close times.
+    fn stream_clock_nanos(env: &Env, stream: &StreamV2) -> u64 {
+        if stream.accrual_clock == 1 {
+            Self::stream_now(env, stream) * math::NANOS_PER_SEC
+        } else {
+            Self::ledger_timestamp_nanos(env)
+        }
+    }
+
+    fn validate_accrual_clock(accrual_clock: u32) -> Result<(), Error> {
+        if accrual_clock > 1 {
+            return Err(Error::InvalidTimeRange);
+        }
+        Ok(())
+    }
+
+    /// Streams are unilateral (0) or mutual (1) to cancel.
+    fn validate_cancellation_type(cancellation_type: u32) -> Result<(), Error> {
+        if cancellation_type > 1 {
+            return Err(Error::InvalidPenalty);
+        }
+        Ok(())
+    }
+
+    /// A piecewise schedule needs 1..=`MAX_CURVE_POINTS` points with strictly
+    /// increasing offsets and non-decreasing bps, ending at the stream end
+    /// with 10000 bps.
+    fn validate_curve(curve: &CurveType, start: u64, end: u64) -> Result<(), Error> {
+        let CurveType::Piecewise(points) = curve else {
+            return Ok(());
+        };
+        if points.is_empty() || points.len() > MAX_CURVE_POINTS {
+            return Err(Error::InvalidTimeRange);
+        }
+        let mut prev_offset = 0u64;
+        let mut prev_bps = 0u32;
+        for point in points.iter() {
+            if point.offset <= prev_offset || point.bps < prev_bps || point.bps > 10_000 {
+                return Err(Error::InvalidTimeRange);
+            }
+            prev_offset = point.offset;
+            prev_bps = point.bps;
+        }
+        if prev_offset != end.saturating_sub(start) || prev_bps != 10_000 {
+            return Err(Error::InvalidTimeRange);
+        }
+        Ok(())
+    }
+
+    /// Piecewise points are fixed offsets from the start over a fixed total,
+    /// so changes to a stream's amount or time range would leave them out of
+    /// step with it.
+    fn require_reshapeable(stream: &StreamV2) -> Result<(), Error> {
+        if let CurveType::Piecewise(_) = stream.curve_type {
+            return Err(Error::InvalidTimeRange);
+        }
+        Ok(())
+    }
+
+    /// Length of `[start, end)` in seconds. Ledger-clock durations are
+    /// converted with `LEDGER_CLOSE_SECS` so flow-rate caps stay per second.
+    fn duration_secs(accrual_clock: u32, start: u64, end: u64) -> i128 {
+        let duration = end.saturating_sub(start) as i128;
+        if accrual_clock == 1 {
+            duration * LEDGER_CLOSE_SECS as i128
+        } else {
+            duration
+        }
+    }
+
+    /// Estimate the ledger sequence that will be current at `timestamp`.
+    fn estimate_ledger_at(env: &Env, timestamp: u64) -> u64 {
+        let now = env.ledger().timestamp();
+        let sequence = env.ledger().sequence() as u64;
+        if timestamp >= now {
+            sequence + (timestamp - now) / LEDGER_CLOSE_SECS
+        } else {
+            sequence.saturating_sub((now - timestamp) / LEDGER_CLOSE_SECS)
+        }
+    }
+
+    /// Estimate the ledger timestamp at which `ledger` will close.
+    fn estimate_timestamp_at(env: &Env, ledger: u64) -> u64 {
+        let now = env.ledger().timestamp();
+        let sequence = env.ledger().sequence() as u64;
+        if ledger >= sequence {
+            now + (ledger - sequence) * LEDGER_CLOSE_SECS
+        } else {
+            now.saturating_sub((sequence - ledger) * LEDGER_CLOSE_SECS)
+        }
+    }
+
     /// If a compliance oracle is configured, verify `addr` is not flagged.
     fn require_compliant(env: &Env, addr: &Address) -> Result<(), Error> {
         if let Some(oracle_addr) = storage::get_compliance_oracle(env) {
@@ -2269,19 +3678,78 @@ impl Contract {
     }
 
     /// Validate that the stream amount and flow rate do not exceed security limits.
-    fn validate_limits(amount: i128, start_time: u64, end_time: u64) -> Result<(), Error> {
-        if amount > MAX_STREAM_AMOUNT {
+    fn validate_limits(
+        env: &Env,
+        token: &Address,
+        amount: i128,
+        start_time: u64,
+        end_time: u64,
+        accrual_clock: u32,
+    ) -> Result<(), Error> {
+        let limits = Self::token_limits(env, token);
+        if amount > limits.max_stream_amount {
             return Err(Error::AmountOverflow);
         }
-        let duration = (end_time - start_time) as i128;
+        let duration = Self::duration_secs(accrual_clock, start_time, end_time);
         if duration > 0 {
-            if amount > MAX_FLOW_RATE.saturating_mul(duration) {
+            if amount > limits.max_flow_rate.saturating_mul(duration) {
                 return Err(Error::AmountOverflow);
             }
         }
         Ok(())
     }
 
+    /// Decimals of `token`, cached after the first lookup. Falls back to the
+    /// 7-decimal stroop scale if the asset does not expose `decimals()`.
+    fn token_decimals(env: &Env, token: &Address) -> u32 {
+        if let Some(decimals) = storage::get_token_decimals(env, token) {
+            return decimals;
+        }
+        let decimals = soroban_sdk::token::TokenClient::new(env, token)
+            .try_decimals()
+            .ok()
+            .and_then(|result| result.ok())
+            .unwrap_or(math::BASE_DECIMALS);
+        storage::set_token_decimals(env, token, decimals);
+        decimals
+    }
+
+    /// Effective caps for `token`: the admin override if set, otherwise the
+    /// global XLM limits expressed in the token's decimals.
+    fn token_limits(env: &Env, token: &Address) -> TokenLimits {
+        storage::get_token_limits(env, token).unwrap_or_else(|| {
+            let decimals = Self::token_decimals(env, token);
+            TokenLimits {
+                max_stream_amount: math::scale_to_decimals(MAX_STREAM_AMOUNT, decimals),
+                max_flow_rate: math::scale_to_decimals(MAX_FLOW_RATE, decimals),
+            }
+        })
+    }
+
+    /// Gas buffer fee per split, in the fee token's decimals.
+    fn split_gas_fee(env: &Env) -> i128 {
+        let decimals = storage::get_fee_token(env)
+            .map(|fee_token| Self::token_decimals(env, &fee_token))
+            .unwrap_or(math::BASE_DECIMALS);
+        math::scale_to_decimals(GAS_FEE_PER_SPLIT_STROOPS, decimals)
+    }
+
+    /// Deduct the split gas fee from `payer`'s buffer in the fee token and
+    /// credit it to the treasury.
+    fn charge_split_gas(env: &Env, payer: &Address) -> Result<(), Error> {
+        let fee_token = storage::get_fee_token(env).ok_or(Error::InsufficientGasBuffer)?;
+        let current = storage::get_gas_buffer(env, payer, &fee_token);
+        let gas_fee = Self::split_gas_fee(env);
+        if current < gas_fee {
+            return Err(Error::InsufficientGasBuffer);
+        }
+        storage::set_gas_buffer(env, payer, &fee_token, current - gas_fee);
+        if let Some(treasury) = storage::get_treasury(env) {
+            storage::add_pending_fees(env, &treasury, &fee_token, gas_fee);
+        }
+        Ok(())
+    }
+
     /// Verify that an asset is whitelisted for use with the protocol.
     ///
     /// # Token Security Assumptions
@@ -2306,13 +3774,19 @@ impl Contract {
         Ok(())
     }
 
-    fn apply_protocol_fee(env: &Env, token: &Address, total_amount: i128) -> Result<i128, Error> {
+    fn apply_protocol_fee(
+        env: &Env,
+        token: &Address,
+        total_amount: i128,
+        affiliate: &Option<Address>,
+    ) -> Result<i128, Error> {
         let mut fee_bps = storage::get_fee_bps(env);
 
         // Whale discount: Apply tiered logic if configured
         if let Some(tiers) = storage::get_fee_tiers(env) {
+            let decimals = Self::token_decimals(env, token);
             for tier in tiers.iter() {
-                if total_amount >= tier.threshold {
+                if total_amount >= math::scale_to_decimals(tier.threshold, decimals) {
                     fee_bps = tier.fee_bps;
                 } else {
                     // Since tiers are sorted ascending, we stop at the first threshold not met
@@ -2328,7 +3802,19 @@ impl Contract {
         let treasury = storage::get_treasury(env).ok_or(Error::NoTreasury)?;
         let fee = (total_amount * fee_bps as i128) / 10_000;
         if fee > 0 {
-            storage::add_pending_fees(env, &treasury, token, fee);
+            let affiliate_cut = match affiliate {
+                Some(affiliate) if storage::get_affiliate(env, affiliate) == Some(true) => {
+                    let cut = (fee * storage::get_affiliate_share_bps(env) as i128) / 10_000;
+                    if cut > 0 {
+                        storage::add_affiliate_fees(env, affiliate, token, cut);
+                    }
+                    cut
+                }
+                _ => 0,
+            };
+            if fee > affiliate_cut {
+                storage::add_pending_fees(env, &treasury, token, fee - affiliate_cut);
+            }
         }
 
         Ok(total_amount - fee)
@@ -2362,7 +3848,8 @@ impl Contract {
     /// - `AssetNotWhitelisted`: If the token is not in the whitelist
     /// - `BelowDustThreshold`: If the amount is below the minimum
     /// - `InvalidTimeRange`: If timing parameters are invalid
-    /// - `InvalidPenalty`: If penalty basis points exceed 10,000
+    /// - `InvalidPenalty`: If penalty basis points exceed 10,000 or
+    ///   `cancellation_type` is neither 0 nor 1
     pub fn create_stream(env: Env, args: StreamArgs) -> Result<u64, Error> {
         Self::require_not_paused(&env)?;
         Self::require_not_emergency(&env)?;
@@ -2375,12 +3862,22 @@ impl Contract {
         {
             return Err(Error::InvalidTimeRange);
         }
+        Self::validate_accrual_clock(args.accrual_clock)?;
+        Self::validate_curve(&args.curve_type, args.start_time, args.end_time)?;
 
-        Self::validate_limits(args.total_amount, args.start_time, args.end_time)?;
+        Self::validate_limits(
+            &env,
+            &args.token,
+            args.total_amount,
+            args.start_time,
+            args.end_time,
+            args.accrual_clock,
+        )?;
 
         if args.penalty_bps > 10_000 {
             return Err(Error::InvalidPenalty);
         }
+        Self::validate_cancellation_type(args.cancellation_type)?;
 
         if args.total_amount < storage::get_min_value(&env, &args.token) {
             return Err(Error::BelowDustThreshold);
@@ -2400,15 +3897,15 @@ impl Contract {
             &args.total_amount,
         );
 
-        let stream_amount = Self::apply_protocol_fee(&env, &args.token, args.total_amount)?;
+        let stream_amount =
+            Self::apply_protocol_fee(&env, &args.token, args.total_amount, &args.affiliate)?;
 
         let stream_id = storage::next_stream_id(&env);
 
         let mut vault_used = None;
         if args.yield_enabled {
             if let Some(vault_addr) = &args.vault_address {
-                let vault_client = VaultClient::new(&env, vault_addr);
-                vault_client.deposit(&stream_amount);
+                Self::vault_deposit(&env, stream_id, vault_addr, &args.token, stream_amount)?;
                 vault_used = Some(vault_addr.clone());
             }
         }
@@ -2438,10 +3935,12 @@ impl Contract {
             yield_recipient: args.yield_recipient,
             split_address: args.split_address.clone(),
             split_bps: args.split_bps,
-            curve_type: args.curve_type,
+            curve_type: args.curve_type.clone(),
+            is_soulbound: args.is_soulbound,
+            accrual_clock: args.accrual_clock,
         };
 
-        storage::set_stream(&env, stream_id, &stream);
+        Self::insert_new_stream(&env, stream_id, &stream);
         storage::update_stats(&env, stream_amount, &args.sender, &args.receiver);
 
         let now = env.ledger().timestamp();
@@ -2490,79 +3989,64 @@ impl Contract {
         Ok(stream_id)
     }
 
-    pub fn create_stream_with_signature(
-        env: Env,
-        args: PermitArgs,
-        signature: soroban_sdk::BytesN<64>,
-    ) -> Result<u64, Error> {
+    // ----------------------------------------------------------------
+    // Allowance Streams
+    // ----------------------------------------------------------------
+
+    /// Create a pay-as-you-go stream: nothing is escrowed, and each
+    /// withdrawal pulls what has accrued from the sender's allowance to this
+    /// contract (`approve` the contract for at least what should be payable).
+    /// The protocol fee is taken from every pull rather than up front.
+    ///
+    /// A withdrawal the sender cannot cover in full pays what is covered and
+    /// marks the stream delinquent. If the sender is still short once
+    /// `grace_period` seconds have passed, the next withdrawal defaults the
+    /// stream, closing it at what has been paid.
+    ///
+    /// Like any stream, an allowance stream has a fixed `end_time` and never
+    /// accrues past `total_amount`; it does not renew. Continue paying after
+    /// the end by creating a new allowance stream.
+    ///
+    /// # Errors
+    /// Those of `create_stream`.
+    pub fn create_allowance_stream(env: Env, args: AllowanceStreamArgs) -> Result<u64, Error> {
         Self::require_not_paused(&env)?;
+        Self::require_not_emergency(&env)?;
+        args.sender.require_auth();
         Self::require_asset_whitelisted(&env, &args.token)?;
-        let now = env.ledger().timestamp();
-
-        if now > args.deadline {
-            return Err(Error::ExpiredDeadline);
-        }
 
-        Self::validate_limits(args.total_amount, args.start_time, args.end_time)?;
+        if args.start_time >= args.end_time
+            || args.cliff_time < args.start_time
+            || args.cliff_time > args.end_time
+        {
+            return Err(Error::InvalidTimeRange);
+        }
+        Self::validate_accrual_clock(args.accrual_clock)?;
+        Self::validate_curve(&args.curve_type, args.start_time, args.end_time)?;
+        Self::validate_cancellation_type(args.cancellation_type)?;
+        Self::validate_limits(
+            &env,
+            &args.token,
+            args.total_amount,
+            args.start_time,
+            args.end_time,
+            args.accrual_clock,
+        )?;
 
         if args.total_amount < storage::get_min_value(&env, &args.token) {
             return Err(Error::BelowDustThreshold);
         }
 
-        let nonce_key = (symbol_short!("NONCE"), args.sender_pubkey.clone());
-        let stored_nonce: u64 = env.storage().instance().get(&nonce_key).unwrap_or(0u64);
-
-        if args.nonce != stored_nonce {
-            return Err(Error::InvalidNonce);
-        }
-
-        let mut msg = soroban_sdk::Bytes::new(&env);
-        msg.extend_from_slice(b"STELLARSTREAM_PERMIT_V2");
-        msg.append(&env.current_contract_address().to_xdr(&env));
-        msg.append(&args.sender_pubkey.clone().into());
-        msg.append(&args.receiver.clone().to_xdr(&env));
-        msg.append(&args.token.clone().to_xdr(&env));
-
-        msg.extend_from_slice(&args.total_amount.to_be_bytes());
-        msg.extend_from_slice(&args.start_time.to_be_bytes());
-        msg.extend_from_slice(&args.cliff_time.to_be_bytes());
-        msg.extend_from_slice(&args.end_time.to_be_bytes());
-        msg.extend_from_slice(&args.nonce.to_be_bytes());
-        msg.extend_from_slice(&args.deadline.to_be_bytes());
-
-        if args.step_duration > 0 {
-            msg.extend_from_slice(&args.step_duration.to_be_bytes());
-            msg.extend_from_slice(&args.multiplier_bps.to_be_bytes());
-        }
-
-        let msg_hash: soroban_sdk::BytesN<32> = env.crypto().sha256(&msg).into();
-        env.crypto()
-            .ed25519_verify(&args.sender_pubkey, &msg_hash.into(), &signature);
-
-        env.storage()
-            .instance()
-            .set(&nonce_key, &(stored_nonce + 1));
-
-        let token_client = soroban_sdk::token::TokenClient::new(&env, &args.token);
-        let sender_addr = Address::from_string_bytes(&args.sender_pubkey.clone().into());
-
-        token_client.transfer_from(
-            &env.current_contract_address(),
-            &sender_addr,
-            &env.current_contract_address(),
-            &args.total_amount,
-        );
-
-        let stream_amount = Self::apply_protocol_fee(&env, &args.token, args.total_amount)?;
+        Self::require_compliant(&env, &args.sender)?;
+        Self::require_compliant(&env, &args.receiver)?;
 
         let stream_id = storage::next_stream_id(&env);
-
         let stream = StreamV2 {
-            sender: sender_addr.clone(),
+            sender: args.sender.clone(),
             receiver: args.receiver.clone(),
             beneficiary: args.receiver.clone(),
             token: args.token.clone(),
-            total_amount: stream_amount,
+            total_amount: args.total_amount,
             start_time: args.start_time,
             end_time: args.end_time,
             cliff_time: args.cliff_time,
@@ -2570,39 +4054,56 @@ impl Contract {
             cancelled: false,
             migrated_from_v1: false,
             v1_stream_id: 0,
-            step_duration: args.step_duration,
-            multiplier_bps: args.multiplier_bps,
-            penalty_bps: 0,      // permit streams default to no penalty
-            vault_address: None, // No vault support by permit yet
+            step_duration: 0,
+            multiplier_bps: 0,
+            penalty_bps: 0,
+            vault_address: None,
             yield_enabled: false,
             is_pending: false,
             is_recurrent: false,
             cycle_duration: 0,
-            cancellation_type: 0,
+            cancellation_type: args.cancellation_type,
             yield_recipient: 0,
             split_address: None,
             split_bps: 0,
-            curve_type: 0,
+            curve_type: args.curve_type.clone(),
+            is_soulbound: false,
+            accrual_clock: args.accrual_clock,
         };
 
-        storage::set_stream(&env, stream_id, &stream);
-        storage::update_stats(&env, stream_amount, &sender_addr, &args.receiver);
+        Self::insert_new_stream(&env, stream_id, &stream);
+        storage::set_allowance_funding(
+            &env,
+            stream_id,
+            &AllowanceFunding {
+                grace_period: args.grace_period,
+                delinquent_since: 0,
+                affiliate: args.affiliate.clone(),
+            },
+        );
+        // Nothing enters the contract, so TVL is unchanged.
+        storage::update_stats(&env, 0, &args.sender, &args.receiver);
 
+        let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
+        data.push_back(args.sender.clone().into_val(&env));
         data.push_back(args.receiver.clone().into_val(&env));
         data.push_back(args.token.clone().into_val(&env));
-        data.push_back(stream_amount.into_val(&env));
+        data.push_back(args.total_amount.into_val(&env));
+        data.push_back(args.start_time.into_val(&env));
         data.push_back(args.cliff_time.into_val(&env));
-        data.push_back(args.nonce.into_val(&env));
+        data.push_back(args.end_time.into_val(&env));
+        data.push_back(args.grace_period.into_val(&env));
         data.push_back(now.into_val(&env));
+        data.push_back(args.memo.clone().into_val(&env));
 
         env.events().publish(
-            (stream_id, symbol_short!("permit")),
+            (stream_id, symbol_short!("create_pg")),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: symbol_short!("permit"),
+                action: symbol_short!("create_pg"),
                 data,
             },
         );
@@ -2610,289 +4111,645 @@ impl Contract {
         Ok(stream_id)
     }
 
+    /// Funding of an allowance stream; None for escrowed streams.
+    pub fn get_allowance_funding(env: Env, stream_id: u64) -> Option<AllowanceFunding> {
+        storage::get_allowance_funding(&env, stream_id)
+    }
+
+    /// How well a stream is funded: what it owes now, what it can pay, and
+    /// how long until accrual outruns that.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`: If the stream does not exist
+    pub fn get_stream_health(env: Env, stream_id: u64) -> Result<StreamHealth, Error> {
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        let now = env.ledger().timestamp();
+        let clock_now = Self::stream_now(&env, &stream);
+        let funding = storage::get_allowance_funding(&env, stream_id);
+        let accrued = match &funding {
+            // What the cancellation left unpaid.
+            Some(_) if stream.cancelled => stream.total_amount - stream.withdrawn_amount,
+            _ => {
+                Self::calculate_unlocked_internal(&stream, Self::stream_clock_nanos(&env, &stream))
+                    .saturating_sub(stream.withdrawn_amount)
+            }
+        };
+        let available = match &funding {
+            Some(_) => Self::allowance_available(&env, &stream),
+            None => stream.total_amount.saturating_sub(stream.withdrawn_amount),
+        };
+
+        // First point of the stream's clock at which the accrual exceeds
+        // `available`; unlocking is monotonic, so bisect up to the end.
+        let covered = |at: u64| {
+            Self::calculate_unlocked_internal(&stream, at * math::NANOS_PER_SEC)
+                .saturating_sub(stream.withdrawn_amount)
+                <= available
+        };
+        let runway_end = if stream.cancelled || !covered(clock_now) {
+            clock_now
+        } else if covered(stream.end_time) {
+            stream.end_time.max(clock_now)
+        } else {
+            let (mut lo, mut hi) = (clock_now, stream.end_time);
+            while hi - lo > 1 {
+                let mid = lo + (hi - lo) / 2;
+                if covered(mid) {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            lo
+        };
+        let runway = if stream.accrual_clock == 1 {
+            Self::estimate_timestamp_at(&env, runway_end).saturating_sub(now)
+        } else {
+            runway_end - clock_now
+        };
+
+        let (delinquent_since, grace_ends) = match &funding {
+            Some(funding) if funding.delinquent_since > 0 => (
+                funding.delinquent_since,
+                funding
+                    .delinquent_since
+                    .saturating_add(funding.grace_period),
+            ),
+            _ => (0, 0),
+        };
+
+        Ok(StreamHealth {
+            status: Self::funding_status(&env, stream_id, &stream, &funding, accrued, available),
+            escrowed: funding.is_none(),
+            accrued,
+            available,
+            runway,
+            delinquent_since,
+            grace_ends,
+        })
+    }
+
+    /// Funding status of a stream that owes `accrued` now and can pay
+    /// `available` (see `get_stream_health`).
+    fn funding_status(
+        env: &Env,
+        stream_id: u64,
+        stream: &StreamV2,
+        funding: &Option<AllowanceFunding>,
+        accrued: i128,
+        available: i128,
+    ) -> FundingStatus {
+        if stream.cancelled && funding.is_none() {
+            return FundingStatus::Funded;
+        }
+        match funding {
+            Some(funding) => {
+                let grace_over = funding.delinquent_since > 0
+                    && env.ledger().timestamp()
+                        > funding
+                            .delinquent_since
+                            .saturating_add(funding.grace_period);
+                if available < accrued && grace_over {
+                    FundingStatus::Insolvent
+                } else if available < accrued {
+                    FundingStatus::Delinquent
+                } else if available < stream.total_amount.saturating_sub(stream.withdrawn_amount) {
+                    FundingStatus::Underfunded
+                } else {
+                    FundingStatus::Funded
+                }
+            }
+            // Vault escrow is outside the contract's balance and the pass.
+            // Only a recent pass counts, and only against the balances it
+            // found short: earned ones, or unvested ones after them.
+            None => match storage::get_solvency_report(env, &stream.token) {
+                Some(report)
+                    if stream.vault_address.is_none()
+                        && stream_id < report.streams
+                        && env.ledger().timestamp()
+                            <= report.checked_at.saturating_add(MAX_SOLVENCY_AGE)
+                        && ((accrued > 0 && report.earned_bps < 10_000)
+                            || (available > accrued && report.unvested_bps < 10_000)) =>
+                {
+                    FundingStatus::Insolvent
+                }
+                _ => FundingStatus::Funded,
+            },
+        }
+    }
+
     // ----------------------------------------------------------------
-    // Issue #402 — Permit2-Style Signature Streaming
+    // Solvency
     // ----------------------------------------------------------------
 
-    /// Allow a receiver to claim and start a stream using an off-chain signed
-    /// intent from the sender.
-    ///
-    /// The sender signs `StreamParams` off-chain (e.g. via Freighter) and shares
-    /// the signature with the receiver. The receiver calls this function to
-    /// atomically verify the signature, pull funds from the sender, and open the
-    /// stream — without requiring the sender to be online at claim time.
+    /// Tally the escrow owed to `token` streams against the contract's
+    /// balance, scanning up to `limit` streams per call so large books stay
+    /// within the instruction budget. Anyone may call this; repeat it until
+    /// the returned report's `cursor` is back to 0.
     ///
-    /// # Verification
-    /// - `expiration_ledger`: the signed intent expires at this ledger number.
-    /// - `nonce`: replay protection; must match the stored nonce for `sender_pubkey`.
-    /// - `signature`: Ed25519 signature over the canonical hash of `params`.
+    /// On completion the report records how much of the earned and then the
+    /// unvested balances the balance covers, which `settle_insolvent_stream`
+    /// pays out at for `MAX_SOLVENCY_AGE` seconds. Balances the contract
+    /// holds for others (claimables, pending fees, bridge deposits, voter
+    /// locks, gas buffers) count as earned. Allowance and vault streams hold
+    /// no escrow in the contract and are not counted. Streams keep accruing while a pass
+    /// runs, so the tally is as of each page.
     ///
-    /// # Authorization
-    /// The receiver must authorize this call (they are the one claiming).
-    pub fn create_via_signature(
-        env: Env,
-        params: StreamParams,
-        signature: soroban_sdk::BytesN<64>,
-    ) -> Result<u64, Error> {
-        Self::require_not_paused(&env)?;
-        Self::require_asset_whitelisted(&env, &params.token)?;
+    /// # Errors
+    /// - `BatchTooLarge`: If `limit` is 0 or above `MAX_SOLVENCY_PAGE`
+    pub fn check_solvency(env: Env, token: Address, limit: u32) -> Result<SolvencyReport, Error> {
+        if limit == 0 || limit > MAX_SOLVENCY_PAGE {
+            return Err(Error::BatchTooLarge);
+        }
+        let mut report = storage::get_solvency_report(&env, &token).unwrap_or(SolvencyReport {
+            cursor: 0,
+            earned: 0,
+            unvested: 0,
+            balance: 0,
+            earned_bps: 10_000,
+            unvested_bps: 10_000,
+            streams: 0,
+            checked_at: 0,
+        });
+        if report.cursor == 0 {
+            report.earned = 0;
+            report.unvested = 0;
+        }
 
-        // 1. Check expiration_ledger deadline
-        if env.ledger().sequence() > params.expiration_ledger {
-            return Err(Error::ExpiredDeadline);
+        let total_streams = storage::get_health(&env).total_v2_streams;
+        let end = report
+            .cursor
+            .saturating_add(limit as u64)
+            .min(total_streams);
+        for id in report.cursor..end {
+            let stream = match storage::get_stream(&env, id) {
+                Some(stream) if stream.token == token => stream,
+                _ => continue,
+            };
+            // Prepaid keeper bounties are owed to keepers or the sender.
+            report.earned = report
+                .earned
+                .saturating_add(storage::get_keeper_buffer(&env, id));
+            if stream.cancelled
+                || stream.vault_address.is_some()
+                || storage::get_allowance_funding(&env, id).is_some()
+            {
+                continue;
+            }
+            let unlocked =
+                Self::calculate_unlocked_internal(&stream, Self::stream_clock_nanos(&env, &stream))
+                    .min(stream.total_amount);
+            report.earned = report
+                .earned
+                .saturating_add(unlocked.saturating_sub(stream.withdrawn_amount).max(0));
+            report.unvested = report
+                .unvested
+                .saturating_add(stream.total_amount.saturating_sub(unlocked));
+        }
+        report.cursor = end;
+
+        if end == total_streams {
+            // Claimable balances, pending fees, refundable bridge deposits,
+            // voter locks and gas buffers are owed in full, like earned ones.
+            report.earned = report
+                .earned
+                .saturating_add(storage::get_claimable_total(&env, &token))
+                .saturating_add(storage::get_held_balance(&env, &token));
+            let now = env.ledger().timestamp();
+            let balance = soroban_sdk::token::TokenClient::new(&env, &token)
+                .balance(&env.current_contract_address());
+            // Earned balances are covered first; unvested ones take the rest.
+            let coverage = |covered: i128, owed: i128| {
+                if covered >= owed {
+                    10_000
+                } else {
+                    (covered.max(0) * 10_000 / owed) as u32
+                }
+            };
+            report.earned_bps = coverage(balance, report.earned);
+            report.unvested_bps = coverage(balance - report.earned, report.unvested);
+            report.balance = balance;
+            report.streams = total_streams;
+            report.checked_at = now;
+            report.cursor = 0;
+
+            let mut data = Vec::new(&env);
+            data.push_back(token.clone().into_val(&env));
+            data.push_back(report.earned.into_val(&env));
+            data.push_back(report.unvested.into_val(&env));
+            data.push_back(balance.into_val(&env));
+            data.push_back(report.earned_bps.into_val(&env));
+            data.push_back(report.unvested_bps.into_val(&env));
+            data.push_back(now.into_val(&env));
+            env.events().publish(
+                (symbol_short!("solvency"), token.clone()),
+                NebulaEvent {
+                    version: 2,
+                    timestamp: now,
+                    action: symbol_short!("solvency"),
+                    data,
+                },
+            );
         }
 
-        // 2. Dust threshold check
-        Self::validate_limits(params.total_amount, params.start_time, params.end_time)?;
+        storage::set_solvency_report(&env, &token, &report);
+        Ok(report)
+    }
 
-        if params.total_amount < storage::get_min_value(&env, &params.token) {
-            return Err(Error::BelowDustThreshold);
+    /// Latest solvency pass over `token` streams.
+    pub fn get_solvency_report(env: Env, token: Address) -> Option<SolvencyReport> {
+        storage::get_solvency_report(&env, &token)
+    }
+
+    /// Close an insolvent stream, paying the beneficiary what is actually
+    /// available. Anyone may call this.
+    ///
+    /// - Allowance streams past their grace period pay the accrual as far
+    ///   as the sender's allowance and balance still cover it.
+    /// - Escrowed streams holding balances that a `check_solvency` pass at
+    ///   most `MAX_SOLVENCY_AGE` old found short pay the earned balance at
+    ///   the pass's `earned_bps` and
+    ///   refund the unvested one to the sender at its `unvested_bps`, so
+    ///   earned balances are protected before unvested ones.
+    ///
+    /// # Returns
+    /// - `Ok(amount)`: The amount paid towards the beneficiary
+    ///
+    /// # Errors
+    /// - `StreamNotFound`: If the stream does not exist
+    /// - `AlreadyCancelled`: If the stream has already been closed
+    /// - `StreamNotInsolvent`: If the stream's status is not `Insolvent`
+    pub fn settle_insolvent_stream(env: Env, stream_id: u64) -> Result<i128, Error> {
+        Self::require_not_paused_only(&env)?;
+        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        let funding = storage::get_allowance_funding(&env, stream_id);
+        // Cancelled allowance streams may still owe what the cancellation left unpaid.
+        if stream.cancelled && (funding.is_none() || stream.withdrawn_amount >= stream.total_amount)
+        {
+            return Err(Error::AlreadyCancelled);
         }
 
-        // 3. Replay-protection nonce check
-        let nonce_key = (symbol_short!("SIG_NONC"), params.sender_pubkey.clone());
-        let stored_nonce: u64 = env.storage().instance().get(&nonce_key).unwrap_or(0u64);
-        if params.nonce != stored_nonce {
-            return Err(Error::InvalidNonce);
+        let now = env.ledger().timestamp();
+        let unlocked = if stream.cancelled {
+            stream.total_amount
+        } else {
+            Self::calculate_unlocked_internal(&stream, Self::stream_clock_nanos(&env, &stream))
+                .min(stream.total_amount)
+        };
+        let earned = unlocked.saturating_sub(stream.withdrawn_amount).max(0);
+        let unvested = stream.total_amount.saturating_sub(unlocked);
+        let available = match &funding {
+            Some(_) => Self::allowance_available(&env, &stream),
+            None => earned + unvested,
+        };
+        if Self::funding_status(&env, stream_id, &stream, &funding, earned, available)
+            != FundingStatus::Insolvent
+        {
+            return Err(Error::StreamNotInsolvent);
         }
 
-        // 4. Build the canonical message and verify the Ed25519 signature.
-        //    The message commits to: domain separator, contract address, and all
-        //    StreamParams fields so nothing can be altered after signing.
-        let mut msg = Bytes::new(&env);
-        msg.extend_from_slice(b"STELLARSTREAM_INTENT_V1");
-        msg.append(&env.current_contract_address().to_xdr(&env));
-        msg.append(&params.sender_pubkey.clone().into());
-        msg.append(&params.receiver.clone().to_xdr(&env));
-        msg.append(&params.token.clone().to_xdr(&env));
-        msg.extend_from_slice(&params.total_amount.to_be_bytes());
-        msg.extend_from_slice(&params.start_time.to_be_bytes());
-        msg.extend_from_slice(&params.cliff_time.to_be_bytes());
-        msg.extend_from_slice(&params.end_time.to_be_bytes());
-        msg.extend_from_slice(&params.nonce.to_be_bytes());
-        msg.extend_from_slice(&params.expiration_ledger.to_be_bytes());
-        if params.step_duration > 0 {
-            msg.extend_from_slice(&params.step_duration.to_be_bytes());
-            msg.extend_from_slice(&params.multiplier_bps.to_be_bytes());
-        }
-
-        let msg_hash: soroban_sdk::BytesN<32> = env.crypto().sha256(&msg).into();
-        env.crypto()
-            .ed25519_verify(&params.sender_pubkey, &msg_hash.into(), &signature);
-
-        // 5. Consume the nonce to prevent replay
-        env.storage()
-            .instance()
-            .set(&nonce_key, &(stored_nonce + 1));
-
-        // 6. Pull funds from the sender into the contract
-        let sender_addr = Address::from_string_bytes(&params.sender_pubkey.clone().into());
-        let token_client = soroban_sdk::token::TokenClient::new(&env, &params.token);
-        token_client.transfer_from(
-            &env.current_contract_address(),
-            &sender_addr,
-            &env.current_contract_address(),
-            &params.total_amount,
-        );
+        let (paid, to_receiver, to_sender) = match &funding {
+            Some(funding) => {
+                let (pulled, net) = Self::pull_allowance(&env, &stream, funding, earned)?;
+                (pulled, net, 0)
+            }
+            None => {
+                let report = storage::get_solvency_report(&env, &stream.token)
+                    .ok_or(Error::StreamNotInsolvent)?;
+                let to_receiver = earned * report.earned_bps as i128 / 10_000;
+                let to_sender = unvested * report.unvested_bps as i128 / 10_000;
+                (to_receiver + to_sender, to_receiver, to_sender)
+            }
+        };
 
-        // 7. Deduct protocol fee and create the stream
-        let stream_amount = Self::apply_protocol_fee(&env, &params.token, params.total_amount)?;
-        let stream_id = storage::next_stream_id(&env);
+        match &funding {
+            Some(_) => stream.withdrawn_amount += paid,
+            None => {
+                // The whole remaining escrow leaves TVL, paid or written off.
+                storage::update_stats(&env, -(earned + unvested), &stream.sender, &stream.receiver);
+                stream.withdrawn_amount = unlocked;
+            }
+        }
+        stream.total_amount = stream.withdrawn_amount;
+        stream.cancelled = true;
+        storage::set_stream(&env, stream_id, &stream);
+        storage::remove_owner_position(&env, &stream.beneficiary, stream_id);
 
-        let stream = StreamV2 {
-            sender: sender_addr.clone(),
-            receiver: params.receiver.clone(),
-            beneficiary: params.receiver.clone(),
-            token: params.token.clone(),
-            total_amount: stream_amount,
-            start_time: params.start_time,
-            end_time: params.end_time,
-            cliff_time: params.cliff_time,
-            withdrawn_amount: 0,
-            cancelled: false,
-            migrated_from_v1: false,
-            v1_stream_id: 0,
-            step_duration: params.step_duration,
-            multiplier_bps: params.multiplier_bps,
-            penalty_bps: 0,
-            vault_address: params.vault_address,
-            yield_enabled: params.yield_enabled,
-            is_pending: false,
-            is_recurrent: false,
-            cycle_duration: 0,
-            cancellation_type: 0,
-            yield_recipient: 0,
-            split_address: None,
-            split_bps: 0,
-            curve_type: 0,
-        };
+        if to_receiver > 0 {
+            Self::check_not_sanctioned(&env, &stream.beneficiary)?;
+            Self::pay_beneficiary(&env, stream_id, &stream, &stream.token, to_receiver);
+        }
+        if to_sender > 0 {
+            Self::check_not_sanctioned(&env, &stream.sender)?;
+            soroban_sdk::token::TokenClient::new(&env, &stream.token).transfer(
+                &env.current_contract_address(),
+                &stream.sender,
+                &to_sender,
+            );
+        }
 
-        // 8. Emit event
-        let now = env.ledger().timestamp();
+        let shortfall = match &funding {
+            Some(_) => earned - paid,
+            None => earned + unvested - paid,
+        };
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
-        data.push_back(params.receiver.clone().into_val(&env));
-        data.push_back(params.token.clone().into_val(&env));
-        data.push_back(stream_amount.into_val(&env));
-        data.push_back(params.expiration_ledger.into_val(&env));
-        data.push_back(params.nonce.into_val(&env));
+        data.push_back(to_receiver.into_val(&env));
+        data.push_back(to_sender.into_val(&env));
+        data.push_back(shortfall.into_val(&env));
         data.push_back(now.into_val(&env));
-
         env.events().publish(
-            (stream_id, symbol_short!("sig_claim")),
+            (stream_id, symbol_short!("settle")),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: symbol_short!("sig_claim"),
+                action: symbol_short!("settle"),
                 data,
             },
         );
 
-        Ok(stream_id)
+        Ok(to_receiver)
     }
 
     // ----------------------------------------------------------------
-    // Issue #378 — Streaming Swap (DEX Integration)
+    // Classic Assets
     // ----------------------------------------------------------------
 
-    /// Create a stream where the sender provides `asset_in`, which is automatically
-    /// swapped to `asset_out` via a Soroban AMM before initializing the stream.
-    ///
-    /// This enables use cases like:
-    /// - Stream XLM but receive USDC (swap XLM -> USDC first)
-    /// - Stream any asset but receive a stablecoin
+    /// The Stellar Asset Contract address of a classic asset. The contract
+    /// need not be deployed yet; streams still require it to be whitelisted.
     ///
-    /// # Parameters
-    /// - `args`: SwapStreamArgs containing all swap and stream configuration
-    ///
-    /// # Returns
-    /// - `Ok(stream_id)` if stream was created successfully
-    /// - `Err(Error)` if swap fails, slippage exceeded, or stream creation fails
-    ///
-    /// # Safety Features
-    /// 1. `min_amount_out` - Absolute minimum output; swap fails if not met
-    /// 2. `slippage_tolerance_bps` - Additional protection (e.g., 50 bps = 0.5%)
-    /// 3. `swap_deadline` - Prevents stale price execution
-    pub fn create_swap_stream(env: Env, args: SwapStreamArgs) -> Result<u64, Error> {
-        Self::require_not_paused(&env)?;
-        Self::require_not_emergency(&env)?;
+    /// # Errors
+    /// - `AssetInterfaceNotSupported`: If the code is not 1–12 characters or
+    ///   the issuer is not a `G...` account
+    pub fn resolve_asset(env: Env, asset: ClassicAsset) -> Result<Address, Error> {
+        let xdr = Self::classic_asset_xdr(&env, &asset)?;
+        Ok(env.deployer().with_stellar_asset(xdr).deployed_address())
+    }
 
-        // Validate swap streaming is enabled
-        if !storage::is_swap_enabled(&env) {
-            return Err(Error::DexNotConfigured);
+    /// Stellar `Asset` XDR of a classic asset.
+    fn classic_asset_xdr(env: &Env, asset: &ClassicAsset) -> Result<Bytes, Error> {
+        match asset {
+            ClassicAsset::Native => Ok(Bytes::from_array(env, &[0, 0, 0, 0])),
+            ClassicAsset::Credit(code, issuer) => {
+                // ASSET_TYPE_CREDIT_ALPHANUM4 / 12, the zero-padded code, then
+                // the issuer as a PUBLIC_KEY_TYPE_ED25519 account id.
+                let (kind, width) = match code.len() {
+                    1..=4 => (1u8, 4u32),
+                    5..=12 => (2u8, 12u32),
+                    _ => return Err(Error::AssetInterfaceNotSupported),
+                };
+                let key = Self::account_public_key(env, issuer)
+                    .ok_or(Error::AssetInterfaceNotSupported)?;
+                let mut padded = [0u8; 12];
+                code.copy_into_slice(&mut padded[..code.len() as usize]);
+                let mut xdr = Bytes::from_array(env, &[0, 0, 0, kind]);
+                xdr.append(&Bytes::from_slice(env, &padded[..width as usize]));
+                xdr.append(&Bytes::from_array(env, &[0, 0, 0, 0]));
+                xdr.append(&key.into());
+                Ok(xdr)
+            }
         }
+    }
 
-        // Validate sender authorized this call
-        args.sender.require_auth();
+    /// `create_stream` in a classic asset: `args.token` is replaced by the
+    /// asset's Stellar Asset Contract.
+    ///
+    /// # Errors
+    /// Those of `resolve_asset` and `create_stream`.
+    pub fn create_classic_stream(
+        env: Env,
+        asset: ClassicAsset,
+        args: StreamArgs,
+    ) -> Result<u64, Error> {
+        let token = Self::resolve_asset(env.clone(), asset)?;
+        Self::create_stream(env, StreamArgs { token, ..args })
+    }
 
-        // Validate input parameters
-        if args.amount_in <= 0 {
-            return Err(Error::InvalidSwapParams);
-        }
-
-        if args.asset_in == args.asset_out {
-            return Err(Error::SameAsset);
-        }
+    /// Whether `holder` can receive `token`: the token reports a balance for
+    /// it (for classic assets held by accounts, a trustline exists) and, for
+    /// Stellar Asset Contracts, it is authorized.
+    fn can_hold(env: &Env, token: &Address, holder: &Address) -> bool {
+        if !matches!(
+            soroban_sdk::token::TokenClient::new(env, token).try_balance(holder),
+            Ok(Ok(_))
+        ) {
+            return false;
+        }
+        // Tokens without `authorized` are not Stellar Asset Contracts.
+        !matches!(
+            soroban_sdk::token::StellarAssetClient::new(env, token).try_authorized(holder),
+            Ok(Ok(false))
+        )
+    }
 
-        // Validate slippage tolerance (0-100% = 0-10000 bps)
-        if args.slippage_tolerance_bps > 10_000 {
-            return Err(Error::InvalidPenalty);
+    /// Store a newly created stream. If its receiver cannot hold the stream
+    /// token, payouts the receiver still cannot take are held in the
+    /// contract as a claimable balance instead of failing the transfer.
+    fn insert_new_stream(env: &Env, stream_id: u64, stream: &StreamV2) {
+        storage::insert_stream(env, stream_id, stream);
+        if Self::can_hold(env, &stream.token, &stream.receiver) {
+            return;
         }
+        storage::set_claimable_payouts(env, stream_id);
 
-        // Check deadline
         let now = env.ledger().timestamp();
-        if args.swap_deadline < now {
-            return Err(Error::ExpiredDeadline);
-        }
+        let mut data = Vec::new(env);
+        data.push_back(stream_id.into_val(env));
+        data.push_back(stream.receiver.clone().into_val(env));
+        data.push_back(stream.token.clone().into_val(env));
+        data.push_back(now.into_val(env));
+        env.events().publish(
+            (stream_id, symbol_short!("no_trust")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("no_trust"),
+                data,
+            },
+        );
+    }
 
-        // Validate stream timing
-        if args.start_time >= args.end_time {
-            return Err(Error::InvalidTimeRange);
-        }
+    /// Payouts of `token` held for `holder` because it could not receive them.
+    pub fn get_claimable(env: Env, holder: Address, token: Address) -> i128 {
+        storage::get_claimable(&env, &holder, &token)
+    }
 
-        if args.cliff_time < args.start_time || args.cliff_time > args.end_time {
-            return Err(Error::InvalidTimeRange);
+    /// Pull `holder`'s claimable balance of `token`, once it can hold it.
+    ///
+    /// # Returns
+    /// - `Ok(amount)`: The amount transferred
+    ///
+    /// # Errors
+    /// - `NothingToWithdraw`: If nothing is claimable
+    pub fn claim_balance(env: Env, holder: Address, token: Address) -> Result<i128, Error> {
+        Self::require_not_paused_only(&env)?;
+        holder.require_auth();
+        let amount = storage::get_claimable(&env, &holder, &token);
+        if amount <= 0 {
+            return Err(Error::NothingToWithdraw);
         }
-
-        // Validate both assets are whitelisted
-        Self::require_asset_whitelisted(&env, &args.asset_in)?;
-        Self::require_asset_whitelisted(&env, &args.asset_out)?;
-
-        // Compliance oracle check
-        Self::require_compliant(&env, &args.sender)?;
-        Self::require_compliant(&env, &args.receiver)?;
-
-        // Get DEX address
-        let dex_address = storage::get_dex_address(&env).ok_or(Error::DexNotConfigured)?;
-
-        // Transfer asset_in from sender to this contract
-        let token_in_client = soroban_sdk::token::TokenClient::new(&env, &args.asset_in);
-        token_in_client.transfer(
-            &args.sender,
+        Self::check_not_sanctioned(&env, &holder)?;
+        storage::set_claimable(&env, &holder, &token, 0);
+        soroban_sdk::token::TokenClient::new(&env, &token).transfer(
             &env.current_contract_address(),
-            &args.amount_in,
+            &holder,
+            &amount,
         );
 
-        // Approve DEX to spend asset_in from this contract
-        let expiration_ledger = env.ledger().sequence().saturating_add(1000);
-        token_in_client.approve(
-            &env.current_contract_address(),
-            &dex_address,
-            &args.amount_in,
-            &expiration_ledger,
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(holder.clone().into_val(&env));
+        data.push_back(token.clone().into_val(&env));
+        data.push_back(amount.into_val(&env));
+        data.push_back(now.into_val(&env));
+        env.events().publish(
+            (symbol_short!("claimed"), holder),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("claimed"),
+                data,
+            },
         );
+        Ok(amount)
+    }
 
-        // Execute swap via DEX
-        let swap_client = SwapClient::new(&env, &dex_address);
+    // ----------------------------------------------------------------
+    // Typed Permits
+    // ----------------------------------------------------------------
 
-        // Calculate effective min_amount_out with slippage tolerance
-        // Apply slippage tolerance as an additional safety margin
-        let effective_min_amount_out = Self::calculate_min_amount_with_slippage(
-            args.amount_in,
-            args.min_amount_out,
-            args.slippage_tolerance_bps,
-        );
+    /// Verify `signer`'s permit for `function` over `args` (the XDR of the
+    /// entry point's typed arguments) and consume its nonce.
+    fn verify_permit(
+        env: &Env,
+        function: Symbol,
+        signer: &Address,
+        nonce: u64,
+        expiry: u64,
+        args: Bytes,
+        signature: &PermitSignature,
+    ) -> Result<(), Error> {
+        if nonce != storage::get_permit_nonce(env, signer) {
+            return Err(Error::InvalidNonce);
+        }
+        Self::authorize_permit(env, function, signer, nonce, expiry, args, signature)?;
+        storage::set_permit_nonce(env, signer, nonce + 1);
+        Ok(())
+    }
 
-        // Execute the swap
-        let amount_out = swap_client.swap(
-            &args.asset_in,
-            &args.asset_out,
-            &args.amount_in,
-            &effective_min_amount_out,
-            &args.swap_deadline,
-        );
+    /// Check `signature` over the typed permit; nonce bookkeeping is left to
+    /// the caller.
+    fn authorize_permit(
+        env: &Env,
+        function: Symbol,
+        signer: &Address,
+        nonce: u64,
+        expiry: u64,
+        args: Bytes,
+        signature: &PermitSignature,
+    ) -> Result<(), Error> {
+        let permit = Permit {
+            domain: PermitDomain {
+                network_id: env.ledger().network_id(),
+                contract: env.current_contract_address(),
+                function,
+                expiry,
+            },
+            signer: signer.clone(),
+            nonce,
+            args_hash: env.crypto().sha256(&args).into(),
+        };
+        let mut msg = Bytes::from_slice(env, PERMIT_MESSAGE_PREFIX);
+        msg.append(&permit.to_xdr(env));
+        let digest: soroban_sdk::BytesN<32> = env.crypto().sha256(&msg).into();
+
+        match signature {
+            PermitSignature::Ed25519(public_key, sig) => {
+                if Self::account_public_key(env, signer).as_ref() != Some(public_key) {
+                    return Err(Error::UnauthorizedSender);
+                }
+                env.crypto().ed25519_verify(public_key, &digest.into(), sig);
+            }
+            PermitSignature::Auth => {
+                signer.require_auth_for_args(soroban_sdk::vec![env, digest.into_val(env)]);
+            }
+        }
+        Ok(())
+    }
 
-        // Safety check: verify we received at least the user's specified minimum
-        if amount_out < args.min_amount_out {
-            return Err(Error::SwapSlippageExceeded);
+    /// The ed25519 key of a `G...` account address; None for contracts.
+    fn account_public_key(env: &Env, address: &Address) -> Option<soroban_sdk::BytesN<32>> {
+        // ScVal::Address(ScAddress::Account(PublicKey::Ed25519(key))): three
+        // zero-padded XDR discriminants, then the 32-byte key.
+        const ACCOUNT_PREFIX: [u8; 12] = [0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 0];
+        let xdr = address.clone().to_xdr(env);
+        if xdr.len() != 44 || xdr.slice(0..12) != Bytes::from_array(env, &ACCOUNT_PREFIX) {
+            return None;
+        }
+        let mut key = [0u8; 32];
+        xdr.slice(12..44).copy_into_slice(&mut key);
+        Some(soroban_sdk::BytesN::from_array(env, &key))
+    }
+
+    /// Create a stream from a typed `Permit` for `create_stream_with_signature`
+    /// signed by `args.sender`, whose arguments are `args` itself. Anyone may
+    /// submit it; the funds are pulled from the sender's allowance.
+    pub fn create_stream_with_signature(
+        env: Env,
+        args: PermitArgs,
+        signature: PermitSignature,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env)?;
+        Self::require_asset_whitelisted(&env, &args.token)?;
+        let now = env.ledger().timestamp();
+
+        if now > args.deadline {
+            return Err(Error::ExpiredDeadline);
         }
 
-        // Calculate stream amount after protocol fee
-        let stream_amount = Self::apply_protocol_fee(&env, &args.asset_out, amount_out)?;
+        Self::validate_limits(
+            &env,
+            &args.token,
+            args.total_amount,
+            args.start_time,
+            args.end_time,
+            0,
+        )?;
 
-        // Check dust threshold for the output asset
-        if stream_amount < storage::get_min_value(&env, &args.asset_out) {
+        if args.total_amount < storage::get_min_value(&env, &args.token) {
             return Err(Error::BelowDustThreshold);
         }
 
-        // Create the stream with the swapped asset
-        let stream_id = storage::next_stream_id(&env);
+        Self::verify_permit(
+            &env,
+            Symbol::new(&env, "create_stream_with_signature"),
+            &args.sender,
+            args.nonce,
+            args.deadline,
+            args.clone().to_xdr(&env),
+            &signature,
+        )?;
 
-        // Handle vault deposit if yield is enabled
-        let mut vault_used = None;
-        if args.yield_enabled {
-            if let Some(vault_addr) = &args.vault_address {
-                let vault_client = VaultClient::new(&env, vault_addr);
-                vault_client.deposit(&stream_amount);
-                vault_used = Some(vault_addr.clone());
-            }
-        }
+        let token_client = soroban_sdk::token::TokenClient::new(&env, &args.token);
+        let sender_addr = args.sender.clone();
+
+        token_client.transfer_from(
+            &env.current_contract_address(),
+            &sender_addr,
+            &env.current_contract_address(),
+            &args.total_amount,
+        );
+
+        let stream_amount =
+            Self::apply_protocol_fee(&env, &args.token, args.total_amount, &args.affiliate)?;
+
+        let stream_id = storage::next_stream_id(&env);
 
         let stream = StreamV2 {
-            sender: args.sender.clone(),
+            sender: sender_addr.clone(),
             receiver: args.receiver.clone(),
             beneficiary: args.receiver.clone(),
-            token: args.asset_out.clone(),
+            token: args.token.clone(),
             total_amount: stream_amount,
             start_time: args.start_time,
             end_time: args.end_time,
@@ -2901,42 +4758,41 @@ impl Contract {
             cancelled: false,
             migrated_from_v1: false,
             v1_stream_id: 0,
-            step_duration: 0,      // Default linear stream
-            multiplier_bps: 10000, // 1.0x multiplier
-            penalty_bps: 0,        // Default no penalty
-            vault_address: vault_used,
-            yield_enabled: args.yield_enabled,
+            step_duration: args.step_duration,
+            multiplier_bps: args.multiplier_bps,
+            penalty_bps: 0,      // permit streams default to no penalty
+            vault_address: None, // No vault support by permit yet
+            yield_enabled: false,
             is_pending: false,
             is_recurrent: false,
             cycle_duration: 0,
-            cancellation_type: args.cancellation_type,
-            yield_recipient: args.yield_recipient,
-            split_address: args.split_address.clone(),
-            split_bps: args.split_bps,
-            curve_type: 0,
+            cancellation_type: 0,
+            yield_recipient: 0,
+            split_address: None,
+            split_bps: 0,
+            curve_type: CurveType::Linear,
+            is_soulbound: false,
+            accrual_clock: 0,
         };
 
-        storage::set_stream(&env, stream_id, &stream);
-        storage::update_stats(&env, stream_amount, &args.sender, &args.receiver);
+        Self::insert_new_stream(&env, stream_id, &stream);
+        storage::update_stats(&env, stream_amount, &sender_addr, &args.receiver);
 
-        // Emit swap stream creation event
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
-        data.push_back(args.sender.clone().into_val(&env));
         data.push_back(args.receiver.clone().into_val(&env));
-        data.push_back(args.asset_in.clone().into_val(&env));
-        data.push_back(args.asset_out.clone().into_val(&env));
-        data.push_back(args.amount_in.into_val(&env));
-        data.push_back(amount_out.into_val(&env));
-        data.push_back(args.min_amount_out.into_val(&env));
+        data.push_back(args.token.clone().into_val(&env));
+        data.push_back(stream_amount.into_val(&env));
+        data.push_back(args.cliff_time.into_val(&env));
+        data.push_back(args.nonce.into_val(&env));
         data.push_back(now.into_val(&env));
 
         env.events().publish(
-            (stream_id, Symbol::new(&env, "swap_stream")),
+            (stream_id, symbol_short!("permit")),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: Symbol::new(&env, "swap_stream"),
+                action: symbol_short!("permit"),
                 data,
             },
         );
@@ -2944,469 +4800,1607 @@ impl Contract {
         Ok(stream_id)
     }
 
-    /// Calculate the minimum amount considering slippage tolerance.
-    /// This provides additional safety beyond the user's specified min_amount_out.
-    fn calculate_min_amount_with_slippage(
-        _amount_in: i128,
-        min_amount_out: i128,
-        slippage_tolerance_bps: u32,
-    ) -> i128 {
-        // Apply slippage tolerance to min_amount_out
-        // This reduces the acceptable output by the slippage percentage
-        let tolerance_multiplier = 10_000 - slippage_tolerance_bps as i128;
-        // Use integer math: (min_amount_out * tolerance_multiplier) / 10000
-        (min_amount_out * tolerance_multiplier) / 10_000
-    }
+    // ----------------------------------------------------------------
+    // Issue #402 — Permit2-Style Signature Streaming
+    // ----------------------------------------------------------------
 
-    /// Get the expected output amount for a swap without executing it.
-    /// Useful for UI to show user expected output before confirming.
-    pub fn get_swap_quote(
+    /// Allow a receiver to claim and start a stream using an off-chain signed
+    /// intent from the sender.
+    ///
+    /// The sender signs `StreamParams` off-chain (e.g. via Freighter) and shares
+    /// the signature with the receiver. The receiver calls this function to
+    /// atomically verify the signature, pull funds from the sender, and open the
+    /// stream — without requiring the sender to be online at claim time.
+    ///
+    /// # Verification
+    /// - `expiration_ledger`: the signed intent expires at this ledger number.
+    /// - `nonce`: replay protection; must match the sender's permit nonce.
+    /// - `signature`: the sender's authorization of a typed `Permit` for
+    ///   `create_via_signature` whose arguments are `params`.
+    ///
+    /// # Authorization
+    /// The receiver must authorize this call (they are the one claiming).
+    pub fn create_via_signature(
         env: Env,
-        amount_in: i128,
-        asset_in: Address,
-        asset_out: Address,
-    ) -> Result<SwapResult, Error> {
-        if !storage::is_swap_enabled(&env) {
-            return Err(Error::DexNotConfigured);
-        }
-
-        if amount_in <= 0 {
-            return Err(Error::InvalidSwapParams);
-        }
+        params: StreamParams,
+        signature: PermitSignature,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env)?;
+        Self::require_asset_whitelisted(&env, &params.token)?;
 
-        if asset_in == asset_out {
-            return Err(Error::SameAsset);
+        // 1. Check expiration_ledger deadline
+        if env.ledger().sequence() > params.expiration_ledger {
+            return Err(Error::ExpiredDeadline);
         }
 
-        let dex_address = storage::get_dex_address(&env).ok_or(Error::DexNotConfigured)?;
-
-        let swap_client = SwapClient::new(&env, &dex_address);
-
-        let amount_out = swap_client.get_amount_out(&asset_in, &asset_out, &amount_in);
+        // 2. Dust threshold check
+        Self::validate_limits(
+            &env,
+            &params.token,
+            params.total_amount,
+            params.start_time,
+            params.end_time,
+            0,
+        )?;
 
-        let spot_price = swap_client.get_spot_price(&asset_in, &asset_out);
+        if params.total_amount < storage::get_min_value(&env, &params.token) {
+            return Err(Error::BelowDustThreshold);
+        }
 
-        // Calculate price impact (simplified - assumes 1:1 for this calculation)
-        // Price impact = ((expected_out - theoretical_out) / theoretical_out) * 10000 bps
-        let theoretical_out = amount_in; // Simplified - assumes 1:1 for demonstration
-        let price_impact = if theoretical_out > 0 {
-            ((amount_out - theoretical_out) * 10000) / theoretical_out
-        } else {
-            0
-        };
+        // 3. Verify the typed permit and consume the sender's nonce
+        Self::verify_permit(
+            &env,
+            Symbol::new(&env, "create_via_signature"),
+            &params.sender,
+            params.nonce,
+            params.expiration_ledger as u64,
+            params.clone().to_xdr(&env),
+            &signature,
+        )?;
+
+        // 4. Pull funds from the sender into the contract
+        let sender_addr = params.sender.clone();
+        let token_client = soroban_sdk::token::TokenClient::new(&env, &params.token);
+        token_client.transfer_from(
+            &env.current_contract_address(),
+            &sender_addr,
+            &env.current_contract_address(),
+            &params.total_amount,
+        );
 
-        Ok(SwapResult {
-            amount_in,
-            amount_out,
-            price_impact_bps: price_impact,
-        })
-    }
+        // 5. Deduct protocol fee and create the stream
+        let stream_amount =
+            Self::apply_protocol_fee(&env, &params.token, params.total_amount, &params.affiliate)?;
+        let stream_id = storage::next_stream_id(&env);
 
-    // ----------------------------------------------------------------
-    // Issue #377 — Push-Pull Rate Re-balancing
-    // ----------------------------------------------------------------
+        let mut vault_used = None;
+        if params.yield_enabled {
+            if let Some(vault_addr) = &params.vault_address {
+                Self::vault_deposit(&env, stream_id, vault_addr, &params.token, stream_amount)?;
+                vault_used = Some(vault_addr.clone());
+            }
+        }
 
-    /// Propose a new rate for an active stream.
-    ///
-    /// The sender can propose to change the stream rate (faster or slower).
-    /// The receiver must accept the proposal for it to take effect.
-    ///
-    /// # Parameters
-    /// - `stream_id`: The ID of the stream to update
-    /// - `new_rate`: The new rate (amount per second)
-    ///
-    /// # Returns
-    /// - `Ok(())` if proposal was created successfully
-    /// - `Err(Error)` if validation fails
-    ///
-    /// # Constraints
-    /// - Only the stream sender can propose
-    /// - Stream must be active (not cancelled, not fully withdrawn)
-    /// - New rate must be > 0
-    /// - Sender must have sufficient balance for the change
-    pub fn propose_rate(
-        env: Env,
-        stream_id: u64,
-        new_rate: i128,
-    ) -> Result<PendingRateUpdate, Error> {
-        Self::require_not_paused(&env)?;
-
-        // Get the stream
-        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
-
-        // Only the sender can propose a rate change
-        let caller = stream.sender.clone();
-        caller.require_auth();
-
-        // Validate stream is active
-        if stream.cancelled {
-            return Err(Error::StreamNotActive);
-        }
-
-        // Check if fully withdrawn
-        if stream.withdrawn_amount >= stream.total_amount {
-            return Err(Error::StreamNotActive);
-        }
-
-        // Validate new rate
-        if new_rate <= 0 {
-            return Err(Error::InvalidNewRate);
-        }
-
-        if new_rate > MAX_FLOW_RATE {
-            return Err(Error::AmountOverflow);
-        }
-
-        // Calculate remaining balance
-        let now = env.ledger().timestamp();
-        let remaining_balance = Self::calculate_remaining_balance(&env, &stream, now)?;
-
-        // Calculate current rate (total_amount / duration)
-        let duration = (stream.end_time - stream.start_time) as i128;
-        let current_rate = if duration > 0 {
-            stream.total_amount / duration
-        } else {
-            0
-        };
-
-        // If rate is unchanged, nothing to do
-        if current_rate == new_rate {
-            return Err(Error::InvalidNewRate);
-        }
-
-        // Calculate new end time based on remaining balance and new rate
-        // new_end_time = now + (remaining_balance / new_rate)
-        let new_end_time = if new_rate > 0 {
-            let additional_seconds = remaining_balance / new_rate;
-            now.saturating_add(additional_seconds as u64)
-        } else {
-            return Err(Error::InvalidNewRate);
-        };
-
-        // Ensure new end time is in the future
-        if new_end_time <= now {
-            return Err(Error::InsufficientBalance);
-        }
-
-        // Check if a pending update already exists
-        if storage::has_pending_rate_update(&env, stream_id) {
-            // Check if existing update has expired
-            if !storage::is_pending_rate_update_expired(&env, stream_id) {
-                return Err(Error::PendingUpdateExists);
-            }
-            // Remove expired update
-            storage::remove_pending_rate_update(&env, stream_id);
-        }
-
-        // Create the pending update
-        let pending_update = PendingRateUpdate {
-            new_rate,
-            proposed_at: now,
-            proposed_by: caller.clone(),
-            original_end_time: stream.end_time,
-            original_total_amount: stream.total_amount,
+        let stream = StreamV2 {
+            sender: sender_addr.clone(),
+            receiver: params.receiver.clone(),
+            beneficiary: params.receiver.clone(),
+            token: params.token.clone(),
+            total_amount: stream_amount,
+            start_time: params.start_time,
+            end_time: params.end_time,
+            cliff_time: params.cliff_time,
+            withdrawn_amount: 0,
+            cancelled: false,
+            migrated_from_v1: false,
+            v1_stream_id: 0,
+            step_duration: params.step_duration,
+            multiplier_bps: params.multiplier_bps,
+            penalty_bps: 0,
+            vault_address: vault_used,
+            yield_enabled: params.yield_enabled,
+            is_pending: false,
+            is_recurrent: false,
+            cycle_duration: 0,
+            cancellation_type: 0,
+            yield_recipient: 0,
+            split_address: None,
+            split_bps: 0,
+            curve_type: CurveType::Linear,
+            is_soulbound: false,
+            accrual_clock: 0,
         };
 
-        // Store the pending update
-        storage::set_pending_rate_update(&env, stream_id, &pending_update);
-
-        // Emit event
-        let expires_at = now.saturating_add(storage::RATE_UPDATE_TTL);
+        // 8. Emit event
+        let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
-        data.push_back(caller.into_val(&env));
-        data.push_back(current_rate.into_val(&env));
-        data.push_back(new_rate.into_val(&env));
-        data.push_back(new_end_time.into_val(&env));
-        data.push_back(expires_at.into_val(&env));
+        data.push_back(params.receiver.clone().into_val(&env));
+        data.push_back(params.token.clone().into_val(&env));
+        data.push_back(stream_amount.into_val(&env));
+        data.push_back(params.expiration_ledger.into_val(&env));
+        data.push_back(params.nonce.into_val(&env));
         data.push_back(now.into_val(&env));
 
         env.events().publish(
-            (stream_id, Symbol::new(&env, "rate_propose")),
+            (stream_id, symbol_short!("sig_claim")),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: Symbol::new(&env, "rate_propose"),
+                action: symbol_short!("sig_claim"),
                 data,
             },
         );
 
-        Ok(pending_update)
+        Ok(stream_id)
     }
 
-    /// Accept a pending rate update proposal.
-    ///
-    /// Only the stream receiver can accept a rate update.
-    /// This recalculates the end_time based on remaining_balance / new_rate.
-    ///
-    /// # Parameters
-    /// - `stream_id`: The ID of the stream with pending update
+    // ----------------------------------------------------------------
+    // Stream Templates
+    // ----------------------------------------------------------------
+
+    /// Register a named stream preset owned by `owner` (an admin or any sender).
     ///
-    /// # Returns
-    /// - `Ok(new_end_time)` if update was accepted
-    /// - `Err(Error)` if validation fails
-    pub fn accept_rate(env: Env, stream_id: u64) -> Result<u64, Error> {
+    /// The skeleton's `cliff_time` and `end_time` are stored relative to its
+    /// `start_time`; its `sender`, `receiver` and `total_amount` are ignored.
+    /// Returns the new template ID, published as version 1.
+    pub fn register_template(
+        env: Env,
+        owner: Address,
+        name: Symbol,
+        skeleton: StreamArgs,
+    ) -> Result<u64, Error> {
         Self::require_not_paused(&env)?;
+        owner.require_auth();
+        Self::validate_template(&env, &skeleton)?;
+
+        let template = StreamTemplate {
+            id: storage::next_template_id(&env),
+            owner,
+            name,
+            version: 1,
+            skeleton,
+            updated_at: env.ledger().timestamp(),
+        };
+        storage::set_template(&env, &template);
+        Self::emit_template_event(&env, &template, symbol_short!("tmpl_reg"));
 
-        // Get the stream
-        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        Ok(template.id)
+    }
 
-        // Only the receiver can accept
-        let caller = stream.receiver.clone();
-        caller.require_auth();
+    /// Publish a new version of a template. Owner-only.
+    ///
+    /// Earlier versions stay readable so streams keep a faithful record of the
+    /// preset they were created from. Returns the new version number.
+    pub fn update_template(env: Env, template_id: u64, skeleton: StreamArgs) -> Result<u32, Error> {
+        Self::require_not_paused(&env)?;
+        let mut template = storage::get_template(&env, template_id).ok_or(Error::StreamNotFound)?;
+        template.owner.require_auth();
+        Self::validate_template(&env, &skeleton)?;
 
-        // Validate stream is active
-        if stream.cancelled {
-            return Err(Error::StreamNotActive);
-        }
+        template.version += 1;
+        template.skeleton = skeleton;
+        template.updated_at = env.ledger().timestamp();
+        storage::set_template(&env, &template);
+        Self::emit_template_event(&env, &template, symbol_short!("tmpl_upd"));
 
-        // Get pending update
-        let pending_update =
-            storage::get_pending_rate_update(&env, stream_id).ok_or(Error::NoPendingUpdate)?;
+        Ok(template.version)
+    }
 
-        // Check if proposal has expired
-        if storage::is_pending_rate_update_expired(&env, stream_id) {
-            storage::remove_pending_rate_update(&env, stream_id);
-            return Err(Error::ExpiredDeadline);
-        }
+    /// Latest version of a template.
+    pub fn get_template(env: Env, template_id: u64) -> Option<StreamTemplate> {
+        storage::get_template(&env, template_id)
+    }
 
-        let now = env.ledger().timestamp();
+    /// A specific, possibly superseded, version of a template.
+    pub fn get_template_version(
+        env: Env,
+        template_id: u64,
+        version: u32,
+    ) -> Option<StreamTemplate> {
+        storage::get_template_version(&env, template_id, version)
+    }
 
-        // Calculate remaining balance at current time
-        let remaining_balance = Self::calculate_remaining_balance(&env, &stream, now)?;
+    /// Template and version a stream was created from, if any.
+    pub fn get_stream_template(env: Env, stream_id: u64) -> Option<StreamTemplateRef> {
+        storage::get_stream_template(&env, stream_id)
+    }
 
-        // Calculate new end time
-        let new_end_time = if pending_update.new_rate > 0 {
-            let additional_seconds = remaining_balance / pending_update.new_rate;
-            now.saturating_add(additional_seconds as u64)
-        } else {
-            return Err(Error::InvalidNewRate);
+    /// Create a stream from the latest version of a template.
+    ///
+    /// Cliff and end times are the template's offsets applied to `start_time`;
+    /// every other parameter comes from the skeleton. The stream goes through
+    /// the same checks as `create_stream` and records the template version used.
+    /// `expected_version` must match the latest version, so a template updated
+    /// after the caller read it is not silently applied.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`: If the template does not exist
+    /// - `InvalidNonce`: If the template is no longer at `expected_version`
+    /// - `InvalidTimeRange`: If the shifted schedule overflows
+    /// - Any error returned by `create_stream`
+    pub fn create_stream_from_template(
+        env: Env,
+        template_id: u64,
+        sender: Address,
+        receiver: Address,
+        total_amount: i128,
+        start_time: u64,
+        expected_version: u32,
+    ) -> Result<u64, Error> {
+        let template = storage::get_template(&env, template_id).ok_or(Error::StreamNotFound)?;
+        if template.version != expected_version {
+            return Err(Error::InvalidNonce);
+        }
+        let skeleton = &template.skeleton;
+
+        let cliff_offset = skeleton.cliff_time - skeleton.start_time;
+        let duration = skeleton.end_time - skeleton.start_time;
+        let cliff_time = start_time
+            .checked_add(cliff_offset)
+            .ok_or(Error::InvalidTimeRange)?;
+        let end_time = start_time
+            .checked_add(duration)
+            .ok_or(Error::InvalidTimeRange)?;
+
+        let args = StreamArgs {
+            sender,
+            receiver,
+            total_amount,
+            start_time,
+            cliff_time,
+            end_time,
+            ..skeleton.clone()
         };
+        let stream_id = Self::create_stream(env.clone(), args)?;
 
-        // Update the stream
-        stream.end_time = new_end_time;
-
-        // Update total_amount to reflect the new remaining balance
-        // (so that total streamed = original - remaining, and remaining = new_rate * new_duration)
-        // Actually, we keep total_amount as-is and only adjust end_time
-        // The stream effectively continues with the new rate until the remaining balance is exhausted
-
-        storage::set_stream(&env, stream_id, &stream);
-
-        // Remove the pending update
-        storage::remove_pending_rate_update(&env, stream_id);
+        let template_ref = StreamTemplateRef {
+            template_id,
+            version: template.version,
+        };
+        storage::set_stream_template(&env, stream_id, &template_ref);
 
-        // Emit event
+        let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
         data.push_back(stream_id.into_val(&env));
-        data.push_back(caller.into_val(&env));
-        data.push_back(pending_update.new_rate.into_val(&env));
-        data.push_back(new_end_time.into_val(&env));
-        data.push_back(remaining_balance.into_val(&env));
+        data.push_back(template_id.into_val(&env));
+        data.push_back(template.version.into_val(&env));
         data.push_back(now.into_val(&env));
 
         env.events().publish(
-            (stream_id, Symbol::new(&env, "rate_accept")),
+            (stream_id, symbol_short!("tmpl_use")),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: Symbol::new(&env, "rate_accept"),
+                action: symbol_short!("tmpl_use"),
                 data,
             },
         );
 
-        Ok(new_end_time)
+        Ok(stream_id)
     }
 
-    /// Cancel a pending rate update proposal.
-    ///
-    /// Either party (sender or receiver) can cancel the proposal.
-    ///
-    /// # Parameters
-    /// - `stream_id`: The ID of the stream with pending update
-    /// - `caller`: The address cancelling the proposal
-    pub fn cancel_rate_proposal(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
-        // Get the stream to validate caller is involved
-        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
-
-        // Caller must be sender or receiver
-        if caller != stream.sender && caller != stream.receiver {
-            return Err(Error::UnauthorizedSender);
+    fn validate_template(env: &Env, skeleton: &StreamArgs) -> Result<(), Error> {
+        Self::require_asset_whitelisted(env, &skeleton.token)?;
+        if skeleton.start_time >= skeleton.end_time
+            || skeleton.cliff_time < skeleton.start_time
+            || skeleton.cliff_time > skeleton.end_time
+        {
+            return Err(Error::InvalidTimeRange);
         }
-
-        // Check if pending update exists
-        if !storage::has_pending_rate_update(&env, stream_id) {
-            return Err(Error::NoPendingUpdate);
+        Self::validate_accrual_clock(skeleton.accrual_clock)?;
+        Self::validate_curve(&skeleton.curve_type, skeleton.start_time, skeleton.end_time)?;
+        if skeleton.penalty_bps > 10_000 {
+            return Err(Error::InvalidPenalty);
         }
+        Self::validate_cancellation_type(skeleton.cancellation_type)?;
+        Ok(())
+    }
 
-        // Check if expired
-        let is_expired = storage::is_pending_rate_update_expired(&env, stream_id);
-
-        // Remove the pending update
-        storage::remove_pending_rate_update(&env, stream_id);
-
-        // Emit event
+    fn emit_template_event(env: &Env, template: &StreamTemplate, action: Symbol) {
         let now = env.ledger().timestamp();
-        let mut data = Vec::new(&env);
-        data.push_back(stream_id.into_val(&env));
-        data.push_back(caller.into_val(&env));
-        data.push_back(if is_expired { 0u32 } else { 1u32 }.into_val(&env));
-        data.push_back(now.into_val(&env));
+        let mut data = Vec::new(env);
+        data.push_back(template.id.into_val(env));
+        data.push_back(template.owner.clone().into_val(env));
+        data.push_back(template.name.clone().into_val(env));
+        data.push_back(template.version.into_val(env));
+        data.push_back(now.into_val(env));
 
         env.events().publish(
-            (stream_id, Symbol::new(&env, "rate_cancel")),
+            (template.id, action.clone()),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: Symbol::new(&env, "rate_cancel"),
+                action,
                 data,
             },
         );
-
-        Ok(())
     }
 
-    /// Get the pending rate update for a stream.
+    // ----------------------------------------------------------------
+    // Issue #378 — Streaming Swap (DEX Integration)
+    // ----------------------------------------------------------------
+
+    /// Create a stream where the sender provides `asset_in`, which is automatically
+    /// swapped to `asset_out` via a Soroban AMM before initializing the stream.
+    ///
+    /// This enables use cases like:
+    /// - Stream XLM but receive USDC (swap XLM -> USDC first)
+    /// - Stream any asset but receive a stablecoin
     ///
     /// # Parameters
-    /// - `stream_id`: The ID of the stream
+    /// - `args`: SwapStreamArgs containing all swap and stream configuration
     ///
     /// # Returns
-    /// - `Some(PendingRateUpdate)` if a proposal exists and is not expired
-    /// - `None` if no proposal exists or if it has expired
-    pub fn get_pending_rate_update(
-        env: Env,
-        stream_id: u64,
-    ) -> Result<Option<PendingRateUpdate>, Error> {
-        // Check if stream exists
-        if storage::get_stream(&env, stream_id).is_none() {
-            return Err(Error::StreamNotFound);
-        }
+    /// - `Ok(stream_id)` if stream was created successfully
+    /// - `Err(Error)` if swap fails, slippage exceeded, or stream creation fails
+    ///
+    /// # Safety Features
+    /// 1. `min_amount_out` - Absolute minimum output; swap fails if not met
+    /// 2. `slippage_tolerance_bps` - Additional protection (e.g., 50 bps = 0.5%)
+    /// 3. `swap_deadline` - Prevents stale price execution
+    ///
+    /// With `swap_on_withdraw` the escrow stays in `asset_in` and nothing is
+    /// swapped here. Each `withdraw` swaps only the newly unlocked slice at the
+    /// prevailing price (dollar-cost averaging), bounded per slice by
+    /// `slippage_tolerance_bps` against the live quote and by
+    /// `max_price_impact_bps` against the spot price. Cancellation settles the
+    /// unswapped escrow in `asset_in`.
+    pub fn create_swap_stream(env: Env, args: SwapStreamArgs) -> Result<u64, Error> {
+        Self::require_not_paused(&env)?;
+        Self::require_not_emergency(&env)?;
 
-        // Check if pending update exists
-        if !storage::has_pending_rate_update(&env, stream_id) {
-            return Ok(None);
+        // Validate swap streaming is enabled
+        if !storage::is_swap_enabled(&env) {
+            return Err(Error::DexNotConfigured);
         }
 
-        // Check if expired
-        if storage::is_pending_rate_update_expired(&env, stream_id) {
-            // Clean up expired update
-            storage::remove_pending_rate_update(&env, stream_id);
-            return Ok(None);
-        }
+        // Validate sender authorized this call
+        args.sender.require_auth();
 
-        Ok(storage::get_pending_rate_update(&env, stream_id))
-    }
+        // Validate input parameters
+        if args.amount_in <= 0 {
+            return Err(Error::InvalidSwapParams);
+        }
 
-    /// Calculate the remaining balance in a stream.
-    /// This is used internally for rate rebalancing calculations.
-    fn calculate_remaining_balance(env: &Env, stream: &StreamV2, now: u64) -> Result<i128, Error> {
-        let effective_now = if now < stream.start_time {
-            stream.start_time
-        } else {
-            now
-        };
+        if args.asset_in == args.asset_out {
+            return Err(Error::SameAsset);
+        }
 
-        // If after end time, remaining is what's not yet withdrawn
-        if effective_now >= stream.end_time {
-            return Ok(stream.total_amount.saturating_sub(stream.withdrawn_amount));
+        // Validate slippage tolerance (0-100% = 0-10000 bps)
+        if args.slippage_tolerance_bps > 10_000 {
+            return Err(Error::InvalidPenalty);
         }
+        Self::validate_cancellation_type(args.cancellation_type)?;
 
-        // Calculate elapsed time since start
-        let elapsed = (effective_now - stream.start_time) as i128;
-        let total_duration = (stream.end_time - stream.start_time) as i128;
+        // Check deadline
+        let now = env.ledger().timestamp();
+        if !args.swap_on_withdraw && args.swap_deadline < now {
+            return Err(Error::ExpiredDeadline);
+        }
 
-        if total_duration <= 0 {
-            return Ok(0);
+        // Validate stream timing
+        if args.start_time >= args.end_time {
+            return Err(Error::InvalidTimeRange);
         }
 
-        // Calculate total unlocked (excluding cliff)
-        let cliff_duration = if stream.cliff_time > stream.start_time {
-            (stream.cliff_time - stream.start_time) as i128
-        } else {
-            0
-        };
+        if args.cliff_time < args.start_time || args.cliff_time > args.end_time {
+            return Err(Error::InvalidTimeRange);
+        }
 
-        let effective_elapsed = elapsed.saturating_sub(cliff_duration);
-        let effective_duration = total_duration.saturating_sub(cliff_duration);
+        // Validate both assets are whitelisted
+        Self::require_asset_whitelisted(&env, &args.asset_in)?;
+        Self::require_asset_whitelisted(&env, &args.asset_out)?;
 
-        if effective_duration <= 0 || effective_elapsed <= 0 {
-            return Ok(stream.total_amount.saturating_sub(stream.withdrawn_amount));
-        }
+        // Compliance oracle check
+        Self::require_compliant(&env, &args.sender)?;
+        Self::require_compliant(&env, &args.receiver)?;
 
-        // Calculate unlocked amount
-        let unlocked = (stream.total_amount * effective_elapsed) / effective_duration;
-        let remaining = stream.total_amount.saturating_sub(unlocked);
-        let withdrawable = remaining.saturating_sub(stream.withdrawn_amount);
+        // Pick the best route up front so a missing route fails before any transfer
+        let route = if args.swap_on_withdraw {
+            None
+        } else {
+            Some(Self::best_route(
+                &env,
+                args.amount_in,
+                &args.asset_in,
+                &args.asset_out,
+            )?)
+        };
 
-        Ok(withdrawable.max(0))
-    }
+        // Transfer asset_in from sender to this contract
+        let token_in_client = soroban_sdk::token::TokenClient::new(&env, &args.asset_in);
+        token_in_client.transfer(
+            &args.sender,
+            &env.current_contract_address(),
+            &args.amount_in,
+        );
 
-    // ----------------------------------------------------------------
-    // Issue #367 — Batch Stream Creation
-    // ----------------------------------------------------------------
+        let (stream_token, amount_out) = if args.swap_on_withdraw {
+            if args.max_price_impact_bps > 10_000 {
+                return Err(Error::InvalidPenalty);
+            }
+            // Escrow stays in asset_in; each withdrawal swaps its own slice.
+            (args.asset_in.clone(), args.amount_in)
+        } else {
+            // Calculate effective min_amount_out with slippage tolerance
+            // Apply slippage tolerance as an additional safety margin
+            let effective_min_amount_out = Self::calculate_min_amount_with_slippage(
+                args.amount_in,
+                args.min_amount_out,
+                args.slippage_tolerance_bps,
+            );
 
-    pub fn create_batch_streams(env: Env, streams: Vec<StreamArgs>) -> Result<Vec<u64>, Error> {
-        Self::require_not_paused(&env)?;
+            // Execute the swap along the route; slippage applies end to end
+            let amount_out = Self::execute_route(
+                &env,
+                &route.ok_or(Error::DexNotConfigured)?,
+                args.amount_in,
+                effective_min_amount_out,
+                args.swap_deadline,
+            )?;
+
+            // Safety check: verify we received at least the user's specified minimum
+            if amount_out < args.min_amount_out {
+                return Err(Error::SwapSlippageExceeded);
+            }
+            (args.asset_out.clone(), amount_out)
+        };
 
-        // Validate batch size limit (max 10 streams)
-        if streams.len() > 10 {
-            return Err(Error::BatchTooLarge);
-        }
+        // Calculate stream amount after protocol fee
+        let stream_amount =
+            Self::apply_protocol_fee(&env, &stream_token, amount_out, &args.affiliate)?;
 
-        if streams.is_empty() {
-            return Err(Error::InvalidTimeRange); // Reuse error for empty batch
+        // Check dust threshold for the streamed asset
+        if stream_amount < storage::get_min_value(&env, &stream_token) {
+            return Err(Error::BelowDustThreshold);
         }
 
-        // Validate all streams upfront to ensure atomicity
-        let mut total_amount: i128 = 0;
-        let sender = streams.get(0).unwrap().sender.clone();
-
-        for args in streams.iter() {
-            // All streams must have the same sender
-            if args.sender != sender {
-                return Err(Error::UnauthorizedSender);
-            }
-            Self::require_asset_whitelisted(&env, &args.token)?;
+        // Create the stream with the swapped asset
+        let stream_id = storage::next_stream_id(&env);
 
-            // Validate time ranges
-            if args.start_time >= args.end_time
-                || args.cliff_time < args.start_time
-                || args.cliff_time > args.end_time
-            {
-                return Err(Error::InvalidTimeRange);
+        // Handle vault deposit if yield is enabled
+        let mut vault_used = None;
+        if args.yield_enabled {
+            if let Some(vault_addr) = &args.vault_address {
+                Self::vault_deposit(&env, stream_id, vault_addr, &stream_token, stream_amount)?;
+                vault_used = Some(vault_addr.clone());
             }
+        }
 
-            // Validate dust threshold
-            if args.total_amount < storage::get_min_value(&env, &args.token) {
-                return Err(Error::BelowDustThreshold);
-            }
+        let stream = StreamV2 {
+            sender: args.sender.clone(),
+            receiver: args.receiver.clone(),
+            beneficiary: args.receiver.clone(),
+            token: stream_token.clone(),
+            total_amount: stream_amount,
+            start_time: args.start_time,
+            end_time: args.end_time,
+            cliff_time: args.cliff_time,
+            withdrawn_amount: 0,
+            cancelled: false,
+            migrated_from_v1: false,
+            v1_stream_id: 0,
+            step_duration: 0,      // Default linear stream
+            multiplier_bps: 10000, // 1.0x multiplier
+            penalty_bps: 0,        // Default no penalty
+            vault_address: vault_used,
+            yield_enabled: args.yield_enabled,
+            is_pending: false,
+            is_recurrent: false,
+            cycle_duration: 0,
+            cancellation_type: args.cancellation_type,
+            yield_recipient: args.yield_recipient,
+            split_address: args.split_address.clone(),
+            split_bps: args.split_bps,
+            curve_type: CurveType::Linear,
+            is_soulbound: false,
+            accrual_clock: 0,
+        };
 
-            total_amount = total_amount
-                .checked_add(args.total_amount)
-                .ok_or(Error::InvalidTimeRange)?; // Overflow protection
+        Self::insert_new_stream(&env, stream_id, &stream);
+        storage::update_stats(&env, stream_amount, &args.sender, &args.receiver);
+        if args.swap_on_withdraw {
+            storage::set_swap_schedule(
+                &env,
+                stream_id,
+                &SwapSchedule {
+                    asset_out: args.asset_out.clone(),
+                    slippage_tolerance_bps: args.slippage_tolerance_bps,
+                    max_price_impact_bps: args.max_price_impact_bps,
+                },
+            );
         }
 
-        // Require auth from the sender
-        sender.require_auth();
+        // Emit swap stream creation event
+        let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(args.sender.clone().into_val(&env));
+        data.push_back(args.receiver.clone().into_val(&env));
+        data.push_back(args.asset_in.clone().into_val(&env));
+        data.push_back(args.asset_out.clone().into_val(&env));
+        data.push_back(args.amount_in.into_val(&env));
+        data.push_back(amount_out.into_val(&env));
+        data.push_back(args.min_amount_out.into_val(&env));
+        data.push_back(now.into_val(&env));
 
-        // Calculate total amount needed and transfer all tokens at once
-        let token_client =
-            soroban_sdk::token::TokenClient::new(&env, &streams.get(0).unwrap().token);
-        token_client.transfer(&sender, &env.current_contract_address(), &total_amount);
+        env.events().publish(
+            (stream_id, Symbol::new(&env, "swap_stream")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: Symbol::new(&env, "swap_stream"),
+                data,
+            },
+        );
 
-        // Create all streams
-        let mut stream_ids = Vec::new(&env);
-        let mut total_created_amount: i128 = 0;
+        Ok(stream_id)
+    }
 
-        for args in streams.iter() {
-            let stream_id = storage::next_stream_id(&env);
-            let stream_amount = Self::apply_protocol_fee(&env, &args.token, args.total_amount)?;
+    /// Calculate the minimum amount considering slippage tolerance.
+    /// This provides additional safety beyond the user's specified min_amount_out.
+    fn calculate_min_amount_with_slippage(
+        _amount_in: i128,
+        min_amount_out: i128,
+        slippage_tolerance_bps: u32,
+    ) -> i128 {
+        // Apply slippage tolerance to min_amount_out
+        // This reduces the acceptable output by the slippage percentage
+        let tolerance_multiplier = 10_000 - slippage_tolerance_bps as i128;
+        // Use integer math: (min_amount_out * tolerance_multiplier) / 10000
+        (min_amount_out * tolerance_multiplier) / 10_000
+    }
 
-            let stream = StreamV2 {
-                sender: args.sender.clone(),
-                receiver: args.receiver.clone(),
-                beneficiary: args.receiver.clone(),
+    /// Swap settings of a `swap_on_withdraw` stream, if it is one.
+    pub fn get_swap_schedule(env: Env, stream_id: u64) -> Option<SwapSchedule> {
+        storage::get_swap_schedule(&env, stream_id)
+    }
+
+    /// Swap an unlocked slice of `amount_in` along the best available route.
+    ///
+    /// The minimum output is the live quote less `slippage_bps` (and never
+    /// below `min_out`); the swap is refused when the quote sits more than
+    /// `max_impact_bps` below the route's spot price. The realized price of
+    /// the slice is published as a `swap_px` event.
+    #[allow(clippy::too_many_arguments)]
+    fn swap_slice(
+        env: &Env,
+        stream_id: u64,
+        asset_in: &Address,
+        asset_out: &Address,
+        amount_in: i128,
+        slippage_bps: u32,
+        max_impact_bps: u32,
+        min_out: i128,
+    ) -> Result<i128, Error> {
+        if !storage::is_swap_enabled(env) {
+            return Err(Error::DexNotConfigured);
+        }
+        if asset_in == asset_out {
+            return Err(Error::SameAsset);
+        }
+        let route = Self::best_route(env, amount_in, asset_in, asset_out)?;
+        let quote = route.amount_out;
+        let spot_out = Self::route_spot_out(env, &route, amount_in)?;
+        let impact_bps = if spot_out > 0 && quote < spot_out {
+            (spot_out - quote) * 10_000 / spot_out
+        } else {
+            0
+        };
+        if impact_bps > max_impact_bps as i128 {
+            return Err(Error::SwapSlippageExceeded);
+        }
+        let min_amount_out =
+            Self::calculate_min_amount_with_slippage(amount_in, quote, slippage_bps).max(min_out);
+        if quote < min_amount_out {
+            return Err(Error::SwapSlippageExceeded);
+        }
+
+        let now = env.ledger().timestamp();
+        let amount_out = Self::execute_route(env, &route, amount_in, min_amount_out, now)?;
+
+        let price = math::FixedPoint::mul_div(amount_out, SPOT_PRICE_SCALE, amount_in)?;
+        let mut data = Vec::new(env);
+        data.push_back(stream_id.into_val(env));
+        data.push_back(asset_in.clone().into_val(env));
+        data.push_back(asset_out.clone().into_val(env));
+        data.push_back(amount_in.into_val(env));
+        data.push_back(amount_out.into_val(env));
+        data.push_back(price.into_val(env));
+        data.push_back(impact_bps.into_val(env));
+        data.push_back(now.into_val(env));
+
+        env.events().publish(
+            (stream_id, symbol_short!("swap_px")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("swap_px"),
+                data,
+            },
+        );
+
+        Ok(amount_out)
+    }
+
+    /// Quote every route from `asset_in` to `asset_out` over the registered
+    /// pools (up to `MAX_ROUTE_HOPS` hops), plus a direct swap through the
+    /// default DEX when one is set. Routes an adapter cannot quote are left out.
+    pub fn get_swap_routes(
+        env: Env,
+        amount_in: i128,
+        asset_in: Address,
+        asset_out: Address,
+    ) -> Result<Vec<SwapRoute>, Error> {
+        if amount_in <= 0 {
+            return Err(Error::InvalidSwapParams);
+        }
+        if asset_in == asset_out {
+            return Err(Error::SameAsset);
+        }
+        Ok(Self::quote_routes(&env, amount_in, &asset_in, &asset_out))
+    }
+
+    fn quote_routes(
+        env: &Env,
+        amount_in: i128,
+        asset_in: &Address,
+        asset_out: &Address,
+    ) -> Vec<SwapRoute> {
+        let mut candidates: Vec<Vec<RouteHop>> = Vec::new(env);
+        if let Some(dex) = storage::get_dex_address(env) {
+            candidates.push_back(soroban_sdk::vec![
+                env,
+                RouteHop {
+                    dex,
+                    token_in: asset_in.clone(),
+                    token_out: asset_out.clone(),
+                },
+            ]);
+        }
+        let pairs = storage::get_dex_pairs(env);
+        let adapters = storage::get_dex_adapters(env);
+        let mut path = Vec::new(env);
+        let mut visited = soroban_sdk::vec![env, asset_in.clone()];
+        Self::collect_routes(
+            env,
+            &pairs,
+            &adapters,
+            asset_in,
+            asset_out,
+            &mut path,
+            &mut visited,
+            &mut candidates,
+        );
+
+        let mut routes = Vec::new(env);
+        for hops in candidates.iter() {
+            let mut amount = amount_in;
+            for hop in hops.iter() {
+                amount = match SwapClient::new(env, &hop.dex).try_get_amount_out(
+                    &hop.token_in,
+                    &hop.token_out,
+                    &amount,
+                ) {
+                    Ok(Ok(out)) if out > 0 => out,
+                    _ => 0,
+                };
+                if amount == 0 {
+                    break;
+                }
+            }
+            if amount > 0 {
+                routes.push_back(SwapRoute {
+                    hops,
+                    amount_out: amount,
+                });
+            }
+        }
+        routes
+    }
+
+    /// Depth-first search over registered pools served by an enabled
+    /// adapter, appending simple paths from `current` to `target`. Follows at
+    /// most `MAX_ROUTE_BRANCHES` pools per token and stops once `routes`
+    /// holds `MAX_SWAP_ROUTES` candidates.
+    #[allow(clippy::too_many_arguments)]
+    fn collect_routes(
+        env: &Env,
+        pairs: &Vec<(Address, Address)>,
+        adapters: &Vec<Address>,
+        current: &Address,
+        target: &Address,
+        path: &mut Vec<RouteHop>,
+        visited: &mut Vec<Address>,
+        routes: &mut Vec<Vec<RouteHop>>,
+    ) {
+        if path.len() >= MAX_ROUTE_HOPS {
+            return;
+        }
+        let mut branches = 0u32;
+        for (token_a, token_b) in pairs.iter() {
+            if branches >= MAX_ROUTE_BRANCHES || routes.len() >= MAX_SWAP_ROUTES {
+                return;
+            }
+            let next = if token_a == *current {
+                token_b.clone()
+            } else if token_b == *current {
+                token_a.clone()
+            } else {
+                continue;
+            };
+            if visited.contains(&next) {
+                continue;
+            }
+            let pool = match storage::get_dex_pool(env, &token_a, &token_b) {
+                Some(pool) if adapters.contains(&pool.dex_address) => pool,
+                _ => continue,
+            };
+            branches += 1;
+
+            path.push_back(RouteHop {
+                dex: pool.dex_address,
+                token_in: current.clone(),
+                token_out: next.clone(),
+            });
+            if next == *target {
+                routes.push_back(path.clone());
+            } else {
+                visited.push_back(next.clone());
+                Self::collect_routes(env, pairs, adapters, &next, target, path, visited, routes);
+                visited.pop_back();
+            }
+            path.pop_back();
+        }
+    }
+
+    /// Highest-output route, or `DexNotConfigured` when swaps are disabled or
+    /// no route exists.
+    fn best_route(
+        env: &Env,
+        amount_in: i128,
+        asset_in: &Address,
+        asset_out: &Address,
+    ) -> Result<SwapRoute, Error> {
+        if !storage::is_swap_enabled(env) {
+            return Err(Error::DexNotConfigured);
+        }
+        let mut best: Option<SwapRoute> = None;
+        for route in Self::quote_routes(env, amount_in, asset_in, asset_out).iter() {
+            if best
+                .as_ref()
+                .is_none_or(|current| route.amount_out > current.amount_out)
+            {
+                best = Some(route);
+            }
+        }
+        best.ok_or(Error::DexNotConfigured)
+    }
+
+    /// Output of `amount_in` along `route` at each hop's spot price.
+    fn route_spot_out(env: &Env, route: &SwapRoute, amount_in: i128) -> Result<i128, Error> {
+        let mut amount = amount_in;
+        for hop in route.hops.iter() {
+            let spot = SwapClient::new(env, &hop.dex).get_spot_price(&hop.token_in, &hop.token_out);
+            amount = math::FixedPoint::mul_div(amount, spot, SPOT_PRICE_SCALE)?;
+        }
+        Ok(amount)
+    }
+
+    /// Swap `amount_in` hop by hop along `route`. Each hop's output is measured
+    /// from the contract's balance and fed into the next; `min_out` is checked
+    /// against the final output only.
+    fn execute_route(
+        env: &Env,
+        route: &SwapRoute,
+        amount_in: i128,
+        min_out: i128,
+        deadline: u64,
+    ) -> Result<i128, Error> {
+        let contract = env.current_contract_address();
+        let last = route.hops.len() - 1;
+        let mut amount = amount_in;
+        for (i, hop) in route.hops.iter().enumerate() {
+            soroban_sdk::token::TokenClient::new(env, &hop.token_in).approve(
+                &contract,
+                &hop.dex,
+                &amount,
+                &env.ledger().sequence().saturating_add(1),
+            );
+            let token_out = soroban_sdk::token::TokenClient::new(env, &hop.token_out);
+            let balance_before = token_out.balance(&contract);
+            let hop_min = if i as u32 == last { min_out } else { 0 };
+            SwapClient::new(env, &hop.dex).swap(
+                &hop.token_in,
+                &hop.token_out,
+                &amount,
+                &hop_min,
+                &deadline,
+            );
+            amount = token_out.balance(&contract) - balance_before;
+            if amount <= 0 {
+                return Err(Error::SwapSlippageExceeded);
+            }
+        }
+        if amount < min_out {
+            return Err(Error::SwapSlippageExceeded);
+        }
+        Ok(amount)
+    }
+
+    /// Get the expected output amount for a swap without executing it.
+    /// Useful for UI to show user expected output before confirming.
+    pub fn get_swap_quote(
+        env: Env,
+        amount_in: i128,
+        asset_in: Address,
+        asset_out: Address,
+    ) -> Result<SwapResult, Error> {
+        if !storage::is_swap_enabled(&env) {
+            return Err(Error::DexNotConfigured);
+        }
+
+        if amount_in <= 0 {
+            return Err(Error::InvalidSwapParams);
+        }
+
+        if asset_in == asset_out {
+            return Err(Error::SameAsset);
+        }
+
+        let amount_out = Self::best_route(&env, amount_in, &asset_in, &asset_out)?.amount_out;
+
+        // Calculate price impact (simplified - assumes 1:1 in economic units, so
+        // amount_in is first normalized from asset_in's to asset_out's decimals)
+        // Price impact = ((expected_out - theoretical_out) / theoretical_out) * 10000 bps
+        let theoretical_out = math::rescale(
+            amount_in,
+            Self::token_decimals(&env, &asset_in),
+            Self::token_decimals(&env, &asset_out),
+        );
+        let price_impact = if theoretical_out > 0 {
+            ((amount_out - theoretical_out) * 10000) / theoretical_out
+        } else {
+            0
+        };
+
+        Ok(SwapResult {
+            amount_in,
+            amount_out,
+            price_impact_bps: price_impact,
+        })
+    }
+
+    // ----------------------------------------------------------------
+    // Issue #377 — Push-Pull Rate Re-balancing
+    // ----------------------------------------------------------------
+
+    /// Propose a new rate for an active stream.
+    ///
+    /// The sender can propose to change the stream rate (faster or slower).
+    /// The receiver must accept the proposal for it to take effect.
+    ///
+    /// # Parameters
+    /// - `stream_id`: The ID of the stream to update
+    /// - `new_rate`: The new rate (amount per second)
+    ///
+    /// # Returns
+    /// - `Ok(())` if proposal was created successfully
+    /// - `Err(Error)` if validation fails
+    ///
+    /// # Constraints
+    /// - Only the stream sender can propose
+    /// - Stream must be active (not cancelled, not fully withdrawn)
+    /// - New rate must be > 0
+    /// - Sender must have sufficient balance for the change
+    pub fn propose_rate(
+        env: Env,
+        stream_id: u64,
+        new_rate: i128,
+    ) -> Result<PendingRateUpdate, Error> {
+        Self::require_not_paused(&env)?;
+
+        // Get the stream
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        // Only the sender can propose a rate change
+        let caller = stream.sender.clone();
+        caller.require_auth();
+
+        // Validate stream is active
+        if stream.cancelled {
+            return Err(Error::StreamNotActive);
+        }
+
+        // Check if fully withdrawn
+        if stream.withdrawn_amount >= stream.total_amount {
+            return Err(Error::StreamNotActive);
+        }
+        Self::require_reshapeable(&stream)?;
+
+        // Validate new rate
+        if new_rate <= 0 {
+            return Err(Error::InvalidNewRate);
+        }
+
+        if new_rate > Self::token_limits(&env, &stream.token).max_flow_rate {
+            return Err(Error::AmountOverflow);
+        }
+
+        // Calculate remaining balance
+        let now = env.ledger().timestamp();
+        let clock_now = Self::stream_now(&env, &stream);
+        let remaining_balance = Self::calculate_remaining_balance(&env, &stream, clock_now)?;
+
+        // Calculate current rate (total_amount / duration)
+        let duration = (stream.end_time - stream.start_time) as i128;
+        let current_rate = if duration > 0 {
+            stream.total_amount / duration
+        } else {
+            0
+        };
+
+        // If rate is unchanged, nothing to do
+        if current_rate == new_rate {
+            return Err(Error::InvalidNewRate);
+        }
+
+        // Calculate new end time based on remaining balance and new rate
+        // new_end_time = now + (remaining_balance / new_rate)
+        let new_end_time = if new_rate > 0 {
+            let additional_seconds = remaining_balance / new_rate;
+            clock_now.saturating_add(additional_seconds as u64)
+        } else {
+            return Err(Error::InvalidNewRate);
+        };
+
+        // Ensure new end time is in the future
+        if new_end_time <= clock_now {
+            return Err(Error::InsufficientBalance);
+        }
+
+        // Check if a pending update already exists
+        if storage::has_pending_rate_update(&env, stream_id) {
+            // Check if existing update has expired
+            if !storage::is_pending_rate_update_expired(&env, stream_id) {
+                return Err(Error::PendingUpdateExists);
+            }
+            // Remove expired update
+            storage::remove_pending_rate_update(&env, stream_id);
+        }
+
+        // Create the pending update
+        let pending_update = PendingRateUpdate {
+            new_rate,
+            proposed_at: now,
+            proposed_by: caller.clone(),
+            original_end_time: stream.end_time,
+            original_total_amount: stream.total_amount,
+        };
+
+        // Store the pending update
+        storage::set_pending_rate_update(&env, stream_id, &pending_update);
+
+        // Emit event
+        let expires_at = now.saturating_add(storage::RATE_UPDATE_TTL);
+        let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(caller.into_val(&env));
+        data.push_back(current_rate.into_val(&env));
+        data.push_back(new_rate.into_val(&env));
+        data.push_back(new_end_time.into_val(&env));
+        data.push_back(expires_at.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (stream_id, Symbol::new(&env, "rate_propose")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: Symbol::new(&env, "rate_propose"),
+                data,
+            },
+        );
+
+        Ok(pending_update)
+    }
+
+    /// Accept a pending rate update proposal.
+    ///
+    /// Only the stream receiver can accept a rate update.
+    /// This recalculates the end_time based on remaining_balance / new_rate.
+    ///
+    /// # Parameters
+    /// - `stream_id`: The ID of the stream with pending update
+    ///
+    /// # Returns
+    /// - `Ok(new_end_time)` if update was accepted
+    /// - `Err(Error)` if validation fails
+    pub fn accept_rate(env: Env, stream_id: u64) -> Result<u64, Error> {
+        Self::require_not_paused(&env)?;
+
+        // Get the stream
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        // Only the receiver can accept
+        stream.receiver.require_auth();
+
+        Self::accept_rate_internal(&env, stream_id, stream)
+    }
+
+    /// Body of `accept_rate` once the receiver is authorized.
+    fn accept_rate_internal(env: &Env, stream_id: u64, mut stream: StreamV2) -> Result<u64, Error> {
+        let env = env.clone();
+        let caller = stream.receiver.clone();
+
+        // Validate stream is active
+        if stream.cancelled {
+            return Err(Error::StreamNotActive);
+        }
+        Self::require_reshapeable(&stream)?;
+
+        // Get pending update
+        let pending_update =
+            storage::get_pending_rate_update(&env, stream_id).ok_or(Error::NoPendingUpdate)?;
+
+        // Check if proposal has expired
+        if storage::is_pending_rate_update_expired(&env, stream_id) {
+            storage::remove_pending_rate_update(&env, stream_id);
+            return Err(Error::ExpiredDeadline);
+        }
+
+        let now = env.ledger().timestamp();
+        let clock_now = Self::stream_now(&env, &stream);
+
+        // Calculate remaining balance at current time
+        let remaining_balance = Self::calculate_remaining_balance(&env, &stream, clock_now)?;
+
+        // Calculate new end time
+        let new_end_time = if pending_update.new_rate > 0 {
+            let additional_seconds = remaining_balance / pending_update.new_rate;
+            clock_now.saturating_add(additional_seconds as u64)
+        } else {
+            return Err(Error::InvalidNewRate);
+        };
+
+        // Update the stream
+        stream.end_time = new_end_time;
+
+        // Update total_amount to reflect the new remaining balance
+        // (so that total streamed = original - remaining, and remaining = new_rate * new_duration)
+        // Actually, we keep total_amount as-is and only adjust end_time
+        // The stream effectively continues with the new rate until the remaining balance is exhausted
+
+        storage::set_stream(&env, stream_id, &stream);
+
+        // Remove the pending update
+        storage::remove_pending_rate_update(&env, stream_id);
+
+        // Emit event
+        let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(caller.into_val(&env));
+        data.push_back(pending_update.new_rate.into_val(&env));
+        data.push_back(new_end_time.into_val(&env));
+        data.push_back(remaining_balance.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (stream_id, Symbol::new(&env, "rate_accept")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: Symbol::new(&env, "rate_accept"),
+                data,
+            },
+        );
+
+        Ok(new_end_time)
+    }
+
+    /// Cancel a pending rate update proposal.
+    ///
+    /// Either party (sender or receiver) can cancel the proposal.
+    ///
+    /// # Parameters
+    /// - `stream_id`: The ID of the stream with pending update
+    /// - `caller`: The address cancelling the proposal
+    pub fn cancel_rate_proposal(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
+        // Get the stream to validate caller is involved
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        // Caller must be sender or receiver
+        if caller != stream.sender && caller != stream.receiver {
+            return Err(Error::UnauthorizedSender);
+        }
+
+        // Check if pending update exists
+        if !storage::has_pending_rate_update(&env, stream_id) {
+            return Err(Error::NoPendingUpdate);
+        }
+
+        // Check if expired
+        let is_expired = storage::is_pending_rate_update_expired(&env, stream_id);
+
+        // Remove the pending update
+        storage::remove_pending_rate_update(&env, stream_id);
+
+        // Emit event
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(caller.into_val(&env));
+        data.push_back(if is_expired { 0u32 } else { 1u32 }.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (stream_id, Symbol::new(&env, "rate_cancel")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: Symbol::new(&env, "rate_cancel"),
+                data,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get the pending rate update for a stream.
+    ///
+    /// # Parameters
+    /// - `stream_id`: The ID of the stream
+    ///
+    /// # Returns
+    /// - `Some(PendingRateUpdate)` if a proposal exists and is not expired
+    /// - `None` if no proposal exists or if it has expired
+    pub fn get_pending_rate_update(
+        env: Env,
+        stream_id: u64,
+    ) -> Result<Option<PendingRateUpdate>, Error> {
+        // Check if stream exists
+        if storage::get_stream(&env, stream_id).is_none() {
+            return Err(Error::StreamNotFound);
+        }
+
+        // Check if pending update exists
+        if !storage::has_pending_rate_update(&env, stream_id) {
+            return Ok(None);
+        }
+
+        // Check if expired
+        if storage::is_pending_rate_update_expired(&env, stream_id) {
+            // Clean up expired update
+            storage::remove_pending_rate_update(&env, stream_id);
+            return Ok(None);
+        }
+
+        Ok(storage::get_pending_rate_update(&env, stream_id))
+    }
+
+    // ----------------------------------------------------------------
+    // Stream Amendments (reduce, extend/shorten, move cliff, terms)
+    // ----------------------------------------------------------------
+
+    /// Propose an amendment to a live stream.
+    ///
+    /// The sender may reduce `total_amount`, move `end_time` or `cliff_time`,
+    /// and change `penalty_bps` or `cancellation_type`. Nothing changes until
+    /// the receiver calls `accept_amendment`. Proposals expire after
+    /// `RATE_UPDATE_TTL`, like rate updates.
+    pub fn propose_amendment(
+        env: Env,
+        stream_id: u64,
+        amendment: StreamAmendment,
+    ) -> Result<PendingAmendment, Error> {
+        Self::require_not_paused(&env)?;
+
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        let caller = stream.sender.clone();
+        caller.require_auth();
+
+        if stream.cancelled {
+            return Err(Error::StreamNotActive);
+        }
+
+        let now = env.ledger().timestamp();
+        let clock_now = Self::stream_now(&env, &stream);
+        if clock_now >= stream.end_time {
+            return Err(Error::StreamNotActive);
+        }
+
+        let unlocked =
+            Self::calculate_unlocked_internal(&stream, Self::stream_clock_nanos(&env, &stream));
+        Self::validate_amendment(&stream, &amendment, unlocked, clock_now)?;
+
+        if let Some(existing) = storage::get_pending_amendment(&env, stream_id) {
+            if !storage::is_pending_amendment_expired(&env, &existing) {
+                return Err(Error::PendingUpdateExists);
+            }
+            storage::remove_pending_amendment(&env, stream_id);
+        }
+
+        let pending = PendingAmendment {
+            amendment: amendment.clone(),
+            proposed_at: now,
+            proposed_by: caller.clone(),
+        };
+        storage::set_pending_amendment(&env, stream_id, &pending);
+
+        let expires_at = now.saturating_add(storage::RATE_UPDATE_TTL);
+        let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(caller.into_val(&env));
+        data.push_back(amendment.into_val(&env));
+        data.push_back(expires_at.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (stream_id, Symbol::new(&env, "amend_propose")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: Symbol::new(&env, "amend_propose"),
+                data,
+            },
+        );
+
+        Ok(pending)
+    }
+
+    /// Accept the pending amendment for a stream. Only the receiver can accept.
+    ///
+    /// Balances vested before acceptance are unaffected: any vested but
+    /// unwithdrawn amount is paid to the beneficiary first, and the remaining
+    /// schedule is rebased to start now. A reduced total refunds the unvested
+    /// difference to the sender.
+    pub fn accept_amendment(env: Env, stream_id: u64) -> Result<StreamV2, Error> {
+        Self::require_not_paused(&env)?;
+
+        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        let caller = stream.receiver.clone();
+        caller.require_auth();
+
+        if stream.cancelled {
+            return Err(Error::StreamNotActive);
+        }
+
+        let pending =
+            storage::get_pending_amendment(&env, stream_id).ok_or(Error::NoPendingUpdate)?;
+
+        if storage::is_pending_amendment_expired(&env, &pending) {
+            storage::remove_pending_amendment(&env, stream_id);
+            return Err(Error::ExpiredDeadline);
+        }
+
+        let now = env.ledger().timestamp();
+        let clock_now = Self::stream_now(&env, &stream);
+        let unlocked =
+            Self::calculate_unlocked_internal(&stream, Self::stream_clock_nanos(&env, &stream))
+                .min(stream.total_amount);
+        let amendment = pending.amendment;
+        Self::validate_amendment(&stream, &amendment, unlocked, clock_now)?;
+
+        let new_total = amendment.new_total_amount.unwrap_or(stream.total_amount);
+        let refund = stream.total_amount - new_total;
+
+        // Only rebase when the vesting schedule itself changes; term-only
+        // amendments (penalty, cancellation type) leave the curve untouched.
+        let schedule_changed = amendment.new_total_amount.is_some()
+            || amendment.new_end_time.is_some()
+            || amendment.new_cliff_time.is_some();
+        let rebase = schedule_changed && clock_now > stream.start_time;
+        let vested_unpaid = if rebase {
+            unlocked.saturating_sub(stream.withdrawn_amount).max(0)
+        } else {
+            0
+        };
+
+        let settle = vested_unpaid + refund;
+        if settle > 0 {
+            if let Some(vault_addr) = &stream.vault_address {
+                if !Self::vault_redeem(&env, stream_id, &stream, vault_addr, settle)? {
+                    return Err(Error::InsufficientBalance);
+                }
+            }
+        }
+
+        // Allowance streams settle the vested balance from the sender's
+        // allowance and have no escrow to refund.
+        let funding = storage::get_allowance_funding(&env, stream_id);
+        if vested_unpaid > 0 {
+            Self::check_not_sanctioned(&env, &stream.beneficiary)?;
+            let payable = match &funding {
+                Some(funding) => {
+                    let (pulled, net) =
+                        Self::pull_allowance(&env, &stream, funding, vested_unpaid)?;
+                    if pulled < vested_unpaid {
+                        return Err(Error::InsufficientBalance);
+                    }
+                    net
+                }
+                None => vested_unpaid,
+            };
+            Self::pay_beneficiary(&env, stream_id, &stream, &stream.token, payable);
+        }
+
+        if refund > 0 && funding.is_none() {
+            Self::check_not_sanctioned(&env, &stream.sender)?;
+            let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
+            token_client.transfer(&env.current_contract_address(), &stream.sender, &refund);
+        }
+
+        if rebase {
+            stream.total_amount = new_total - unlocked;
+            stream.withdrawn_amount = 0;
+            stream.start_time = clock_now;
+        } else {
+            stream.total_amount = new_total;
+        }
+        if let Some(end_time) = amendment.new_end_time {
+            stream.end_time = end_time;
+        }
+        if let Some(cliff_time) = amendment.new_cliff_time {
+            stream.cliff_time = cliff_time;
+        }
+        if stream.cliff_time < stream.start_time {
+            stream.cliff_time = stream.start_time;
+        }
+        if let Some(penalty_bps) = amendment.new_penalty_bps {
+            stream.penalty_bps = penalty_bps;
+        }
+        if let Some(cancellation_type) = amendment.new_cancellation_type {
+            stream.cancellation_type = cancellation_type;
+        }
+
+        storage::set_stream(&env, stream_id, &stream);
+        storage::remove_pending_amendment(&env, stream_id);
+
+        if settle > 0 && funding.is_none() {
+            storage::update_stats(&env, -settle, &stream.sender, &stream.receiver);
+        }
+
+        let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(caller.into_val(&env));
+        data.push_back(amendment.into_val(&env));
+        data.push_back(vested_unpaid.into_val(&env));
+        data.push_back(refund.into_val(&env));
+        data.push_back(stream.total_amount.into_val(&env));
+        data.push_back(stream.start_time.into_val(&env));
+        data.push_back(stream.cliff_time.into_val(&env));
+        data.push_back(stream.end_time.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (stream_id, Symbol::new(&env, "amend_accept")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: Symbol::new(&env, "amend_accept"),
+                data,
+            },
+        );
+
+        Ok(stream)
+    }
+
+    /// Cancel a pending amendment. Either the sender or the receiver may cancel.
+    pub fn cancel_amendment(env: Env, stream_id: u64, caller: Address) -> Result<(), Error> {
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        if caller != stream.sender && caller != stream.receiver {
+            return Err(Error::UnauthorizedSender);
+        }
+        caller.require_auth();
+
+        let pending =
+            storage::get_pending_amendment(&env, stream_id).ok_or(Error::NoPendingUpdate)?;
+        let is_expired = storage::is_pending_amendment_expired(&env, &pending);
+
+        storage::remove_pending_amendment(&env, stream_id);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(caller.into_val(&env));
+        data.push_back(if is_expired { 0u32 } else { 1u32 }.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (stream_id, Symbol::new(&env, "amend_cancel")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: Symbol::new(&env, "amend_cancel"),
+                data,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Get the pending amendment for a stream, or `None` if absent or expired.
+    pub fn get_pending_amendment(
+        env: Env,
+        stream_id: u64,
+    ) -> Result<Option<PendingAmendment>, Error> {
+        if storage::get_stream(&env, stream_id).is_none() {
+            return Err(Error::StreamNotFound);
+        }
+
+        Ok(storage::get_pending_amendment(&env, stream_id)
+            .filter(|pending| !storage::is_pending_amendment_expired(&env, pending)))
+    }
+
+    /// Validate an amendment against the stream's current state.
+    fn validate_amendment(
+        stream: &StreamV2,
+        amendment: &StreamAmendment,
+        unlocked: i128,
+        now: u64,
+    ) -> Result<(), Error> {
+        if amendment.new_total_amount.is_none()
+            && amendment.new_end_time.is_none()
+            && amendment.new_cliff_time.is_none()
+            && amendment.new_penalty_bps.is_none()
+            && amendment.new_cancellation_type.is_none()
+        {
+            return Err(Error::InvalidNewRate);
+        }
+
+        // Schedule changes rebase the curve; only terms can change on a
+        // piecewise stream.
+        if amendment.new_total_amount.is_some()
+            || amendment.new_end_time.is_some()
+            || amendment.new_cliff_time.is_some()
+        {
+            Self::require_reshapeable(stream)?;
+        }
+
+        if let Some(new_total) = amendment.new_total_amount {
+            // Amendments can only reduce the escrow; use `top_up` to add funds.
+            if new_total > stream.total_amount {
+                return Err(Error::InvalidNewRate);
+            }
+            // Already-vested funds belong to the receiver.
+            if new_total < unlocked {
+                return Err(Error::InsufficientBalance);
+            }
+        }
+
+        let end_time = amendment.new_end_time.unwrap_or(stream.end_time);
+        let cliff_time = amendment.new_cliff_time.unwrap_or(stream.cliff_time);
+        if end_time <= now.max(stream.start_time) || cliff_time > end_time {
+            return Err(Error::InvalidTimeRange);
+        }
+
+        if let Some(penalty_bps) = amendment.new_penalty_bps {
+            if penalty_bps > 10_000 {
+                return Err(Error::InvalidPenalty);
+            }
+        }
+        if let Some(cancellation_type) = amendment.new_cancellation_type {
+            Self::validate_cancellation_type(cancellation_type)?;
+        }
+
+        Ok(())
+    }
+
+    /// Calculate the remaining balance in a stream.
+    /// This is used internally for rate rebalancing calculations.
+    fn calculate_remaining_balance(env: &Env, stream: &StreamV2, now: u64) -> Result<i128, Error> {
+        let effective_now = if now < stream.start_time {
+            stream.start_time
+        } else {
+            now
+        };
+
+        // If after end time, remaining is what's not yet withdrawn
+        if effective_now >= stream.end_time {
+            return Ok(stream.total_amount.saturating_sub(stream.withdrawn_amount));
+        }
+
+        // Calculate elapsed time since start
+        let elapsed = (effective_now - stream.start_time) as i128;
+        let total_duration = (stream.end_time - stream.start_time) as i128;
+
+        if total_duration <= 0 {
+            return Ok(0);
+        }
+
+        // Calculate total unlocked (excluding cliff)
+        let cliff_duration = if stream.cliff_time > stream.start_time {
+            (stream.cliff_time - stream.start_time) as i128
+        } else {
+            0
+        };
+
+        let effective_elapsed = elapsed.saturating_sub(cliff_duration);
+        let effective_duration = total_duration.saturating_sub(cliff_duration);
+
+        if effective_duration <= 0 || effective_elapsed <= 0 {
+            return Ok(stream.total_amount.saturating_sub(stream.withdrawn_amount));
+        }
+
+        // Calculate unlocked amount
+        let unlocked = (stream.total_amount * effective_elapsed) / effective_duration;
+        let remaining = stream.total_amount.saturating_sub(unlocked);
+        let withdrawable = remaining.saturating_sub(stream.withdrawn_amount);
+
+        Ok(withdrawable.max(0))
+    }
+
+    // ----------------------------------------------------------------
+    // Issue #367 — Batch Stream Creation
+    // ----------------------------------------------------------------
+
+    pub fn create_batch_streams(env: Env, streams: Vec<StreamArgs>) -> Result<Vec<u64>, Error> {
+        Self::require_not_paused(&env)?;
+
+        // Validate batch size limit (max 10 streams)
+        if streams.len() > 10 {
+            return Err(Error::BatchTooLarge);
+        }
+
+        if streams.is_empty() {
+            return Err(Error::InvalidTimeRange); // Reuse error for empty batch
+        }
+
+        // Validate all streams upfront to ensure atomicity
+        let mut total_amount: i128 = 0;
+        let sender = streams.get(0).unwrap().sender.clone();
+
+        for args in streams.iter() {
+            // All streams must have the same sender
+            if args.sender != sender {
+                return Err(Error::UnauthorizedSender);
+            }
+            Self::require_asset_whitelisted(&env, &args.token)?;
+
+            // Validate time ranges
+            if args.start_time >= args.end_time
+                || args.cliff_time < args.start_time
+                || args.cliff_time > args.end_time
+            {
+                return Err(Error::InvalidTimeRange);
+            }
+            Self::validate_accrual_clock(args.accrual_clock)?;
+            Self::validate_curve(&args.curve_type, args.start_time, args.end_time)?;
+
+            // Validate dust threshold
+            if args.total_amount < storage::get_min_value(&env, &args.token) {
+                return Err(Error::BelowDustThreshold);
+            }
+
+            total_amount = total_amount
+                .checked_add(args.total_amount)
+                .ok_or(Error::InvalidTimeRange)?; // Overflow protection
+        }
+
+        // Require auth from the sender
+        sender.require_auth();
+
+        // Calculate total amount needed and transfer all tokens at once
+        let token_client =
+            soroban_sdk::token::TokenClient::new(&env, &streams.get(0).unwrap().token);
+        token_client.transfer(&sender, &env.current_contract_address(), &total_amount);
+
+        // Create all streams
+        let mut stream_ids = Vec::new(&env);
+        let mut total_created_amount: i128 = 0;
+
+        for args in streams.iter() {
+            let stream_id = storage::next_stream_id(&env);
+            let stream_amount =
+                Self::apply_protocol_fee(&env, &args.token, args.total_amount, &args.affiliate)?;
+
+            let stream = StreamV2 {
+                sender: args.sender.clone(),
+                receiver: args.receiver.clone(),
+                beneficiary: args.receiver.clone(),
                 token: args.token.clone(),
                 total_amount: stream_amount,
                 start_time: args.start_time,
@@ -3428,290 +6422,1117 @@ impl Contract {
                 yield_recipient: args.yield_recipient,
                 split_address: args.split_address.clone(),
                 split_bps: args.split_bps,
-                curve_type: args.curve_type,
+                curve_type: args.curve_type.clone(),
+                is_soulbound: args.is_soulbound,
+                accrual_clock: args.accrual_clock,
+            };
+
+            Self::insert_new_stream(&env, stream_id, &stream);
+            storage::update_stats(&env, stream_amount, &args.sender, &args.receiver);
+
+            let now = env.ledger().timestamp();
+            let mut data = Vec::new(&env);
+            data.push_back(stream_id.into_val(&env));
+            data.push_back(args.sender.clone().into_val(&env));
+            data.push_back(args.receiver.clone().into_val(&env));
+            data.push_back(args.token.clone().into_val(&env));
+            data.push_back(stream_amount.into_val(&env));
+            data.push_back(args.start_time.into_val(&env));
+            data.push_back(args.cliff_time.into_val(&env));
+            data.push_back(args.end_time.into_val(&env));
+            data.push_back(now.into_val(&env));
+
+            env.events().publish(
+                (stream_id, symbol_short!("create_v2")),
+                NebulaEvent {
+                    version: 2,
+                    timestamp: now,
+                    action: symbol_short!("create_v2"),
+                    data,
+                },
+            );
+
+            stream_ids.push_back(stream_id);
+            total_created_amount = total_created_amount.checked_add(stream_amount).unwrap();
+        }
+
+        // Emit batch creation summary event
+        let now = env.ledger().timestamp();
+        let mut batch_data = Vec::new(&env);
+        batch_data.push_back(sender.clone().into_val(&env));
+        batch_data.push_back((stream_ids.len() as u32).into_val(&env));
+        batch_data.push_back(total_created_amount.into_val(&env));
+        batch_data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (symbol_short!("btch_crt"), sender.clone()),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("btch_crt"),
+                data: batch_data,
+            },
+        );
+
+        Ok(stream_ids)
+    }
+
+    // ----------------------------------------------------------------
+    // Issue #408 — Multi-sig Transaction Buffer (Stream Request Approval)
+    // ----------------------------------------------------------------
+
+    /// Initiate a stream request that requires multi-sig approval before execution.
+    /// This stores the stream parameters and sets approvals = 1 (first approval from initiator).
+    ///
+    /// The stream will only be created once the approval threshold is met.
+    pub fn initiate_stream_request(env: Env, args: StreamArgs) -> Result<u64, Error> {
+        Self::require_not_paused(&env)?;
+        args.sender.require_auth();
+        Self::require_asset_whitelisted(&env, &args.token)?;
+
+        // Validate time range
+        if args.start_time >= args.end_time
+            || args.cliff_time < args.start_time
+            || args.cliff_time > args.end_time
+        {
+            return Err(Error::InvalidTimeRange);
+        }
+        Self::validate_accrual_clock(args.accrual_clock)?;
+        Self::validate_curve(&args.curve_type, args.start_time, args.end_time)?;
+
+        // Validate penalty
+        if args.penalty_bps > 10_000 {
+            return Err(Error::InvalidPenalty);
+        }
+        Self::validate_cancellation_type(args.cancellation_type)?;
+
+        // Validate dust threshold
+        if args.total_amount < storage::get_min_value(&env, &args.token) {
+            return Err(Error::BelowDustThreshold);
+        }
+
+        let request_id = storage::next_stream_request_id(&env);
+        let threshold = storage::get_threshold(&env);
+
+        // Create pending request with first approval from the sender
+        let mut approved_by = Vec::new(&env);
+        approved_by.push_back(args.sender.clone());
+
+        let request = storage::PendingStreamRequest {
+            args: args.clone(),
+            approvals: 1,
+            approved_by,
+            created_at: env.ledger().timestamp(),
+            executed: false,
+        };
+
+        storage::set_pending_stream_request(&env, request_id, &request);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(request_id.into_val(&env));
+        data.push_back(args.sender.clone().into_val(&env));
+        data.push_back(args.receiver.clone().into_val(&env));
+        data.push_back(args.token.clone().into_val(&env));
+        data.push_back(args.total_amount.into_val(&env));
+        data.push_back(threshold.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (symbol_short!("req_init"), request_id),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("req_init"),
+                data,
+            },
+        );
+
+        Ok(request_id)
+    }
+
+    /// Approve a pending stream request. When approvals reach the threshold,
+    /// the stream is automatically created.
+    pub fn approve_stream_request(
+        env: Env,
+        request_id: u64,
+        approver: Address,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env)?;
+
+        // Get the admin list and threshold
+        let admin_list = storage::get_admin_list(&env);
+        let threshold = storage::get_threshold(&env);
+
+        // Verify the approver is in the admin list and require auth
+        if !admin_list.contains(&approver) {
+            return Err(Error::NotEnoughSigners);
+        }
+        approver.require_auth();
+
+        // Get the pending request
+        let mut request = storage::get_pending_stream_request(&env, request_id)
+            .ok_or(Error::StreamRequestNotFound)?;
+
+        // Check if already executed
+        if request.executed {
+            return Err(Error::StreamReqAlreadyExecuted);
+        }
+
+        // Check if already approved by this admin
+        if request.approved_by.contains(&approver) {
+            return Err(Error::AlreadyApproved);
+        }
+
+        // Add approval
+        request.approvals += 1;
+        request.approved_by.push_back(approver.clone());
+
+        let now = env.ledger().timestamp();
+
+        // If threshold reached, execute the stream creation
+        if request.approvals >= threshold {
+            request.executed = true;
+            storage::set_pending_stream_request(&env, request_id, &request);
+
+            // Execute the stream creation
+            let stream_id = Self::execute_stream_creation_internal(&env, request.args)?;
+
+            // Remove the pending request
+            storage::remove_pending_stream_request(&env, request_id);
+
+            let mut data = Vec::new(&env);
+            data.push_back(request_id.into_val(&env));
+            data.push_back(stream_id.into_val(&env));
+            data.push_back(now.into_val(&env));
+
+            env.events().publish(
+                (symbol_short!("req_exec"), request_id),
+                NebulaEvent {
+                    version: 2,
+                    timestamp: now,
+                    action: symbol_short!("req_exec"),
+                    data,
+                },
+            );
+
+            Ok(stream_id)
+        } else {
+            // Update the pending request with new approval
+            storage::set_pending_stream_request(&env, request_id, &request);
+
+            let mut data = Vec::new(&env);
+            data.push_back(request_id.into_val(&env));
+            data.push_back(approver.clone().into_val(&env));
+            data.push_back(request.approvals.into_val(&env));
+            data.push_back(threshold.into_val(&env));
+            data.push_back(now.into_val(&env));
+
+            env.events().publish(
+                (symbol_short!("req_appr"), request_id),
+                NebulaEvent {
+                    version: 2,
+                    timestamp: now,
+                    action: symbol_short!("req_appr"),
+                    data,
+                },
+            );
+
+            Ok(0) // Return 0 to indicate request not yet executed, but approval recorded
+        }
+    }
+
+    /// Get a pending stream request details
+    pub fn get_stream_request(env: Env, request_id: u64) -> Option<storage::PendingStreamRequest> {
+        storage::get_pending_stream_request(&env, request_id)
+    }
+
+    /// Internal helper to execute stream creation (used by approve_stream_request)
+    fn execute_stream_creation_internal(env: &Env, args: StreamArgs) -> Result<u64, Error> {
+        // Transfer tokens from sender
+        let token_client = soroban_sdk::token::TokenClient::new(env, &args.token);
+        token_client.transfer(
+            &args.sender,
+            &env.current_contract_address(),
+            &args.total_amount,
+        );
+
+        let stream_amount =
+            Self::apply_protocol_fee(env, &args.token, args.total_amount, &args.affiliate)?;
+
+        let stream_id = storage::next_stream_id(env);
+
+        let mut vault_used = None;
+        if args.yield_enabled {
+            if let Some(vault_addr) = &args.vault_address {
+                Self::vault_deposit(env, stream_id, vault_addr, &args.token, stream_amount)?;
+                vault_used = Some(vault_addr.clone());
+            }
+        }
+
+        let stream = StreamV2 {
+            sender: args.sender.clone(),
+            receiver: args.receiver.clone(),
+            beneficiary: args.receiver.clone(),
+            token: args.token.clone(),
+            total_amount: stream_amount,
+            start_time: args.start_time,
+            end_time: args.end_time,
+            cliff_time: args.cliff_time,
+            withdrawn_amount: 0,
+            cancelled: false,
+            migrated_from_v1: false,
+            v1_stream_id: 0,
+            step_duration: args.step_duration,
+            multiplier_bps: args.multiplier_bps,
+            penalty_bps: args.penalty_bps,
+            vault_address: vault_used,
+            yield_enabled: args.yield_enabled,
+            is_pending: false,
+            is_recurrent: args.is_recurrent,
+            cycle_duration: args.cycle_duration,
+            cancellation_type: args.cancellation_type,
+            yield_recipient: args.yield_recipient,
+            split_address: args.split_address.clone(),
+            split_bps: args.split_bps,
+            curve_type: args.curve_type.clone(),
+            is_soulbound: args.is_soulbound,
+            accrual_clock: args.accrual_clock,
+        };
+
+        Self::insert_new_stream(env, stream_id, &stream);
+        storage::update_stats(env, stream_amount, &args.sender, &args.receiver);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(env);
+        data.push_back(stream_id.into_val(env));
+        data.push_back(args.sender.clone().into_val(env));
+        data.push_back(args.receiver.clone().into_val(env));
+        data.push_back(args.token.clone().into_val(env));
+        data.push_back(stream_amount.into_val(env));
+        data.push_back(args.start_time.into_val(env));
+        data.push_back(args.cliff_time.into_val(env));
+        data.push_back(args.end_time.into_val(env));
+        data.push_back(now.into_val(env));
+
+        env.events().publish(
+            (stream_id, symbol_short!("create_v2")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("create_v2"),
+                data,
+            },
+        );
+
+        Ok(stream_id)
+    }
+
+    // ----------------------------------------------------------------
+    // Time-locked Admin Operations
+    // ----------------------------------------------------------------
+
+    /// Queue an admin mutation behind the `ADMIN_DELAY` (48h) timelock.
+    ///
+    /// `signers` must hold at least the admin threshold of current admins.
+    /// Parameters are validated here so a bad operation fails fast instead of
+    /// after the delay.
+    pub fn schedule_op(env: Env, signers: Vec<Address>, op: Operation) -> Result<(), Error> {
+        storage::require_multisig(&env, &signers)?;
+        Self::validate_op(&env, &op)?;
+
+        let execution_time = env.ledger().timestamp() + storage::ADMIN_DELAY;
+        storage::schedule_op(&env, &op, execution_time);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(execution_time.into_val(&env));
+        data.push_back(op.into_val(&env));
+
+        env.events().publish(
+            (symbol_short!("schedule"),),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("schedule"),
+                data,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Drop a scheduled operation before it executes. Requires the same
+    /// admin threshold as scheduling.
+    pub fn cancel_op(env: Env, signers: Vec<Address>, op: Operation) -> Result<(), Error> {
+        storage::require_multisig(&env, &signers)?;
+        storage::get_scheduled_op_time(&env, &op).ok_or(Error::OpNotScheduled)?;
+
+        storage::clear_op(&env, &op);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(op.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (symbol_short!("cancel_op"),),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("cancel_op"),
+                data,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// All operations currently waiting in the timelock, in scheduling order.
+    pub fn get_pending_ops(env: Env) -> Vec<ScheduledOperation> {
+        storage::get_pending_ops(&env)
+    }
+
+    pub fn execute_op(env: Env, op: Operation) -> Result<(), Error> {
+        let admin = storage::try_get_admin(&env)?;
+        admin.require_auth();
+
+        let execution_time =
+            storage::get_scheduled_op_time(&env, &op).ok_or(Error::OpNotScheduled)?;
+
+        if env.ledger().timestamp() < execution_time {
+            return Err(Error::NotExecutionTime);
+        }
+
+        // Execute the actual operation
+        match &op {
+            Operation::SetAdmins(new_admins, new_threshold) => {
+                Self::set_admins_internal(env.clone(), new_admins.clone(), *new_threshold)?;
+            }
+            Operation::TransferAdmin(new_admin) => {
+                Self::transfer_admin_internal(env.clone(), new_admin.clone())?;
+            }
+            Operation::SetMinValue(asset, min) => {
+                Self::set_min_value_internal(env.clone(), asset.clone(), *min)?;
+            }
+            Operation::SetTokenLimits(asset, max_stream_amount, max_flow_rate) => {
+                storage::set_token_limits(
+                    &env,
+                    asset,
+                    &TokenLimits {
+                        max_stream_amount: *max_stream_amount,
+                        max_flow_rate: *max_flow_rate,
+                    },
+                );
+            }
+            Operation::SetFeeBps(bps) => storage::set_fee_bps(&env, *bps),
+            Operation::SetFeeTiers(tiers) => storage::set_fee_tiers(&env, tiers.clone()),
+            Operation::SetTreasury(treasury) => storage::set_treasury(&env, treasury),
+            Operation::SetComplianceOracle(oracle) => storage::set_compliance_oracle(&env, oracle),
+            Operation::SetOracleAddress(oracle) => storage::set_oracle_address(&env, oracle),
+            Operation::SetDexAddress(dex) => storage::set_dex_address(&env, dex),
+            Operation::SetDexPool(token_in, token_out, pool_info) => {
+                // Other pairs may have filled the registry since scheduling.
+                if Self::dex_pairs_full(&env, token_in, token_out) {
+                    return Err(Error::BatchTooLarge);
+                }
+                storage::set_dex_pool(&env, token_in, token_out, pool_info)
+            }
+            Operation::AddToWhitelist(asset) => {
+                storage::add_to_whitelist(&env, asset);
+                // Cache decimals up front so limits are normalized from the first stream.
+                Self::token_decimals(&env, asset);
+            }
+            Operation::SetAffiliateShareBps(bps) => storage::set_affiliate_share_bps(&env, *bps),
+            Operation::SetDaoToken(token) => storage::set_dao_token(&env, token),
+            Operation::SetVotingThreshold(threshold) => {
+                storage::set_voting_threshold(&env, *threshold)
+            }
+            Operation::SetFeeCollector(collector) => storage::set_fee_collector(&env, collector),
+            Operation::SetFeeToken(token) => storage::set_fee_token(&env, token),
+            Operation::SetFeePerRecipient(amount) => storage::set_fee_per_recipient(&env, *amount),
+            Operation::SetRecoveryCouncil(council, threshold) => {
+                storage::set_recovery_council(&env, council, *threshold)
+            }
+            Operation::SetGuardian(guardian) => storage::set_guardian(&env, guardian),
+            Operation::SetBridgeAllowed(bridge, allowed) => {
+                storage::set_bridge_allowed(&env, bridge, *allowed)
+            }
+            Operation::SetGovernanceConfig(config) => storage::set_governance_config(&env, config),
+            Operation::SetVetoThreshold(threshold) => storage::set_veto_threshold(&env, *threshold),
+            Operation::SetKeeperBounty(task, token, bounty) => {
+                storage::set_keeper_bounty(&env, *task, token, *bounty)
+            }
+            Operation::SetFeeDistribution(shares) => storage::set_fee_distribution(&env, shares),
+            Operation::SetDexAdapter(adapter, enabled) => {
+                storage::set_dex_adapter(&env, adapter, *enabled)
+            }
+            Operation::SetVault(vault, config) => storage::set_vault_config(&env, vault, config),
+            Operation::RemoveVault(vault) => storage::remove_vault_config(&env, vault),
+            Operation::RemoveDexPool(token_in, token_out) => {
+                storage::remove_dex_pool(&env, token_in, token_out)
+            }
+            Operation::SetSwapEnabled(enabled) => storage::set_swap_enabled(&env, *enabled),
+            Operation::SetAffiliateApproved(affiliate, approved) => {
+                storage::set_affiliate(&env, affiliate, *approved)
+            }
+            Operation::SetKeeper(keeper, approved) => storage::set_keeper(&env, keeper, *approved),
+        }
+
+        storage::clear_op(&env, &op);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (symbol_short!("executed"),),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("executed"),
+                data,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Parameter checks shared by every timelocked operation.
+    fn validate_op(env: &Env, op: &Operation) -> Result<(), Error> {
+        match op {
+            Operation::SetAdmins(admins, threshold)
+            | Operation::SetRecoveryCouncil(admins, threshold)
+                if *threshold == 0 || *threshold > admins.len() =>
+            {
+                return Err(Error::InvalidThreshold);
+            }
+            Operation::SetTokenLimits(_, max_stream_amount, max_flow_rate)
+                if *max_stream_amount <= 0 || *max_flow_rate <= 0 =>
+            {
+                return Err(Error::AmountOverflow);
+            }
+            // Protocol fee cannot exceed MAX_FEE_BPS = 500 (5%).
+            Operation::SetFeeBps(bps) if *bps > MAX_FEE_BPS => return Err(Error::FeeTooHigh),
+            // Tiers must be in ascending order of threshold; none may exceed the cap.
+            Operation::SetFeeTiers(tiers) => {
+                for tier in tiers.iter() {
+                    if tier.fee_bps > MAX_FEE_BPS {
+                        return Err(Error::FeeTooHigh);
+                    }
+                }
+            }
+            Operation::SetGovernanceConfig(config)
+                if config.quorum < 0 || config.voting_period == 0 =>
+            {
+                return Err(Error::InvalidTimeRange);
+            }
+            Operation::SetKeeperBounty(_, _, bounty) if *bounty < 0 => {
+                return Err(Error::BelowDustThreshold);
+            }
+            // Up to MAX_FEE_SHARES non-zero shares summing to exactly 100%.
+            Operation::SetFeeDistribution(shares) => {
+                if shares.len() > MAX_FEE_SHARES {
+                    return Err(Error::BatchTooLarge);
+                }
+                let mut total_bps = 0u32;
+                for share in shares.iter() {
+                    if share.bps == 0 {
+                        return Err(Error::InvalidThreshold);
+                    }
+                    total_bps = total_bps.saturating_add(share.bps);
+                }
+                if !shares.is_empty() && total_bps != 10_000 {
+                    return Err(Error::InvalidThreshold);
+                }
+            }
+            Operation::SetVetoThreshold(threshold) if *threshold < 0 => {
+                return Err(Error::InvalidThreshold);
+            }
+            Operation::SetVault(_, config) if config.deposit_cap < 0 => {
+                return Err(Error::InvalidThreshold);
+            }
+            Operation::SetAffiliateShareBps(bps) if *bps > 10_000 => {
+                return Err(Error::FeeTooHigh);
+            }
+            // Pass 0 to disable disbursement fee collection.
+            Operation::SetFeePerRecipient(amount) if *amount < 0 => {
+                return Err(Error::BelowDustThreshold);
+            }
+            Operation::SetDexPool(token_in, token_out, _)
+                if Self::dex_pairs_full(env, token_in, token_out) =>
+            {
+                return Err(Error::BatchTooLarge);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether registering `token_in`/`token_out` would exceed `MAX_DEX_PAIRS`.
+    /// Reconfiguring an already registered pair is always allowed.
+    fn dex_pairs_full(env: &Env, token_in: &Address, token_out: &Address) -> bool {
+        let pairs = storage::get_dex_pairs(env);
+        pairs.len() >= MAX_DEX_PAIRS && !pairs.contains(&(token_in.clone(), token_out.clone()))
+    }
+
+    // ----------------------------------------------------------------
+    // Emergency Guardian
+    // ----------------------------------------------------------------
+
+    /// Pause the contract immediately. The guardian (set via
+    /// `Operation::SetGuardian`) can only pause; unpausing stays with the admin.
+    pub fn guardian_pause(env: Env, guardian: Address) -> Result<(), Error> {
+        if storage::get_guardian(&env) != Some(guardian.clone()) {
+            return Err(Error::UnauthorizedSender);
+        }
+        guardian.require_auth();
+        storage::set_paused(&env, true);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(guardian.clone().into_val(&env));
+        data.push_back(now.into_val(&env));
+        env.events().publish(
+            (symbol_short!("pause"), guardian.clone()),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("pause"),
+                data,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn get_guardian(env: Env) -> Option<Address> {
+        storage::get_guardian(&env)
+    }
+
+    /// Pull a page of streams out of an unhealthy yield vault. Callable by
+    /// any admin or the guardian.
+    ///
+    /// Each stream's shares (or principal) are redeemed back into the
+    /// contract and its `vault_address` is cleared, so it keeps streaming
+    /// from the contract's own balance. Yield above the principal goes to the
+    /// stream's yield recipient; a redemption that comes back short writes
+    /// the missing principal off that stream's `total_amount`. Streams whose
+    /// redemption fails outright stay in the vault and are counted as failed,
+    /// so the exit can be retried. The vault is also dropped from the
+    /// allowlist on the first page.
+    ///
+    /// Up to `limit` of the vault's listed streams are tried, from index
+    /// `start`. Exited streams leave the list, so the next page starts at
+    /// the returned `cursor`. Streams deposited before positions were
+    /// tracked are only listed once `track_vault_stream` has been called.
+    ///
+    /// # Errors
+    /// - `UnauthorizedSender`: If `caller` is neither an admin nor the guardian
+    /// - `BatchTooLarge`: If `limit` is 0 or above `MAX_PAGE_SIZE`
+    pub fn emergency_exit_vault(
+        env: Env,
+        caller: Address,
+        vault: Address,
+        start: u32,
+        limit: u32,
+    ) -> Result<VaultExitReport, Error> {
+        let admins = storage::try_get_admin_list(&env)?;
+        if !admins.contains(&caller) && storage::get_guardian(&env) != Some(caller.clone()) {
+            return Err(Error::UnauthorizedSender);
+        }
+        caller.require_auth();
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(Error::BatchTooLarge);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut report = VaultExitReport {
+            exited: 0,
+            failed: 0,
+            principal: 0,
+            recovered: 0,
+            shortfall: 0,
+            cursor: start,
+            remaining: 0,
+        };
+
+        let streams = storage::get_vault_streams(&env, &vault);
+        let end = start.saturating_add(limit).min(streams.len());
+        for stream_id in streams.slice(start.min(end)..end).iter() {
+            let mut stream = match storage::get_stream(&env, stream_id) {
+                Some(stream) if stream.vault_address == Some(vault.clone()) => stream,
+                _ => {
+                    storage::remove_vault_stream(&env, &vault, stream_id);
+                    continue;
+                }
             };
+            // Streams whose redemption fails stay listed so the exit can be retried.
+            match Self::exit_vault_stream(&env, stream_id, &mut stream, &vault) {
+                Some((principal, recovered, shortfall)) => {
+                    report.exited += 1;
+                    report.principal += principal;
+                    report.recovered += recovered;
+                    report.shortfall += shortfall;
+                }
+                None => {
+                    report.failed += 1;
+                    report.cursor += 1;
+                }
+            }
+        }
+        report.remaining = storage::get_vault_streams(&env, &vault)
+            .len()
+            .saturating_sub(report.cursor);
 
-            storage::set_stream(&env, stream_id, &stream);
-            storage::update_stats(&env, stream_amount, &args.sender, &args.receiver);
+        storage::remove_vault_config(&env, &vault);
 
-            let now = env.ledger().timestamp();
-            let mut data = Vec::new(&env);
-            data.push_back(stream_id.into_val(&env));
-            data.push_back(args.sender.clone().into_val(&env));
-            data.push_back(args.receiver.clone().into_val(&env));
-            data.push_back(args.token.clone().into_val(&env));
-            data.push_back(stream_amount.into_val(&env));
-            data.push_back(args.start_time.into_val(&env));
-            data.push_back(args.cliff_time.into_val(&env));
-            data.push_back(args.end_time.into_val(&env));
-            data.push_back(now.into_val(&env));
+        let mut data = Vec::new(&env);
+        data.push_back(vault.clone().into_val(&env));
+        data.push_back(caller.into_val(&env));
+        data.push_back(report.exited.into_val(&env));
+        data.push_back(report.failed.into_val(&env));
+        data.push_back(report.recovered.into_val(&env));
+        data.push_back(report.shortfall.into_val(&env));
+        env.events().publish(
+            (symbol_short!("vlt_exit"), vault),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("vlt_exit"),
+                data,
+            },
+        );
+        Ok(report)
+    }
 
-            env.events().publish(
-                (stream_id, symbol_short!("create_v2")),
-                NebulaEvent {
-                    version: 2,
-                    timestamp: now,
-                    action: symbol_short!("create_v2"),
-                    data,
-                },
-            );
+    /// List a stream deposited into its vault before positions were tracked,
+    /// recording its unwithdrawn balance as principal, so
+    /// `emergency_exit_vault` and `get_vault_deposits` see it. Anyone may
+    /// call this; it only indexes what the stream already holds.
+    ///
+    /// # Errors
+    /// - `StreamNotFound`: If the stream does not exist
+    /// - `StreamNotMigratable`: If the stream is cancelled, has no vault or
+    ///   already has a position
+    pub fn track_vault_stream(env: Env, stream_id: u64) -> Result<VaultPosition, Error> {
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        let vault = match &stream.vault_address {
+            Some(vault) if !stream.cancelled => vault.clone(),
+            _ => return Err(Error::StreamNotMigratable),
+        };
+        if storage::get_vault_position(&env, stream_id).is_some() {
+            return Err(Error::StreamNotMigratable);
+        }
+        let principal = Self::vault_principal(&env, stream_id, &stream);
+        let position = VaultPosition {
+            kind: VaultKind::Principal,
+            shares: principal,
+            principal,
+        };
+        storage::set_vault_position(&env, stream_id, &position);
+        storage::add_vault_stream(&env, &vault, stream_id);
+        let deposits = storage::get_vault_deposits(&env, &vault).saturating_add(principal);
+        storage::set_vault_deposits(&env, &vault, deposits);
+        Ok(position)
+    }
 
-            stream_ids.push_back(stream_id);
-            total_created_amount = total_created_amount.checked_add(stream_amount).unwrap();
+    /// Move a stream's principal from its current yield vault into another
+    /// allowlisted vault. Sender only.
+    ///
+    /// The old position is redeemed in full, paying its yield to the yield
+    /// recipient, and the principal is deposited into `new_vault` within its
+    /// deposit cap.
+    ///
+    /// # Returns
+    /// - `Ok(principal)`: The principal moved
+    ///
+    /// # Errors
+    /// - `StreamNotMigratable`: If the stream has no vault or is already in `new_vault`
+    /// - `AssetNotWhitelisted`: If `new_vault` is not allowlisted
+    /// - `AmountOverflow`: If the deposit would exceed `new_vault`'s cap
+    /// - `InsufficientBalance`: If the old vault cannot return the principal
+    pub fn migrate_stream_vault(
+        env: Env,
+        stream_id: u64,
+        new_vault: Address,
+    ) -> Result<i128, Error> {
+        Self::require_not_paused_only(&env)?;
+        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        stream.sender.require_auth();
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        let old_vault = stream
+            .vault_address
+            .clone()
+            .ok_or(Error::StreamNotMigratable)?;
+        if old_vault == new_vault {
+            return Err(Error::StreamNotMigratable);
+        }
+        if storage::get_vault_config(&env, &new_vault).is_none() {
+            return Err(Error::AssetNotWhitelisted);
         }
 
-        // Emit batch creation summary event
-        let now = env.ledger().timestamp();
-        let mut batch_data = Vec::new(&env);
-        batch_data.push_back(sender.clone().into_val(&env));
-        batch_data.push_back((stream_ids.len() as u32).into_val(&env));
-        batch_data.push_back(total_created_amount.into_val(&env));
-        batch_data.push_back(now.into_val(&env));
+        let principal = Self::vault_principal(&env, stream_id, &stream);
+        if principal > 0 {
+            if !Self::vault_redeem(&env, stream_id, &stream, &old_vault, principal)? {
+                return Err(Error::InsufficientBalance);
+            }
+            Self::vault_deposit(&env, stream_id, &new_vault, &stream.token, principal)?;
+        } else {
+            storage::remove_vault_position(&env, stream_id);
+        }
+        stream.vault_address = Some(new_vault.clone());
+        stream.is_pending = false;
+        storage::set_stream(&env, stream_id, &stream);
 
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(old_vault.into_val(&env));
+        data.push_back(new_vault.into_val(&env));
+        data.push_back(principal.into_val(&env));
+        data.push_back(now.into_val(&env));
         env.events().publish(
-            (symbol_short!("btch_crt"), sender.clone()),
+            (stream_id, symbol_short!("vlt_move")),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: symbol_short!("btch_crt"),
-                data: batch_data,
+                action: symbol_short!("vlt_move"),
+                data,
             },
         );
-
-        Ok(stream_ids)
+        Ok(principal)
     }
 
     // ----------------------------------------------------------------
-    // Issue #408 — Multi-sig Transaction Buffer (Stream Request Approval)
+    // Issue: Recurrent Streams — refill_stream
     // ----------------------------------------------------------------
 
-    /// Initiate a stream request that requires multi-sig approval before execution.
-    /// This stores the stream parameters and sets approvals = 1 (first approval from initiator).
+    /// Attempt to auto-renew a recurrent stream after its cycle has ended.
     ///
-    /// The stream will only be created once the approval threshold is met.
-    pub fn initiate_stream_request(env: Env, args: StreamArgs) -> Result<u64, Error> {
+    /// The contract uses `transfer_from` to pull `total_amount` tokens from
+    /// the sender — the sender must have pre-approved the contract as spender
+    /// via the token's `approve` function before calling this.
+    ///
+    /// - If the transfer succeeds: a new cycle begins immediately.
+    /// - If the transfer fails (insufficient allowance / balance):
+    ///   `is_recurrent` is set to `false` and the stream stays completed.
+    ///
+    /// This function is permissionless and can be triggered by keeper bots.
+    pub fn refill_stream(env: Env, stream_id: u64) -> Result<(), Error> {
+        Self::refill(&env, stream_id)?;
+        Ok(())
+    }
+
+    /// Renew a recurrent stream; returns whether a new cycle was funded.
+    fn refill(env: &Env, stream_id: u64) -> Result<bool, Error> {
+        let env = env.clone();
         Self::require_not_paused(&env)?;
-        args.sender.require_auth();
-        Self::require_asset_whitelisted(&env, &args.token)?;
 
-        // Validate time range
-        if args.start_time >= args.end_time
-            || args.cliff_time < args.start_time
-            || args.cliff_time > args.end_time
-        {
-            return Err(Error::InvalidTimeRange);
-        }
+        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
 
-        // Validate penalty
-        if args.penalty_bps > 10_000 {
-            return Err(Error::InvalidPenalty);
+        if !stream.is_recurrent {
+            return Err(Error::StreamNotMigratable);
+        }
+        if stream.cancelled {
+            return Err(Error::AlreadyCancelled);
         }
 
-        // Validate dust threshold
-        if args.total_amount < storage::get_min_value(&env, &args.token) {
-            return Err(Error::BelowDustThreshold);
+        let now = env.ledger().timestamp();
+        if Self::stream_now(&env, &stream) < stream.end_time {
+            return Err(Error::NotExecutionTime);
         }
+        // A piecewise schedule only repeats over a cycle of its own length.
+        Self::validate_curve(
+            &stream.curve_type,
+            stream.end_time,
+            stream.end_time + stream.cycle_duration,
+        )?;
 
-        let request_id = storage::next_stream_request_id(&env);
-        let threshold = storage::get_threshold(&env);
+        let original_amount = stream.total_amount;
+        let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
 
-        // Create pending request with first approval from the sender
-        let mut approved_by = Vec::new(&env);
-        approved_by.push_back(args.sender.clone());
+        // Contract is the approved spender; sender must have set allowance beforehand.
+        let result = token_client.try_transfer_from(
+            &env.current_contract_address(),
+            &stream.sender,
+            &env.current_contract_address(),
+            &original_amount,
+        );
 
-        let request = storage::PendingStreamRequest {
-            args: args.clone(),
-            approvals: 1,
-            approved_by,
-            created_at: env.ledger().timestamp(),
-            executed: false,
-        };
+        if result.is_err() {
+            // Allowance exhausted or transfer failed — disable recurrence.
+            stream.is_recurrent = false;
+            storage::set_stream(&env, stream_id, &stream);
+            return Ok(false);
+        }
 
-        storage::set_pending_stream_request(&env, request_id, &request);
+        let old_end_time = stream.end_time;
+        let new_end_time = old_end_time + stream.cycle_duration;
+
+        stream.start_time = old_end_time;
+        stream.end_time = new_end_time;
+        stream.withdrawn_amount = 0; // Reset for the new cycle
+
+        storage::set_stream(&env, stream_id, &stream);
+        storage::update_stats(&env, original_amount, &stream.sender, &stream.receiver);
 
-        let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
-        data.push_back(request_id.into_val(&env));
-        data.push_back(args.sender.clone().into_val(&env));
-        data.push_back(args.receiver.clone().into_val(&env));
-        data.push_back(args.token.clone().into_val(&env));
-        data.push_back(args.total_amount.into_val(&env));
-        data.push_back(threshold.into_val(&env));
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(stream.sender.clone().into_val(&env));
+        data.push_back(original_amount.into_val(&env));
+        data.push_back(old_end_time.into_val(&env));
+        data.push_back(new_end_time.into_val(&env));
         data.push_back(now.into_val(&env));
 
         env.events().publish(
-            (symbol_short!("req_init"), request_id),
+            (stream_id, symbol_short!("refill")),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: symbol_short!("req_init"),
+                action: symbol_short!("refill"),
                 data,
             },
         );
 
-        Ok(request_id)
+        Ok(true)
     }
 
-    /// Approve a pending stream request. When approvals reach the threshold,
-    /// the stream is automatically created.
-    pub fn approve_stream_request(
-        env: Env,
-        request_id: u64,
-        approver: Address,
-    ) -> Result<u64, Error> {
-        Self::require_not_paused(&env)?;
+    // ----------------------------------------------------------------
+    // Issue: Protocol Fees — admin helpers + treasury withdrawal
+    // ----------------------------------------------------------------
 
-        // Get the admin list and threshold
-        let admin_list = storage::get_admin_list(&env);
-        let threshold = storage::get_threshold(&env);
+    /// Get the current treasury address.
+    pub fn get_treasury(env: Env) -> Option<Address> {
+        storage::get_treasury(&env)
+    }
 
-        // Verify the approver is in the admin list and require auth
-        if !admin_list.contains(&approver) {
-            return Err(Error::NotEnoughSigners);
+    // ----------------------------------------------------------------
+    // Issue #412 — Compliance Oracle
+    // ----------------------------------------------------------------
+
+    pub fn get_compliance_oracle(env: Env) -> Option<Address> {
+        storage::get_compliance_oracle(&env)
+    }
+
+    // ----------------------------------------------------------------
+    // Issue #413 — Circular Event Log
+    // ----------------------------------------------------------------
+
+    /// Append a raw event payload to the stream's circular log (max 50 entries).
+    /// Any caller may append; the log is informational and not access-controlled.
+    pub fn append_event_log(env: Env, stream_id: u64, data: Bytes) -> Result<(), Error> {
+        // Verify the stream exists before accepting log entries.
+        storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        storage::append_event_log(&env, stream_id, data);
+        Ok(())
+    }
+
+    /// Return the last ≤50 event payloads for `stream_id` in insertion order.
+    pub fn get_stream_event_log(env: Env, stream_id: u64) -> soroban_sdk::Vec<Bytes> {
+        storage::get_event_log(&env, stream_id)
+    }
+
+    /// Get the current tiered fee configuration.
+    pub fn get_fee_tiers(env: Env) -> Vec<FeeTier> {
+        match storage::get_fee_tiers(&env) {
+            Some(tiers) => tiers,
+            None => Vec::new(&env),
         }
-        approver.require_auth();
+    }
 
-        // Get the pending request
-        let mut request = storage::get_pending_stream_request(&env, request_id)
-            .ok_or(Error::StreamRequestNotFound)?;
+    /// Delist an asset. Admin-only and immediate: like `pause`, it only
+    /// blocks new streams, so it is not routed through the timelock.
+    pub fn remove_from_whitelist(env: Env, asset: Address) -> Result<(), Error> {
+        storage::try_get_admin(&env)?.require_auth();
+        storage::remove_from_whitelist(&env, &asset);
+        Ok(())
+    }
+
+    pub fn is_asset_whitelisted(env: Env, asset: Address) -> bool {
+        storage::is_asset_whitelisted(&env, &asset)
+    }
+
+    /// Get the current protocol fee in basis points.
+    pub fn get_fee_bps(env: Env) -> u32 {
+        storage::get_fee_bps(&env)
+    }
 
-        // Check if already executed
-        if request.executed {
-            return Err(Error::StreamReqAlreadyExecuted);
-        }
+    /// Withdraw accumulated protocol fees for `token`. Admin-only.
+    ///
+    /// Fees are paid out according to `Operation::SetFeeDistribution`, or
+    /// sent to the treasury in full when no table is configured.
+    pub fn withdraw_treasury(env: Env, token: Address) -> Result<i128, Error> {
+        storage::try_get_admin(&env)?.require_auth();
 
-        // Check if already approved by this admin
-        if request.approved_by.contains(&approver) {
-            return Err(Error::AlreadyApproved);
+        let treasury = storage::get_treasury(&env).ok_or(Error::NoTreasury)?;
+        let amount = storage::get_pending_fees(&env, &treasury, &token);
+        if amount <= 0 {
+            return Err(Error::NothingToWithdraw);
         }
 
-        // Add approval
-        request.approvals += 1;
-        request.approved_by.push_back(approver.clone());
+        storage::clear_pending_fees(&env, &treasury, &token);
+        Self::distribute_treasury_fees(&env, &treasury, &token, amount);
+        Ok(amount)
+    }
 
-        let now = env.ledger().timestamp();
+    /// Withdraw the pending fees of every whitelisted asset in one call.
+    /// Admin-only. Returns the amount paid out per token; tokens with
+    /// nothing pending are skipped.
+    pub fn withdraw_treasury_all(env: Env) -> Result<soroban_sdk::Map<Address, i128>, Error> {
+        storage::try_get_admin(&env)?.require_auth();
 
-        // If threshold reached, execute the stream creation
-        if request.approvals >= threshold {
-            request.executed = true;
-            storage::set_pending_stream_request(&env, request_id, &request);
+        let treasury = storage::get_treasury(&env).ok_or(Error::NoTreasury)?;
+        let mut withdrawn = soroban_sdk::Map::new(&env);
+        for token in storage::get_whitelisted_assets(&env).iter() {
+            let amount = storage::get_pending_fees(&env, &treasury, &token);
+            if amount <= 0 {
+                continue;
+            }
+            storage::clear_pending_fees(&env, &treasury, &token);
+            Self::distribute_treasury_fees(&env, &treasury, &token, amount);
+            withdrawn.set(token, amount);
+        }
+        if withdrawn.is_empty() {
+            return Err(Error::NothingToWithdraw);
+        }
+        Ok(withdrawn)
+    }
 
-            // Execute the stream creation
-            let stream_id = Self::execute_stream_creation_internal(&env, request.args)?;
+    /// Current treasury fee distribution table (empty = all to the treasury).
+    pub fn get_fee_distribution(env: Env) -> Vec<FeeShare> {
+        storage::get_fee_distribution(&env)
+    }
 
-            // Remove the pending request
-            storage::remove_pending_stream_request(&env, request_id);
+    pub fn get_whitelisted_assets(env: Env) -> Vec<Address> {
+        storage::get_whitelisted_assets(&env)
+    }
 
-            let mut data = Vec::new(&env);
-            data.push_back(request_id.into_val(&env));
-            data.push_back(stream_id.into_val(&env));
-            data.push_back(now.into_val(&env));
+    /// List an asset whitelisted before the contract kept a list of them, so
+    /// `withdraw_treasury_all` and `get_whitelisted_assets` see it. Anyone may
+    /// call this; it only indexes assets that are already whitelisted.
+    ///
+    /// # Errors
+    /// - `AssetNotWhitelisted`: If `asset` is not whitelisted
+    pub fn backfill_whitelisted_asset(env: Env, asset: Address) -> Result<(), Error> {
+        if !storage::is_asset_whitelisted(&env, &asset) {
+            return Err(Error::AssetNotWhitelisted);
+        }
+        storage::add_to_whitelist(&env, &asset);
+        Ok(())
+    }
 
-            env.events().publish(
-                (symbol_short!("req_exec"), request_id),
-                NebulaEvent {
-                    version: 2,
-                    timestamp: now,
-                    action: symbol_short!("req_exec"),
-                    data,
-                },
-            );
+    /// Pay `amount` of `token` out along the fee distribution table. The last
+    /// share takes the rounding residual so nothing stays behind.
+    fn distribute_treasury_fees(env: &Env, treasury: &Address, token: &Address, amount: i128) {
+        let mut shares = storage::get_fee_distribution(env);
+        if shares.is_empty() {
+            shares.push_back(FeeShare {
+                recipient: treasury.clone(),
+                bps: 10_000,
+                stream_duration: 0,
+            });
+        }
+
+        let token_client = soroban_sdk::token::TokenClient::new(env, token);
+        let last = shares.len() - 1;
+        let mut distributed = 0i128;
+        for (i, share) in shares.iter().enumerate() {
+            let part = if i as u32 == last {
+                math::calculate_residual_share(amount, distributed)
+            } else {
+                math::calculate_share(amount, share.bps)
+            };
+            distributed += part;
+            if part <= 0 {
+                continue;
+            }
 
-            Ok(stream_id)
-        } else {
-            // Update the pending request with new approval
-            storage::set_pending_stream_request(&env, request_id, &request);
+            // Budget streams below the dust floor are paid out directly.
+            let stream_id =
+                if share.stream_duration > 0 && part >= storage::get_min_value(env, token) {
+                    Some(Self::open_fee_stream(env, treasury, &share, token, part))
+                } else {
+                    token_client.transfer(&env.current_contract_address(), &share.recipient, &part);
+                    None
+                };
 
-            let mut data = Vec::new(&env);
-            data.push_back(request_id.into_val(&env));
-            data.push_back(approver.clone().into_val(&env));
-            data.push_back(request.approvals.into_val(&env));
-            data.push_back(threshold.into_val(&env));
-            data.push_back(now.into_val(&env));
+            let now = env.ledger().timestamp();
+            let mut data = Vec::new(env);
+            data.push_back(share.recipient.clone().into_val(env));
+            data.push_back(token.clone().into_val(env));
+            data.push_back(part.into_val(env));
+            data.push_back(now.into_val(env));
+            data.push_back(stream_id.into_val(env));
 
             env.events().publish(
-                (symbol_short!("req_appr"), request_id),
+                (symbol_short!("fee_out"), share.recipient.clone()),
                 NebulaEvent {
                     version: 2,
                     timestamp: now,
-                    action: symbol_short!("req_appr"),
+                    action: symbol_short!("fee_out"),
                     data,
                 },
             );
-
-            Ok(0) // Return 0 to indicate request not yet executed, but approval recorded
         }
     }
 
-    /// Get a pending stream request details
-    pub fn get_stream_request(env: Env, request_id: u64) -> Option<storage::PendingStreamRequest> {
-        storage::get_pending_stream_request(&env, request_id)
-    }
-
-    /// Internal helper to execute stream creation (used by approve_stream_request)
-    fn execute_stream_creation_internal(env: &Env, args: StreamArgs) -> Result<u64, Error> {
-        // Transfer tokens from sender
-        let token_client = soroban_sdk::token::TokenClient::new(env, &args.token);
-        token_client.transfer(
-            &args.sender,
-            &env.current_contract_address(),
-            &args.total_amount,
-        );
-
-        let stream_amount = Self::apply_protocol_fee(env, &args.token, args.total_amount)?;
-
+    /// Open a linear stream of already-held fees from the treasury to a
+    /// budget recipient. No protocol fee is charged on it, and cancelling
+    /// returns the unearned part to the treasury.
+    fn open_fee_stream(
+        env: &Env,
+        treasury: &Address,
+        share: &FeeShare,
+        token: &Address,
+        amount: i128,
+    ) -> u64 {
+        let now = env.ledger().timestamp();
+        let end_time = now.saturating_add(share.stream_duration);
         let stream_id = storage::next_stream_id(env);
-
-        let mut vault_used = None;
-        if args.yield_enabled {
-            if let Some(vault_addr) = &args.vault_address {
-                let vault_client = VaultClient::new(env, vault_addr);
-                vault_client.deposit(&stream_amount);
-                vault_used = Some(vault_addr.clone());
-            }
-        }
-
         let stream = StreamV2 {
-            sender: args.sender.clone(),
-            receiver: args.receiver.clone(),
-            beneficiary: args.receiver.clone(),
-            token: args.token.clone(),
-            total_amount: stream_amount,
-            start_time: args.start_time,
-            end_time: args.end_time,
-            cliff_time: args.cliff_time,
+            sender: treasury.clone(),
+            receiver: share.recipient.clone(),
+            beneficiary: share.recipient.clone(),
+            token: token.clone(),
+            total_amount: amount,
+            start_time: now,
+            end_time,
+            cliff_time: now,
             withdrawn_amount: 0,
             cancelled: false,
             migrated_from_v1: false,
             v1_stream_id: 0,
-            step_duration: args.step_duration,
-            multiplier_bps: args.multiplier_bps,
-            penalty_bps: args.penalty_bps,
-            vault_address: vault_used,
-            yield_enabled: args.yield_enabled,
+            step_duration: 0,
+            multiplier_bps: 0,
+            penalty_bps: 0,
+            vault_address: None,
+            yield_enabled: false,
             is_pending: false,
-            is_recurrent: args.is_recurrent,
-            cycle_duration: args.cycle_duration,
-            cancellation_type: args.cancellation_type,
-            yield_recipient: args.yield_recipient,
-            split_address: args.split_address.clone(),
-            split_bps: args.split_bps,
-            curve_type: args.curve_type,
+            is_recurrent: false,
+            cycle_duration: 0,
+            cancellation_type: 0,
+            yield_recipient: 0,
+            split_address: None,
+            split_bps: 0,
+            curve_type: CurveType::Linear,
+            is_soulbound: false,
+            accrual_clock: 0,
         };
 
-        storage::set_stream(env, stream_id, &stream);
-        storage::update_stats(env, stream_amount, &args.sender, &args.receiver);
+        Self::insert_new_stream(env, stream_id, &stream);
+        storage::update_stats(env, amount, treasury, &share.recipient);
 
-        let now = env.ledger().timestamp();
         let mut data = Vec::new(env);
         data.push_back(stream_id.into_val(env));
-        data.push_back(args.sender.clone().into_val(env));
-        data.push_back(args.receiver.clone().into_val(env));
-        data.push_back(args.token.clone().into_val(env));
-        data.push_back(stream_amount.into_val(env));
-        data.push_back(args.start_time.into_val(env));
-        data.push_back(args.cliff_time.into_val(env));
-        data.push_back(args.end_time.into_val(env));
+        data.push_back(treasury.clone().into_val(env));
+        data.push_back(share.recipient.clone().into_val(env));
+        data.push_back(token.clone().into_val(env));
+        data.push_back(amount.into_val(env));
+        data.push_back(now.into_val(env));
+        data.push_back(now.into_val(env));
+        data.push_back(end_time.into_val(env));
         data.push_back(now.into_val(env));
 
         env.events().publish(
@@ -3724,394 +7545,578 @@ impl Contract {
             },
         );
 
-        Ok(stream_id)
+        stream_id
+    }
+
+    /// Query pending fee balance for a `(recipient, token)` pair.
+    pub fn get_pending_fees(env: Env, recipient: Address, token: Address) -> i128 {
+        storage::get_pending_fees(&env, &recipient, &token)
     }
 
     // ----------------------------------------------------------------
-    // Time-locked Admin Operations
+    // Affiliate Fee Sharing
     // ----------------------------------------------------------------
 
-    pub fn schedule_op(env: Env, op: Operation) -> Result<(), Error> {
-        let admin = storage::try_get_admin(&env)?;
-        admin.require_auth();
+    pub fn get_affiliate_share_bps(env: Env) -> u32 {
+        storage::get_affiliate_share_bps(&env)
+    }
 
-        let execution_time = env.ledger().timestamp() + storage::ADMIN_DELAY;
-        storage::schedule_op(&env, &op, execution_time);
+    /// Self-register as an affiliate. Fees are only shared once an admin
+    /// approves the registration.
+    pub fn register_affiliate(env: Env, affiliate: Address) -> Result<(), Error> {
+        affiliate.require_auth();
+        if storage::get_affiliate(&env, &affiliate).is_some() {
+            return Err(Error::AlreadyApproved);
+        }
+        storage::set_affiliate(&env, &affiliate, false);
+        Ok(())
+    }
+
+    pub fn is_affiliate_approved(env: Env, affiliate: Address) -> bool {
+        storage::get_affiliate(&env, &affiliate) == Some(true)
+    }
+
+    /// Fees credited to `affiliate` in `token` and not yet withdrawn.
+    pub fn get_affiliate_fees(env: Env, affiliate: Address, token: Address) -> i128 {
+        storage::get_affiliate_fees(&env, &affiliate, &token)
+    }
+
+    /// Withdraw the caller's accumulated affiliate fees for `token`.
+    ///
+    /// # Errors
+    /// - `UnauthorizedSender`: If `affiliate` never registered
+    /// - `NothingToWithdraw`: If no fees are credited to it
+    pub fn withdraw_affiliate_fees(
+        env: Env,
+        affiliate: Address,
+        token: Address,
+    ) -> Result<i128, Error> {
+        affiliate.require_auth();
+        if storage::get_affiliate(&env, &affiliate).is_none() {
+            return Err(Error::UnauthorizedSender);
+        }
+
+        let amount = storage::get_affiliate_fees(&env, &affiliate, &token);
+        if amount <= 0 {
+            return Err(Error::NothingToWithdraw);
+        }
+
+        storage::clear_affiliate_fees(&env, &affiliate, &token);
+
+        let token_client = soroban_sdk::token::TokenClient::new(&env, &token);
+        token_client.transfer(&env.current_contract_address(), &affiliate, &amount);
 
         let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
-        data.push_back(execution_time.into_val(&env));
+        data.push_back(affiliate.clone().into_val(&env));
+        data.push_back(token.clone().into_val(&env));
+        data.push_back(amount.into_val(&env));
+        data.push_back(now.into_val(&env));
 
         env.events().publish(
-            (symbol_short!("schedule"),),
+            (symbol_short!("aff_out"), affiliate.clone()),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: symbol_short!("schedule"),
+                action: symbol_short!("aff_out"),
                 data,
             },
         );
 
-        Ok(())
+        Ok(amount)
     }
 
-    pub fn execute_op(env: Env, op: Operation) -> Result<(), Error> {
-        let admin = storage::try_get_admin(&env)?;
-        admin.require_auth();
+    /// The nonce `signer`'s next permit must carry. Shared by every
+    /// permit-based entry point and consumed by each accepted permit.
+    pub fn get_permit_nonce(env: Env, signer: Address) -> u64 {
+        storage::get_permit_nonce(&env, &signer)
+    }
 
-        let execution_time =
-            storage::get_scheduled_op_time(&env, &op).ok_or(Error::OpNotScheduled)?;
+    /// The nonce `beneficiary`'s next `withdraw_meta` must carry. Withdrawal
+    /// permits share the signer's permit nonce, so this is
+    /// `get_permit_nonce(beneficiary)` for every stream.
+    pub fn get_withdrawal_nonce(env: Env, beneficiary: Address, _stream_id: u64) -> u64 {
+        storage::get_permit_nonce(&env, &beneficiary)
+    }
 
-        if env.ledger().timestamp() < execution_time {
-            return Err(Error::NotExecutionTime);
+    /// The nonce `signer`'s next `execute_meta` permit on `stream_id` must carry.
+    pub fn get_meta_nonce(env: Env, signer: Address, stream_id: u64) -> u64 {
+        storage::get_meta_nonce(&env, &signer, stream_id)
+    }
+
+    /// Run a stream action signed off-chain by `signer` and submitted by a
+    /// relayer, who is paid `relayer_fee` in the stream token out of funds
+    /// the action already moves through this contract: the signer's payout
+    /// for `Cancel`, the pulled amount for `TopUp` (the stream is credited
+    /// the rest), and the beneficiary's withdrawable balance otherwise.
+    ///
+    /// The signer authorizes a typed `Permit` for `execute_meta` whose
+    /// arguments are a `MetaIntent`; nonces are tracked per (signer, stream).
+    /// The action then runs exactly as its direct entry point would, and the
+    /// fee may not exceed `MAX_META_FEE_BPS` of the action's value: the
+    /// signer's payout for `Cancel`, the amount for `TopUp`, and the stream's
+    /// outstanding balance otherwise.
+    ///
+    /// # Returns
+    /// - `Ok(value)`: The action's value
+    ///
+    /// # Errors
+    /// - `ExpiredDeadline`: If `deadline` has passed
+    /// - `InvalidNonce`: If `nonce` is not the signer's current meta nonce
+    /// - `UnauthorizedSender`: If the signer may not perform the action
+    /// - `InvalidRelayerFee`: If the fee is negative, above the cap or more
+    ///   than the funds it is taken from
+    /// - Those of the dispatched action
+    #[allow(clippy::too_many_arguments)]
+    pub fn execute_meta(
+        env: Env,
+        signer: Address,
+        stream_id: u64,
+        action: MetaAction,
+        relayer: Address,
+        relayer_fee: i128,
+        nonce: u64,
+        deadline: u64,
+        signature: PermitSignature,
+    ) -> Result<i128, Error> {
+        Self::require_not_paused(&env)?;
+        let now = env.ledger().timestamp();
+        if now > deadline {
+            return Err(Error::ExpiredDeadline);
+        }
+        if relayer_fee < 0 {
+            return Err(Error::InvalidRelayerFee);
+        }
+        if nonce != storage::get_meta_nonce(&env, &signer, stream_id) {
+            return Err(Error::InvalidNonce);
         }
+        let intent = MetaIntent {
+            stream_id,
+            action: action.clone(),
+            relayer: relayer.clone(),
+            relayer_fee,
+        };
+        Self::authorize_permit(
+            &env,
+            Symbol::new(&env, "execute_meta"),
+            &signer,
+            nonce,
+            deadline,
+            intent.to_xdr(&env),
+            &signature,
+        )?;
+        storage::set_meta_nonce(&env, &signer, stream_id, nonce + 1);
 
-        // Execute the actual operation
-        match &op {
-            Operation::SetAdmins(new_admins, new_threshold) => {
-                Self::set_admins_internal(env.clone(), new_admins.clone(), *new_threshold)?;
+        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        let token = stream.token.clone();
+        let outstanding = stream.total_amount.saturating_sub(stream.withdrawn_amount);
+        let value = match action {
+            MetaAction::Cancel => {
+                if stream.cancelled {
+                    return Err(Error::AlreadyCancelled);
+                }
+                let is_sender = signer == stream.sender;
+                if stream.cancellation_type == 1 || (!is_sender && signer != stream.beneficiary) {
+                    return Err(Error::UnauthorizedSender);
+                }
+                let (to_receiver, to_sender) =
+                    Self::cancel_internal(&env, stream_id, stream, &signer, relayer_fee)?;
+                if is_sender {
+                    to_sender
+                } else {
+                    to_receiver
+                }
             }
-            Operation::TransferAdmin(new_admin) => {
-                Self::transfer_admin_internal(env.clone(), new_admin.clone())?;
+            MetaAction::AcceptRate => {
+                if signer != stream.receiver {
+                    return Err(Error::UnauthorizedSender);
+                }
+                Self::withhold_relayer_fee(&env, stream_id, &mut stream, &signer, relayer_fee)?;
+                Self::accept_rate_internal(&env, stream_id, stream)?;
+                outstanding
             }
-            Operation::SetMinValue(asset, min) => {
-                Self::set_min_value_internal(env.clone(), asset.clone(), *min)?;
+            MetaAction::TransferBeneficiary(new_beneficiary) => {
+                if signer != stream.beneficiary {
+                    return Err(Error::UnauthorizedSender);
+                }
+                Self::withhold_relayer_fee(&env, stream_id, &mut stream, &signer, relayer_fee)?;
+                Self::transfer_position(&env, stream_id, stream, new_beneficiary)?;
+                outstanding
+            }
+            MetaAction::SetSplit(split_address, split_bps) => {
+                if signer != stream.beneficiary {
+                    return Err(Error::UnauthorizedSender);
+                }
+                if split_bps >= 10_000 {
+                    return Err(Error::InvalidPenalty);
+                }
+                if stream.cancelled {
+                    return Err(Error::AlreadyCancelled);
+                }
+                Self::withhold_relayer_fee(&env, stream_id, &mut stream, &signer, relayer_fee)?;
+                Self::set_split_internal(&env, stream_id, stream, split_address, split_bps);
+                outstanding
+            }
+            MetaAction::TopUp(amount) => {
+                Self::top_up_internal(&env, stream_id, &signer, amount, Some(relayer_fee))?;
+                amount
             }
+        };
+
+        if relayer_fee > value * MAX_META_FEE_BPS as i128 / 10_000 {
+            return Err(Error::InvalidRelayerFee);
+        }
+        if relayer_fee > 0 {
+            soroban_sdk::token::TokenClient::new(&env, &token).transfer(
+                &env.current_contract_address(),
+                &relayer,
+                &relayer_fee,
+            );
         }
 
-        storage::clear_op(&env, &op);
-
-        let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
+        data.push_back(stream_id.into_val(&env));
+        data.push_back(signer.into_val(&env));
+        data.push_back(relayer.into_val(&env));
+        data.push_back(relayer_fee.into_val(&env));
+        data.push_back(value.into_val(&env));
         data.push_back(now.into_val(&env));
-
         env.events().publish(
-            (symbol_short!("executed"),),
+            (stream_id, symbol_short!("meta_exec")),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: symbol_short!("executed"),
+                action: symbol_short!("meta_exec"),
                 data,
             },
         );
+        Ok(value)
+    }
 
+    /// Take a relayer's fee out of the beneficiary's withdrawable balance,
+    /// for meta actions that pay the signer nothing. The fee is left in the
+    /// contract for `execute_meta` to pay out.
+    fn withhold_relayer_fee(
+        env: &Env,
+        stream_id: u64,
+        stream: &mut StreamV2,
+        signer: &Address,
+        relayer_fee: i128,
+    ) -> Result<(), Error> {
+        if relayer_fee == 0 {
+            return Ok(());
+        }
+        // Allowance streams escrow nothing to take the fee from.
+        if *signer != stream.beneficiary || storage::get_allowance_funding(env, stream_id).is_some()
+        {
+            return Err(Error::InvalidRelayerFee);
+        }
+        let available =
+            Self::calculate_unlocked_internal(stream, Self::stream_clock_nanos(env, stream))
+                .saturating_sub(stream.withdrawn_amount);
+        if relayer_fee > available {
+            return Err(Error::InvalidRelayerFee);
+        }
+        if let Some(vault) = &stream.vault_address {
+            if !Self::vault_redeem(env, stream_id, stream, vault, relayer_fee)? {
+                return Err(Error::InsufficientBalance);
+            }
+        }
+        stream.withdrawn_amount += relayer_fee;
+        storage::set_stream(env, stream_id, stream);
+        storage::update_stats(env, -relayer_fee, &stream.sender, &stream.receiver);
         Ok(())
     }
 
     // ----------------------------------------------------------------
-    // Issue: Recurrent Streams — refill_stream
+    // Nebula-DAO Vote-Weight Integration (Issue: Governance)
     // ----------------------------------------------------------------
 
-    /// Attempt to auto-renew a recurrent stream after its cycle has ended.
-    ///
-    /// The contract uses `transfer_from` to pull `total_amount` tokens from
-    /// the sender — the sender must have pre-approved the contract as spender
-    /// via the token's `approve` function before calling this.
-    ///
-    /// - If the transfer succeeds: a new cycle begins immediately.
-    /// - If the transfer fails (insufficient allowance / balance):
-    ///   `is_recurrent` is set to `false` and the stream stays completed.
+    /// Query the DAO token balance of `addr` as a proxy for voting power.
+    /// Returns `Err(DaoTokenNotSet)` if no DAO token has been configured.
     ///
-    /// This function is permissionless and can be triggered by keeper bots.
-    pub fn refill_stream(env: Env, stream_id: u64) -> Result<(), Error> {
-        Self::require_not_paused(&env)?;
-
-        let mut stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
-
-        if !stream.is_recurrent {
-            return Err(Error::StreamNotMigratable);
-        }
-        if stream.cancelled {
-            return Err(Error::AlreadyCancelled);
-        }
+    /// This is the live balance; treasury proposals only count tokens escrowed
+    /// via `lock_voting_tokens` (see `get_proposal_voting_power`).
+    pub fn check_voting_power(env: Env, addr: Address) -> Result<i128, Error> {
+        let dao_token = storage::get_dao_token(&env).ok_or(Error::UnauthorizedSender)?;
+        let client = DaoTokenClient::new(&env, &dao_token);
+        Ok(client.balance(&addr))
+    }
 
-        let now = env.ledger().timestamp();
-        if now < stream.end_time {
-            return Err(Error::NotExecutionTime);
+    /// Escrow `amount` DAO tokens to gain voting power on treasury proposals.
+    ///
+    /// Topping up an existing lock restarts its `locked_at`, so the combined
+    /// amount only counts on proposals created afterwards.
+    pub fn lock_voting_tokens(env: Env, holder: Address, amount: i128) -> Result<VoterLock, Error> {
+        holder.require_auth();
+        if amount <= 0 {
+            return Err(Error::InsufficientBalance);
         }
-
-        let original_amount = stream.total_amount;
-        let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
-
-        // Contract is the approved spender; sender must have set allowance beforehand.
-        let result = token_client.try_transfer_from(
-            &env.current_contract_address(),
-            &stream.sender,
+        let dao_token = storage::get_dao_token(&env).ok_or(Error::UnauthorizedSender)?;
+        soroban_sdk::token::TokenClient::new(&env, &dao_token).transfer(
+            &holder,
             &env.current_contract_address(),
-            &original_amount,
+            &amount,
         );
 
-        if result.is_err() {
-            // Allowance exhausted or transfer failed — disable recurrence.
-            stream.is_recurrent = false;
-            storage::set_stream(&env, stream_id, &stream);
-            return Ok(());
-        }
-
-        let old_end_time = stream.end_time;
-        let new_end_time = old_end_time + stream.cycle_duration;
-
-        stream.start_time = old_end_time;
-        stream.end_time = new_end_time;
-        stream.withdrawn_amount = 0; // Reset for the new cycle
-
-        storage::set_stream(&env, stream_id, &stream);
-        storage::update_stats(&env, original_amount, &stream.sender, &stream.receiver);
+        let now = env.ledger().timestamp();
+        let mut lock = storage::get_voter_lock(&env, &holder).unwrap_or(VoterLock {
+            amount: 0,
+            locked_at: now,
+            unlock_after: 0,
+        });
+        lock.amount = lock.amount.checked_add(amount).ok_or(Error::Overflow)?;
+        lock.locked_at = now;
+        storage::set_voter_lock(&env, &holder, &lock);
 
         let mut data = Vec::new(&env);
-        data.push_back(stream_id.into_val(&env));
-        data.push_back(stream.sender.clone().into_val(&env));
-        data.push_back(original_amount.into_val(&env));
-        data.push_back(old_end_time.into_val(&env));
-        data.push_back(new_end_time.into_val(&env));
+        data.push_back(holder.clone().into_val(&env));
+        data.push_back(amount.into_val(&env));
+        data.push_back(lock.amount.into_val(&env));
         data.push_back(now.into_val(&env));
-
         env.events().publish(
-            (stream_id, symbol_short!("refill")),
+            (symbol_short!("gov_lock"), holder),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: symbol_short!("refill"),
+                action: symbol_short!("gov_lock"),
                 data,
             },
         );
 
-        Ok(())
+        Ok(lock)
     }
 
-    // ----------------------------------------------------------------
-    // Issue: Protocol Fees — admin helpers + treasury withdrawal
-    // ----------------------------------------------------------------
-
-    /// Set the protocol treasury address. Admin-only.
-    pub fn set_treasury(env: Env, treasury: Address) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::set_treasury(&env, &treasury);
-        Ok(())
-    }
+    /// Return escrowed DAO tokens once every proposal the holder voted on
+    /// has closed.
+    pub fn unlock_voting_tokens(env: Env, holder: Address) -> Result<i128, Error> {
+        holder.require_auth();
+        let lock = storage::get_voter_lock(&env, &holder).ok_or(Error::NothingToWithdraw)?;
+        let now = env.ledger().timestamp();
+        if now < lock.unlock_after {
+            return Err(Error::NotExecutionTime);
+        }
+        let dao_token = storage::get_dao_token(&env).ok_or(Error::UnauthorizedSender)?;
 
-    /// Get the current treasury address.
-    pub fn get_treasury(env: Env) -> Option<Address> {
-        storage::get_treasury(&env)
-    }
+        storage::remove_voter_lock(&env, &holder);
+        soroban_sdk::token::TokenClient::new(&env, &dao_token).transfer(
+            &env.current_contract_address(),
+            &holder,
+            &lock.amount,
+        );
 
-    // ----------------------------------------------------------------
-    // Issue #412 — Compliance Oracle
-    // ----------------------------------------------------------------
+        let mut data = Vec::new(&env);
+        data.push_back(holder.clone().into_val(&env));
+        data.push_back(lock.amount.into_val(&env));
+        data.push_back(now.into_val(&env));
+        env.events().publish(
+            (symbol_short!("gov_unlk"), holder),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("gov_unlk"),
+                data,
+            },
+        );
 
-    /// Set the sanctions-list oracle. Admin-only.
-    /// Pass the address of a contract implementing `ComplianceTrait`.
-    pub fn set_compliance_oracle(env: Env, oracle: Address) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::set_compliance_oracle(&env, &oracle);
-        Ok(())
+        Ok(lock.amount)
     }
 
-    pub fn get_compliance_oracle(env: Env) -> Option<Address> {
-        storage::get_compliance_oracle(&env)
+    pub fn get_voter_lock(env: Env, holder: Address) -> Option<VoterLock> {
+        storage::get_voter_lock(&env, &holder)
     }
 
-    // ----------------------------------------------------------------
-    // Issue #413 — Circular Event Log
-    // ----------------------------------------------------------------
-
-    /// Append a raw event payload to the stream's circular log (max 50 entries).
-    /// Any caller may append; the log is informational and not access-controlled.
-    pub fn append_event_log(env: Env, stream_id: u64, data: Bytes) -> Result<(), Error> {
-        // Verify the stream exists before accepting log entries.
-        storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
-        storage::append_event_log(&env, stream_id, data);
-        Ok(())
+    /// Voting power `holder` has on a proposal snapshotted at `snapshot`:
+    /// escrowed tokens locked strictly before that time.
+    pub fn get_proposal_voting_power(env: Env, holder: Address, snapshot: u64) -> i128 {
+        match storage::get_voter_lock(&env, &holder) {
+            Some(lock) if lock.locked_at < snapshot => lock.amount,
+            _ => 0,
+        }
     }
 
-    /// Return the last ≤50 event payloads for `stream_id` in insertion order.
-    pub fn get_stream_event_log(env: Env, stream_id: u64) -> soroban_sdk::Vec<Bytes> {
-        storage::get_event_log(&env, stream_id)
+    pub fn get_governance_config(env: Env) -> GovernanceConfig {
+        storage::get_governance_config(&env)
     }
 
-    /// Set the protocol fee in basis points (e.g. 100 = 1%). Admin-only.
-    /// Cannot exceed MAX_FEE_BPS = 500 (5%).
-    pub fn set_fee_bps(env: Env, bps: u32) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
+    /// Propose a treasury split. The proposer needs at least the configured
+    /// voting threshold of escrowed power, locked before this ledger.
+    pub fn propose_treasury_split(
+        env: Env,
+        proposer: Address,
+        token: Address,
+        recipients: Vec<Address>,
+        amounts: Vec<i128>,
+    ) -> Result<u64, Error> {
+        proposer.require_auth();
 
-        if bps > MAX_FEE_BPS {
-            return Err(Error::FeeTooHigh);
+        if recipients.is_empty() || recipients.len() != amounts.len() {
+            return Err(Error::InvalidSwapParams);
         }
-
-        storage::set_fee_bps(&env, bps);
-        Ok(())
-    }
-
-    /// Set tiered fee configuration for "Whale" discounts. Admin-only.
-    /// Tiers must be provided in ascending order of threshold for proper calculation.
-    pub fn set_fee_tiers(env: Env, tiers: Vec<FeeTier>) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-
-        // Validate that no tier exceeds the hard protocol cap.
-        for tier in tiers.iter() {
-            if tier.fee_bps > MAX_FEE_BPS {
-                return Err(Error::FeeTooHigh);
+        for amount in amounts.iter() {
+            if amount <= 0 {
+                return Err(Error::InsufficientBalance);
             }
         }
 
-        storage::set_fee_tiers(&env, tiers);
-        Ok(())
-    }
-
-    /// Get the current tiered fee configuration.
-    pub fn get_fee_tiers(env: Env) -> Vec<FeeTier> {
-        match storage::get_fee_tiers(&env) {
-            Some(tiers) => tiers,
-            None => Vec::new(&env),
-        }
-    }
-
-    pub fn add_to_whitelist(env: Env, asset: Address) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::add_to_whitelist(&env, &asset);
-        Ok(())
-    }
-
-    pub fn remove_from_whitelist(env: Env, asset: Address) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::remove_from_whitelist(&env, &asset);
-        Ok(())
-    }
-
-    pub fn is_asset_whitelisted(env: Env, asset: Address) -> bool {
-        storage::is_asset_whitelisted(&env, &asset)
-    }
-
-    /// Get the current protocol fee in basis points.
-    pub fn get_fee_bps(env: Env) -> u32 {
-        storage::get_fee_bps(&env)
-    }
-
-    /// Withdraw accumulated protocol fees to the configured treasury. Admin-only.
-    pub fn withdraw_treasury(env: Env, token: Address) -> Result<i128, Error> {
-        storage::try_get_admin(&env)?.require_auth();
-
-        let treasury = storage::get_treasury(&env).ok_or(Error::NoTreasury)?;
-        let amount = storage::get_pending_fees(&env, &treasury, &token);
-        if amount <= 0 {
-            return Err(Error::NothingToWithdraw);
+        let now = env.ledger().timestamp();
+        let power = Self::get_proposal_voting_power(env.clone(), proposer.clone(), now);
+        if power <= 0 || power < storage::get_voting_threshold(&env) {
+            return Err(Error::UnauthorizedSender);
         }
 
-        storage::clear_pending_fees(&env, &treasury, &token);
-
-        let token_client = soroban_sdk::token::TokenClient::new(&env, &token);
-        token_client.transfer(&env.current_contract_address(), &treasury, &amount);
+        let config = storage::get_governance_config(&env);
+        let proposal = TreasuryProposal {
+            id: storage::next_proposal_id(&env),
+            proposer: proposer.clone(),
+            token,
+            recipients,
+            amounts,
+            created_at: now,
+            voting_ends: now.saturating_add(config.voting_period),
+            quorum: config.quorum,
+            for_votes: 0,
+            against_votes: 0,
+            abstain_votes: 0,
+            status: ProposalStatus::Active,
+            split_id: None,
+        };
+        storage::set_proposal(&env, &proposal);
 
-        let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
-        data.push_back(treasury.clone().into_val(&env));
-        data.push_back(token.clone().into_val(&env));
-        data.push_back(amount.into_val(&env));
-        data.push_back(now.into_val(&env));
-
+        data.push_back(proposal.id.into_val(&env));
+        data.push_back(proposer.into_val(&env));
+        data.push_back(proposal.token.clone().into_val(&env));
+        data.push_back(proposal.voting_ends.into_val(&env));
         env.events().publish(
-            (symbol_short!("fee_out"), treasury.clone()),
+            (symbol_short!("prop_new"), proposal.id),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: symbol_short!("fee_out"),
+                action: symbol_short!("prop_new"),
                 data,
             },
         );
 
-        Ok(amount)
-    }
-
-    /// Query pending fee balance for a `(recipient, token)` pair.
-    pub fn get_pending_fees(env: Env, recipient: Address, token: Address) -> i128 {
-        storage::get_pending_fees(&env, &recipient, &token)
+        Ok(proposal.id)
     }
 
-    /// Get the current withdrawal nonce for a beneficiary on a specific stream.
-    /// Used for meta-transaction withdrawals to prevent replay attacks.
-    pub fn get_withdrawal_nonce(env: Env, beneficiary: Address, stream_id: u64) -> u64 {
-        let nonce_key = (symbol_short!("W_NONCE"), beneficiary, stream_id);
-        env.storage().instance().get(&nonce_key).unwrap_or(0u64)
-    }
+    /// Cast a vote weighted by the voter's escrowed power at the proposal's
+    /// snapshot. The lock stays in place until voting closes.
+    pub fn vote_on_proposal(
+        env: Env,
+        voter: Address,
+        proposal_id: u64,
+        choice: VoteChoice,
+    ) -> Result<i128, Error> {
+        voter.require_auth();
 
-    // ----------------------------------------------------------------
-    // Nebula-DAO Vote-Weight Integration (Issue: Governance)
-    // ----------------------------------------------------------------
+        let mut proposal =
+            storage::get_proposal(&env, proposal_id).ok_or(Error::PendingSplitNotFound)?;
+        let now = env.ledger().timestamp();
+        if proposal.status != ProposalStatus::Active || now >= proposal.voting_ends {
+            return Err(Error::NotExecutionTime);
+        }
+        if storage::get_proposal_vote(&env, proposal_id, &voter).is_some() {
+            return Err(Error::AlreadyApproved);
+        }
 
-    /// Set the DAO governance token contract address. Admin-only.
-    pub fn set_dao_token(env: Env, token: Address) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::set_dao_token(&env, &token);
-        Ok(())
-    }
+        let power =
+            Self::get_proposal_voting_power(env.clone(), voter.clone(), proposal.created_at);
+        if power <= 0 {
+            return Err(Error::UnauthorizedSender);
+        }
 
-    /// Set the minimum voting power threshold for treasury splits. Admin-only.
-    pub fn set_voting_threshold(env: Env, threshold: i128) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::set_voting_threshold(&env, threshold);
-        Ok(())
-    }
+        let tally = match choice {
+            VoteChoice::For => &mut proposal.for_votes,
+            VoteChoice::Against => &mut proposal.against_votes,
+            VoteChoice::Abstain => &mut proposal.abstain_votes,
+        };
+        *tally = tally.checked_add(power).ok_or(Error::Overflow)?;
+        storage::set_proposal(&env, &proposal);
+        storage::set_proposal_vote(&env, proposal_id, &voter, choice);
 
-    /// Query the DAO token balance of `addr` as a proxy for voting power.
-    /// Returns `Err(DaoTokenNotSet)` if no DAO token has been configured.
-    pub fn check_voting_power(env: Env, addr: Address) -> Result<i128, Error> {
-        let dao_token = storage::get_dao_token(&env).ok_or(Error::UnauthorizedSender)?;
-        let client = DaoTokenClient::new(&env, &dao_token);
-        Ok(client.balance(&addr))
-    }
+        let mut lock = storage::get_voter_lock(&env, &voter).ok_or(Error::UnauthorizedSender)?;
+        if lock.unlock_after < proposal.voting_ends {
+            lock.unlock_after = proposal.voting_ends;
+            storage::set_voter_lock(&env, &voter, &lock);
+        }
 
-    /// Execute an admin-only split from the Treasury account.
-    ///
-    /// The caller must hold DAO token balance >= the configured voting threshold.
-    /// Funds are transferred from the treasury address to each recipient.
-    ///
-    /// # Parameters
-    /// - `caller`: The address initiating the split (must have sufficient voting power).
-    /// - `token`: The token to distribute.
-    /// - `recipients`: List of recipient addresses.
-    /// - `amounts`: Corresponding amounts for each recipient.
-    pub fn treasury_split_by_vote(
-        env: Env,
-        caller: Address,
-        token: Address,
-        recipients: Vec<Address>,
-        amounts: Vec<i128>,
-    ) -> Result<(), Error> {
-        caller.require_auth();
+        let mut data = Vec::new(&env);
+        data.push_back(proposal_id.into_val(&env));
+        data.push_back(voter.clone().into_val(&env));
+        data.push_back(choice.into_val(&env));
+        data.push_back(power.into_val(&env));
+        data.push_back(now.into_val(&env));
+        env.events().publish(
+            (symbol_short!("prop_vote"), proposal_id),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("prop_vote"),
+                data,
+            },
+        );
 
-        // Check voting power
-        let voting_power = Self::check_voting_power(env.clone(), caller.clone())?;
-        let threshold = storage::get_voting_threshold(&env);
-        if voting_power < threshold {
-            return Err(Error::UnauthorizedSender);
-        }
+        Ok(power)
+    }
 
-        let treasury = storage::get_treasury(&env).ok_or(Error::NoTreasury)?;
-        let token_client = soroban_sdk::token::TokenClient::new(&env, &token);
+    /// Close voting on a proposal. If quorum is met and `for` beats `against`
+    /// the split is queued as a `PendingTreasurySplit` behind the usual
+    /// 48-hour veto window; otherwise the proposal is defeated.
+    pub fn finalize_proposal(env: Env, proposal_id: u64) -> Result<ProposalStatus, Error> {
+        let mut proposal =
+            storage::get_proposal(&env, proposal_id).ok_or(Error::PendingSplitNotFound)?;
+        if proposal.status != ProposalStatus::Active {
+            return Err(Error::StreamReqAlreadyExecuted);
+        }
+        if env.ledger().timestamp() < proposal.voting_ends {
+            return Err(Error::NotExecutionTime);
+        }
 
-        for i in 0..recipients.len() {
-            let recipient = recipients.get(i).unwrap();
-            let amount = amounts.get(i).unwrap();
-            token_client.transfer(&treasury, &recipient, &amount);
+        let turnout = proposal
+            .for_votes
+            .saturating_add(proposal.against_votes)
+            .saturating_add(proposal.abstain_votes);
+        if turnout >= proposal.quorum && proposal.for_votes > proposal.against_votes {
+            proposal.split_id = Some(Self::queue_treasury_split(
+                &env,
+                &proposal.proposer,
+                &proposal.token,
+                proposal.recipients.clone(),
+                proposal.amounts.clone(),
+            ));
+            proposal.status = ProposalStatus::Queued;
+        } else {
+            proposal.status = ProposalStatus::Defeated;
         }
+        storage::set_proposal(&env, &proposal);
 
         let now = env.ledger().timestamp();
         let mut data = Vec::new(&env);
-        data.push_back(caller.into_val(&env));
-        data.push_back(token.clone().into_val(&env));
+        data.push_back(proposal_id.into_val(&env));
+        data.push_back(proposal.status.clone().into_val(&env));
+        data.push_back(proposal.split_id.into_val(&env));
         data.push_back(now.into_val(&env));
-
         env.events().publish(
-            (symbol_short!("dao_split"), token),
+            (symbol_short!("prop_done"), proposal_id),
             NebulaEvent {
                 version: 2,
                 timestamp: now,
-                action: symbol_short!("dao_split"),
+                action: symbol_short!("prop_done"),
                 data,
             },
         );
 
-        Ok(())
+        Ok(proposal.status)
+    }
+
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Option<TreasuryProposal> {
+        storage::get_proposal(&env, proposal_id)
+    }
+
+    pub fn get_proposal_vote(env: Env, proposal_id: u64, voter: Address) -> Option<VoteChoice> {
+        storage::get_proposal_vote(&env, proposal_id, &voter)
     }
 
     // ----------------------------------------------------------------
@@ -4132,28 +8137,43 @@ impl Contract {
         initiator.require_auth();
         storage::try_get_admin(&env)?.require_auth();
 
-        let unlock_time = env.ledger().timestamp() + storage::ADMIN_DELAY;
-        let split_id = storage::next_treasury_split_id(&env);
+        Ok(Self::queue_treasury_split(
+            &env, &initiator, &token, recipients, amounts,
+        ))
+    }
+
+    /// Store a pending split that unlocks after the veto window.
+    fn queue_treasury_split(
+        env: &Env,
+        initiator: &Address,
+        token: &Address,
+        recipients: Vec<Address>,
+        amounts: Vec<i128>,
+    ) -> u64 {
+        let now = env.ledger().timestamp();
+        let unlock_time = now + storage::ADMIN_DELAY;
+        let split_id = storage::next_treasury_split_id(env);
 
         storage::set_pending_treasury_split(
-            &env,
+            env,
             split_id,
             &storage::PendingTreasurySplit {
                 initiator: initiator.clone(),
                 token: token.clone(),
                 recipients,
                 amounts,
+                created_at: now,
                 unlock_time,
                 executed: false,
+                cancelled: false,
             },
         );
 
-        let now = env.ledger().timestamp();
-        let mut data = Vec::new(&env);
-        data.push_back(split_id.into_val(&env));
-        data.push_back(initiator.into_val(&env));
-        data.push_back(token.into_val(&env));
-        data.push_back(unlock_time.into_val(&env));
+        let mut data = Vec::new(env);
+        data.push_back(split_id.into_val(env));
+        data.push_back(initiator.into_val(env));
+        data.push_back(token.into_val(env));
+        data.push_back(unlock_time.into_val(env));
 
         env.events().publish(
             (symbol_short!("ts_init"), split_id),
@@ -4165,16 +8185,19 @@ impl Contract {
             },
         );
 
-        Ok(split_id)
+        split_id
     }
 
     /// Execute a pending treasury split after the 48-hour timelock has elapsed.
+    /// Vetoed splits can never execute.
     ///
-    /// Reverts with `TreasurySplitTimelocked` if called too early, or
-    /// `TreasurySplitAlreadyExecuted` if already executed.
+    /// Anyone may trigger execution once the split is unlocked; the treasury
+    /// still authorizes the transfers.
+    ///
+    /// Reverts with `NotExecutionTime` if called too early, or
+    /// `StreamReqAlreadyExecuted` if already executed.
     pub fn execute_treasury_split(env: Env, caller: Address, split_id: u64) -> Result<(), Error> {
         caller.require_auth();
-        storage::try_get_admin(&env)?.require_auth();
 
         let mut split = storage::get_pending_treasury_split(&env, split_id)
             .ok_or(Error::PendingSplitNotFound)?;
@@ -4182,6 +8205,9 @@ impl Contract {
         if split.executed {
             return Err(Error::StreamReqAlreadyExecuted);
         }
+        if split.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
 
         if env.ledger().timestamp() < split.unlock_time {
             return Err(Error::NotExecutionTime);
@@ -4218,6 +8244,112 @@ impl Contract {
         Ok(())
     }
 
+    /// Oppose a pending treasury split during its 48-hour veto window.
+    ///
+    /// Recovery council members each count as one council vote; anyone else
+    /// contributes the DAO tokens they had escrowed before the split was
+    /// created. The split is cancelled once council votes reach the recovery
+    /// threshold or token weight reaches `Operation::SetVetoThreshold`.
+    pub fn veto_treasury_split(
+        env: Env,
+        vetoer: Address,
+        split_id: u64,
+    ) -> Result<SplitVetoTally, Error> {
+        vetoer.require_auth();
+
+        let mut split = storage::get_pending_treasury_split(&env, split_id)
+            .ok_or(Error::PendingSplitNotFound)?;
+        if split.executed {
+            return Err(Error::StreamReqAlreadyExecuted);
+        }
+        if split.cancelled {
+            return Err(Error::AlreadyCancelled);
+        }
+        let now = env.ledger().timestamp();
+        if now >= split.unlock_time {
+            return Err(Error::NotExecutionTime);
+        }
+        if storage::has_vetoed_split(&env, split_id, &vetoer) {
+            return Err(Error::AlreadyApproved);
+        }
+
+        let mut tally = storage::get_split_veto(&env, split_id);
+        let is_council = storage::get_recovery_council(&env)
+            .map(|council| council.contains(&vetoer))
+            .unwrap_or(false);
+        if is_council {
+            tally.council_votes += 1;
+        } else {
+            let power =
+                Self::get_proposal_voting_power(env.clone(), vetoer.clone(), split.created_at);
+            if power <= 0 {
+                return Err(Error::UnauthorizedSender);
+            }
+            tally.weight = tally.weight.checked_add(power).ok_or(Error::Overflow)?;
+        }
+        storage::add_split_veto(&env, split_id, &vetoer, &tally);
+
+        let veto_threshold = storage::get_veto_threshold(&env);
+        if (is_council && tally.council_votes >= storage::get_recovery_threshold(&env))
+            || (veto_threshold > 0 && tally.weight >= veto_threshold)
+        {
+            split.cancelled = true;
+            storage::set_pending_treasury_split(&env, split_id, &split);
+        }
+
+        let mut data = Vec::new(&env);
+        data.push_back(split_id.into_val(&env));
+        data.push_back(vetoer.into_val(&env));
+        data.push_back(tally.weight.into_val(&env));
+        data.push_back(tally.council_votes.into_val(&env));
+        data.push_back(split.cancelled.into_val(&env));
+        data.push_back(now.into_val(&env));
+
+        env.events().publish(
+            (symbol_short!("ts_veto"), split_id),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("ts_veto"),
+                data,
+            },
+        );
+
+        Ok(tally)
+    }
+
+    /// Number of treasury splits ever initiated.
+    pub fn get_treasury_split_count(env: Env) -> u64 {
+        storage::get_treasury_split_count(&env)
+    }
+
+    pub fn get_treasury_split_veto(env: Env, split_id: u64) -> SplitVetoTally {
+        storage::get_split_veto(&env, split_id)
+    }
+
+    /// Treasury splits that have neither executed nor been vetoed, with
+    /// their veto tallies, among split IDs `start..start + limit`. At most
+    /// `MAX_PAGE_SIZE` IDs are scanned per call; page on until `start`
+    /// reaches `get_treasury_split_count`.
+    pub fn get_pending_treasury_splits(env: Env, start: u64, limit: u32) -> Vec<PendingSplitInfo> {
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(storage::get_treasury_split_count(&env));
+        let mut pending = Vec::new(&env);
+        for split_id in start..end {
+            if let Some(split) = storage::get_pending_treasury_split(&env, split_id) {
+                if !split.executed && !split.cancelled {
+                    pending.push_back(PendingSplitInfo {
+                        split_id,
+                        split,
+                        veto: storage::get_split_veto(&env, split_id),
+                    });
+                }
+            }
+        }
+        pending
+    }
+
     // ----------------------------------------------------------------
     // Issue #601 - Multi-Asset Batch Disbursement
     // Issue #604 - Gas-Efficient Loop Iteration
@@ -4267,12 +8399,10 @@ impl Contract {
         storage::acquire_lock(&env)?;
 
         // Issue #632 - gas buffer check.
-        let current_gas = storage::get_gas_buffer(&env, &sender);
-        if current_gas < GAS_FEE_PER_SPLIT_STROOPS {
+        if let Err(e) = Self::charge_split_gas(&env, &sender) {
             storage::release_lock(&env);
-            return Err(Error::InsufficientGasBuffer);
+            return Err(e);
         }
-        storage::set_gas_buffer(&env, &sender, current_gas - GAS_FEE_PER_SPLIT_STROOPS);
 
         // Issue #604 - validate all amounts before any external call
         for entry in recipients.iter() {
@@ -4336,12 +8466,10 @@ impl Contract {
         storage::acquire_lock(&env)?;
 
         // Issue #632 — gas buffer check.
-        let current_gas = storage::get_gas_buffer(&env, &from);
-        if current_gas < GAS_FEE_PER_SPLIT_STROOPS {
+        if let Err(e) = Self::charge_split_gas(&env, &from) {
             storage::release_lock(&env);
-            return Err(Error::InsufficientGasBuffer);
+            return Err(e);
         }
-        storage::set_gas_buffer(&env, &from, current_gas - GAS_FEE_PER_SPLIT_STROOPS);
 
         // Issue #604 — hoist all storage reads before the loop
         let fee_per_recipient = storage::get_fee_per_recipient(&env);
@@ -4418,13 +8546,6 @@ impl Contract {
     // Issue #602 — Protocol Fee Capture: admin helpers
     // ----------------------------------------------------------------
 
-    /// Set the address that collects disbursement fees. Admin-only.
-    pub fn set_fee_collector(env: Env, collector: Address) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::set_fee_collector(&env, &collector);
-        Ok(())
-    }
-
     /// Get the current fee collector address.
     pub fn get_fee_collector(env: Env) -> Option<Address> {
         storage::get_fee_collector(&env)
@@ -4478,24 +8599,6 @@ impl Contract {
         Ok(amount)
     }
 
-    /// Set the token used to pay disbursement fees. Admin-only.
-    pub fn set_fee_token(env: Env, token: Address) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        storage::set_fee_token(&env, &token);
-        Ok(())
-    }
-
-    /// Set the per-recipient fee amount (in fee_token base units). Admin-only.
-    /// Pass 0 to disable fee collection.
-    pub fn set_fee_per_recipient(env: Env, amount: i128) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
-        if amount < 0 {
-            return Err(Error::BelowDustThreshold);
-        }
-        storage::set_fee_per_recipient(&env, amount);
-        Ok(())
-    }
-
     /// Get the current per-recipient fee amount.
     pub fn get_fee_per_recipient(env: Env) -> i128 {
         storage::get_fee_per_recipient(&env)
@@ -4516,10 +8619,11 @@ impl Contract {
 
         token_client.transfer(&sender, &env.current_contract_address(), &amount);
 
-        let current = storage::get_gas_buffer(&env, &sender);
+        let current = storage::get_gas_buffer(&env, &sender, &fee_token);
         storage::set_gas_buffer(
             &env,
             &sender,
+            &fee_token,
             current.checked_add(amount).ok_or(Error::Overflow)?,
         );
 
@@ -4538,55 +8642,189 @@ impl Contract {
             return Err(Error::BelowDustThreshold);
         }
 
-        let current = storage::get_gas_buffer(&env, &admin);
+        let fee_token = storage::get_fee_token(&env).ok_or(Error::NoTreasury)?;
+        let current = storage::get_gas_buffer(&env, &admin, &fee_token);
         if current < amount {
             return Err(Error::InsufficientGasBuffer);
         }
 
-        storage::set_gas_buffer(&env, &admin, current - amount);
+        storage::set_gas_buffer(&env, &admin, &fee_token, current - amount);
 
-        let fee_token = storage::get_fee_token(&env).ok_or(Error::NoTreasury)?;
         let token_client = soroban_sdk::token::TokenClient::new(&env, &fee_token);
         token_client.transfer(&env.current_contract_address(), &to, &amount);
 
         Ok(())
     }
 
+    /// Gas buffer of `user` in the current fee token.
     pub fn get_gas_buffer_balance(env: Env, user: Address) -> i128 {
-        storage::get_gas_buffer(&env, &user)
+        storage::get_fee_token(&env)
+            .map(|fee_token| storage::get_gas_buffer(&env, &user, &fee_token))
+            .unwrap_or(0)
     }
 
     // ----------------------------------------------------------------
-    // Emergency Recovery Multi-Sig (Issue: Security Critical)
+    // Keeper Incentives
     // ----------------------------------------------------------------
 
-    /// Configure the recovery council and required signature threshold.
-    ///
-    /// Can only be called by the current admin. The council addresses are
-    /// stored on-chain and cannot be changed without another admin call,
-    /// preventing a compromised admin from silently swapping council members
-    /// after the fact.
-    ///
-    /// # Parameters
-    /// - `admin`: Current contract admin (must sign).
-    /// - `council`: Vec of council member addresses (hardcoded or set here at init).
-    /// - `threshold`: Minimum signatures required to execute recovery.
-    pub fn set_recovery_council(
+    pub fn is_keeper(env: Env, keeper: Address) -> bool {
+        storage::is_keeper(&env, &keeper)
+    }
+
+    /// Bounty paid per successful `task` on a stream of `token`.
+    pub fn get_keeper_bounty(env: Env, task: KeeperTask, token: Address) -> i128 {
+        storage::get_keeper_bounty(&env, task, &token)
+    }
+
+    /// Prepay keeper bounties for a stream, in the stream's token.
+    /// Returns the new buffer balance.
+    pub fn fund_keeper_buffer(
         env: Env,
-        admin: Address,
-        council: Vec<Address>,
-        threshold: u32,
-    ) -> Result<(), Error> {
-        storage::try_get_admin(&env)?.require_auth();
+        sender: Address,
+        stream_id: u64,
+        amount: i128,
+    ) -> Result<i128, Error> {
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        if stream.sender != sender {
+            return Err(Error::UnauthorizedSender);
+        }
+        sender.require_auth();
+        if amount <= 0 {
+            return Err(Error::BelowDustThreshold);
+        }
 
-        if threshold == 0 || threshold > council.len() {
-            return Err(Error::InvalidThreshold);
+        let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
+        token_client.transfer(&sender, &env.current_contract_address(), &amount);
+
+        let balance = storage::get_keeper_buffer(&env, stream_id)
+            .checked_add(amount)
+            .ok_or(Error::Overflow)?;
+        storage::set_keeper_buffer(&env, stream_id, balance);
+        Ok(balance)
+    }
+
+    /// Return a stream's unused keeper buffer to its sender.
+    pub fn withdraw_keeper_buffer(env: Env, stream_id: u64) -> Result<i128, Error> {
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        stream.sender.require_auth();
+
+        let balance = storage::get_keeper_buffer(&env, stream_id);
+        if balance <= 0 {
+            return Err(Error::NothingToWithdraw);
+        }
+        storage::set_keeper_buffer(&env, stream_id, 0);
+
+        let token_client = soroban_sdk::token::TokenClient::new(&env, &stream.token);
+        token_client.transfer(&env.current_contract_address(), &stream.sender, &balance);
+        Ok(balance)
+    }
+
+    pub fn get_keeper_buffer(env: Env, stream_id: u64) -> i128 {
+        storage::get_keeper_buffer(&env, stream_id)
+    }
+
+    /// Lifetime bounties paid to `keeper` in `token`.
+    pub fn get_keeper_payouts(env: Env, keeper: Address, token: Address) -> i128 {
+        storage::get_keeper_paid(&env, &keeper, &token)
+    }
+
+    /// Refill a recurrent stream as a keeper. The bounty is only paid when a
+    /// new cycle was actually funded. Returns the bounty paid.
+    pub fn keeper_refill(env: Env, keeper: Address, stream_id: u64) -> Result<i128, Error> {
+        Self::require_keeper(&env, &keeper)?;
+        if !Self::refill(&env, stream_id)? {
+            return Ok(0);
+        }
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+        Self::pay_keeper(&env, &keeper, stream_id, &stream.token, KeeperTask::Refill)
+    }
+
+    /// Extend a stream's storage TTL as a keeper. A bounty is paid at most
+    /// once per TTL window, i.e. when the previous bump could have lapsed
+    /// below the renewal threshold. Returns the bounty paid.
+    pub fn keeper_bump_ttl(env: Env, keeper: Address, stream_id: u64) -> Result<i128, Error> {
+        Self::require_keeper(&env, &keeper)?;
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        let sequence = env.ledger().sequence();
+        let due_at = storage::get_keeper_last_bump(&env, stream_id)
+            .saturating_add(storage::STREAM_TTL_BUMP - storage::STREAM_TTL_THRESHOLD);
+        if sequence < due_at {
+            return Err(Error::NotExecutionTime);
         }
 
-        storage::set_recovery_council(&env, &council, threshold);
+        storage::bump_streams_ttl(&env, &soroban_sdk::vec![&env, stream_id]);
+        storage::set_keeper_last_bump(&env, stream_id, sequence);
+        Self::pay_keeper(&env, &keeper, stream_id, &stream.token, KeeperTask::BumpTtl)
+    }
+
+    /// Archive a fully withdrawn stream as a keeper. After the bounty, any
+    /// unused keeper buffer is returned to the sender. Returns the bounty paid.
+    pub fn keeper_archive(env: Env, keeper: Address, stream_id: u64) -> Result<i128, Error> {
+        Self::require_keeper(&env, &keeper)?;
+        Self::require_not_paused(&env)?;
+        let stream = storage::get_stream(&env, stream_id).ok_or(Error::StreamNotFound)?;
+
+        // Pay first: archiving refunds whatever is left in the buffer.
+        let paid = Self::pay_keeper(&env, &keeper, stream_id, &stream.token, KeeperTask::Archive)?;
+        Self::archive_stream(&env, stream_id, &stream, &keeper)?;
+        Ok(paid)
+    }
+
+    fn require_keeper(env: &Env, keeper: &Address) -> Result<(), Error> {
+        if !storage::is_keeper(env, keeper) {
+            return Err(Error::UnauthorizedSender);
+        }
+        keeper.require_auth();
         Ok(())
     }
 
+    /// Pay the configured bounty for `task` out of the stream's keeper
+    /// buffer, capped at what is left in it.
+    fn pay_keeper(
+        env: &Env,
+        keeper: &Address,
+        stream_id: u64,
+        token: &Address,
+        task: KeeperTask,
+    ) -> Result<i128, Error> {
+        let buffer = storage::get_keeper_buffer(env, stream_id);
+        let paid = storage::get_keeper_bounty(env, task, token).min(buffer);
+        if paid <= 0 {
+            return Ok(0);
+        }
+
+        storage::set_keeper_buffer(env, stream_id, buffer - paid);
+        storage::add_keeper_paid(env, keeper, token, paid);
+        let token_client = soroban_sdk::token::TokenClient::new(env, token);
+        token_client.transfer(&env.current_contract_address(), keeper, &paid);
+
+        let now = env.ledger().timestamp();
+        let mut data = Vec::new(env);
+        data.push_back(stream_id.into_val(env));
+        data.push_back(keeper.into_val(env));
+        data.push_back(task.into_val(env));
+        data.push_back(token.into_val(env));
+        data.push_back(paid.into_val(env));
+        data.push_back(now.into_val(env));
+
+        env.events().publish(
+            (stream_id, symbol_short!("keeper")),
+            NebulaEvent {
+                version: 2,
+                timestamp: now,
+                action: symbol_short!("keeper"),
+                data,
+            },
+        );
+
+        Ok(paid)
+    }
+
+    // ----------------------------------------------------------------
+    // Emergency Recovery Multi-Sig (Issue: Security Critical)
+    // ----------------------------------------------------------------
+
     /// Return the configured recovery council.
     pub fn get_recovery_council(env: Env) -> Option<Vec<Address>> {
         storage::get_recovery_council(&env)
@@ -4594,18 +8832,22 @@ impl Contract {
 
     /// Initiate the 7-day recovery grace period.
     ///
-    /// Any single council member can call this to start the clock. Once
-    /// initiated, the council has 7 days to gather enough signatures before
-    /// calling `recovery_split`.
+    /// Any single council member can call this to start the clock; their call
+    /// counts as the first approval. Other members then approve via
+    /// `add_recovery_approval`, and once the threshold is met `recovery_split`
+    /// can move the funds after 7 days.
     ///
     /// # Parameters
     /// - `initiator`: A council member address (must sign).
-    pub fn init_recovery(env: Env, initiator: Address) -> Result<(), Error> {
-        let council = storage::get_recovery_council(&env).ok_or(Error::NotCouncilMember)?;
-
-        if !council.contains(&initiator) {
-            return Err(Error::NotCouncilMember);
-        }
+    /// - `token`: The token to recover.
+    /// - `destination`: Address that receives the recovered funds.
+    pub fn init_recovery(
+        env: Env,
+        initiator: Address,
+        token: Address,
+        destination: Address,
+    ) -> Result<(), Error> {
+        Self::require_council_member(&env, &initiator)?;
         initiator.require_auth();
 
         if storage::get_recovery_initiated_at(&env).is_some() {
@@ -4613,33 +8855,115 @@ impl Contract {
         }
 
         let now = env.ledger().timestamp();
-        storage::set_recovery_initiated_at(&env, now);
+        storage::set_recovery_initiated_at(&env, now, &token, &destination);
+        storage::add_recovery_approval(&env, &initiator);
 
-        env.events()
-            .publish((soroban_sdk::symbol_short!("rec_init"), initiator), now);
+        env.events().publish(
+            (soroban_sdk::symbol_short!("rec_init"), initiator),
+            (token, destination, now),
+        );
 
         Ok(())
     }
 
-    /// Execute an emergency recovery split after the 7-day grace period.
+    /// Approve the in-flight recovery. Approvals accumulate across
+    /// transactions; returns the number collected so far from members still
+    /// on the council.
+    pub fn add_recovery_approval(env: Env, signer: Address) -> Result<u32, Error> {
+        Self::require_council_member(&env, &signer)?;
+        signer.require_auth();
+
+        if storage::get_recovery_initiated_at(&env).is_none() {
+            return Err(Error::RecoveryNotInitiated);
+        }
+        if storage::get_recovery_approvals(&env).contains(&signer) {
+            return Err(Error::AlreadyApproved);
+        }
+        storage::add_recovery_approval(&env, &signer);
+
+        let approvals = Self::council_count(&env, &storage::get_recovery_approvals(&env));
+        env.events()
+            .publish((soroban_sdk::symbol_short!("rec_appr"), signer), approvals);
+
+        Ok(approvals)
+    }
+
+    /// Abort the in-flight recovery.
+    ///
+    /// The admin aborts immediately; council members vote, and the recovery
+    /// is aborted once a majority of the current council has voted. Returns
+    /// whether the recovery was aborted.
+    pub fn cancel_recovery(env: Env, caller: Address) -> Result<bool, Error> {
+        caller.require_auth();
+
+        if storage::get_recovery_initiated_at(&env).is_none() {
+            return Err(Error::RecoveryNotInitiated);
+        }
+
+        let aborted = if storage::try_get_admin(&env)? == caller {
+            true
+        } else {
+            Self::require_council_member(&env, &caller)?;
+            if storage::get_recovery_cancel_votes(&env).contains(&caller) {
+                return Err(Error::AlreadyApproved);
+            }
+            storage::add_recovery_cancel_vote(&env, &caller);
+
+            let votes = Self::council_count(&env, &storage::get_recovery_cancel_votes(&env));
+            let council_size = storage::get_recovery_council(&env)
+                .map(|council| council.len())
+                .unwrap_or(0);
+            env.events().publish(
+                (soroban_sdk::symbol_short!("rec_cvote"), caller.clone()),
+                votes,
+            );
+            votes * 2 > council_size
+        };
+
+        if aborted {
+            storage::clear_recovery(&env);
+            env.events().publish(
+                (soroban_sdk::symbol_short!("rec_cncl"), caller),
+                env.ledger().timestamp(),
+            );
+        }
+
+        Ok(aborted)
+    }
+
+    /// Return the in-flight recovery, if any.
+    pub fn get_recovery_state(env: Env) -> Option<RecoveryState> {
+        let initiated_at = storage::get_recovery_initiated_at(&env)?;
+        let (token, destination) = storage::get_recovery_target(&env)?;
+        Some(RecoveryState {
+            token,
+            destination,
+            initiated_at,
+            executable_at: initiated_at.saturating_add(storage::RECOVERY_GRACE_PERIOD),
+            approvals: storage::get_recovery_approvals(&env),
+            cancel_votes: storage::get_recovery_cancel_votes(&env),
+        })
+    }
+
+    /// Execute an emergency recovery after the 7-day grace period.
     ///
-    /// Requires at least `recovery_threshold` council signatures. Transfers
-    /// the full contract token balance to `destination`.
+    /// Requires at least `recovery_threshold` approvals from members still on
+    /// the council. Only the token balance not owed to streams, fee
+    /// recipients, bridges, voters or gas-buffer owners is moved to the
+    /// recovery destination: the surplus the last `check_solvency` pass over
+    /// `token` found, which must have completed at most `MAX_SOLVENCY_AGE`
+    /// ago.
     ///
     /// # Parameters
-    /// - `council_signatures`: Vec of council member addresses signing this call.
-    /// - `token`: The token to recover.
-    /// - `destination`: Address that receives the recovered funds.
-    pub fn recovery_split(
-        env: Env,
-        council_signatures: Vec<Address>,
-        token: Address,
-        destination: Address,
-    ) -> Result<i128, Error> {
-        let council = storage::get_recovery_council(&env).ok_or(Error::NotCouncilMember)?;
+    /// - `caller`: A council member address (must sign).
+    pub fn recovery_split(env: Env, caller: Address) -> Result<i128, Error> {
+        Self::require_council_member(&env, &caller)?;
+        caller.require_auth();
 
         let initiated_at =
             storage::get_recovery_initiated_at(&env).ok_or(Error::RecoveryNotInitiated)?;
+        let (token, destination) =
+            storage::get_recovery_target(&env).ok_or(Error::RecoveryNotInitiated)?;
 
         // Enforce 7-day grace period.
         let now = env.ledger().timestamp();
@@ -4647,36 +8971,29 @@ impl Contract {
             return Err(Error::NotExecutionTime);
         }
 
-        let threshold = storage::get_recovery_threshold(&env);
-        let mut approvals = storage::get_recovery_approvals(&env);
-
-        // Validate and auth each signer; deduplicate.
-        for signer in council_signatures.iter() {
-            if !council.contains(&signer) {
-                return Err(Error::NotCouncilMember);
-            }
-            if approvals.contains(&signer) {
-                return Err(Error::AlreadyApproved);
-            }
-            signer.require_auth();
-            approvals.push_back(signer.clone());
-        }
-
-        if approvals.len() < threshold {
-            // Persist partial approvals so subsequent calls can accumulate.
-            env.storage()
-                .instance()
-                .set(&crate::storage::DataKeyV2::RecoveryApprovals, &approvals);
-            storage::bump_instance(&env);
+        if Self::council_count(&env, &storage::get_recovery_approvals(&env))
+            < storage::get_recovery_threshold(&env)
+        {
             return Err(Error::NotEnoughSigners);
         }
 
-        // Transfer the full balance of `token` to `destination`.
+        // Stream escrow stays put; only the uncommitted surplus is recovered.
+        // A running pass holds partial tallies, so only a completed one counts.
+        let report = storage::get_solvency_report(&env, &token)
+            .filter(|report| {
+                report.cursor == 0 && now <= report.checked_at.saturating_add(MAX_SOLVENCY_AGE)
+            })
+            .ok_or(Error::NotExecutionTime)?;
         let token_client = soroban_sdk::token::TokenClient::new(&env, &token);
-        let balance = token_client.balance(&env.current_contract_address());
-
-        if balance > 0 {
-            token_client.transfer(&env.current_contract_address(), &destination, &balance);
+        // Tokens that arrived since the pass fund newer escrow, so the surplus
+        // is taken as of the pass.
+        let recoverable = report
+            .balance
+            .saturating_sub(report.earned)
+            .saturating_sub(report.unvested)
+            .min(token_client.balance(&env.current_contract_address()));
+        if recoverable > 0 {
+            token_client.transfer(&env.current_contract_address(), &destination, &recoverable);
         }
 
         // Clear recovery state to prevent replay.
@@ -4684,10 +9001,28 @@ impl Contract {
 
         env.events().publish(
             (soroban_sdk::symbol_short!("rec_exec"), destination.clone()),
-            balance,
+            (token, recoverable),
         );
 
-        Ok(balance)
+        Ok(recoverable)
+    }
+
+    /// How many of `members` are on the current council, so approvals and
+    /// votes of members removed by `Operation::SetRecoveryCouncil` lapse.
+    fn council_count(env: &Env, members: &Vec<Address>) -> u32 {
+        let council = storage::get_recovery_council(env).unwrap_or(Vec::new(env));
+        members
+            .iter()
+            .filter(|member| council.contains(member))
+            .count() as u32
+    }
+
+    fn require_council_member(env: &Env, member: &Address) -> Result<(), Error> {
+        let council = storage::get_recovery_council(env).ok_or(Error::NotCouncilMember)?;
+        if !council.contains(member) {
+            return Err(Error::NotCouncilMember);
+        }
+        Ok(())
     }
 
     // ----------------------------------------------------------------
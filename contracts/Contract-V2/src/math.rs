@@ -4,6 +4,8 @@
 /// 10_000_000. This gives enough headroom for Stellar token amounts (which
 /// use 7 decimal places) while keeping all arithmetic in i128.
 use crate::contracterror::Error;
+use crate::types::{CurvePoint, CurveType};
+use soroban_sdk::Vec;
 
 pub const SCALE: i128 = 10_000_000; // 10^7
 
@@ -72,6 +74,152 @@ pub fn calculate_exponential_unlocked(total: i128, duration: i128, elapsed: i128
     total * ratio_sq / SCALE
 }
 
+/// `elapsed / duration` as a SCALE fraction (0 ..= SCALE).
+fn progress_ratio(duration: i128, elapsed: i128) -> i128 {
+    if elapsed >= duration {
+        return SCALE;
+    }
+    match FixedPoint::mul_div(SCALE, elapsed, duration) {
+        Ok(v) => v,
+        Err(_) => elapsed / (duration / SCALE).max(1),
+    }
+}
+
+/// `floor(total * fraction / SCALE)` via `mul_div`, falling back to
+/// divide-first on overflow.
+fn apply_fraction(total: i128, fraction: i128) -> i128 {
+    let fraction = fraction.clamp(0, SCALE);
+    match FixedPoint::mul_div(total, fraction, SCALE) {
+        Ok(v) => v,
+        Err(_) => total / SCALE * fraction,
+    }
+}
+
+/// Integer square root (floor) for non-negative `n`.
+fn isqrt(n: i128) -> i128 {
+    if n < 2 {
+        return n.max(0);
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+/// `log2(x)` for a SCALE fraction `x >= SCALE`, returned as a SCALE fraction.
+///
+/// Halves `x` into `[1, 2)` for the integer part, then extracts fractional
+/// bits by repeated squaring.
+fn log2_scaled(x: i128) -> i128 {
+    let mut y = x;
+    let mut result = 0i128;
+    while y >= 2 * SCALE {
+        y /= 2;
+        result += SCALE;
+    }
+    let mut bit = SCALE / 2;
+    while bit > 0 {
+        y = y * y / SCALE;
+        if y >= 2 * SCALE {
+            y /= 2;
+            result += bit;
+        }
+        bit /= 2;
+    }
+    result
+}
+
+/// Compute `floor(total * (elapsed / duration)^3)` for cubic (strongly
+/// back-loaded) streams. Returns 0 on degenerate input.
+pub fn calculate_cubic_unlocked(total: i128, duration: i128, elapsed: i128) -> i128 {
+    if duration <= 0 || elapsed <= 0 || total <= 0 {
+        return 0;
+    }
+    if elapsed >= duration {
+        return total;
+    }
+    let ratio = progress_ratio(duration, elapsed);
+    // ratio ≤ 10^7, so ratio^3 ≤ 10^21 fits in i128.
+    apply_fraction(total, ratio * ratio / SCALE * ratio / SCALE)
+}
+
+/// Compute `floor(total * sqrt(elapsed / duration))` for front-loaded
+/// streams. Returns 0 on degenerate input.
+pub fn calculate_sqrt_unlocked(total: i128, duration: i128, elapsed: i128) -> i128 {
+    if duration <= 0 || elapsed <= 0 || total <= 0 {
+        return 0;
+    }
+    if elapsed >= duration {
+        return total;
+    }
+    // sqrt(ratio / SCALE) * SCALE = sqrt(ratio * SCALE)
+    apply_fraction(total, isqrt(progress_ratio(duration, elapsed) * SCALE))
+}
+
+/// Compute `floor(total * log2(1 + 15 * elapsed / duration) / 4)` for
+/// front-loaded streams: a quarter of the way in, ~56% has unlocked.
+/// Returns 0 on degenerate input.
+pub fn calculate_log_unlocked(total: i128, duration: i128, elapsed: i128) -> i128 {
+    if duration <= 0 || elapsed <= 0 || total <= 0 {
+        return 0;
+    }
+    if elapsed >= duration {
+        return total;
+    }
+    let ratio = progress_ratio(duration, elapsed);
+    apply_fraction(total, log2_scaled(SCALE + 15 * ratio) / 4)
+}
+
+/// Interpolate a piecewise-linear schedule. Point offsets are multiplied by
+/// `unit` to bring them into the same unit as `elapsed` (e.g. nanoseconds).
+/// The schedule starts from an implicit (0, 0 bps) point; past the last point
+/// the full `total` is unlocked.
+pub fn calculate_piecewise_unlocked(
+    total: i128,
+    elapsed: i128,
+    points: &Vec<CurvePoint>,
+    unit: i128,
+) -> i128 {
+    if elapsed <= 0 || total <= 0 {
+        return 0;
+    }
+    let mut prev_offset = 0i128;
+    let mut prev_amount = 0i128;
+    for point in points.iter() {
+        let offset = (point.offset as i128).saturating_mul(unit);
+        let amount = apply_fraction(total, point.bps as i128 * (SCALE / 10_000));
+        if elapsed < offset {
+            return prev_amount
+                + calculate_flow(amount - prev_amount, offset - prev_offset, elapsed - prev_offset);
+        }
+        prev_offset = offset;
+        prev_amount = amount;
+    }
+    total
+}
+
+/// Unlocked amount of `total` under `curve` after `elapsed` of `duration`.
+/// Piecewise offsets are scaled by `unit` (see `calculate_piecewise_unlocked`).
+pub fn calculate_curve_unlocked(
+    curve: &CurveType,
+    total: i128,
+    duration: i128,
+    elapsed: i128,
+    unit: i128,
+) -> i128 {
+    match curve {
+        CurveType::Linear => calculate_flow(total, duration, elapsed),
+        CurveType::Exponential => calculate_exponential_unlocked(total, duration, elapsed),
+        CurveType::Cubic => calculate_cubic_unlocked(total, duration, elapsed),
+        CurveType::SquareRoot => calculate_sqrt_unlocked(total, duration, elapsed),
+        CurveType::Logarithmic => calculate_log_unlocked(total, duration, elapsed),
+        CurveType::Piecewise(points) => calculate_piecewise_unlocked(total, elapsed, points, unit),
+    }
+}
+
 /// Calculate a share based on basis points (1 BPS = 0.01%).
 ///
 /// Logic: (amount * bps) / 10,000
@@ -84,6 +232,32 @@ pub fn calculate_share(amount: i128, bps: u32) -> i128 {
     (amount * bps as i128) / 10_000
 }
 
+/// Decimals that the protocol's stroop-denominated constants are expressed in.
+pub const BASE_DECIMALS: u32 = 7;
+
+/// Rescale `amount` between two decimal precisions. Scaling up saturates on
+/// overflow; scaling down truncates.
+pub fn rescale(amount: i128, from_decimals: u32, to_decimals: u32) -> i128 {
+    if to_decimals >= from_decimals {
+        match 10i128.checked_pow(to_decimals - from_decimals) {
+            Some(factor) => amount.saturating_mul(factor),
+            None if amount == 0 => 0,
+            None if amount > 0 => i128::MAX,
+            None => i128::MIN,
+        }
+    } else {
+        match 10i128.checked_pow(from_decimals - to_decimals) {
+            Some(factor) => amount / factor,
+            None => 0,
+        }
+    }
+}
+
+/// Express a stroop-denominated (7-decimal) constant in a token's own decimals.
+pub fn scale_to_decimals(amount: i128, decimals: u32) -> i128 {
+    rescale(amount, BASE_DECIMALS, decimals)
+}
+
 /// Calculate the residual share for the final recipient to ensure
 /// strict atomicity and prevent locked funds (Rounding Strategy).
 pub fn calculate_residual_share(total_amount: i128, sum_distributed: i128) -> i128 {
@@ -172,6 +346,21 @@ impl FixedPoint {
 mod tests {
     use super::*;
 
+    // ── decimal normalization ────────────────────────────────────────────────
+
+    #[test]
+    fn test_scale_to_decimals() {
+        // 10 XLM-equivalent in 6-, 7- and 18-decimal tokens.
+        assert_eq!(scale_to_decimals(100_000_000, 6), 10_000_000);
+        assert_eq!(scale_to_decimals(100_000_000, 7), 100_000_000);
+        assert_eq!(
+            scale_to_decimals(100_000_000, 18),
+            10_000_000_000_000_000_000
+        );
+        assert_eq!(scale_to_decimals(i128::MAX / 10, 18), i128::MAX);
+        assert_eq!(rescale(1_234_567, 7, 0), 0);
+    }
+
     // ── calculate_flow ───────────────────────────────────────────────────────
 
     #[test]
@@ -337,6 +526,31 @@ mod tests {
         assert_eq!(calculate_exponential_unlocked(1_000_000, 100, -1), 0);
     }
 
+    // ── front-/back-loaded curves ────────────────────────────────────────────
+
+    #[test]
+    fn test_cubic_and_sqrt_at_quarter() {
+        // (0.25)^3 = 1.5625%, sqrt(0.25) = 50%
+        assert_eq!(calculate_cubic_unlocked(1_000_000, 100, 25), 15_625);
+        assert_eq!(calculate_sqrt_unlocked(1_000_000, 100, 25), 500_000);
+        assert_eq!(calculate_sqrt_unlocked(1_000_000, 100, 100), 1_000_000);
+    }
+
+    #[test]
+    fn test_log_front_loaded_and_monotonic() {
+        let total = 1_000_000_i128;
+        // log2(1 + 15 * 0.2) / 4 = log2(4) / 4 = 50%
+        let at_fifth = calculate_log_unlocked(total, 100, 20);
+        assert!((499_990..=500_000).contains(&at_fifth), "{at_fifth}");
+        let mut prev = 0;
+        for t in 1..100 {
+            let log = calculate_log_unlocked(total, 100, t);
+            assert!(log >= prev && log >= calculate_flow(total, 100, t));
+            prev = log;
+        }
+        assert_eq!(calculate_log_unlocked(total, 100, 100), total);
+    }
+
     // ── BPS Math ─────────────────────────────────────────────────────────────
 
     #[test]
@@ -1,6 +1,13 @@
 use crate::contracterror::Error;
-use crate::types::{PendingRateUpdate, StreamV2};
-use soroban_sdk::{contracttype, symbol_short, Address, Bytes, Env, Symbol, Vec};
+use crate::math;
+use crate::types::{
+    AllowanceFunding, BridgeDeposit, CurvePoint, CurveType, FeeShare, GovernanceConfig, HeldEntry,
+    KeeperTask, PendingAmendment, PendingRateUpdate, ScheduledOperation, SolvencyReport,
+    SplitVetoTally, StreamTemplate, StreamTemplateRef, StreamV2, SwapSchedule, TokenLimits,
+    TreasuryProposal, VaultConfig, VaultPosition, VoteChoice, VoterLock, DEFAULT_VOTING_PERIOD,
+};
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{contracttype, symbol_short, Address, Bytes, Env, IntoVal, Symbol, Val, Vec};
 
 const STATUS_ACTIVE: u8 = 0;
 const STATUS_CANCELLED: u8 = 1;
@@ -15,6 +22,9 @@ const YIELD_ENABLED_SHIFT: u32 = 49;
 const IS_RECURRENT_SHIFT: u32 = 50;
 const CANCELLATION_TYPE_SHIFT: u32 = 51;
 const CANCELLATION_TYPE_MASK: u128 = 0xFFFF_FFFF;
+const IS_SOULBOUND_SHIFT: u32 = 83;
+const ACCRUAL_CLOCK_SHIFT: u32 = 84;
+const ACCRUAL_CLOCK_MASK: u128 = 0xFF;
 
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
@@ -147,7 +157,8 @@ pub enum DataKeyV2 {
     PendingRateUpdateExpiry(u64), // 29
 
     // -- Issue #632 — Gas Tank Buffer ----------------------------------
-    /// Per-sender XLM gas buffer in stroops.
+    /// Per-sender gas buffer in the fee token, from before buffers were kept
+    /// per token. Read as part of the current fee token's buffer.
     GasBuffer(Address), // 30
 
     // -- Emergency Recovery Multi-Sig (Issue: Security Critical) --------
@@ -163,6 +174,168 @@ pub enum DataKeyV2 {
     // -- Issue #938 — Variable-Fee Tiered Logic -----------------------
     FeeTiers,      // 35
     EventLog(u64), // 36
+
+    // -- Stream Amendments ---------------------------------------------
+    /// Pending sender-proposed amendment for a stream (stream_id -> PendingAmendment)
+    PendingAmendment(u64), // 37
+
+    // -- Transferable Stream Positions ---------------------------------
+    /// Single-stream approved operator (stream_id -> Address)
+    PositionApproval(u64), // 38
+    /// Blanket operator approval (owner, operator) -> bool
+    OperatorApproval(Address, Address), // 39
+    /// Number of stream positions owned by a beneficiary (owner -> u32)
+    OwnerPositionCount(Address), // 40
+
+    // -- Asset Decimals Normalization ----------------------------------
+    /// Cached `decimals()` of a token (token -> u32)
+    TokenDecimals(Address), // 41
+    /// Admin-configured caps overriding the normalized defaults (token -> TokenLimits)
+    TokenLimits(Address), // 42
+}
+
+/// Overflow key space: `DataKeyV2` is at the 50-case limit of a contract
+/// spec union, so new keys are appended here.
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKeyV2Ext {
+    // -- Stream Templates ----------------------------------------------
+    /// Counter for generating unique template IDs
+    TemplateCount, // 0
+    /// Every published version of a template (template_id, version) -> StreamTemplate
+    TemplateVersion(u64, u32), // 1
+    /// Latest version number of a template (template_id -> u32)
+    TemplateLatest(u64), // 2
+    /// Template a stream was created from (stream_id -> StreamTemplateRef)
+    StreamTemplate(u64), // 3
+
+    // -- Affiliate Fee Sharing -----------------------------------------
+    /// Share of each protocol fee paid to the stream's affiliate, in bps of the fee
+    AffiliateShareBps, // 4
+    /// Registered affiliate -> approved by admin (bool)
+    Affiliate(Address), // 5
+
+    // -- Timelocked Admin Operations -----------------------------------
+    /// Scheduled operations in scheduling order (Vec<Operation>)
+    PendingOps, // 6
+    /// Emergency guardian allowed to pause without the timelock
+    Guardian, // 7
+
+    // -- Bridge-In -----------------------------------------------------
+    /// Bridge contract allowed to deliver funds via `on_token_receive` (bool)
+    BridgeAllowed(Address), // 8
+    /// Counter for generating unique bridge deposit IDs
+    BridgeDepositCount, // 9
+    /// Bridged funds held for refund (deposit_id -> BridgeDeposit)
+    BridgeDeposit(u64), // 10
+
+    // -- Treasury Governance -------------------------------------------
+    /// DAO tokens escrowed for voting (holder -> VoterLock)
+    VoterLock(Address), // 11
+    /// Counter for generating unique proposal IDs
+    ProposalCount, // 12
+    /// Treasury proposal (proposal_id -> TreasuryProposal)
+    Proposal(u64), // 13
+    /// Vote cast on a proposal (proposal_id, voter) -> VoteChoice
+    ProposalVote(u64, Address), // 14
+    /// Quorum and voting period (GovernanceConfig)
+    GovernanceConfig, // 15
+
+    // -- Treasury Split Veto -------------------------------------------
+    /// Escrowed DAO weight needed to veto a treasury split (i128, 0 = council only)
+    VetoThreshold, // 16
+    /// Opposition gathered against a split (split_id -> SplitVetoTally)
+    SplitVeto(u64), // 17
+    /// Whether an address already vetoed a split (split_id, vetoer) -> bool
+    SplitVetoVote(u64, Address), // 18
+
+    // -- Emergency Recovery --------------------------------------------
+    /// Token and destination of the in-flight recovery ((Address, Address))
+    RecoveryTarget, // 19
+    /// Council members voting to abort the in-flight recovery (Vec<Address>)
+    RecoveryCancelVotes, // 20
+
+    // -- Keeper Incentives ---------------------------------------------
+    /// Keeper approved to earn bounties (bool)
+    Keeper(Address), // 21
+    /// Bounty per successful task, per token: (task, token) -> i128
+    KeeperBounty(KeeperTask, Address), // 22
+    /// Sender-prepaid bounty buffer of a stream, in its token (stream_id -> i128)
+    KeeperBuffer(u64), // 23
+    /// Lifetime bounties paid: (keeper, token) -> i128
+    KeeperPaid(Address, Address), // 24
+    /// Ledger sequence of the last paid TTL bump (stream_id -> u32)
+    KeeperLastBump(u64), // 25
+
+    // -- Unlock Curves -------------------------------------------------
+    /// Points of a piecewise-linear unlock curve (stream_id -> Vec<CurvePoint>)
+    CurveSchedule(u64), // 26
+
+    // -- Treasury Fee Distribution -------------------------------------
+    /// Destinations of treasury fee withdrawals (Vec<FeeShare>)
+    FeeDistribution, // 27
+    /// Every currently whitelisted asset, for bulk withdrawals (Vec<Address>)
+    WhitelistedAssets, // 28
+
+    // -- Streaming Swaps -----------------------------------------------
+    /// Per-withdrawal swap settings of a stream (stream_id -> SwapSchedule)
+    SwapSchedule(u64), // 29
+    /// Registered pool pairs, each routable in both directions (Vec<(Address, Address)>)
+    DexPairs, // 30
+    /// DEX adapter contracts enabled for routing (Vec<Address>)
+    DexAdapters, // 31
+
+    // -- Yield Vaults --------------------------------------------------
+    /// Allowlisted vaults (vault -> VaultConfig)
+    VaultConfig(Address), // 32
+    /// Principal currently deposited in a vault by all streams (vault -> i128)
+    VaultDeposits(Address), // 33
+    /// A stream's shares and principal in its vault (stream_id -> VaultPosition)
+    VaultPosition(u64), // 34
+    /// Streams holding a position in a vault (vault -> Vec<u64>)
+    VaultStreams(Address), // 35
+
+    // -- Typed Permits -------------------------------------------------
+    /// Next permit nonce of a signer (Address -> u64)
+    PermitNonce(Address), // 36
+    /// Next `execute_meta` nonce of a signer on a stream: (Address, stream_id) -> u64
+    MetaNonce(Address, u64), // 37
+
+    // -- Allowance Streams ---------------------------------------------
+    /// Funding of a stream paid from the sender's allowance (stream_id -> AllowanceFunding)
+    AllowanceFunding(u64), // 38
+    /// Latest solvency pass over a token's escrow (token -> SolvencyReport)
+    SolvencyReport(Address), // 39
+
+    // -- Claimable Balances --------------------------------------------
+    /// Payouts held for a holder that could not receive them: (holder, token) -> i128
+    Claimable(Address, Address), // 40
+    /// Sum of claimable balances of a token (token -> i128)
+    ClaimableTotal(Address), // 41
+    /// Stream whose receiver could not hold its token at creation (stream_id -> bool)
+    ClaimablePayouts(u64), // 42
+
+    // -- Owner Position Index ------------------------------------------
+    /// Stream ID at a slot of an owner's position list: (owner, slot) -> u64
+    OwnerPosition(Address, u32), // 43
+    /// Slot a stream occupies in its beneficiary's position list (stream_id -> u32)
+    OwnerPositionSlot(u64), // 44
+
+    // -- Affiliate Fee Balances ----------------------------------------
+    /// Fees credited to a registered affiliate: (affiliate, token) -> i128
+    AffiliateFees(Address, Address), // 45
+
+    // -- Held Balances -------------------------------------------------
+    /// Tokens held outside stream escrow for fee recipients, bridges, voters
+    /// and gas-buffer owners (token -> i128)
+    HeldBalance(Address), // 46
+
+    // -- Per-Token Gas Buffers -----------------------------------------
+    /// Per-sender gas buffer, per token: (sender, token) -> i128
+    TokenGasBuffer(Address, Address), // 47
+    /// Token and amount of an entry counted in `HeldBalance`
+    /// (HeldEntry -> (Address, i128))
+    HeldTracked(HeldEntry), // 48
 }
 
 /// Global stream counter.
@@ -263,9 +436,9 @@ pub fn require_multisig(env: &Env, signers: &Vec<Address>) -> Result<(), Error>
     let admins = try_get_admin_list(env)?;
     let threshold = get_threshold(env);
 
-    // Every supplied signer must be a registered admin.
-    for signer in signers.iter() {
-        if !admins.contains(&signer) {
+    // Every supplied signer must be a registered admin, listed once.
+    for (i, signer) in signers.iter().enumerate() {
+        if !admins.contains(&signer) || signers.first_index_of(&signer) != Some(i as u32) {
             return Err(Error::NotEnoughSigners);
         }
         signer.require_auth();
@@ -300,7 +473,7 @@ pub(crate) fn pack_stream_metadata(stream: &StreamV2) -> u128 {
 
     let mut packed = status as u128;
     packed |= ((stream.penalty_bps as u128) & PENALTY_BPS_MASK) << PENALTY_BPS_SHIFT;
-    packed |= ((stream.curve_type as u128) & CURVE_TYPE_MASK) << CURVE_TYPE_SHIFT;
+    packed |= ((curve_code(&stream.curve_type) as u128) & CURVE_TYPE_MASK) << CURVE_TYPE_SHIFT;
 
     if stream.migrated_from_v1 {
         packed |= 1u128 << MIGRATED_FROM_V1_SHIFT;
@@ -311,12 +484,18 @@ pub(crate) fn pack_stream_metadata(stream: &StreamV2) -> u128 {
     if stream.is_recurrent {
         packed |= 1u128 << IS_RECURRENT_SHIFT;
     }
+    if stream.is_soulbound {
+        packed |= 1u128 << IS_SOULBOUND_SHIFT;
+    }
+    packed |= ((stream.accrual_clock as u128) & ACCRUAL_CLOCK_MASK) << ACCRUAL_CLOCK_SHIFT;
 
     packed
         | ((stream.cancellation_type as u128 & CANCELLATION_TYPE_MASK) << CANCELLATION_TYPE_SHIFT)
 }
 
-pub(crate) fn unpack_stream_metadata(packed: u128) -> (u8, u32, u8, bool, bool, bool, u32) {
+pub(crate) fn unpack_stream_metadata(
+    packed: u128,
+) -> (u8, u32, u8, bool, bool, bool, u32, bool, u32) {
     let status = (packed & STATUS_MASK) as u8;
     let penalty_bps = ((packed >> PENALTY_BPS_SHIFT) & PENALTY_BPS_MASK) as u32;
     let curve_type = ((packed >> CURVE_TYPE_SHIFT) & CURVE_TYPE_MASK) as u8;
@@ -324,6 +503,8 @@ pub(crate) fn unpack_stream_metadata(packed: u128) -> (u8, u32, u8, bool, bool,
     let yield_enabled = ((packed >> YIELD_ENABLED_SHIFT) & 1) != 0;
     let is_recurrent = ((packed >> IS_RECURRENT_SHIFT) & 1) != 0;
     let cancellation_type = ((packed >> CANCELLATION_TYPE_SHIFT) & CANCELLATION_TYPE_MASK) as u32;
+    let is_soulbound = ((packed >> IS_SOULBOUND_SHIFT) & 1) != 0;
+    let accrual_clock = ((packed >> ACCRUAL_CLOCK_SHIFT) & ACCRUAL_CLOCK_MASK) as u32;
 
     (
         status,
@@ -333,13 +514,53 @@ pub(crate) fn unpack_stream_metadata(packed: u128) -> (u8, u32, u8, bool, bool,
         yield_enabled,
         is_recurrent,
         cancellation_type,
+        is_soulbound,
+        accrual_clock,
     )
 }
 
+/// Discriminant of a curve as stored in `packed_meta`. Codes 0 and 1 match
+/// the former `curve_type: u32` values, so existing streams decode unchanged.
+fn curve_code(curve: &CurveType) -> u8 {
+    match curve {
+        CurveType::Linear => 0,
+        CurveType::Exponential => 1,
+        CurveType::Cubic => 2,
+        CurveType::SquareRoot => 3,
+        CurveType::Logarithmic => 4,
+        CurveType::Piecewise(_) => 5,
+    }
+}
+
+/// Rebuild a stream's curve from its stored code, loading the piecewise
+/// schedule from its own entry.
+fn curve_from_code(env: &Env, stream_id: u64, code: u8) -> CurveType {
+    match code {
+        1 => CurveType::Exponential,
+        2 => CurveType::Cubic,
+        3 => CurveType::SquareRoot,
+        4 => CurveType::Logarithmic,
+        5 => {
+            let key = DataKeyV2Ext::CurveSchedule(stream_id);
+            let points: Vec<CurvePoint> = env
+                .storage()
+                .persistent()
+                .get(&key)
+                .unwrap_or(Vec::new(env));
+            if !points.is_empty() {
+                env.storage()
+                    .persistent()
+                    .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+            }
+            CurveType::Piecewise(points)
+        }
+        _ => CurveType::Linear,
+    }
+}
+
 /// Persist a V2 stream in persistent storage and set its initial TTL.
-pub fn set_stream(env: &Env, stream_id: u64, stream: &StreamV2) {
-    let key = DataKeyV2::Stream(stream_id);
-    let stored = StoredStreamV2 {
+fn to_stored(stream: &StreamV2) -> StoredStreamV2 {
+    StoredStreamV2 {
         sender: stream.sender.clone(),
         receiver: stream.receiver.clone(),
         beneficiary: stream.beneficiary.clone(),
@@ -358,13 +579,33 @@ pub fn set_stream(env: &Env, stream_id: u64, stream: &StreamV2) {
         yield_recipient: stream.yield_recipient,
         split_address: stream.split_address.clone(),
         split_bps: stream.split_bps,
-    };
-    env.storage().persistent().set(&key, &stored);
+    }
+}
+
+pub fn set_stream(env: &Env, stream_id: u64, stream: &StreamV2) {
+    let key = DataKeyV2::Stream(stream_id);
+    env.storage().persistent().set(&key, &to_stored(stream));
     env.storage()
         .persistent()
         .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
 }
 
+/// Persist a newly created stream and index it under its beneficiary.
+///
+/// A piecewise curve's points are fixed at creation and kept in their own
+/// entry, so later `set_stream` writes do not re-serialize them.
+pub fn insert_stream(env: &Env, stream_id: u64, stream: &StreamV2) {
+    set_stream(env, stream_id, stream);
+    if let CurveType::Piecewise(points) = &stream.curve_type {
+        let key = DataKeyV2Ext::CurveSchedule(stream_id);
+        env.storage().persistent().set(&key, points);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    }
+    add_owner_position(env, &stream.beneficiary, stream_id);
+}
+
 /// Read a V2 stream from persistent storage.
 pub fn get_stream(env: &Env, stream_id: u64) -> Option<StreamV2> {
     let key = DataKeyV2::Stream(stream_id);
@@ -383,6 +624,8 @@ pub fn get_stream(env: &Env, stream_id: u64) -> Option<StreamV2> {
             yield_enabled,
             is_recurrent,
             cancellation_type,
+            is_soulbound,
+            accrual_clock,
         ) = unpack_stream_metadata(stored.packed_meta);
 
         StreamV2 {
@@ -410,7 +653,9 @@ pub fn get_stream(env: &Env, stream_id: u64) -> Option<StreamV2> {
             yield_recipient: stored.yield_recipient,
             split_address: stored.split_address,
             split_bps: stored.split_bps,
-            curve_type: curve_type as u32,
+            curve_type: curve_from_code(env, stream_id, curve_type),
+            is_soulbound,
+            accrual_clock,
         }
     })
 }
@@ -562,6 +807,38 @@ pub fn bump_streams_ttl(env: &Env, ids: &soroban_sdk::Vec<u64>) -> u32 {
             env.storage()
                 .persistent()
                 .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+            let schedule = DataKeyV2Ext::CurveSchedule(id);
+            if env.storage().persistent().has(&schedule) {
+                env.storage().persistent().extend_ttl(
+                    &schedule,
+                    STREAM_TTL_THRESHOLD,
+                    STREAM_TTL_BUMP,
+                );
+            }
+            let position = DataKeyV2Ext::VaultPosition(id);
+            if env.storage().persistent().has(&position) {
+                env.storage().persistent().extend_ttl(
+                    &position,
+                    STREAM_TTL_THRESHOLD,
+                    STREAM_TTL_BUMP,
+                );
+            }
+            let funding = DataKeyV2Ext::AllowanceFunding(id);
+            if env.storage().persistent().has(&funding) {
+                env.storage().persistent().extend_ttl(
+                    &funding,
+                    STREAM_TTL_THRESHOLD,
+                    STREAM_TTL_BUMP,
+                );
+            }
+            let claimable = DataKeyV2Ext::ClaimablePayouts(id);
+            if env.storage().persistent().has(&claimable) {
+                env.storage().persistent().extend_ttl(
+                    &claimable,
+                    STREAM_TTL_THRESHOLD,
+                    STREAM_TTL_BUMP,
+                );
+            }
             count += 1;
         }
     }
@@ -572,7 +849,8 @@ pub fn bump_streams_ttl(env: &Env, ids: &soroban_sdk::Vec<u64>) -> u32 {
 // Dust threshold helpers
 // ----------------------------------------------------------------
 
-/// 10 XLM in stroops (1 XLM = 10_000_000 stroops).
+/// 10 XLM in stroops (1 XLM = 10_000_000 stroops). Normalized to each
+/// token's decimals when no per-asset override is set.
 pub const DEFAULT_MIN_VALUE: i128 = 100_000_000; // 10 XLM
 
 /// Set a per-asset minimum stream amount. Admin-only enforcement is
@@ -584,12 +862,46 @@ pub fn set_min_value(env: &Env, asset: &Address, min: i128) {
     bump_instance(env);
 }
 
-/// Return the minimum stream amount for `asset`, defaulting to 10 XLM.
+/// Return the minimum stream amount for `asset`, defaulting to the
+/// equivalent of 10 XLM in the asset's decimals.
 pub fn get_min_value(env: &Env, asset: &Address) -> i128 {
     env.storage()
         .instance()
         .get(&DataKeyV2::MinValue(asset.clone()))
-        .unwrap_or(DEFAULT_MIN_VALUE)
+        .unwrap_or_else(|| {
+            let decimals = get_token_decimals(env, asset).unwrap_or(math::BASE_DECIMALS);
+            math::scale_to_decimals(DEFAULT_MIN_VALUE, decimals)
+        })
+}
+
+/// Cache a token's `decimals()` value.
+pub fn set_token_decimals(env: &Env, token: &Address, decimals: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2::TokenDecimals(token.clone()), &decimals);
+    bump_instance(env);
+}
+
+/// Cached `decimals()` of a token, if known.
+pub fn get_token_decimals(env: &Env, token: &Address) -> Option<u32> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2::TokenDecimals(token.clone()))
+}
+
+/// Set per-token stream caps. Admin-only enforcement is done in the contract layer.
+pub fn set_token_limits(env: &Env, token: &Address, limits: &TokenLimits) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2::TokenLimits(token.clone()), limits);
+    bump_instance(env);
+}
+
+/// Admin-configured caps for a token, if any.
+pub fn get_token_limits(env: &Env, token: &Address) -> Option<TokenLimits> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2::TokenLimits(token.clone()))
 }
 
 // ----------------------------------------------------------------
@@ -600,9 +912,35 @@ pub fn schedule_op(env: &Env, op: &crate::types::Operation, execution_time: u64)
     env.storage()
         .instance()
         .set(&DataKeyV2::ScheduledOp(op.clone()), &execution_time);
+
+    let mut pending = get_pending_op_list(env);
+    if !pending.contains(op) {
+        pending.push_back(op.clone());
+        env.storage()
+            .instance()
+            .set(&DataKeyV2Ext::PendingOps, &pending);
+    }
     bump_instance(env);
 }
 
+fn get_pending_op_list(env: &Env) -> Vec<crate::types::Operation> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::PendingOps)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Scheduled operations with their execution times, in scheduling order.
+pub fn get_pending_ops(env: &Env) -> Vec<ScheduledOperation> {
+    let mut result = Vec::new(env);
+    for op in get_pending_op_list(env).iter() {
+        if let Some(execution_time) = get_scheduled_op_time(env, &op) {
+            result.push_back(ScheduledOperation { op, execution_time });
+        }
+    }
+    result
+}
+
 pub fn get_scheduled_op_time(env: &Env, op: &crate::types::Operation) -> Option<u64> {
     env.storage()
         .instance()
@@ -613,7 +951,96 @@ pub fn clear_op(env: &Env, op: &crate::types::Operation) {
     env.storage()
         .instance()
         .remove(&DataKeyV2::ScheduledOp(op.clone()));
+
+    let mut pending = get_pending_op_list(env);
+    if let Some(index) = pending.first_index_of(op) {
+        pending.remove(index);
+        env.storage()
+            .instance()
+            .set(&DataKeyV2Ext::PendingOps, &pending);
+    }
+    bump_instance(env);
+}
+
+pub fn set_guardian(env: &Env, guardian: &Option<Address>) {
+    match guardian {
+        Some(guardian) => env
+            .storage()
+            .instance()
+            .set(&DataKeyV2Ext::Guardian, guardian),
+        None => env.storage().instance().remove(&DataKeyV2Ext::Guardian),
+    }
+    bump_instance(env);
+}
+
+pub fn get_guardian(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKeyV2Ext::Guardian)
+}
+
+// ----------------------------------------------------------------
+// Bridge-in helpers
+// ----------------------------------------------------------------
+
+pub fn set_bridge_allowed(env: &Env, bridge: &Address, allowed: bool) {
+    let key = DataKeyV2Ext::BridgeAllowed(bridge.clone());
+    if allowed {
+        env.storage().instance().set(&key, &true);
+    } else {
+        env.storage().instance().remove(&key);
+    }
+    bump_instance(env);
+}
+
+pub fn is_bridge_allowed(env: &Env, bridge: &Address) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::BridgeAllowed(bridge.clone()))
+        .unwrap_or(false)
+}
+
+/// Store a held bridge deposit and return its ID.
+pub fn add_bridge_deposit(env: &Env, deposit: &BridgeDeposit) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKeyV2Ext::BridgeDepositCount)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::BridgeDepositCount, &(id + 1));
+    let key = DataKeyV2Ext::BridgeDeposit(id);
+    env.storage().persistent().set(&key, deposit);
+    track_held(
+        env,
+        &HeldEntry::BridgeDeposit(id),
+        &deposit.token,
+        deposit.amount,
+    );
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
     bump_instance(env);
+    id
+}
+
+pub fn get_bridge_deposit(env: &Env, deposit_id: u64) -> Option<BridgeDeposit> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::BridgeDeposit(deposit_id))
+}
+
+pub fn remove_bridge_deposit(env: &Env, deposit_id: u64) {
+    if let Some(deposit) = get_bridge_deposit(env, deposit_id) {
+        track_held(
+            env,
+            &HeldEntry::BridgeDeposit(deposit_id),
+            &deposit.token,
+            0,
+        );
+    }
+    env.storage()
+        .persistent()
+        .remove(&DataKeyV2Ext::BridgeDeposit(deposit_id));
 }
 
 // ----------------------------------------------------------------
@@ -661,6 +1088,12 @@ pub fn add_pending_fees(env: &Env, recipient: &Address, token: &Address, amount:
     let key = DataKeyV2::PendingFees(recipient.clone(), token.clone());
     let current: i128 = env.storage().instance().get(&key).unwrap_or(0);
     env.storage().instance().set(&key, &(current + amount));
+    track_held(
+        env,
+        &HeldEntry::PendingFees(recipient.clone(), token.clone()),
+        token,
+        current + amount,
+    );
     bump_instance(env);
 }
 
@@ -674,16 +1107,83 @@ pub fn get_pending_fees(env: &Env, recipient: &Address, token: &Address) -> i128
 
 /// Clear the pending fee balance for `(recipient, token)` after withdrawal.
 pub fn clear_pending_fees(env: &Env, recipient: &Address, token: &Address) {
+    track_held(
+        env,
+        &HeldEntry::PendingFees(recipient.clone(), token.clone()),
+        token,
+        0,
+    );
     env.storage()
         .instance()
         .remove(&DataKeyV2::PendingFees(recipient.clone(), token.clone()));
     bump_instance(env);
 }
 
+/// Tokens held outside stream escrow — pending treasury and affiliate fees,
+/// refundable bridge deposits, voter locks and gas buffers — that recovery
+/// and rebalancing must leave in place.
+pub fn get_held_balance(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::HeldBalance(token.clone()))
+        .unwrap_or(0)
+}
+
+/// Token and amount of `entry` counted in the held balance, if any.
+/// Entries credited before held balances were tracked have none until
+/// they are next written or backfilled.
+pub fn get_held_tracked(env: &Env, entry: &HeldEntry) -> Option<(Address, i128)> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::HeldTracked(entry.clone()))
+}
+
+/// Record that `entry` now holds `amount` of `token`, moving the held
+/// balance by the difference from what was tracked for it. Only tracked
+/// amounts are ever taken back out, so paying out an untracked legacy
+/// balance leaves the other entries counted.
+pub fn track_held(env: &Env, entry: &HeldEntry, token: &Address, amount: i128) {
+    let key = DataKeyV2Ext::HeldTracked(entry.clone());
+    if let Some((tracked_token, tracked)) = get_held_tracked(env, entry) {
+        adjust_held_balance(env, &tracked_token, -tracked);
+    }
+    adjust_held_balance(env, token, amount);
+    if amount == 0 {
+        env.storage().persistent().remove(&key);
+        return;
+    }
+    env.storage()
+        .persistent()
+        .set(&key, &(token.clone(), amount));
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+fn adjust_held_balance(env: &Env, token: &Address, delta: i128) {
+    if delta == 0 {
+        return;
+    }
+    let key = DataKeyV2Ext::HeldBalance(token.clone());
+    env.storage()
+        .persistent()
+        .set(&key, &(get_held_balance(env, token) + delta));
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
 pub fn add_to_whitelist(env: &Env, asset: &Address) {
     env.storage()
         .instance()
         .set(&DataKeyV2::WhitelistedAsset(asset.clone()), &true);
+    let mut assets = get_whitelisted_assets(env);
+    if !assets.contains(asset) {
+        assets.push_back(asset.clone());
+        env.storage()
+            .instance()
+            .set(&DataKeyV2Ext::WhitelistedAssets, &assets);
+    }
     bump_instance(env);
 }
 
@@ -691,9 +1191,24 @@ pub fn remove_from_whitelist(env: &Env, asset: &Address) {
     env.storage()
         .instance()
         .remove(&DataKeyV2::WhitelistedAsset(asset.clone()));
+    let mut assets = get_whitelisted_assets(env);
+    if let Some(index) = assets.first_index_of(asset) {
+        assets.remove(index);
+        env.storage()
+            .instance()
+            .set(&DataKeyV2Ext::WhitelistedAssets, &assets);
+    }
     bump_instance(env);
 }
 
+/// Every whitelisted asset, in the order it was added.
+pub fn get_whitelisted_assets(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::WhitelistedAssets)
+        .unwrap_or(Vec::new(env))
+}
+
 pub fn is_asset_whitelisted(env: &Env, asset: &Address) -> bool {
     env.storage()
         .instance()
@@ -701,6 +1216,21 @@ pub fn is_asset_whitelisted(env: &Env, asset: &Address) -> bool {
         .unwrap_or(false)
 }
 
+pub fn set_fee_distribution(env: &Env, shares: &Vec<FeeShare>) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::FeeDistribution, shares);
+    bump_instance(env);
+}
+
+/// Treasury fee destinations; empty means everything goes to the treasury.
+pub fn get_fee_distribution(env: &Env) -> Vec<FeeShare> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::FeeDistribution)
+        .unwrap_or(Vec::new(env))
+}
+
 // ----------------------------------------------------------------
 // Pending Stream Requests (Multi-sig Approval)
 // ----------------------------------------------------------------
@@ -812,114 +1342,570 @@ pub fn get_fee_per_recipient(env: &Env) -> i128 {
 // Issue #632 — Gas Tank Buffer
 // ----------------------------------------------------------------
 
-/// Set a sender's internal gas buffer (in stroops).
-pub fn set_gas_buffer(env: &Env, sender: &Address, amount: i128) {
-    env.storage()
-        .instance()
-        .set(&DataKeyV2::GasBuffer(sender.clone()), &amount);
+/// Set a sender's internal gas buffer held in `token`. `amount` is the
+/// whole balance as returned by `get_gas_buffer`, so a legacy buffer folded
+/// into it is dropped here.
+pub fn set_gas_buffer(env: &Env, sender: &Address, token: &Address, amount: i128) {
+    let key = DataKeyV2Ext::TokenGasBuffer(sender.clone(), token.clone());
+    track_held(
+        env,
+        &HeldEntry::GasBuffer(sender.clone(), token.clone()),
+        token,
+        amount,
+    );
+    if get_fee_token(env).as_ref() == Some(token) {
+        env.storage()
+            .instance()
+            .remove(&DataKeyV2::GasBuffer(sender.clone()));
+    }
+    env.storage().instance().set(&key, &amount);
     bump_instance(env);
 }
 
-/// Get a sender's internal gas buffer (in stroops).
-pub fn get_gas_buffer(env: &Env, sender: &Address) -> i128 {
-    env.storage()
+/// Get a sender's internal gas buffer held in `token`. Buffers deposited
+/// before per-token accounting count towards the current fee token.
+pub fn get_gas_buffer(env: &Env, sender: &Address, token: &Address) -> i128 {
+    let current: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKeyV2Ext::TokenGasBuffer(sender.clone(), token.clone()))
+        .unwrap_or(0);
+    if get_fee_token(env).as_ref() != Some(token) {
+        return current;
+    }
+    let legacy: i128 = env
+        .storage()
         .instance()
         .get(&DataKeyV2::GasBuffer(sender.clone()))
-        .unwrap_or(0)
+        .unwrap_or(0);
+    current + legacy
 }
 
 // ----------------------------------------------------------------
-// Issue #603 — Reentrancy Guard
+// Keeper incentive helpers
 // ----------------------------------------------------------------
 
-/// Returns true if the contract is currently inside a multi-transfer execution.
-pub fn is_locked(env: &Env) -> bool {
+pub fn set_keeper(env: &Env, keeper: &Address, approved: bool) {
+    let key = DataKeyV2Ext::Keeper(keeper.clone());
+    if approved {
+        env.storage().instance().set(&key, &true);
+    } else {
+        env.storage().instance().remove(&key);
+    }
+    bump_instance(env);
+}
+
+pub fn is_keeper(env: &Env, keeper: &Address) -> bool {
     env.storage()
         .instance()
-        .get(&DataKeyV2::Locked)
+        .get(&DataKeyV2Ext::Keeper(keeper.clone()))
         .unwrap_or(false)
 }
 
-/// Acquire the reentrancy lock. Returns Err if already locked.
-pub fn acquire_lock(env: &Env) -> Result<(), crate::contracterror::Error> {
-    if is_locked(env) {
-        return Err(crate::contracterror::Error::Reentrant);
-    }
-    env.storage().instance().set(&DataKeyV2::Locked, &true);
-    Ok(())
+pub fn set_keeper_bounty(env: &Env, task: KeeperTask, token: &Address, bounty: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::KeeperBounty(task, token.clone()), &bounty);
+    bump_instance(env);
 }
 
-/// Release the reentrancy lock.
-pub fn release_lock(env: &Env) {
-    env.storage().instance().remove(&DataKeyV2::Locked);
+pub fn get_keeper_bounty(env: &Env, task: KeeperTask, token: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::KeeperBounty(task, token.clone()))
+        .unwrap_or(0)
 }
 
-// ----------------------------------------------------------------
-// Issue #378 — Streaming Swap (DEX Integration)
-// ----------------------------------------------------------------
+/// Set the prepaid keeper buffer of a stream (held in the stream's token).
+pub fn set_keeper_buffer(env: &Env, stream_id: u64, amount: i128) {
+    let key = DataKeyV2Ext::KeeperBuffer(stream_id);
+    if amount > 0 {
+        env.storage().persistent().set(&key, &amount);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
 
-use crate::types::DexPoolInfo;
+pub fn get_keeper_buffer(env: &Env, stream_id: u64) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::KeeperBuffer(stream_id))
+        .unwrap_or(0)
+}
 
-/// Set the default DEX contract address for swap operations.
-pub fn set_dex_address(env: &Env, dex_address: &Address) {
+/// Add `amount` to the lifetime bounties paid to `keeper` in `token`.
+pub fn add_keeper_paid(env: &Env, keeper: &Address, token: &Address, amount: i128) {
+    let key = DataKeyV2Ext::KeeperPaid(keeper.clone(), token.clone());
+    let paid: i128 = env.storage().persistent().get(&key).unwrap_or(0);
     env.storage()
-        .instance()
-        .set(&DataKeyV2::DexAddress, dex_address);
-    bump_instance(env);
+        .persistent()
+        .set(&key, &paid.saturating_add(amount));
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
 }
 
-/// Get the configured DEX contract address, if set.
-pub fn get_dex_address(env: &Env) -> Option<Address> {
-    env.storage().instance().get(&DataKeyV2::DexAddress)
+pub fn get_keeper_paid(env: &Env, keeper: &Address, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::KeeperPaid(keeper.clone(), token.clone()))
+        .unwrap_or(0)
 }
 
-/// Set a specific DEX pool configuration for an asset pair.
-pub fn set_dex_pool(env: &Env, token_in: &Address, token_out: &Address, pool_info: &DexPoolInfo) {
-    env.storage().instance().set(
-        &DataKeyV2::DexPool(token_in.clone(), token_out.clone()),
-        pool_info,
-    );
-    bump_instance(env);
+/// Ledger sequence of the last keeper-paid TTL bump of a stream (0 = never).
+pub fn get_keeper_last_bump(env: &Env, stream_id: u64) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::KeeperLastBump(stream_id))
+        .unwrap_or(0)
 }
 
-/// Get the DEX pool configuration for an asset pair.
-pub fn get_dex_pool(env: &Env, token_in: &Address, token_out: &Address) -> Option<DexPoolInfo> {
+pub fn set_keeper_last_bump(env: &Env, stream_id: u64, sequence: u32) {
+    let key = DataKeyV2Ext::KeeperLastBump(stream_id);
+    env.storage().persistent().set(&key, &sequence);
     env.storage()
-        .instance()
-        .get(&DataKeyV2::DexPool(token_in.clone(), token_out.clone()))
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
 }
 
-/// Enable or disable swap streaming globally.
-pub fn set_swap_enabled(env: &Env, enabled: bool) {
+/// Drop the piecewise curve schedule of an archived stream, if any.
+pub fn remove_curve_schedule(env: &Env, stream_id: u64) {
     env.storage()
-        .instance()
-        .set(&DataKeyV2::SwapEnabled, &enabled);
-    bump_instance(env);
+        .persistent()
+        .remove(&DataKeyV2Ext::CurveSchedule(stream_id));
 }
 
-/// Check if swap streaming is enabled globally.
-pub fn is_swap_enabled(env: &Env) -> bool {
+/// Drop the keeper bookkeeping of an archived stream.
+pub fn clear_keeper_stream(env: &Env, stream_id: u64) {
     env.storage()
-        .instance()
-        .get(&DataKeyV2::SwapEnabled)
-        .unwrap_or(false) // Default to disabled
+        .persistent()
+        .remove(&DataKeyV2Ext::KeeperBuffer(stream_id));
+    env.storage()
+        .persistent()
+        .remove(&DataKeyV2Ext::KeeperLastBump(stream_id));
 }
 
 // ----------------------------------------------------------------
-// Issue #377 — Push-Pull Rate Re-balancing
+// Streaming Swaps
 // ----------------------------------------------------------------
 
-/// TTL for pending rate updates (7 days in seconds)
-pub const RATE_UPDATE_TTL: u64 = 604_800;
-
-/// Set a pending rate update for a stream.
-pub fn set_pending_rate_update(env: &Env, stream_id: u64, update: &PendingRateUpdate) {
+pub fn set_swap_schedule(env: &Env, stream_id: u64, schedule: &SwapSchedule) {
+    let key = DataKeyV2Ext::SwapSchedule(stream_id);
+    env.storage().persistent().set(&key, schedule);
     env.storage()
-        .instance()
-        .set(&DataKeyV2::PendingRateUpdate(stream_id), update);
-    env.storage().instance().set(
-        &DataKeyV2::PendingRateUpdateExpiry(stream_id),
-        &update.proposed_at,
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+/// Swap settings of a `swap_on_withdraw` stream, if it is one.
+pub fn get_swap_schedule(env: &Env, stream_id: u64) -> Option<SwapSchedule> {
+    let key = DataKeyV2Ext::SwapSchedule(stream_id);
+    let schedule: Option<SwapSchedule> = env.storage().persistent().get(&key);
+    if schedule.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    }
+    schedule
+}
+
+pub fn remove_swap_schedule(env: &Env, stream_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKeyV2Ext::SwapSchedule(stream_id));
+}
+
+/// Size of the swap schedule entry `create_swap_stream` would write.
+pub fn swap_schedule_entry_size(env: &Env, stream_id: u64, schedule: &SwapSchedule) -> u32 {
+    entry_size(env, DataKeyV2Ext::SwapSchedule(stream_id), schedule.clone())
+}
+
+// ----------------------------------------------------------------
+// Yield Vaults
+// ----------------------------------------------------------------
+
+pub fn set_vault_config(env: &Env, vault: &Address, config: &VaultConfig) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::VaultConfig(vault.clone()), config);
+    bump_instance(env);
+}
+
+/// Drop `vault` from the allowlist. Positions already open in it can still
+/// be redeemed.
+pub fn remove_vault_config(env: &Env, vault: &Address) {
+    env.storage()
+        .instance()
+        .remove(&DataKeyV2Ext::VaultConfig(vault.clone()));
+    bump_instance(env);
+}
+
+pub fn get_vault_config(env: &Env, vault: &Address) -> Option<VaultConfig> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::VaultConfig(vault.clone()))
+}
+
+/// Principal all streams currently hold in `vault`.
+pub fn get_vault_deposits(env: &Env, vault: &Address) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::VaultDeposits(vault.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_vault_deposits(env: &Env, vault: &Address, total: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::VaultDeposits(vault.clone()), &total);
+}
+
+pub fn set_vault_position(env: &Env, stream_id: u64, position: &VaultPosition) {
+    let key = DataKeyV2Ext::VaultPosition(stream_id);
+    env.storage().persistent().set(&key, position);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+/// Vault holding of a stream; None for streams without a vault and for
+/// streams that deposited before positions were tracked.
+pub fn get_vault_position(env: &Env, stream_id: u64) -> Option<VaultPosition> {
+    let key = DataKeyV2Ext::VaultPosition(stream_id);
+    let position: Option<VaultPosition> = env.storage().persistent().get(&key);
+    if position.is_some() {
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    }
+    position
+}
+
+pub fn remove_vault_position(env: &Env, stream_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKeyV2Ext::VaultPosition(stream_id));
+}
+
+pub fn set_allowance_funding(env: &Env, stream_id: u64, funding: &AllowanceFunding) {
+    let key = DataKeyV2Ext::AllowanceFunding(stream_id);
+    env.storage().persistent().set(&key, funding);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+/// Funding of an allowance stream; None for escrowed streams.
+pub fn get_allowance_funding(env: &Env, stream_id: u64) -> Option<AllowanceFunding> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::AllowanceFunding(stream_id))
+}
+
+pub fn remove_allowance_funding(env: &Env, stream_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKeyV2Ext::AllowanceFunding(stream_id));
+}
+
+pub fn get_claimable(env: &Env, holder: &Address, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::Claimable(holder.clone(), token.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_claimable(env: &Env, holder: &Address, token: &Address, amount: i128) {
+    let key = DataKeyV2Ext::Claimable(holder.clone(), token.clone());
+    let total_key = DataKeyV2Ext::ClaimableTotal(token.clone());
+    let previous: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    let total: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
+    // The total lives next to the entries it sums so both share a TTL.
+    env.storage()
+        .persistent()
+        .set(&total_key, &(total - previous + amount));
+    env.storage()
+        .persistent()
+        .extend_ttl(&total_key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    if amount == 0 {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &amount);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    }
+}
+
+/// Sum of all claimable balances of `token`, owed by the contract.
+pub fn get_claimable_total(env: &Env, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::ClaimableTotal(token.clone()))
+        .unwrap_or(0)
+}
+
+pub fn has_claimable_payouts(env: &Env, stream_id: u64) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKeyV2Ext::ClaimablePayouts(stream_id))
+}
+
+pub fn set_claimable_payouts(env: &Env, stream_id: u64) {
+    let key = DataKeyV2Ext::ClaimablePayouts(stream_id);
+    env.storage().persistent().set(&key, &true);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+pub fn remove_claimable_payouts(env: &Env, stream_id: u64) {
+    env.storage()
+        .persistent()
+        .remove(&DataKeyV2Ext::ClaimablePayouts(stream_id));
+}
+
+pub fn get_solvency_report(env: &Env, token: &Address) -> Option<SolvencyReport> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::SolvencyReport(token.clone()))
+}
+
+pub fn set_solvency_report(env: &Env, token: &Address, report: &SolvencyReport) {
+    let key = DataKeyV2Ext::SolvencyReport(token.clone());
+    env.storage().persistent().set(&key, report);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+/// Streams with principal in `vault`, for emergency exits.
+pub fn get_vault_streams(env: &Env, vault: &Address) -> Vec<u64> {
+    let key = DataKeyV2Ext::VaultStreams(vault.clone());
+    let streams: Option<Vec<u64>> = env.storage().persistent().get(&key);
+    match streams {
+        Some(streams) => {
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+            streams
+        }
+        None => Vec::new(env),
+    }
+}
+
+pub fn set_vault_streams(env: &Env, vault: &Address, streams: &Vec<u64>) {
+    let key = DataKeyV2Ext::VaultStreams(vault.clone());
+    if streams.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, streams);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    }
+}
+
+pub fn add_vault_stream(env: &Env, vault: &Address, stream_id: u64) {
+    let mut streams = get_vault_streams(env, vault);
+    if !streams.contains(stream_id) {
+        streams.push_back(stream_id);
+        set_vault_streams(env, vault, &streams);
+    }
+}
+
+pub fn remove_vault_stream(env: &Env, vault: &Address, stream_id: u64) {
+    let mut streams = get_vault_streams(env, vault);
+    if let Some(index) = streams.first_index_of(stream_id) {
+        streams.remove(index);
+        set_vault_streams(env, vault, &streams);
+    }
+}
+
+// ----------------------------------------------------------------
+// Typed Permits
+// ----------------------------------------------------------------
+
+pub fn get_permit_nonce(env: &Env, signer: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::PermitNonce(signer.clone()))
+        .unwrap_or(0)
+}
+
+pub fn set_permit_nonce(env: &Env, signer: &Address, nonce: u64) {
+    let key = DataKeyV2Ext::PermitNonce(signer.clone());
+    env.storage().persistent().set(&key, &nonce);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+pub fn get_meta_nonce(env: &Env, signer: &Address, stream_id: u64) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::MetaNonce(signer.clone(), stream_id))
+        .unwrap_or(0)
+}
+
+pub fn set_meta_nonce(env: &Env, signer: &Address, stream_id: u64, nonce: u64) {
+    let key = DataKeyV2Ext::MetaNonce(signer.clone(), stream_id);
+    env.storage().persistent().set(&key, &nonce);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+// ----------------------------------------------------------------
+// Issue #603 — Reentrancy Guard
+// ----------------------------------------------------------------
+
+/// Returns true if the contract is currently inside a multi-transfer execution.
+pub fn is_locked(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2::Locked)
+        .unwrap_or(false)
+}
+
+/// Acquire the reentrancy lock. Returns Err if already locked.
+pub fn acquire_lock(env: &Env) -> Result<(), crate::contracterror::Error> {
+    if is_locked(env) {
+        return Err(crate::contracterror::Error::Reentrant);
+    }
+    env.storage().instance().set(&DataKeyV2::Locked, &true);
+    Ok(())
+}
+
+/// Release the reentrancy lock.
+pub fn release_lock(env: &Env) {
+    env.storage().instance().remove(&DataKeyV2::Locked);
+}
+
+// ----------------------------------------------------------------
+// Issue #378 — Streaming Swap (DEX Integration)
+// ----------------------------------------------------------------
+
+use crate::types::DexPoolInfo;
+
+/// Set the default DEX contract address for swap operations.
+pub fn set_dex_address(env: &Env, dex_address: &Address) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2::DexAddress, dex_address);
+    bump_instance(env);
+}
+
+/// Get the configured DEX contract address, if set.
+pub fn get_dex_address(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&DataKeyV2::DexAddress)
+}
+
+/// Set a specific DEX pool configuration for an asset pair and register the
+/// pair for route discovery.
+pub fn set_dex_pool(env: &Env, token_in: &Address, token_out: &Address, pool_info: &DexPoolInfo) {
+    env.storage().instance().set(
+        &DataKeyV2::DexPool(token_in.clone(), token_out.clone()),
+        pool_info,
+    );
+    let mut pairs = get_dex_pairs(env);
+    let pair = (token_in.clone(), token_out.clone());
+    if !pairs.contains(&pair) {
+        pairs.push_back(pair);
+        env.storage()
+            .instance()
+            .set(&DataKeyV2Ext::DexPairs, &pairs);
+    }
+    bump_instance(env);
+}
+
+/// Drop the pool of a pair and unregister it from route discovery.
+pub fn remove_dex_pool(env: &Env, token_in: &Address, token_out: &Address) {
+    env.storage()
+        .instance()
+        .remove(&DataKeyV2::DexPool(token_in.clone(), token_out.clone()));
+    let mut pairs = get_dex_pairs(env);
+    if let Some(index) = pairs.first_index_of((token_in.clone(), token_out.clone())) {
+        pairs.remove(index);
+        env.storage()
+            .instance()
+            .set(&DataKeyV2Ext::DexPairs, &pairs);
+    }
+    bump_instance(env);
+}
+
+/// Get the DEX pool configuration for an asset pair.
+pub fn get_dex_pool(env: &Env, token_in: &Address, token_out: &Address) -> Option<DexPoolInfo> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2::DexPool(token_in.clone(), token_out.clone()))
+}
+
+/// Every registered pool pair, in registration order.
+pub fn get_dex_pairs(env: &Env) -> Vec<(Address, Address)> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::DexPairs)
+        .unwrap_or(Vec::new(env))
+}
+
+pub fn set_dex_adapter(env: &Env, adapter: &Address, enabled: bool) {
+    let mut adapters = get_dex_adapters(env);
+    match adapters.first_index_of(adapter) {
+        Some(index) if !enabled => {
+            adapters.remove(index);
+        }
+        None if enabled => adapters.push_back(adapter.clone()),
+        _ => return,
+    };
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::DexAdapters, &adapters);
+    bump_instance(env);
+}
+
+/// DEX adapters enabled for routing.
+pub fn get_dex_adapters(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::DexAdapters)
+        .unwrap_or(Vec::new(env))
+}
+
+/// Enable or disable swap streaming globally.
+pub fn set_swap_enabled(env: &Env, enabled: bool) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2::SwapEnabled, &enabled);
+    bump_instance(env);
+}
+
+/// Check if swap streaming is enabled globally.
+pub fn is_swap_enabled(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2::SwapEnabled)
+        .unwrap_or(false) // Default to disabled
+}
+
+// ----------------------------------------------------------------
+// Issue #377 — Push-Pull Rate Re-balancing
+// ----------------------------------------------------------------
+
+/// TTL for pending rate updates (7 days in seconds)
+pub const RATE_UPDATE_TTL: u64 = 604_800;
+
+/// Set a pending rate update for a stream.
+pub fn set_pending_rate_update(env: &Env, stream_id: u64, update: &PendingRateUpdate) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2::PendingRateUpdate(stream_id), update);
+    env.storage().instance().set(
+        &DataKeyV2::PendingRateUpdateExpiry(stream_id),
+        &update.proposed_at,
     );
     bump_instance(env);
 }
@@ -964,6 +1950,37 @@ pub fn has_pending_rate_update(env: &Env, stream_id: u64) -> bool {
         .has(&DataKeyV2::PendingRateUpdate(stream_id))
 }
 
+// ----------------------------------------------------------------
+// Stream Amendments
+// ----------------------------------------------------------------
+
+/// Store a pending amendment for a stream.
+pub fn set_pending_amendment(env: &Env, stream_id: u64, pending: &PendingAmendment) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2::PendingAmendment(stream_id), pending);
+    bump_instance(env);
+}
+
+/// Get the pending amendment for a stream, if any.
+pub fn get_pending_amendment(env: &Env, stream_id: u64) -> Option<PendingAmendment> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2::PendingAmendment(stream_id))
+}
+
+/// Check if a pending amendment has outlived `RATE_UPDATE_TTL`.
+pub fn is_pending_amendment_expired(env: &Env, pending: &PendingAmendment) -> bool {
+    env.ledger().timestamp() > pending.proposed_at.saturating_add(RATE_UPDATE_TTL)
+}
+
+/// Remove the pending amendment for a stream.
+pub fn remove_pending_amendment(env: &Env, stream_id: u64) {
+    env.storage()
+        .instance()
+        .remove(&DataKeyV2::PendingAmendment(stream_id));
+}
+
 // ----------------------------------------------------------------
 // Emergency Recovery Multi-Sig (Issue: Security Critical)
 // ----------------------------------------------------------------
@@ -995,11 +2012,15 @@ pub fn get_recovery_threshold(env: &Env) -> u32 {
         .unwrap_or(1)
 }
 
-/// Record the timestamp when recovery was initiated.
-pub fn set_recovery_initiated_at(env: &Env, ts: u64) {
+/// Start a recovery of `token` to `destination` at `ts`.
+pub fn set_recovery_initiated_at(env: &Env, ts: u64, token: &Address, destination: &Address) {
     env.storage()
         .instance()
         .set(&DataKeyV2::RecoveryInitiatedAt, &ts);
+    env.storage().instance().set(
+        &DataKeyV2Ext::RecoveryTarget,
+        &(token.clone(), destination.clone()),
+    );
     // Reset approvals list on new initiation.
     let empty: Vec<Address> = Vec::new(env);
     env.storage()
@@ -1015,6 +2036,11 @@ pub fn get_recovery_initiated_at(env: &Env) -> Option<u64> {
         .get(&DataKeyV2::RecoveryInitiatedAt)
 }
 
+/// Return the (token, destination) of the in-flight recovery, if any.
+pub fn get_recovery_target(env: &Env) -> Option<(Address, Address)> {
+    env.storage().instance().get(&DataKeyV2Ext::RecoveryTarget)
+}
+
 /// Clear recovery state (after execution or cancellation).
 pub fn clear_recovery(env: &Env) {
     env.storage()
@@ -1023,6 +2049,12 @@ pub fn clear_recovery(env: &Env) {
     env.storage()
         .instance()
         .remove(&DataKeyV2::RecoveryApprovals);
+    env.storage()
+        .instance()
+        .remove(&DataKeyV2Ext::RecoveryTarget);
+    env.storage()
+        .instance()
+        .remove(&DataKeyV2Ext::RecoveryCancelVotes);
     bump_instance(env);
 }
 
@@ -1030,17 +2062,35 @@ pub fn clear_recovery(env: &Env) {
 pub fn get_recovery_approvals(env: &Env) -> Vec<Address> {
     env.storage()
         .instance()
-        .get(&DataKeyV2::RecoveryApprovals)
+        .get(&DataKeyV2::RecoveryApprovals)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Append `signer` to the recovery approvals list.
+pub fn add_recovery_approval(env: &Env, signer: &Address) {
+    let mut approvals = get_recovery_approvals(env);
+    approvals.push_back(signer.clone());
+    env.storage()
+        .instance()
+        .set(&DataKeyV2::RecoveryApprovals, &approvals);
+    bump_instance(env);
+}
+
+/// Return the council members that voted to abort the current recovery.
+pub fn get_recovery_cancel_votes(env: &Env) -> Vec<Address> {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::RecoveryCancelVotes)
         .unwrap_or_else(|| Vec::new(env))
 }
 
-/// Append `signer` to the recovery approvals list.
-pub fn add_recovery_approval(env: &Env, signer: &Address) {
-    let mut approvals = get_recovery_approvals(env);
-    approvals.push_back(signer.clone());
+/// Append `member` to the recovery cancellation votes.
+pub fn add_recovery_cancel_vote(env: &Env, member: &Address) {
+    let mut votes = get_recovery_cancel_votes(env);
+    votes.push_back(member.clone());
     env.storage()
         .instance()
-        .set(&DataKeyV2::RecoveryApprovals, &approvals);
+        .set(&DataKeyV2Ext::RecoveryCancelVotes, &votes);
     bump_instance(env);
 }
 
@@ -1143,8 +2193,12 @@ pub struct PendingTreasurySplit {
     pub token: Address,
     pub recipients: Vec<Address>,
     pub amounts: Vec<i128>,
+    /// Start of the veto window; veto weight is snapshotted here
+    pub created_at: u64,
     pub unlock_time: u64,
     pub executed: bool,
+    /// Set when the community or council vetoes the split
+    pub cancelled: bool,
 }
 
 pub fn next_treasury_split_id(env: &Env) -> u64 {
@@ -1172,3 +2226,475 @@ pub fn get_pending_treasury_split(env: &Env, split_id: u64) -> Option<PendingTre
         .instance()
         .get(&DataKeyV2::PendingTreasurySplit(split_id))
 }
+
+/// Number of treasury splits ever created (IDs are `0..count`).
+pub fn get_treasury_split_count(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2::TreasurySplitCount)
+        .unwrap_or(0)
+}
+
+pub fn set_veto_threshold(env: &Env, threshold: i128) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::VetoThreshold, &threshold);
+    bump_instance(env);
+}
+
+pub fn get_veto_threshold(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::VetoThreshold)
+        .unwrap_or(0)
+}
+
+pub fn get_split_veto(env: &Env, split_id: u64) -> SplitVetoTally {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::SplitVeto(split_id))
+        .unwrap_or(SplitVetoTally {
+            weight: 0,
+            council_votes: 0,
+        })
+}
+
+/// Record `vetoer`'s opposition and store the updated tally.
+pub fn add_split_veto(env: &Env, split_id: u64, vetoer: &Address, tally: &SplitVetoTally) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::SplitVeto(split_id), tally);
+    env.storage().instance().set(
+        &DataKeyV2Ext::SplitVetoVote(split_id, vetoer.clone()),
+        &true,
+    );
+    bump_instance(env);
+}
+
+pub fn has_vetoed_split(env: &Env, split_id: u64, vetoer: &Address) -> bool {
+    env.storage()
+        .instance()
+        .has(&DataKeyV2Ext::SplitVetoVote(split_id, vetoer.clone()))
+}
+
+// ----------------------------------------------------------------
+// Treasury governance helpers
+// ----------------------------------------------------------------
+
+pub fn set_governance_config(env: &Env, config: &GovernanceConfig) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::GovernanceConfig, config);
+    bump_instance(env);
+}
+
+pub fn get_governance_config(env: &Env) -> GovernanceConfig {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::GovernanceConfig)
+        .unwrap_or(GovernanceConfig {
+            quorum: 0,
+            voting_period: DEFAULT_VOTING_PERIOD,
+        })
+}
+
+pub fn set_voter_lock(env: &Env, holder: &Address, lock: &VoterLock) {
+    if let Some(dao_token) = get_dao_token(env) {
+        track_held(
+            env,
+            &HeldEntry::VoterLock(holder.clone()),
+            &dao_token,
+            lock.amount,
+        );
+    }
+    let key = DataKeyV2Ext::VoterLock(holder.clone());
+    env.storage().persistent().set(&key, lock);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+pub fn get_voter_lock(env: &Env, holder: &Address) -> Option<VoterLock> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::VoterLock(holder.clone()))
+}
+
+pub fn remove_voter_lock(env: &Env, holder: &Address) {
+    if let Some((dao_token, _)) = get_held_tracked(env, &HeldEntry::VoterLock(holder.clone())) {
+        track_held(env, &HeldEntry::VoterLock(holder.clone()), &dao_token, 0);
+    }
+    env.storage()
+        .persistent()
+        .remove(&DataKeyV2Ext::VoterLock(holder.clone()));
+}
+
+pub fn next_proposal_id(env: &Env) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKeyV2Ext::ProposalCount)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::ProposalCount, &(id + 1));
+    bump_instance(env);
+    id
+}
+
+pub fn set_proposal(env: &Env, proposal: &TreasuryProposal) {
+    let key = DataKeyV2Ext::Proposal(proposal.id);
+    env.storage().persistent().set(&key, proposal);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+pub fn get_proposal(env: &Env, proposal_id: u64) -> Option<TreasuryProposal> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::Proposal(proposal_id))
+}
+
+pub fn set_proposal_vote(env: &Env, proposal_id: u64, voter: &Address, choice: VoteChoice) {
+    let key = DataKeyV2Ext::ProposalVote(proposal_id, voter.clone());
+    env.storage().persistent().set(&key, &choice);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+pub fn get_proposal_vote(env: &Env, proposal_id: u64, voter: &Address) -> Option<VoteChoice> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::ProposalVote(proposal_id, voter.clone()))
+}
+
+// ----------------------------------------------------------------
+// Transferable Stream Positions
+// ----------------------------------------------------------------
+
+/// Number of stream positions owned by `owner`.
+pub fn get_owner_position_count(env: &Env, owner: &Address) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2::OwnerPositionCount(owner.clone()))
+        .unwrap_or(0)
+}
+
+/// Stream ID in `slot` of `owner`'s position list.
+pub fn get_owner_position(env: &Env, owner: &Address, slot: u32) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::OwnerPosition(owner.clone(), slot))
+}
+
+fn set_owner_position_count(env: &Env, owner: &Address, count: u32) {
+    let key = DataKeyV2::OwnerPositionCount(owner.clone());
+    if count == 0 {
+        env.storage().persistent().remove(&key);
+        return;
+    }
+    env.storage().persistent().set(&key, &count);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+fn set_owner_position(env: &Env, owner: &Address, slot: u32, stream_id: u64) {
+    let key = DataKeyV2Ext::OwnerPosition(owner.clone(), slot);
+    env.storage().persistent().set(&key, &stream_id);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    let slot_key = DataKeyV2Ext::OwnerPositionSlot(stream_id);
+    env.storage().persistent().set(&slot_key, &slot);
+    env.storage()
+        .persistent()
+        .extend_ttl(&slot_key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+/// Whether `stream_id` is listed among `owner`'s positions.
+pub fn has_owner_position(env: &Env, owner: &Address, stream_id: u64) -> bool {
+    env.storage()
+        .persistent()
+        .get::<_, u32>(&DataKeyV2Ext::OwnerPositionSlot(stream_id))
+        .is_some_and(|slot| get_owner_position(env, owner, slot) == Some(stream_id))
+}
+
+pub fn add_owner_position(env: &Env, owner: &Address, stream_id: u64) {
+    let count = get_owner_position_count(env, owner);
+    set_owner_position(env, owner, count, stream_id);
+    set_owner_position_count(env, owner, count + 1);
+}
+
+/// Drop `stream_id` from `owner`'s positions by moving the last entry into
+/// its slot, so removal touches a constant number of entries.
+pub fn remove_owner_position(env: &Env, owner: &Address, stream_id: u64) {
+    let slot_key = DataKeyV2Ext::OwnerPositionSlot(stream_id);
+    let slot: u32 = match env.storage().persistent().get(&slot_key) {
+        Some(slot) => slot,
+        None => return,
+    };
+    if get_owner_position(env, owner, slot) != Some(stream_id) {
+        return;
+    }
+    let last = get_owner_position_count(env, owner) - 1;
+    if slot != last {
+        if let Some(moved) = get_owner_position(env, owner, last) {
+            set_owner_position(env, owner, slot, moved);
+        }
+    }
+    env.storage()
+        .persistent()
+        .remove(&DataKeyV2Ext::OwnerPosition(owner.clone(), last));
+    env.storage().persistent().remove(&slot_key);
+    set_owner_position_count(env, owner, last);
+}
+
+pub fn set_position_approval(env: &Env, stream_id: u64, operator: &Option<Address>) {
+    let key = DataKeyV2::PositionApproval(stream_id);
+    match operator {
+        Some(operator) => {
+            env.storage().persistent().set(&key, operator);
+            env.storage()
+                .persistent()
+                .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+        }
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+pub fn get_position_approval(env: &Env, stream_id: u64) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2::PositionApproval(stream_id))
+}
+
+pub fn set_operator_approval(env: &Env, owner: &Address, operator: &Address, approved: bool) {
+    let key = DataKeyV2::OperatorApproval(owner.clone(), operator.clone());
+    if approved {
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    } else {
+        env.storage().persistent().remove(&key);
+    }
+}
+
+pub fn is_operator_approved(env: &Env, owner: &Address, operator: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2::OperatorApproval(
+            owner.clone(),
+            operator.clone(),
+        ))
+        .unwrap_or(false)
+}
+
+// ----------------------------------------------------------------
+// Stream Templates
+// ----------------------------------------------------------------
+
+pub fn next_template_id(env: &Env) -> u64 {
+    let id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKeyV2Ext::TemplateCount)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::TemplateCount, &(id + 1));
+    bump_instance(env);
+    id
+}
+
+/// Store a template version and mark it as the latest one.
+pub fn set_template(env: &Env, template: &StreamTemplate) {
+    let key = DataKeyV2Ext::TemplateVersion(template.id, template.version);
+    env.storage().persistent().set(&key, template);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+
+    let latest = DataKeyV2Ext::TemplateLatest(template.id);
+    env.storage().persistent().set(&latest, &template.version);
+    env.storage()
+        .persistent()
+        .extend_ttl(&latest, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+pub fn get_template_version(env: &Env, template_id: u64, version: u32) -> Option<StreamTemplate> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::TemplateVersion(template_id, version))
+}
+
+/// Latest version of a template.
+pub fn get_template(env: &Env, template_id: u64) -> Option<StreamTemplate> {
+    let version: u32 = env
+        .storage()
+        .persistent()
+        .get(&DataKeyV2Ext::TemplateLatest(template_id))?;
+    get_template_version(env, template_id, version)
+}
+
+pub fn set_stream_template(env: &Env, stream_id: u64, template_ref: &StreamTemplateRef) {
+    let key = DataKeyV2Ext::StreamTemplate(stream_id);
+    env.storage().persistent().set(&key, template_ref);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+pub fn get_stream_template(env: &Env, stream_id: u64) -> Option<StreamTemplateRef> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::StreamTemplate(stream_id))
+}
+
+// ----------------------------------------------------------------
+// Affiliate Fee Sharing
+// ----------------------------------------------------------------
+
+pub fn set_affiliate_share_bps(env: &Env, bps: u32) {
+    env.storage()
+        .instance()
+        .set(&DataKeyV2Ext::AffiliateShareBps, &bps);
+    bump_instance(env);
+}
+
+/// Affiliate share of protocol fees in bps (default 0 = treasury keeps everything).
+pub fn get_affiliate_share_bps(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKeyV2Ext::AffiliateShareBps)
+        .unwrap_or(0)
+}
+
+/// Record an affiliate registration and its approval status.
+pub fn set_affiliate(env: &Env, affiliate: &Address, approved: bool) {
+    let key = DataKeyV2Ext::Affiliate(affiliate.clone());
+    env.storage().persistent().set(&key, &approved);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+}
+
+/// `None` if never registered, otherwise whether the admin approved it.
+pub fn get_affiliate(env: &Env, affiliate: &Address) -> Option<bool> {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::Affiliate(affiliate.clone()))
+}
+
+/// Add `amount` to the fees credited to `affiliate` in `token`. Kept apart
+/// from `PendingFees` so treasury balances cannot leave through the
+/// affiliate withdrawal path.
+pub fn add_affiliate_fees(env: &Env, affiliate: &Address, token: &Address, amount: i128) {
+    let key = DataKeyV2Ext::AffiliateFees(affiliate.clone(), token.clone());
+    let current: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+    env.storage().persistent().set(&key, &(current + amount));
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, STREAM_TTL_THRESHOLD, STREAM_TTL_BUMP);
+    track_held(
+        env,
+        &HeldEntry::AffiliateFees(affiliate.clone(), token.clone()),
+        token,
+        current + amount,
+    );
+}
+
+pub fn get_affiliate_fees(env: &Env, affiliate: &Address, token: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKeyV2Ext::AffiliateFees(
+            affiliate.clone(),
+            token.clone(),
+        ))
+        .unwrap_or(0)
+}
+
+pub fn clear_affiliate_fees(env: &Env, affiliate: &Address, token: &Address) {
+    track_held(
+        env,
+        &HeldEntry::AffiliateFees(affiliate.clone(), token.clone()),
+        token,
+        0,
+    );
+    env.storage()
+        .persistent()
+        .remove(&DataKeyV2Ext::AffiliateFees(
+            affiliate.clone(),
+            token.clone(),
+        ));
+}
+
+// ----------------------------------------------------------------
+// Ledger footprint sizing
+// ----------------------------------------------------------------
+
+/// XDR-serialized size of a ledger entry's key plus value.
+fn entry_size<K: IntoVal<Env, Val>, V: IntoVal<Env, Val>>(env: &Env, key: K, value: V) -> u32 {
+    key.to_xdr(env).len() + value.to_xdr(env).len()
+}
+
+/// Size of the persistent entry `set_stream` would write for `stream`.
+pub fn stream_entry_size(env: &Env, stream_id: u64, stream: &StreamV2) -> u32 {
+    entry_size(env, DataKeyV2::Stream(stream_id), to_stored(stream))
+}
+
+/// Size of the piecewise curve schedule entry `insert_stream` would write.
+pub fn curve_schedule_entry_size(env: &Env, stream_id: u64, points: &Vec<CurvePoint>) -> u32 {
+    entry_size(env, DataKeyV2Ext::CurveSchedule(stream_id), points.clone())
+}
+
+/// Size of the count entry of `owner`'s position index.
+pub fn owner_position_count_entry_size(env: &Env, owner: &Address) -> u32 {
+    entry_size(env, DataKeyV2::OwnerPositionCount(owner.clone()), u32::MAX)
+}
+
+/// Size of the slot entries `add_owner_position` writes for one stream.
+pub fn owner_position_entry_size(env: &Env, owner: &Address, slot: u32, stream_id: u64) -> u32 {
+    entry_size(
+        env,
+        DataKeyV2Ext::OwnerPosition(owner.clone(), slot),
+        stream_id,
+    ) + entry_size(env, DataKeyV2Ext::OwnerPositionSlot(stream_id), slot)
+}
+
+pub fn is_user_seen(env: &Env, user: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKeyV2::UserSeen(user.clone()))
+}
+
+pub fn user_seen_entry_size(env: &Env, user: &Address) -> u32 {
+    entry_size(env, DataKeyV2::UserSeen(user.clone()), true)
+}
+
+/// Instance entries rewritten by every stream creation or withdrawal:
+/// the stream counter, TVL and user count.
+pub fn stats_entry_size(env: &Env) -> u32 {
+    entry_size(env, STREAM_COUNT_V2, u64::MAX)
+        + entry_size(env, V2_TVL, i128::MAX)
+        + entry_size(env, V2_USER_COUNT, u32::MAX)
+}
+
+pub fn pending_fees_entry_size(env: &Env, recipient: &Address, token: &Address) -> u32 {
+    entry_size(
+        env,
+        DataKeyV2::PendingFees(recipient.clone(), token.clone()),
+        i128::MAX,
+    )
+}
+
+pub fn affiliate_fees_entry_size(env: &Env, affiliate: &Address, token: &Address) -> u32 {
+    entry_size(
+        env,
+        DataKeyV2Ext::AffiliateFees(affiliate.clone(), token.clone()),
+        i128::MAX,
+    )
+}
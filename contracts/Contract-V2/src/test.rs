@@ -1,11 +1,14 @@
 #![cfg(test)]
 
 use super::*;
-use crate::types::{PendingRateUpdate, PermitArgs, SimulationReport, StreamArgs, SwapStreamArgs};
+use crate::types::{
+    CurvePoint, FeeShare, GovernanceConfig, KeeperTask, Operation, PendingRateUpdate, PermitArgs,
+    ProposalStatus, SimulationReport, StreamArgs, SwapStreamArgs, VoteChoice,
+};
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
+    testutils::{Address as _, AuthorizedFunction, Ledger},
     token::TokenClient,
-    vec, Address, Env, String,
+    vec, Address, Bytes, Env, String,
 };
 
 // ── Helpers ──────────────────────────────────────────────────────────────────
@@ -34,6 +37,21 @@ fn setup_v2<'a>(env: &'a Env, admin: &'a Address) -> (Address, ContractClient<'a
     (id, client)
 }
 
+/// Schedule `op`, wait out the admin timelock and execute it, then restore
+/// the ledger clock so the calling test keeps its own timeline.
+fn run_op(env: &Env, client: &ContractClient, admin: &Address, op: Operation) {
+    client.schedule_op(&vec![env, admin.clone()], &op);
+    let now = env.ledger().timestamp();
+    env.ledger()
+        .with_mut(|li| li.timestamp = now + storage::ADMIN_DELAY);
+    client.execute_op(&op);
+    env.ledger().with_mut(|li| li.timestamp = now);
+}
+
+fn whitelist(env: &Env, client: &ContractClient, admin: &Address, asset: &Address) {
+    run_op(env, client, admin, Operation::AddToWhitelist(asset.clone()));
+}
+
 fn stream_args(
     sender: &Address,
     receiver: &Address,
@@ -61,7 +79,9 @@ fn stream_args(
         split_bps: 0,
         penalty_bps: 0,
         memo: None,
-        curve_type: 0,
+        curve_type: CurveType::Linear,
+        is_soulbound: false,
+        accrual_clock: 0,
     }
 }
 
@@ -97,7 +117,9 @@ fn test_packed_stream_metadata_round_trip() {
         split_address: None,
         split_bps: 0,
         penalty_bps: 0,
-        curve_type: 0,
+        curve_type: CurveType::Linear,
+        is_soulbound: true,
+        accrual_clock: 1,
     };
     let packed = crate::storage::pack_stream_metadata(&stream);
     let (
@@ -108,6 +130,8 @@ fn test_packed_stream_metadata_round_trip() {
         yield_enabled,
         is_recurrent,
         cancellation_type,
+        is_soulbound,
+        accrual_clock,
     ) = crate::storage::unpack_stream_metadata(packed);
 
     assert_eq!(status, 2);
@@ -117,6 +141,8 @@ fn test_packed_stream_metadata_round_trip() {
     assert!(yield_enabled);
     assert!(is_recurrent);
     assert_eq!(cancellation_type, 7);
+    assert!(is_soulbound);
+    assert_eq!(accrual_clock, 1);
 }
 
 // ── Init tests ───────────────────────────────────────────────────────────────
@@ -470,7 +496,7 @@ fn test_permit_stream_fails_with_wrong_nonce() {
     let token_admin = Address::generate(&env);
     let (token_id, _, _) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     // Generate a dummy keypair (32-byte pubkey, 64-byte sig)
     let pubkey = soroban_sdk::BytesN::from_array(&env, &[1u8; 32]);
@@ -479,7 +505,7 @@ fn test_permit_stream_fails_with_wrong_nonce() {
     // Nonce 99 != stored nonce 0 — should fail with InvalidNonce
     let result = v2_client.try_create_stream_with_signature(
         &PermitArgs {
-            sender_pubkey: pubkey.clone(),
+            sender: Address::generate(&env),
             receiver: receiver.clone(),
             token: token_id.clone(),
             total_amount: 1000i128,
@@ -492,8 +518,9 @@ fn test_permit_stream_fails_with_wrong_nonce() {
             multiplier_bps: 0,
             vault_address: None,
             yield_enabled: false,
+            affiliate: None,
         },
-        &bad_sig,
+        &PermitSignature::Ed25519(pubkey, bad_sig),
     );
     assert!(result.is_err());
 }
@@ -509,7 +536,7 @@ fn test_permit_stream_fails_if_deadline_passed() {
     let token_admin = Address::generate(&env);
     let (token_id, _, _) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     let pubkey = soroban_sdk::BytesN::from_array(&env, &[1u8; 32]);
     let bad_sig = soroban_sdk::BytesN::from_array(&env, &[0u8; 64]);
@@ -517,7 +544,7 @@ fn test_permit_stream_fails_if_deadline_passed() {
     // deadline = 100, now = 500 — expired
     let result = v2_client.try_create_stream_with_signature(
         &PermitArgs {
-            sender_pubkey: pubkey.clone(),
+            sender: Address::generate(&env),
             receiver: receiver.clone(),
             token: token_id.clone(),
             total_amount: 1000i128,
@@ -530,12 +557,310 @@ fn test_permit_stream_fails_if_deadline_passed() {
             multiplier_bps: 0,
             vault_address: None,
             yield_enabled: false,
+            affiliate: None,
         },
-        &bad_sig,
+        &PermitSignature::Ed25519(pubkey, bad_sig),
     );
     assert!(result.is_err());
 }
 
+fn permit_args(sender: &Address, receiver: &Address, token: &Address, nonce: u64) -> PermitArgs {
+    PermitArgs {
+        sender: sender.clone(),
+        receiver: receiver.clone(),
+        token: token.clone(),
+        total_amount: 100_000_000,
+        start_time: 0,
+        cliff_time: 0,
+        end_time: 100,
+        nonce,
+        deadline: 1_000,
+        step_duration: 0,
+        multiplier_bps: 0,
+        vault_address: None,
+        yield_enabled: false,
+        affiliate: None,
+    }
+}
+
+#[test]
+fn test_typed_permit_binds_domain_and_consumes_address_nonce() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let relayer = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+    token_client.approve(&sender, &v2_id, &100_000_000, &1_000);
+
+    let args = permit_args(&sender, &receiver, &token_id, 0);
+    let sid = v2_client.create_stream_with_signature(&args, &PermitSignature::Auth);
+
+    // The sender authorized exactly the SEP-53 digest of the typed permit.
+    let permit = Permit {
+        domain: PermitDomain {
+            network_id: env.ledger().network_id(),
+            contract: v2_id.clone(),
+            function: Symbol::new(&env, "create_stream_with_signature"),
+            expiry: args.deadline,
+        },
+        signer: sender.clone(),
+        nonce: 0,
+        args_hash: env.crypto().sha256(&args.clone().to_xdr(&env)).into(),
+    };
+    let mut msg = Bytes::from_slice(&env, PERMIT_MESSAGE_PREFIX);
+    msg.append(&permit.to_xdr(&env));
+    let digest: soroban_sdk::BytesN<32> = env.crypto().sha256(&msg).into();
+    assert_eq!(
+        env.auths()
+            .into_iter()
+            .find(|(address, _)| *address == sender)
+            .map(|(_, invocation)| invocation.function),
+        Some(AuthorizedFunction::Contract((
+            v2_id.clone(),
+            Symbol::new(&env, "create_stream_with_signature"),
+            vec![&env, digest.into_val(&env)],
+        )))
+    );
+    assert_eq!(v2_client.get_stream(&sid).unwrap().sender, sender);
+    assert_eq!(v2_client.get_permit_nonce(&sender), 1);
+    assert_eq!(
+        v2_client.try_create_stream_with_signature(&args, &PermitSignature::Auth),
+        Err(Ok(Error::InvalidNonce))
+    );
+
+    // The beneficiary's nonce is its own, whichever stream it signs for.
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    assert_eq!(
+        v2_client.withdraw_meta(
+            &sid,
+            &50_000_000,
+            &1_000_000,
+            &relayer,
+            &0,
+            &1_000,
+            &PermitSignature::Auth
+        ),
+        50_000_000
+    );
+    assert_eq!(token_client.balance(&receiver), 49_000_000);
+    assert_eq!(token_client.balance(&relayer), 1_000_000);
+    assert_eq!(v2_client.get_permit_nonce(&receiver), 1);
+    assert_eq!(v2_client.get_withdrawal_nonce(&receiver, &sid), 1);
+    assert_eq!(v2_client.get_permit_nonce(&sender), 1);
+}
+
+#[test]
+fn test_typed_permit_ed25519_key_must_be_signers() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, _) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+
+    // A `G...` account built from its ed25519 key.
+    let key = soroban_sdk::BytesN::from_array(&env, &[7u8; 32]);
+    let mut account_xdr = Bytes::from_array(&env, &[0, 0, 0, 18, 0, 0, 0, 0, 0, 0, 0, 0]);
+    account_xdr.append(&key.clone().into());
+    let account = Address::from_xdr(&env, &account_xdr).unwrap();
+    let sig = soroban_sdk::BytesN::from_array(&env, &[0u8; 64]);
+
+    // The account's own key gets past the binding check to the signature check.
+    let result = v2_client.try_create_stream_with_signature(
+        &permit_args(&account, &receiver, &token_id, 0),
+        &PermitSignature::Ed25519(key.clone(), sig.clone()),
+    );
+    assert!(result.is_err() && result != Err(Ok(Error::UnauthorizedSender)));
+
+    let other_key = soroban_sdk::BytesN::from_array(&env, &[8u8; 32]);
+    assert_eq!(
+        v2_client.try_create_stream_with_signature(
+            &permit_args(&account, &receiver, &token_id, 0),
+            &PermitSignature::Ed25519(other_key.clone(), sig.clone()),
+        ),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+
+    // Contract accounts have no ed25519 key and must use `Auth`.
+    let wallet = Address::generate(&env);
+    let mut params = StreamParams {
+        sender: wallet.clone(),
+        receiver: receiver.clone(),
+        token: token_id.clone(),
+        total_amount: 100_000_000,
+        start_time: 0,
+        cliff_time: 0,
+        end_time: 100,
+        nonce: 0,
+        expiration_ledger: 1_000,
+        step_duration: 0,
+        multiplier_bps: 0,
+        vault_address: None,
+        yield_enabled: false,
+        affiliate: None,
+    };
+    assert_eq!(
+        v2_client.try_create_via_signature(&params, &PermitSignature::Ed25519(key, sig)),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+    params.nonce = 1;
+    assert_eq!(
+        v2_client.try_create_via_signature(&params, &PermitSignature::Auth),
+        Err(Ok(Error::InvalidNonce))
+    );
+    assert_eq!(v2_client.get_permit_nonce(&account), 0);
+    assert_eq!(v2_client.get_permit_nonce(&wallet), 0);
+}
+
+#[test]
+fn test_meta_cancel_pays_capped_relayer_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let relayer = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &200_000_000);
+    let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    let other = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+
+    // Half-way through, the receiver's payout is 50 tokens: the fee cap is 5%,
+    // and the fee comes out of that payout.
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    let execute = |fee: i128, nonce: u64| {
+        v2_client.try_execute_meta(
+            &receiver,
+            &sid,
+            &MetaAction::Cancel,
+            &relayer,
+            &fee,
+            &nonce,
+            &1_000,
+            &PermitSignature::Auth,
+        )
+    };
+    assert_eq!(execute(2_600_000, 0), Err(Ok(Error::InvalidRelayerFee)));
+    assert_eq!(execute(2_500_000, 1), Err(Ok(Error::InvalidNonce)));
+    assert_eq!(execute(2_500_000, 0), Ok(Ok(50_000_000)));
+
+    assert!(v2_client.get_stream(&sid).unwrap().cancelled);
+    assert_eq!(token_client.balance(&receiver), 47_500_000);
+    assert_eq!(token_client.balance(&relayer), 2_500_000);
+    assert_eq!(token_client.balance(&sender), 50_000_000);
+    assert_eq!(token_client.balance(&v2_id), 100_000_000);
+
+    // Nonces are per (signer, stream).
+    assert_eq!(v2_client.get_meta_nonce(&receiver, &sid), 1);
+    assert_eq!(v2_client.get_meta_nonce(&receiver, &other), 0);
+    assert_eq!(v2_client.get_meta_nonce(&sender, &sid), 0);
+    assert_eq!(v2_client.get_permit_nonce(&receiver), 0);
+}
+
+#[test]
+fn test_meta_actions_check_signer_role() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let new_owner = Address::generate(&env);
+    let relayer = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &200_000_000);
+    let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+
+    let execute = |signer: &Address, action: MetaAction, fee: i128| {
+        let nonce = v2_client.get_meta_nonce(signer, &sid);
+        v2_client.try_execute_meta(
+            signer,
+            &sid,
+            &action,
+            &relayer,
+            &fee,
+            &nonce,
+            &1_000,
+            &PermitSignature::Auth,
+        )
+    };
+    assert_eq!(
+        execute(&receiver, MetaAction::TopUp(10_000_000), 0),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+    assert_eq!(
+        execute(
+            &sender,
+            MetaAction::TransferBeneficiary(new_owner.clone()),
+            0
+        ),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+
+    // A relayed top-up is pulled from the sender's allowance; the relayer's
+    // fee is held back from it.
+    token_client.approve(&sender, &v2_id, &10_000_000, &1_000);
+    assert_eq!(
+        execute(&sender, MetaAction::TopUp(10_000_000), 500_000),
+        Ok(Ok(10_000_000))
+    );
+    assert_eq!(
+        v2_client.get_stream(&sid).unwrap().total_amount,
+        109_500_000
+    );
+    assert_eq!(token_client.balance(&relayer), 500_000);
+    assert_eq!(token_client.balance(&sender), 90_000_000);
+
+    // Other actions pay the relayer from the beneficiary's withdrawable balance.
+    env.ledger().with_mut(|li| li.timestamp = 10);
+    assert_eq!(
+        execute(
+            &receiver,
+            MetaAction::TransferBeneficiary(new_owner.clone()),
+            20_000_000
+        ),
+        Err(Ok(Error::InvalidRelayerFee))
+    );
+    assert_eq!(
+        execute(
+            &receiver,
+            MetaAction::TransferBeneficiary(new_owner.clone()),
+            1_000_000
+        ),
+        Ok(Ok(109_500_000))
+    );
+    let stream = v2_client.get_stream(&sid).unwrap();
+    assert_eq!(stream.beneficiary, new_owner);
+    assert_eq!(stream.withdrawn_amount, 1_000_000);
+    assert_eq!(token_client.balance(&relayer), 1_500_000);
+    assert_eq!(token_client.balance(&v2_id), 108_500_000);
+    assert_eq!(
+        execute(
+            &new_owner,
+            MetaAction::SetSplit(Some(receiver.clone()), 10_000),
+            0
+        ),
+        Err(Ok(Error::InvalidPenalty))
+    );
+}
+
 // ── Emergency Pause tests ───────────────────────────────────────────────────
 
 #[test]
@@ -606,7 +931,7 @@ fn test_permit_stream_fails_when_paused() {
 
     let result = client.try_create_stream_with_signature(
         &PermitArgs {
-            sender_pubkey: pubkey.clone(),
+            sender: Address::generate(&env),
             receiver: receiver.clone(),
             token: token_id.clone(),
             total_amount: 1000i128,
@@ -619,8 +944,9 @@ fn test_permit_stream_fails_when_paused() {
             multiplier_bps: 0,
             vault_address: None,
             yield_enabled: false,
+            affiliate: None,
         },
-        &bad_sig,
+        &PermitSignature::Ed25519(pubkey, bad_sig),
     );
     assert!(result.is_err());
 }
@@ -639,7 +965,7 @@ fn test_bump_active_streams_ttl_returns_count_of_existing() {
     let token_admin = Address::generate(&env);
     let (token_id, _, _) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     // Mint and approve tokens so migrate_stream can pull them
     let v1_id = {
@@ -705,7 +1031,7 @@ fn test_set_admins_replaces_list_and_threshold() {
     let new_admins = soroban_sdk::vec![&env, a1.clone(), a2.clone(), a3.clone()];
     let op = crate::types::Operation::SetAdmins(new_admins, 2);
 
-    client.schedule_op(&op);
+    client.schedule_op(&vec![&env, admin.clone()], &op);
 
     // Advance time by 48 hours and 1 second
     env.ledger().set_timestamp(48 * 60 * 60 + 1);
@@ -726,7 +1052,12 @@ fn test_transfer_admin_changes_admin() {
     let (_, client) = setup_v2(&env, &admin);
 
     let new_admin = Address::generate(&env);
-    client.transfer_admin(&new_admin);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::TransferAdmin(new_admin.clone()),
+    );
 
     assert_eq!(client.admin(), new_admin);
 }
@@ -739,7 +1070,12 @@ fn test_transfer_admin_old_admin_loses_access() {
     let (_, client) = setup_v2(&env, &admin);
 
     let new_admin = Address::generate(&env);
-    client.transfer_admin(&new_admin);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::TransferAdmin(new_admin.clone()),
+    );
 
     // After transfer, the admin list contains only new_admin.
     assert_eq!(client.get_admins().len(), 1u32);
@@ -755,7 +1091,10 @@ fn test_transfer_admin_requires_current_admin_auth() {
     let new_admin = Address::generate(&env);
 
     // No auth mocked at all — should panic/error because admin hasn't authorised.
-    let result = client.try_transfer_admin(&new_admin);
+    let result = client.try_schedule_op(
+        &vec![&env, admin.clone()],
+        &Operation::TransferAdmin(new_admin.clone()),
+    );
     assert!(result.is_err());
 }
 
@@ -783,7 +1122,7 @@ fn test_admin_can_manage_asset_whitelist() {
 
     assert!(!v2_client.is_asset_whitelisted(&token));
 
-    v2_client.add_to_whitelist(&token);
+    whitelist(&env, &v2_client, &admin, &token);
     assert!(v2_client.is_asset_whitelisted(&token));
 
     v2_client.remove_from_whitelist(&token);
@@ -857,7 +1196,7 @@ fn test_cliff_period_locks_funds() {
     let token_admin = Address::generate(&env);
     let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     // Mint tokens to sender
     asset_client.mint(&sender, &100_000_000);
@@ -922,7 +1261,7 @@ fn test_v2_cancel_splits_funds() {
     let token_admin = Address::generate(&env);
     let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&sender, &100_000_000);
 
@@ -973,7 +1312,7 @@ fn test_geometric_rate_unlock_math() {
     let token_admin = Address::generate(&env);
     let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&sender, &100_000_000);
 
@@ -1061,7 +1400,7 @@ fn test_create_batch_streams_success() {
     asset_client.mint(&sender, &1_000_000_000);
 
     let (v2_address, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     // Create batch of 2 streams
     let streams = soroban_sdk::vec![
@@ -1147,7 +1486,7 @@ fn test_create_batch_streams_max_limit() {
     let (token_id, _, _) = create_token(&env, &token_admin);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     // Create 11 streams (exceeds limit)
     let mut streams = Vec::new(&env);
@@ -1195,7 +1534,7 @@ fn test_create_batch_streams_atomic_failure() {
     asset_client.mint(&sender, &200_000_000);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     // Create batch with total amount exceeding balance (100M + 110M = 210M > 200M)
     let streams = soroban_sdk::vec![
@@ -1273,9 +1612,14 @@ fn test_create_stream_deducts_protocol_fee_to_treasury() {
     asset_client.mint(&sender, &1_000_000_000);
 
     let (contract_id, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
-    v2_client.set_treasury(&treasury);
-    v2_client.set_fee_bps(&10u32);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(10u32));
 
     let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
 
@@ -1300,8 +1644,8 @@ fn test_create_stream_with_fee_requires_treasury() {
     asset_client.mint(&sender, &1_000_000_000);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
-    v2_client.set_fee_bps(&10u32);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(10u32));
 
     let result =
         v2_client.try_create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
@@ -1321,7 +1665,12 @@ fn test_gas_buffer_deposit_withdraw_and_query() {
     asset_client.mint(&admin, &1_000_000_000);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_fee_token(&token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeToken(token_id.clone()),
+    );
 
     // Initial buffer is empty.
     assert_eq!(v2_client.get_gas_buffer_balance(&admin), 0);
@@ -1337,6 +1686,43 @@ fn test_gas_buffer_deposit_withdraw_and_query() {
     assert_eq!(token_client.balance(&beneficiary), 40_000_000);
 }
 
+#[test]
+fn test_legacy_gas_buffer_counts_towards_fee_token() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let beneficiary = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeToken(token_id.clone()),
+    );
+
+    // A buffer stored under the pre-per-token key.
+    asset_client.mint(&v2_id, &50_000_000);
+    env.as_contract(&v2_id, || {
+        env.storage().instance().set(
+            &storage::DataKeyV2::GasBuffer(admin.clone()),
+            &50_000_000i128,
+        );
+    });
+    assert_eq!(v2_client.get_gas_buffer_balance(&admin), 50_000_000);
+
+    v2_client.withdraw_gas_buffer(&admin, &20_000_000, &beneficiary);
+    assert_eq!(v2_client.get_gas_buffer_balance(&admin), 30_000_000);
+    assert_eq!(token_client.balance(&beneficiary), 20_000_000);
+    assert_eq!(
+        v2_client.check_balance_integrity(&token_id),
+        (30_000_000, 30_000_000)
+    );
+}
+
 #[test]
 fn test_split_multi_asset_requires_gas_buffer() {
     let env = Env::default();
@@ -1352,7 +1738,12 @@ fn test_split_multi_asset_requires_gas_buffer() {
     asset_client.mint(&sender, &1_000_000_000);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_fee_token(&token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeToken(token_id.clone()),
+    );
 
     let recipients = soroban_sdk::vec![
         &env,
@@ -1398,7 +1789,12 @@ fn test_split_funds_success() {
     asset_client.mint(&sender, &1_000_000_000);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_fee_token(&token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeToken(token_id.clone()),
+    );
 
     // Deposit exactly one execution's gas buffer fee.
     v2_client.deposit_gas_buffer(&sender, &GAS_FEE_PER_SPLIT_STROOPS);
@@ -1438,7 +1834,12 @@ fn test_split_funds_fails_atomically_on_insufficient_balance() {
     asset_client.mint(&sender, &(GAS_FEE_PER_SPLIT_STROOPS + 100_000_000));
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_fee_token(&token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeToken(token_id.clone()),
+    );
 
     // Deposit exactly one execution's gas buffer fee.
     v2_client.deposit_gas_buffer(&sender, &GAS_FEE_PER_SPLIT_STROOPS);
@@ -1483,7 +1884,12 @@ fn test_split_multi_asset_fails_on_non_token_asset() {
     asset_client.mint(&sender, &1_000_000_000);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_fee_token(&token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeToken(token_id.clone()),
+    );
     v2_client.deposit_gas_buffer(&sender, &GAS_FEE_PER_SPLIT_STROOPS);
 
     let bad_asset = Address::generate(&env); // not an asset contract
@@ -1515,7 +1921,12 @@ fn test_split_multi_asset_fails_on_too_many_recipients() {
     asset_client.mint(&sender, &10_000_000_000);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_fee_token(&token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeToken(token_id.clone()),
+    );
 
     let mut recipients = soroban_sdk::vec!(&env);
     for i in 0..121 {
@@ -1545,9 +1956,14 @@ fn test_withdraw_treasury_transfers_pending_fees() {
     asset_client.mint(&sender, &1_000_000_000);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
-    v2_client.set_treasury(&treasury);
-    v2_client.set_fee_bps(&10u32);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(10u32));
     v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 200_000_000));
 
     let withdrawn = v2_client.withdraw_treasury(&token_id);
@@ -1556,6 +1972,195 @@ fn test_withdraw_treasury_transfers_pending_fees() {
     assert_eq!(token_client.balance(&treasury), 200_000);
 }
 
+#[test]
+fn test_withdraw_treasury_follows_distribution_table() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let ops = Address::generate(&env);
+    let insurance = Address::generate(&env);
+    let payroll = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    asset_client.mint(&sender, &4_000_000_000);
+
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(500u32));
+    let share = |recipient: &Address, bps, stream_duration| FeeShare {
+        recipient: recipient.clone(),
+        bps,
+        stream_duration,
+    };
+    let table = soroban_sdk::vec![
+        &env,
+        share(&ops, 3_000, 0),
+        share(&insurance, 2_000, 0),
+        share(&payroll, 5_000, 1_000),
+    ];
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeDistribution(table.clone()),
+    );
+    assert_eq!(v2_client.get_fee_distribution(), table);
+
+    let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 4_000_000_000));
+    assert_eq!(v2_client.withdraw_treasury(&token_id), 200_000_000);
+    assert_eq!(token_client.balance(&ops), 60_000_000);
+    assert_eq!(token_client.balance(&insurance), 40_000_000);
+    assert_eq!(token_client.balance(&treasury), 0);
+
+    // The payroll share arrives as a budget stream funded by the treasury.
+    let budget_id = sid + 1;
+    let budget = v2_client.get_stream(&budget_id).unwrap();
+    assert_eq!(budget.sender, treasury);
+    assert_eq!(budget.receiver, payroll);
+    assert_eq!(budget.total_amount, 100_000_000);
+    assert_eq!(budget.end_time, budget.start_time + 1_000);
+
+    env.ledger().with_mut(|li| li.timestamp += 500);
+    assert_eq!(v2_client.withdraw(&budget_id, &payroll), 50_000_000);
+}
+
+#[test]
+fn test_withdraw_treasury_all_sweeps_whitelisted_tokens() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_a, client_a, asset_a) = create_token(&env, &token_admin);
+    let (token_b, client_b, asset_b) = create_token(&env, &token_admin);
+    let (token_c, _, _) = create_token(&env, &token_admin);
+    asset_a.mint(&sender, &200_000_000);
+    asset_b.mint(&sender, &400_000_000);
+
+    let (_, v2_client) = setup_v2(&env, &admin);
+    for token in [&token_a, &token_b, &token_c] {
+        whitelist(&env, &v2_client, &admin, token);
+    }
+    assert_eq!(v2_client.get_whitelisted_assets().len(), 3);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(10u32));
+    v2_client.create_stream(&stream_args(&sender, &receiver, &token_a, 200_000_000));
+    v2_client.create_stream(&stream_args(&sender, &receiver, &token_b, 400_000_000));
+
+    let withdrawn = v2_client.withdraw_treasury_all();
+    assert_eq!(withdrawn.len(), 2);
+    assert_eq!(withdrawn.get(token_a.clone()), Some(200_000));
+    assert_eq!(withdrawn.get(token_b.clone()), Some(400_000));
+    assert_eq!(client_a.balance(&treasury), 200_000);
+    assert_eq!(client_b.balance(&treasury), 400_000);
+    assert_eq!(
+        v2_client.try_withdraw_treasury_all(),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+
+    v2_client.remove_from_whitelist(&token_c);
+    assert_eq!(
+        v2_client.get_whitelisted_assets(),
+        soroban_sdk::vec![&env, token_a, token_b]
+    );
+}
+
+#[test]
+fn test_backfill_lists_assets_whitelisted_before_upgrade() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (other, _, _) = create_token(&env, &token_admin);
+    asset_client.mint(&sender, &200_000_000);
+
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    // Whitelisted through the per-asset flag only, as before the asset list.
+    env.as_contract(&v2_id, || {
+        env.storage().instance().set(
+            &storage::DataKeyV2::WhitelistedAsset(token_id.clone()),
+            &true,
+        );
+    });
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(10u32));
+    v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 200_000_000));
+    assert_eq!(
+        v2_client.try_withdraw_treasury_all(),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+
+    assert_eq!(
+        v2_client.try_backfill_whitelisted_asset(&other),
+        Err(Ok(Error::AssetNotWhitelisted))
+    );
+    v2_client.backfill_whitelisted_asset(&token_id);
+    assert_eq!(
+        v2_client.get_whitelisted_assets(),
+        soroban_sdk::vec![&env, token_id.clone()]
+    );
+    assert_eq!(
+        v2_client.withdraw_treasury_all().get(token_id.clone()),
+        Some(200_000)
+    );
+    assert_eq!(token_client.balance(&treasury), 200_000);
+}
+
+#[test]
+fn test_fee_distribution_must_cover_all_fees() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    let share = |bps| FeeShare {
+        recipient: Address::generate(&env),
+        bps,
+        stream_duration: 0,
+    };
+
+    for table in [
+        soroban_sdk::vec![&env, share(6_000), share(3_000)],
+        soroban_sdk::vec![&env, share(10_000), share(0)],
+    ] {
+        assert_eq!(
+            v2_client.try_schedule_op(
+                &vec![&env, admin.clone()],
+                &Operation::SetFeeDistribution(table)
+            ),
+            Err(Ok(Error::InvalidThreshold))
+        );
+    }
+}
+
 // ── Governance: Stream-Weighted Voting Power tests ───────────────────────────
 
 #[test]
@@ -1569,7 +2174,7 @@ fn test_get_active_volume_single_stream_as_receiver() {
     let token_admin = Address::generate(&env);
     let (token_id, _, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&sender, &100_000_000);
 
@@ -1612,7 +2217,7 @@ fn test_get_active_volume_single_stream_as_sender() {
     let token_admin = Address::generate(&env);
     let (token_id, _, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&sender, &100_000_000);
 
@@ -1654,7 +2259,7 @@ fn test_get_active_volume_multiple_streams() {
     let token_admin = Address::generate(&env);
     let (token_id, _, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&sender, &600_000_000);
 
@@ -1741,7 +2346,7 @@ fn test_get_active_volume_after_partial_withdrawal() {
     let token_admin = Address::generate(&env);
     let (token_id, _, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&sender, &100_000_000);
 
@@ -1788,7 +2393,7 @@ fn test_get_active_volume_excludes_cancelled_streams() {
     let token_admin = Address::generate(&env);
     let (token_id, _, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&sender, &200_000_000);
 
@@ -1858,7 +2463,7 @@ fn test_get_active_volume_unrelated_user_returns_zero() {
     let token_admin = Address::generate(&env);
     let (token_id, _, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&sender, &100_000_000);
 
@@ -1915,7 +2520,7 @@ fn test_get_active_volume_mixed_roles() {
     let token_admin = Address::generate(&env);
     let (token_id, _, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&user, &200_000_000);
     asset_client.mint(&other1, &200_000_000);
@@ -1983,7 +2588,7 @@ fn test_rebalance_after_clawback() {
     let token_admin = Address::generate(&env);
     let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     asset_client.mint(&sender, &1000_000_000);
 
@@ -2135,12 +2740,24 @@ fn test_yield_bearing_stream() {
     let token_admin = Address::generate(&env);
     let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     // Register Mock Vault
     let vault_id = env.register_contract(None, MockVault);
     let vault_client = MockVaultClient::new(&env, &vault_id);
     vault_client.set_token(&token_id, &v2_client.address);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetVault(
+            vault_id.clone(),
+            VaultConfig {
+                kind: VaultKind::Principal,
+                deposit_cap: 0,
+            },
+        ),
+    );
 
     asset_client.mint(&sender, &1000_000_000);
     asset_client.mint(&vault_id, &100_000_000);
@@ -2206,7 +2823,495 @@ fn test_yield_bearing_stream() {
     assert_eq!(token_client.balance(&receiver), 500_000_000);
 }
 
-// ── Issue #934: Decommission Logic ───────────────────────────────────────────
+mod mock_share_vault {
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env};
+
+    /// Share vault whose share price (`rate`, scaled 1e7) is set by the test.
+    #[contract]
+    pub struct MockShareVault;
+
+    #[contractimpl]
+    impl MockShareVault {
+        pub fn set_token(env: Env, token: Address) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("token"), &token);
+            env.storage()
+                .instance()
+                .set(&symbol_short!("rate"), &10_000_000i128);
+        }
+
+        pub fn set_rate(env: Env, rate: i128) {
+            env.storage().instance().set(&symbol_short!("rate"), &rate);
+        }
+
+        pub fn deposit(env: Env, from: Address, assets: i128) -> i128 {
+            let vault = env.current_contract_address();
+            let token: Address = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("token"))
+                .unwrap();
+            soroban_sdk::token::TokenClient::new(&env, &token)
+                .transfer_from(&vault, &from, &vault, &assets);
+            assets * 10_000_000 / Self::rate(&env)
+        }
+
+        pub fn redeem(env: Env, owner: Address, shares: i128) -> i128 {
+            let token: Address = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("token"))
+                .unwrap();
+            let assets = Self::convert_to_assets(env.clone(), shares);
+            soroban_sdk::token::TokenClient::new(&env, &token).transfer(
+                &env.current_contract_address(),
+                &owner,
+                &assets,
+            );
+            assets
+        }
+
+        pub fn convert_to_assets(env: Env, shares: i128) -> i128 {
+            shares * Self::rate(&env) / 10_000_000
+        }
+
+        fn rate(env: &Env) -> i128 {
+            env.storage()
+                .instance()
+                .get(&symbol_short!("rate"))
+                .unwrap()
+        }
+    }
+}
+
+use mock_share_vault::{MockShareVault, MockShareVaultClient};
+
+fn share_vault_config(deposit_cap: i128) -> VaultConfig {
+    VaultConfig {
+        kind: VaultKind::Shares,
+        deposit_cap,
+    }
+}
+
+#[test]
+fn test_share_vault_redeems_pro_rata_and_routes_yield() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+
+    let vault_id = env.register(MockShareVault, ());
+    let vault = MockShareVaultClient::new(&env, &vault_id);
+    vault.set_token(&token_id);
+    vault.set_rate(&20_000_000);
+    asset_client.mint(&vault_id, &100_000_000);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetVault(vault_id.clone(), share_vault_config(0)),
+    );
+
+    asset_client.mint(&sender, &100_000_000);
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.vault_address = Some(vault_id.clone());
+    args.yield_enabled = true;
+    args.yield_recipient = 0;
+    let sid = v2_client.create_stream(&args);
+
+    // 100 tokens bought 50 shares at 2.0 per share.
+    assert_eq!(token_client.balance(&v2_id), 0);
+    let position = v2_client.get_vault_position(&sid).unwrap();
+    assert_eq!(
+        (position.shares, position.principal),
+        (50_000_000, 100_000_000)
+    );
+    assert_eq!(v2_client.get_vault_deposits(&vault_id), 100_000_000);
+
+    // At 2.2 per share, half the principal redeems half the shares for 55.
+    vault.set_rate(&22_000_000);
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 50_000_000);
+    assert_eq!(token_client.balance(&receiver), 50_000_000);
+    assert_eq!(token_client.balance(&sender), 5_000_000);
+    let position = v2_client.get_vault_position(&sid).unwrap();
+    assert_eq!(
+        (position.shares, position.principal),
+        (25_000_000, 50_000_000)
+    );
+    assert_eq!(v2_client.get_vault_deposits(&vault_id), 50_000_000);
+
+    // The last withdrawal redeems every remaining share.
+    vault.set_rate(&24_000_000);
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 50_000_000);
+    assert_eq!(token_client.balance(&receiver), 100_000_000);
+    assert_eq!(token_client.balance(&sender), 15_000_000);
+    let position = v2_client.get_vault_position(&sid).unwrap();
+    assert_eq!((position.shares, position.principal), (0, 0));
+    assert_eq!(v2_client.get_vault_deposits(&vault_id), 0);
+    assert_eq!(token_client.balance(&v2_id), 0);
+}
+
+#[test]
+fn test_vault_allowlist_cap_and_pending_resolution() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+
+    let vault_id = env.register(MockVault, ());
+    let vault = MockVaultClient::new(&env, &vault_id);
+    vault.set_token(&token_id, &v2_id);
+    asset_client.mint(&sender, &300_000_000);
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.vault_address = Some(vault_id.clone());
+    args.yield_enabled = true;
+    args.yield_recipient = 1;
+
+    assert_eq!(
+        v2_client.try_create_stream(&args),
+        Err(Ok(Error::AssetNotWhitelisted))
+    );
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetVault(
+            vault_id.clone(),
+            VaultConfig {
+                kind: VaultKind::Principal,
+                deposit_cap: 150_000_000,
+            },
+        ),
+    );
+    let sid = v2_client.create_stream(&args);
+    assert_eq!(
+        v2_client.try_create_stream(&args),
+        Err(Ok(Error::AmountOverflow))
+    );
+    // The mock vault takes no tokens on deposit; hand it the escrow.
+    token_client.transfer(&v2_id, &vault_id, &100_000_000);
+    asset_client.mint(&vault_id, &10_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(
+        v2_client.try_resolve_pending_stream(&sid),
+        Err(Ok(Error::NoPendingUpdate))
+    );
+    vault.set_paused(&true);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 0);
+    assert!(v2_client.get_stream(&sid).unwrap().is_pending);
+    assert_eq!(v2_client.resolve_pending_stream(&sid), 0);
+
+    // Anyone may retry once the vault recovers; the receiver gets the yield.
+    vault.set_paused(&false);
+    assert_eq!(v2_client.resolve_pending_stream(&sid), 100_000_000);
+    assert!(!v2_client.get_stream(&sid).unwrap().is_pending);
+    assert_eq!(token_client.balance(&receiver), 110_000_000);
+    assert_eq!(v2_client.get_vault_deposits(&vault_id), 0);
+
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::RemoveVault(vault_id.clone()),
+    );
+    assert_eq!(v2_client.get_vault_config(&vault_id), None);
+    assert_eq!(
+        v2_client.try_create_stream(&args),
+        Err(Ok(Error::AssetNotWhitelisted))
+    );
+}
+
+/// Register a share vault for `token` at 1.0 per share and allowlist it.
+fn setup_share_vault<'a>(
+    env: &Env,
+    client: &ContractClient,
+    admin: &Address,
+    token: &Address,
+) -> (Address, MockShareVaultClient<'a>) {
+    let vault_id = env.register(MockShareVault, ());
+    let vault = MockShareVaultClient::new(env, &vault_id);
+    vault.set_token(token);
+    run_op(
+        env,
+        client,
+        admin,
+        Operation::SetVault(vault_id.clone(), share_vault_config(0)),
+    );
+    (vault_id, vault)
+}
+
+#[test]
+fn test_emergency_exit_vault_writes_off_shortfall() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let guardian = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetGuardian(Some(guardian.clone())),
+    );
+    let (vault_id, vault) = setup_share_vault(&env, &v2_client, &admin, &token_id);
+
+    asset_client.mint(&sender, &200_000_000);
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.vault_address = Some(vault_id.clone());
+    args.yield_enabled = true;
+    let first = v2_client.create_stream(&args);
+    let second = v2_client.create_stream(&args);
+
+    assert_eq!(
+        v2_client.try_emergency_exit_vault(&sender, &vault_id, &0, &10),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+
+    // The vault lost 10%: each stream gets 90 back and is written down.
+    vault.set_rate(&9_000_000);
+    let report = v2_client.emergency_exit_vault(&guardian, &vault_id, &0, &10);
+    assert_eq!(
+        report,
+        VaultExitReport {
+            exited: 2,
+            failed: 0,
+            principal: 200_000_000,
+            recovered: 180_000_000,
+            shortfall: 20_000_000,
+            cursor: 0,
+            remaining: 0,
+        }
+    );
+    assert_eq!(token_client.balance(&v2_id), 180_000_000);
+    assert_eq!(v2_client.get_vault_deposits(&vault_id), 0);
+    assert_eq!(v2_client.get_vault_config(&vault_id), None);
+    for sid in [first, second] {
+        let stream = v2_client.get_stream(&sid).unwrap();
+        assert_eq!(stream.vault_address, None);
+        assert_eq!(stream.total_amount, 90_000_000);
+        assert_eq!(v2_client.get_vault_position(&sid), None);
+    }
+
+    // The streams carry on from the contract's balance.
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    v2_client.withdraw(&first, &receiver);
+    v2_client.withdraw(&second, &receiver);
+    assert_eq!(token_client.balance(&receiver), 180_000_000);
+    assert_eq!(token_client.balance(&v2_id), 0);
+}
+
+#[test]
+fn test_emergency_exit_vault_pages_and_finds_legacy_streams() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    let vault_id = env.register(MockVault, ());
+    let vault = MockVaultClient::new(&env, &vault_id);
+    vault.set_token(&token_id, &v2_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetVault(
+            vault_id.clone(),
+            VaultConfig {
+                kind: VaultKind::Principal,
+                deposit_cap: 0,
+            },
+        ),
+    );
+
+    asset_client.mint(&sender, &300_000_000);
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.vault_address = Some(vault_id.clone());
+    args.yield_enabled = true;
+    let ids = [
+        v2_client.create_stream(&args),
+        v2_client.create_stream(&args),
+        v2_client.create_stream(&args),
+    ];
+    // The mock vault takes no tokens on deposit; hand it the escrow.
+    token_client.transfer(&v2_id, &vault_id, &300_000_000);
+    asset_client.mint(&vault_id, &30_000_000);
+
+    // The first stream predates position tracking.
+    env.as_contract(&v2_id, || {
+        storage::remove_vault_position(&env, ids[0]);
+        storage::remove_vault_stream(&env, &vault_id, ids[0]);
+        storage::set_vault_deposits(&env, &vault_id, 200_000_000);
+    });
+    let position = v2_client.track_vault_stream(&ids[0]);
+    assert_eq!(position.principal, 100_000_000);
+    assert_eq!(v2_client.get_vault_deposits(&vault_id), 300_000_000);
+    assert_eq!(
+        v2_client.try_track_vault_stream(&ids[0]),
+        Err(Ok(Error::StreamNotMigratable))
+    );
+
+    assert_eq!(
+        v2_client.try_emergency_exit_vault(&admin, &vault_id, &0, &(MAX_PAGE_SIZE + 1)),
+        Err(Ok(Error::BatchTooLarge))
+    );
+    // A failing stream keeps its place; the cursor moves past it.
+    vault.set_paused(&true);
+    let report = v2_client.emergency_exit_vault(&admin, &vault_id, &0, &1);
+    assert_eq!((report.failed, report.cursor, report.remaining), (1, 1, 2));
+    vault.set_paused(&false);
+    let report = v2_client.emergency_exit_vault(&admin, &vault_id, &report.cursor, &1);
+    assert_eq!((report.exited, report.cursor, report.remaining), (1, 1, 1));
+    let report = v2_client.emergency_exit_vault(&admin, &vault_id, &report.cursor, &1);
+    assert_eq!((report.exited, report.cursor, report.remaining), (1, 1, 0));
+    let report = v2_client.emergency_exit_vault(&admin, &vault_id, &0, &10);
+    assert_eq!((report.exited, report.cursor, report.remaining), (1, 0, 0));
+
+    for sid in ids {
+        assert_eq!(v2_client.get_stream(&sid).unwrap().vault_address, None);
+    }
+    assert_eq!(v2_client.get_vault_deposits(&vault_id), 0);
+    // Principal plus the mock's 10% yield, paid to the yield recipient.
+    assert_eq!(token_client.balance(&v2_id), 300_000_000);
+}
+
+#[test]
+fn test_resolve_pending_stream_writes_off_vault_loss() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    let (vault_id, vault) = setup_share_vault(&env, &v2_client, &admin, &token_id);
+
+    asset_client.mint(&sender, &100_000_000);
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.vault_address = Some(vault_id.clone());
+    args.yield_enabled = true;
+    let sid = v2_client.create_stream(&args);
+
+    // Shares worth 80% of the principal leave the withdrawal pending.
+    vault.set_rate(&8_000_000);
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 0);
+    assert!(v2_client.get_stream(&sid).unwrap().is_pending);
+    assert_eq!(
+        v2_client.get_vault_position(&sid).unwrap().principal,
+        100_000_000
+    );
+
+    // Resolving exits the vault at a loss and pays the written-down accrual.
+    assert_eq!(v2_client.resolve_pending_stream(&sid), 40_000_000);
+    let stream = v2_client.get_stream(&sid).unwrap();
+    assert!(!stream.is_pending);
+    assert_eq!(stream.vault_address, None);
+    assert_eq!(stream.total_amount, 80_000_000);
+    assert_eq!(v2_client.get_vault_position(&sid), None);
+    assert_eq!(v2_client.get_vault_deposits(&vault_id), 0);
+    assert_eq!(token_client.balance(&receiver), 40_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 40_000_000);
+    assert_eq!(token_client.balance(&v2_id), 0);
+}
+
+#[test]
+fn test_migrate_stream_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    let (old_id, old_vault) = setup_share_vault(&env, &v2_client, &admin, &token_id);
+    let new_id = env.register(MockShareVault, ());
+    MockShareVaultClient::new(&env, &new_id).set_token(&token_id);
+
+    asset_client.mint(&sender, &100_000_000);
+    asset_client.mint(&old_id, &50_000_000);
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.vault_address = Some(old_id.clone());
+    args.yield_enabled = true;
+    let sid = v2_client.create_stream(&args);
+
+    assert_eq!(
+        v2_client.try_migrate_stream_vault(&sid, &new_id),
+        Err(Ok(Error::AssetNotWhitelisted))
+    );
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetVault(new_id.clone(), share_vault_config(50_000_000)),
+    );
+    assert_eq!(
+        v2_client.try_migrate_stream_vault(&sid, &new_id),
+        Err(Ok(Error::AmountOverflow))
+    );
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetVault(new_id.clone(), share_vault_config(0)),
+    );
+
+    // Leaving the old vault at 1.5 per share pays the sender its yield.
+    old_vault.set_rate(&15_000_000);
+    assert_eq!(v2_client.migrate_stream_vault(&sid, &new_id), 100_000_000);
+    assert_eq!(token_client.balance(&sender), 50_000_000);
+    assert_eq!(
+        v2_client.get_stream(&sid).unwrap().vault_address,
+        Some(new_id.clone())
+    );
+    assert_eq!(v2_client.get_vault_deposits(&old_id), 0);
+    assert_eq!(v2_client.get_vault_deposits(&new_id), 100_000_000);
+    assert_eq!(
+        v2_client.try_migrate_stream_vault(&sid, &new_id),
+        Err(Ok(Error::StreamNotMigratable))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 100_000_000);
+    assert_eq!(token_client.balance(&receiver), 100_000_000);
+    assert_eq!(token_client.balance(&new_id), 0);
+}
+
+// ── Issue #934: Decommission Logic ───────────────────────────────────────────
 
 #[test]
 fn test_decommission_blocks_create_stream() {
@@ -2253,7 +3358,7 @@ fn test_decommission_withdraw_allowed_within_claim_window() {
     let receiver = Address::generate(&env);
     let (token_id, token_client, sac) = create_token(&env, &admin);
     sac.mint(&sender, &1_000_000_000);
-    client.add_to_whitelist(&token_id);
+    whitelist(&env, &client, &admin, &token_id);
 
     client.create_stream(&StreamArgs {
         sender: sender.clone(),
@@ -2293,7 +3398,7 @@ fn test_decommission_withdraw_blocked_after_claim_window() {
     let receiver = Address::generate(&env);
     let (token_id, _, sac) = create_token(&env, &admin);
     sac.mint(&sender, &1_000_000_000);
-    client.add_to_whitelist(&token_id);
+    whitelist(&env, &client, &admin, &token_id);
 
     client.create_stream(&StreamArgs {
         sender: sender.clone(),
@@ -2387,7 +3492,7 @@ fn test_sanctions_oracle_blocks_cancel_to_sanctioned_receiver() {
     let receiver = Address::generate(&env);
     let (token_id, _, sac) = create_token(&env, &admin);
     sac.mint(&sender, &1_000_000_000);
-    client.add_to_whitelist(&token_id);
+    whitelist(&env, &client, &admin, &token_id);
 
     client.create_stream(&StreamArgs {
         sender: sender.clone(),
@@ -2409,7 +3514,12 @@ fn test_sanctions_oracle_blocks_cancel_to_sanctioned_receiver() {
     let oracle_client = mock_oracle::MockOracleClient::new(&env, &oracle_id);
     oracle_client.block(&receiver);
 
-    client.set_oracle_address(&oracle_id);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetOracleAddress(oracle_id.clone()),
+    );
 
     // Cancel should fail because receiver is sanctioned
     let result = client.try_cancel(&0, &sender);
@@ -2429,7 +3539,7 @@ fn test_sanctions_oracle_blocks_withdraw_to_sanctioned_beneficiary() {
     let receiver = Address::generate(&env);
     let (token_id, _, sac) = create_token(&env, &admin);
     sac.mint(&sender, &1_000_000_000);
-    client.add_to_whitelist(&token_id);
+    whitelist(&env, &client, &admin, &token_id);
 
     client.create_stream(&StreamArgs {
         sender: sender.clone(),
@@ -2450,7 +3560,12 @@ fn test_sanctions_oracle_blocks_withdraw_to_sanctioned_beneficiary() {
     let oracle_client = mock_oracle::MockOracleClient::new(&env, &oracle_id);
     oracle_client.block(&receiver);
 
-    client.set_oracle_address(&oracle_id);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetOracleAddress(oracle_id.clone()),
+    );
 
     let result = client.try_withdraw(&0, &receiver);
     assert!(result.is_err());
@@ -2469,7 +3584,7 @@ fn test_sanctions_oracle_no_oracle_set_allows_transfer() {
     let receiver = Address::generate(&env);
     let (token_id, token_client, sac) = create_token(&env, &admin);
     sac.mint(&sender, &1_000_000_000);
-    client.add_to_whitelist(&token_id);
+    whitelist(&env, &client, &admin, &token_id);
 
     client.create_stream(&StreamArgs {
         sender: sender.clone(),
@@ -2514,7 +3629,7 @@ fn test_set_get_fee_tiers_success() {
         },
     ];
 
-    client.set_fee_tiers(&tiers);
+    run_op(&env, &client, &admin, Operation::SetFeeTiers(tiers.clone()));
 
     let fetched_tiers = client.get_fee_tiers();
     assert_eq!(fetched_tiers.len(), 2);
@@ -2538,21 +3653,23 @@ fn test_set_fee_tiers_admin_only() {
     ];
 
     // Attempt to set tiers without admin auth (should fail)
-    let result = client.try_set_fee_tiers(&tiers);
+    let op = Operation::SetFeeTiers(tiers);
+    let result = client.try_schedule_op(&vec![&env, admin.clone()], &op);
     assert!(result.is_err());
 
     // Mock non_admin auth, still fails
     use soroban_sdk::testutils::{MockAuth, MockAuthInvoke};
+    let signers = vec![&env, non_admin.clone()];
     env.mock_auths(&[MockAuth {
         address: &non_admin,
         invoke: &MockAuthInvoke {
             contract: &client.address,
-            fn_name: "set_fee_tiers",
-            args: (tiers.clone(),).into_val(&env),
+            fn_name: "schedule_op",
+            args: (signers.clone(), op.clone()).into_val(&env),
             sub_invokes: &[],
         },
     }]);
-    let result = client.try_set_fee_tiers(&tiers);
+    let result = client.try_schedule_op(&signers, &op);
     assert!(result.is_err());
 }
 
@@ -2573,7 +3690,7 @@ fn test_set_fee_tiers_fee_too_high() {
         },
     ];
 
-    let result = client.try_set_fee_tiers(&tiers);
+    let result = client.try_schedule_op(&vec![&env, admin.clone()], &Operation::SetFeeTiers(tiers));
     assert_eq!(result, Err(Ok(Error::FeeTooHigh)));
 }
 
@@ -2602,9 +3719,14 @@ fn test_create_stream_with_tiered_fee_no_discount() {
     asset_client.mint(&sender, &1_000_000_000);
 
     let (contract_id, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_treasury(&treasury);
-    v2_client.add_to_whitelist(&token_id);
-    v2_client.set_fee_bps(&200u32); // Default fee 2%
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(200u32)); // Default fee 2%
 
     let tiers = vec![
         &env,
@@ -2613,7 +3735,12 @@ fn test_create_stream_with_tiered_fee_no_discount() {
             fee_bps: 100,           // 1%
         },
     ];
-    v2_client.set_fee_tiers(&tiers);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeTiers(tiers.clone()),
+    );
 
     // Amount below threshold (100M < 500M), should apply default 2% fee
     let total_amount = 100_000_000;
@@ -2645,9 +3772,14 @@ fn test_create_stream_with_tiered_fee_discount_applied() {
     asset_client.mint(&sender, &2_000_000_000);
 
     let (contract_id, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_treasury(&treasury);
-    v2_client.add_to_whitelist(&token_id);
-    v2_client.set_fee_bps(&200u32); // Default fee 2%
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(200u32)); // Default fee 2%
 
     let tiers = vec![
         &env,
@@ -2660,7 +3792,12 @@ fn test_create_stream_with_tiered_fee_discount_applied() {
             fee_bps: 50,              // 0.5%
         },
     ];
-    v2_client.set_fee_tiers(&tiers);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeTiers(tiers.clone()),
+    );
 
     // Amount above second threshold (1.5B > 1B), should apply 0.5% fee
     let total_amount = 1_500_000_000;
@@ -2692,9 +3829,14 @@ fn test_create_stream_with_tiered_fee_exact_threshold() {
     asset_client.mint(&sender, &1_000_000_000);
 
     let (contract_id, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_treasury(&treasury);
-    v2_client.add_to_whitelist(&token_id);
-    v2_client.set_fee_bps(&200u32); // Default fee 2%
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(200u32)); // Default fee 2%
 
     let tiers = vec![
         &env,
@@ -2703,7 +3845,12 @@ fn test_create_stream_with_tiered_fee_exact_threshold() {
             fee_bps: 100,           // 1%
         },
     ];
-    v2_client.set_fee_tiers(&tiers);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetFeeTiers(tiers.clone()),
+    );
 
     // Amount exactly at threshold (500M), should apply 1% fee
     let total_amount = 500_000_000;
@@ -2735,9 +3882,14 @@ fn test_create_stream_with_tiered_fee_no_tiers_set() {
     asset_client.mint(&sender, &2_000_000_000);
 
     let (contract_id, v2_client) = setup_v2(&env, &admin);
-    v2_client.set_treasury(&treasury);
-    v2_client.add_to_whitelist(&token_id);
-    v2_client.set_fee_bps(&200u32); // Default fee 2%
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(200u32)); // Default fee 2%
 
     // No tiers set, should apply default 2% fee regardless of amount
     let total_amount = 1_500_000_000;
@@ -2769,7 +3921,7 @@ fn test_create_stream_overflow_total_amount() {
     asset_client.mint(&sender, &overflow_amount);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     let stream_args = StreamArgs {
         sender: sender.clone(),
@@ -2792,7 +3944,9 @@ fn test_create_stream_overflow_total_amount() {
         yield_recipient: 0,
         split_address: None,
         split_bps: 0,
-        curve_type: 0,
+        curve_type: CurveType::Linear,
+        is_soulbound: false,
+        accrual_clock: 0,
     };
 
     let result = v2_client.try_create_stream(&stream_args);
@@ -2815,7 +3969,7 @@ fn test_create_stream_overflow_flow_rate() {
     asset_client.mint(&sender, &overflow_amount);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     let stream_args = StreamArgs {
         sender: sender.clone(),
@@ -2838,7 +3992,9 @@ fn test_create_stream_overflow_flow_rate() {
         yield_recipient: 0,
         split_address: None,
         split_bps: 0,
-        curve_type: 0,
+        curve_type: CurveType::Linear,
+        is_soulbound: false,
+        accrual_clock: 0,
     };
 
     let result = v2_client.try_create_stream(&stream_args);
@@ -2860,7 +4016,7 @@ fn test_propose_rate_overflow() {
     asset_client.mint(&sender, &total_amount);
 
     let (_, v2_client) = setup_v2(&env, &admin);
-    v2_client.add_to_whitelist(&token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
 
     let stream_args = StreamArgs {
         sender: sender.clone(),
@@ -2883,7 +4039,9 @@ fn test_propose_rate_overflow() {
         yield_recipient: 0,
         split_address: None,
         split_bps: 0,
-        curve_type: 0,
+        curve_type: CurveType::Linear,
+        is_soulbound: false,
+        accrual_clock: 0,
     };
 
     let sid = v2_client.create_stream(&stream_args);
@@ -2892,3 +4050,2828 @@ fn test_propose_rate_overflow() {
     let result = v2_client.try_propose_rate(&sid, &overflow_rate);
     assert_eq!(result, Err(Ok(Error::AmountOverflow)));
 }
+
+// ── Stream Amendments tests ──────────────────────────────────────────────────
+
+fn no_amendment() -> StreamAmendment {
+    StreamAmendment {
+        new_total_amount: None,
+        new_end_time: None,
+        new_cliff_time: None,
+        new_penalty_bps: None,
+        new_cancellation_type: None,
+    }
+}
+
+#[test]
+fn test_amendment_reduce_and_extend_preserves_vested() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+
+    let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+
+    // At t=30, 30M has vested. Reduce to 80M and extend to t=200.
+    env.ledger().with_mut(|li| li.timestamp = 30);
+    v2_client.propose_amendment(
+        &sid,
+        &StreamAmendment {
+            new_total_amount: Some(80_000_000),
+            new_end_time: Some(200),
+            new_penalty_bps: Some(500),
+            ..no_amendment()
+        },
+    );
+    assert!(v2_client.get_pending_amendment(&sid).is_some());
+
+    let stream = v2_client.accept_amendment(&sid);
+
+    // Vested 30M settled to the receiver; unvested 20M refunded to the sender.
+    assert_eq!(token_client.balance(&receiver), 30_000_000);
+    assert_eq!(token_client.balance(&sender), 20_000_000);
+    assert_eq!(stream.total_amount, 50_000_000);
+    assert_eq!(stream.withdrawn_amount, 0);
+    assert_eq!(stream.start_time, 30);
+    assert_eq!(stream.end_time, 200);
+    assert_eq!(stream.penalty_bps, 500);
+    assert!(v2_client.get_pending_amendment(&sid).is_none());
+
+    env.ledger().with_mut(|li| li.timestamp = 200);
+    v2_client.withdraw(&sid, &receiver);
+    assert_eq!(token_client.balance(&receiver), 80_000_000);
+}
+
+#[test]
+fn test_amendment_cannot_reduce_below_vested() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+
+    let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    let result = v2_client.try_propose_amendment(
+        &sid,
+        &StreamAmendment {
+            new_total_amount: Some(40_000_000),
+            ..no_amendment()
+        },
+    );
+    assert_eq!(result, Err(Ok(Error::InsufficientBalance)));
+
+    let result = v2_client.try_propose_amendment(
+        &sid,
+        &StreamAmendment {
+            new_total_amount: Some(120_000_000),
+            ..no_amendment()
+        },
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidNewRate)));
+
+    // Streams are only unilateral (0) or mutual (1) to cancel.
+    let result = v2_client.try_propose_amendment(
+        &sid,
+        &StreamAmendment {
+            new_cancellation_type: Some(2),
+            ..no_amendment()
+        },
+    );
+    assert_eq!(result, Err(Ok(Error::InvalidPenalty)));
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.cancellation_type = 2;
+    assert_eq!(
+        v2_client.try_create_stream(&args),
+        Err(Ok(Error::InvalidPenalty))
+    );
+}
+
+#[test]
+fn test_amendment_expires_and_can_be_cancelled() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.end_time = 1_000_000;
+    let sid = v2_client.create_stream(&args);
+
+    let amendment = StreamAmendment {
+        new_cliff_time: Some(500_000),
+        ..no_amendment()
+    };
+    v2_client.propose_amendment(&sid, &amendment);
+
+    // A second proposal is rejected while the first is live.
+    assert_eq!(
+        v2_client.try_propose_amendment(&sid, &amendment),
+        Err(Ok(Error::PendingUpdateExists))
+    );
+
+    v2_client.cancel_amendment(&sid, &receiver);
+    assert!(v2_client.get_pending_amendment(&sid).is_none());
+
+    v2_client.propose_amendment(&sid, &amendment);
+    env.ledger()
+        .with_mut(|li| li.timestamp = storage::RATE_UPDATE_TTL + 1);
+    assert!(v2_client.get_pending_amendment(&sid).is_none());
+    assert_eq!(
+        v2_client.try_accept_amendment(&sid),
+        Err(Ok(Error::ExpiredDeadline))
+    );
+    assert_eq!(v2_client.get_stream(&sid).unwrap().cliff_time, 0);
+}
+
+// ── Transferable Stream Positions tests ──────────────────────────────────────
+
+#[test]
+fn test_position_transfer_from_with_approvals_moves_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let operator = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &200_000_000);
+
+    let s0 = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    let s1 = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    assert_eq!(
+        v2_client.positions_of(&receiver, &0, &10),
+        vec![&env, s0, s1]
+    );
+
+    // Unapproved operators cannot move the position.
+    assert_eq!(
+        v2_client.try_transfer_from(&operator, &receiver, &buyer, &s0),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+
+    // Single-position approval is consumed by the transfer.
+    v2_client.approve(&s0, &Some(operator.clone()));
+    assert_eq!(v2_client.get_approved(&s0), Some(operator.clone()));
+    v2_client.transfer_from(&operator, &receiver, &buyer, &s0);
+    assert_eq!(v2_client.owner_of(&s0), buyer);
+    assert_eq!(v2_client.get_approved(&s0), None);
+
+    // Blanket approval covers every position of the owner.
+    v2_client.set_approval_for_all(&receiver, &operator, &true);
+    assert!(v2_client.is_approved_for_all(&receiver, &operator));
+    v2_client.transfer_from(&operator, &receiver, &buyer, &s1);
+
+    assert_eq!(v2_client.balance_of(&receiver), 0);
+    assert_eq!(v2_client.positions_of(&buyer, &0, &10), vec![&env, s0, s1]);
+}
+
+#[test]
+fn test_closed_positions_leave_owner_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let buyer = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &300_000_000);
+
+    let s0 = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    let s1 = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    let s2 = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    assert_eq!(v2_client.positions_of(&receiver, &1, &1), vec![&env, s1]);
+
+    // Cancelling moves the last position into the freed slot.
+    v2_client.cancel(&s1, &sender);
+    assert_eq!(v2_client.balance_of(&receiver), 2);
+    assert_eq!(
+        v2_client.positions_of(&receiver, &0, &10),
+        vec![&env, s0, s2]
+    );
+    assert_eq!(
+        v2_client.try_transfer_beneficiary(&s1, &buyer),
+        Err(Ok(Error::AlreadyCancelled))
+    );
+
+    v2_client.cancel(&s0, &sender);
+    assert_eq!(v2_client.positions_of(&receiver, &0, &10), vec![&env, s2]);
+    assert_eq!(v2_client.positions_of(&receiver, &5, &10), vec![&env]);
+}
+
+#[test]
+fn test_track_position_indexes_streams_created_before_the_index() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &200_000_000);
+
+    let legacy = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    let closed = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    v2_client.cancel(&closed, &sender);
+    // Created before positions were indexed.
+    env.as_contract(&v2_id, || {
+        storage::remove_owner_position(&env, &receiver, legacy);
+    });
+    assert_eq!(v2_client.balance_of(&receiver), 0);
+
+    v2_client.track_position(&legacy);
+    assert_eq!(
+        v2_client.positions_of(&receiver, &0, &10),
+        vec![&env, legacy]
+    );
+    assert_eq!(
+        v2_client.try_track_position(&legacy),
+        Err(Ok(Error::StreamNotMigratable))
+    );
+    assert_eq!(
+        v2_client.try_track_position(&closed),
+        Err(Ok(Error::StreamNotMigratable))
+    );
+}
+
+#[test]
+fn test_soulbound_position_cannot_be_transferred() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let other = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.is_soulbound = true;
+    let sid = v2_client.create_stream(&args);
+
+    assert!(v2_client.get_stream(&sid).unwrap().is_soulbound);
+    assert_eq!(
+        v2_client.try_transfer_beneficiary(&sid, &other),
+        Err(Ok(Error::StreamNotTransferable))
+    );
+    assert_eq!(
+        v2_client.try_approve(&sid, &Some(other.clone())),
+        Err(Ok(Error::StreamNotTransferable))
+    );
+    assert_eq!(v2_client.owner_of(&sid), receiver);
+}
+
+#[test]
+fn test_position_transfer_blocked_to_sanctioned_address() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+
+    let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+
+    let oracle_id = env.register(mock_oracle::MockOracle, ());
+    let oracle_client = mock_oracle::MockOracleClient::new(&env, &oracle_id);
+    let bad_actor = Address::generate(&env);
+    oracle_client.block(&bad_actor);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetOracleAddress(oracle_id.clone()),
+    );
+
+    assert_eq!(
+        v2_client.try_transfer_beneficiary(&sid, &bad_actor),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+    assert_eq!(v2_client.owner_of(&sid), receiver);
+}
+
+// ── Asset decimals normalization tests ──────────────────────────────────────
+
+#[contract]
+pub struct MockSixDecimalToken;
+
+#[contractimpl]
+impl MockSixDecimalToken {
+    pub fn decimals(_env: Env) -> u32 {
+        6
+    }
+}
+
+#[test]
+fn test_limits_and_dust_threshold_follow_token_decimals() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    let usdc_id = env.register(MockSixDecimalToken, ());
+    whitelist(&env, &v2_client, &admin, &usdc_id);
+
+    // 10 XLM-equivalent dust threshold and caps expressed with 6 decimals.
+    assert_eq!(v2_client.get_min_value(&usdc_id), 10_000_000);
+    let limits = v2_client.get_token_limits(&usdc_id);
+    assert_eq!(limits.max_stream_amount, MAX_STREAM_AMOUNT / 10);
+    assert_eq!(limits.max_flow_rate, MAX_FLOW_RATE / 10);
+
+    // 7-decimal SAC tokens keep the original stroop limits.
+    let token_admin = Address::generate(&env);
+    let (token_id, _, _) = create_token(&env, &token_admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    assert_eq!(v2_client.get_min_value(&token_id), 100_000_000);
+    assert_eq!(
+        v2_client.get_token_limits(&token_id).max_stream_amount,
+        MAX_STREAM_AMOUNT
+    );
+}
+
+#[test]
+fn test_set_token_limits_overrides_caps() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &1_000_000_000);
+
+    assert_eq!(
+        v2_client.try_schedule_op(
+            &vec![&env, admin.clone()],
+            &Operation::SetTokenLimits(token_id.clone(), 0, 1)
+        ),
+        Err(Ok(Error::AmountOverflow))
+    );
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTokenLimits(token_id.clone(), 500_000_000, MAX_FLOW_RATE),
+    );
+
+    let result =
+        v2_client.try_create_stream(&stream_args(&sender, &receiver, &token_id, 600_000_000));
+    assert_eq!(result, Err(Ok(Error::AmountOverflow)));
+
+    v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 500_000_000));
+}
+
+// ── Ledger-sequence accrual clock tests ─────────────────────────────────────
+
+#[test]
+fn test_ledger_clock_stream_accrues_per_ledger() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| {
+        li.sequence_number = 1_000;
+        li.timestamp = 5_000;
+    });
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+
+    // Ledgers 1_000 → 1_100.
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.start_time = 1_000;
+    args.cliff_time = 1_000;
+    args.end_time = 1_100;
+    args.accrual_clock = 1;
+    let sid = v2_client.create_stream(&args);
+
+    // Timestamp drift has no effect; only the ledger sequence matters.
+    env.ledger().with_mut(|li| {
+        li.sequence_number = 1_025;
+        li.timestamp = 999_999;
+    });
+    v2_client.withdraw(&sid, &receiver);
+    assert_eq!(token_client.balance(&receiver), 25_000_000);
+
+    assert_eq!(
+        v2_client.predict_balance_at_ledger(&sid, &1_050),
+        50_000_000
+    );
+    // 375 seconds ahead ≈ 75 ledgers at LEDGER_CLOSE_SECS.
+    assert_eq!(
+        v2_client.predict_balance_at(&sid, &(999_999 + 375)),
+        100_000_000
+    );
+    assert_eq!(
+        v2_client.predict_balance_at(&sid, &(999_999 + 125)),
+        50_000_000
+    );
+}
+
+#[test]
+fn test_simulation_and_creation_reject_unknown_clock() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.accrual_clock = 1;
+    assert!(v2_client.simulate_stream_creation(&args).would_succeed);
+
+    args.accrual_clock = 2;
+    let report = v2_client.simulate_stream_creation(&args);
+    assert!(!report.params_check.passed);
+    assert_eq!(
+        v2_client.try_create_stream(&args),
+        Err(Ok(Error::InvalidTimeRange))
+    );
+}
+
+// ── Unlock curve tests ──────────────────────────────────────────────────────
+
+#[test]
+fn test_piecewise_curve_follows_schedule() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &300_000_000);
+
+    // Half unlocks over the first quarter, the rest over the remainder.
+    let points = soroban_sdk::vec![
+        &env,
+        CurvePoint {
+            offset: 25,
+            bps: 5_000
+        },
+        CurvePoint {
+            offset: 100,
+            bps: 10_000
+        },
+    ];
+    let mut args = stream_args(&sender, &receiver, &token_id, 300_000_000);
+    args.curve_type = CurveType::Piecewise(points.clone());
+    let sid = v2_client.create_stream(&args);
+
+    assert_eq!(
+        v2_client.get_stream(&sid).unwrap().curve_type,
+        CurveType::Piecewise(points)
+    );
+    assert_eq!(v2_client.predict_balance_at(&sid, &10), 60_000_000);
+    assert_eq!(v2_client.predict_balance_at(&sid, &25), 150_000_000);
+    assert_eq!(v2_client.predict_balance_at(&sid, &50), 200_000_000);
+    assert_eq!(v2_client.predict_balance_at(&sid, &100), 300_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 25);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 150_000_000);
+    assert_eq!(token_client.balance(&receiver), 150_000_000);
+}
+
+#[test]
+fn test_piecewise_stream_rejects_reshaping() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &200_000_000);
+
+    let points = soroban_sdk::vec![
+        &env,
+        CurvePoint {
+            offset: 25,
+            bps: 5_000
+        },
+        CurvePoint {
+            offset: 100,
+            bps: 10_000
+        },
+    ];
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.curve_type = CurveType::Piecewise(points);
+    let sid = v2_client.create_stream(&args);
+
+    env.ledger().with_mut(|li| li.timestamp = 10);
+    assert_eq!(
+        v2_client.try_top_up(&sid, &sender, &10_000_000),
+        Err(Ok(Error::InvalidTimeRange))
+    );
+    assert_eq!(
+        v2_client.try_propose_rate(&sid, &2_000_000),
+        Err(Ok(Error::InvalidTimeRange))
+    );
+    for amendment in [
+        StreamAmendment {
+            new_end_time: Some(200),
+            ..no_amendment()
+        },
+        StreamAmendment {
+            new_cliff_time: Some(20),
+            ..no_amendment()
+        },
+    ] {
+        assert_eq!(
+            v2_client.try_propose_amendment(&sid, &amendment),
+            Err(Ok(Error::InvalidTimeRange))
+        );
+    }
+
+    // Terms can still change; the schedule is untouched.
+    v2_client.propose_amendment(
+        &sid,
+        &StreamAmendment {
+            new_penalty_bps: Some(500),
+            ..no_amendment()
+        },
+    );
+    let stream = v2_client.accept_amendment(&sid);
+    assert_eq!((stream.start_time, stream.end_time), (0, 100));
+    assert_eq!(v2_client.predict_balance_at(&sid, &25), 50_000_000);
+}
+
+#[test]
+fn test_piecewise_refill_requires_matching_cycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &400_000_000);
+    TokenClient::new(&env, &token_id).approve(&sender, &v2_id, &400_000_000, &1_000);
+
+    let points = soroban_sdk::vec![
+        &env,
+        CurvePoint {
+            offset: 25,
+            bps: 5_000
+        },
+        CurvePoint {
+            offset: 100,
+            bps: 10_000
+        },
+    ];
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.curve_type = CurveType::Piecewise(points);
+    args.is_recurrent = true;
+    args.cycle_duration = 100;
+    let matching = v2_client.create_stream(&args);
+    args.cycle_duration = 50;
+    let shorter = v2_client.create_stream(&args);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    v2_client.refill_stream(&matching);
+    let stream = v2_client.get_stream(&matching).unwrap();
+    assert_eq!((stream.start_time, stream.end_time), (100, 200));
+    assert_eq!(v2_client.predict_balance_at(&matching, &125), 50_000_000);
+
+    assert_eq!(
+        v2_client.try_refill_stream(&shorter),
+        Err(Ok(Error::InvalidTimeRange))
+    );
+}
+
+#[test]
+fn test_front_and_back_loaded_curves() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &400_000_000);
+
+    let mut at_quarter = Vec::new(&env);
+    for curve in [
+        CurveType::Cubic,
+        CurveType::Linear,
+        CurveType::Logarithmic,
+        CurveType::SquareRoot,
+    ] {
+        let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+        args.curve_type = curve;
+        let sid = v2_client.create_stream(&args);
+        at_quarter.push_back(v2_client.predict_balance_at(&sid, &25));
+        assert_eq!(v2_client.predict_balance_at(&sid, &100), 100_000_000);
+    }
+
+    assert_eq!(at_quarter.get(0).unwrap(), 1_562_500);
+    assert_eq!(at_quarter.get(1).unwrap(), 25_000_000);
+    // log2(1 + 15 / 4) / 4 ≈ 56.2%
+    assert!((56_000_000..56_300_000).contains(&at_quarter.get(2).unwrap()));
+    assert_eq!(at_quarter.get(3).unwrap(), 50_000_000);
+}
+
+#[test]
+fn test_invalid_piecewise_schedule_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+
+    let point = |offset, bps| CurvePoint { offset, bps };
+    let schedules = [
+        Vec::new(&env),
+        // Does not reach the stream end.
+        soroban_sdk::vec![&env, point(50, 10_000)],
+        // Cumulative share decreases.
+        soroban_sdk::vec![&env, point(50, 6_000), point(100, 5_000)],
+        // Ends below 100%.
+        soroban_sdk::vec![&env, point(50, 2_000), point(100, 9_000)],
+    ];
+    for points in schedules {
+        let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+        args.curve_type = CurveType::Piecewise(points);
+        assert!(!v2_client.simulate_stream_creation(&args).would_succeed);
+        assert_eq!(
+            v2_client.try_create_stream(&args),
+            Err(Ok(Error::InvalidTimeRange))
+        );
+    }
+}
+
+// ── Stream Templates tests ──────────────────────────────────────────────────
+
+#[test]
+fn test_create_stream_from_template_records_version() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &200_000_000);
+
+    // 10s cliff, 100s duration, relative to the skeleton's own start.
+    let mut skeleton = stream_args(&admin, &admin, &token_id, 0);
+    skeleton.start_time = 50;
+    skeleton.cliff_time = 60;
+    skeleton.end_time = 150;
+    skeleton.penalty_bps = 500;
+    let template_id = v2_client.register_template(&admin, &symbol_short!("vest"), &skeleton);
+
+    let sid = v2_client.create_stream_from_template(
+        &template_id,
+        &sender,
+        &receiver,
+        &100_000_000,
+        &2_000,
+        &1,
+    );
+    let stream = v2_client.get_stream(&sid).unwrap();
+    assert_eq!(stream.sender, sender);
+    assert_eq!(stream.receiver, receiver);
+    assert_eq!(stream.start_time, 2_000);
+    assert_eq!(stream.cliff_time, 2_010);
+    assert_eq!(stream.end_time, 2_100);
+    assert_eq!(stream.penalty_bps, 500);
+    assert_eq!(
+        v2_client.get_stream_template(&sid),
+        Some(StreamTemplateRef {
+            template_id,
+            version: 1
+        })
+    );
+
+    skeleton.end_time = 250;
+    assert_eq!(v2_client.update_template(&template_id, &skeleton), 2);
+    // A caller still expecting version 1 is refused.
+    assert_eq!(
+        v2_client.try_create_stream_from_template(
+            &template_id,
+            &sender,
+            &receiver,
+            &100_000_000,
+            &2_000,
+            &1,
+        ),
+        Err(Ok(Error::InvalidNonce))
+    );
+    let sid2 = v2_client.create_stream_from_template(
+        &template_id,
+        &sender,
+        &receiver,
+        &100_000_000,
+        &2_000,
+        &2,
+    );
+    assert_eq!(v2_client.get_stream(&sid2).unwrap().end_time, 2_200);
+    assert_eq!(v2_client.get_stream_template(&sid2).unwrap().version, 2);
+
+    // Existing streams keep pointing at the version they were built from.
+    assert_eq!(v2_client.get_stream_template(&sid).unwrap().version, 1);
+    let v1 = v2_client.get_template_version(&template_id, &1).unwrap();
+    assert_eq!(v1.skeleton.end_time, 150);
+    assert_eq!(v2_client.get_template(&template_id).unwrap().version, 2);
+}
+
+#[test]
+fn test_template_validation_and_missing_template() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, _) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+
+    let mut skeleton = stream_args(&sender, &sender, &token_id, 0);
+    assert_eq!(
+        v2_client.try_register_template(&sender, &symbol_short!("payroll"), &skeleton),
+        Err(Ok(Error::AssetNotWhitelisted))
+    );
+
+    whitelist(&env, &v2_client, &admin, &token_id);
+    skeleton.cliff_time = skeleton.end_time + 1;
+    assert_eq!(
+        v2_client.try_register_template(&sender, &symbol_short!("payroll"), &skeleton),
+        Err(Ok(Error::InvalidTimeRange))
+    );
+
+    assert_eq!(
+        v2_client.try_create_stream_from_template(&7, &sender, &sender, &1_000, &0, &1),
+        Err(Ok(Error::StreamNotFound))
+    );
+    assert_eq!(
+        v2_client.try_update_template(&7, &stream_args(&sender, &sender, &token_id, 0)),
+        Err(Ok(Error::StreamNotFound))
+    );
+}
+
+// ── Affiliate fee sharing tests ─────────────────────────────────────────────
+
+#[test]
+fn test_affiliate_share_of_protocol_fee() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let affiliate = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    asset_client.mint(&sender, &1_000_000_000);
+
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(100u32)); // 1%
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetAffiliateShareBps(2_500u32),
+    ); // 25% of the fee
+
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.affiliate = Some(affiliate.clone());
+
+    // Registered but not yet approved: treasury keeps the whole fee.
+    v2_client.register_affiliate(&affiliate);
+    v2_client.create_stream(&args);
+    assert_eq!(v2_client.get_pending_fees(&treasury, &token_id), 1_000_000);
+    assert_eq!(v2_client.get_affiliate_fees(&affiliate, &token_id), 0);
+
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetAffiliateApproved(affiliate.clone(), true),
+    );
+    assert!(v2_client.is_affiliate_approved(&affiliate));
+    v2_client.create_stream(&args);
+    v2_client.create_batch_streams(&vec![&env, args.clone()]);
+    assert_eq!(v2_client.get_pending_fees(&treasury, &token_id), 2_500_000);
+    assert_eq!(v2_client.get_affiliate_fees(&affiliate, &token_id), 500_000);
+    assert_eq!(v2_client.get_pending_fees(&affiliate, &token_id), 0);
+
+    // Treasury balances are not reachable through the affiliate path.
+    assert_eq!(
+        v2_client.try_withdraw_affiliate_fees(&treasury, &token_id),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+
+    assert_eq!(
+        v2_client.withdraw_affiliate_fees(&affiliate, &token_id),
+        500_000
+    );
+    assert_eq!(token_client.balance(&affiliate), 500_000);
+    assert_eq!(
+        v2_client.try_withdraw_affiliate_fees(&affiliate, &token_id),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+}
+
+#[test]
+fn test_affiliate_registration_rules() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let affiliate = Address::generate(&env);
+    let (_, v2_client) = setup_v2(&env, &admin);
+
+    assert_eq!(
+        v2_client.try_schedule_op(
+            &vec![&env, admin.clone()],
+            &Operation::SetAffiliateShareBps(10_001u32)
+        ),
+        Err(Ok(Error::FeeTooHigh))
+    );
+
+    v2_client.register_affiliate(&affiliate);
+    assert!(!v2_client.is_affiliate_approved(&affiliate));
+    assert_eq!(
+        v2_client.try_register_affiliate(&affiliate),
+        Err(Ok(Error::AlreadyApproved))
+    );
+
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetAffiliateApproved(affiliate.clone(), true),
+    );
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetAffiliateApproved(affiliate.clone(), false),
+    );
+    assert!(!v2_client.is_affiliate_approved(&affiliate));
+}
+
+// ── Timelocked admin operations tests ───────────────────────────────────────
+
+#[test]
+fn test_schedule_list_and_cancel_ops() {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let (_, client) = setup_v2(&env, &admin);
+    let signers = vec![&env, admin.clone()];
+
+    let fee_op = Operation::SetFeeBps(100);
+    let treasury_op = Operation::SetTreasury(treasury.clone());
+    client.schedule_op(&signers, &fee_op);
+    client.schedule_op(&signers, &treasury_op);
+
+    let pending = client.get_pending_ops();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending.get(0).unwrap().op, fee_op);
+    assert_eq!(
+        pending.get(0).unwrap().execution_time,
+        1_000 + storage::ADMIN_DELAY
+    );
+
+    // Nothing takes effect before the delay.
+    assert_eq!(
+        client.try_execute_op(&fee_op),
+        Err(Ok(Error::NotExecutionTime))
+    );
+    assert_eq!(client.get_fee_bps(), 0);
+
+    client.cancel_op(&signers, &fee_op);
+    assert_eq!(client.get_pending_ops().len(), 1);
+    assert_eq!(
+        client.try_cancel_op(&signers, &fee_op),
+        Err(Ok(Error::OpNotScheduled))
+    );
+
+    env.ledger()
+        .with_mut(|li| li.timestamp = 1_000 + storage::ADMIN_DELAY);
+    assert_eq!(
+        client.try_execute_op(&fee_op),
+        Err(Ok(Error::OpNotScheduled))
+    );
+    client.execute_op(&treasury_op);
+    assert_eq!(client.get_treasury(), Some(treasury));
+    assert_eq!(client.get_pending_ops().len(), 0);
+}
+
+#[test]
+fn test_schedule_op_requires_admin_threshold() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let a2 = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let (_, client) = setup_v2(&env, &admin);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetAdmins(vec![&env, admin.clone(), a2.clone()], 2),
+    );
+
+    let op = Operation::SetFeeBps(50);
+    assert_eq!(
+        client.try_schedule_op(&vec![&env, admin.clone()], &op),
+        Err(Ok(Error::NotEnoughSigners))
+    );
+    assert_eq!(
+        client.try_schedule_op(&vec![&env, admin.clone(), outsider], &op),
+        Err(Ok(Error::NotEnoughSigners))
+    );
+    // The same admin listed twice counts once.
+    assert_eq!(
+        client.try_schedule_op(&vec![&env, admin.clone(), admin.clone()], &op),
+        Err(Ok(Error::NotEnoughSigners))
+    );
+    assert_eq!(
+        client.try_schedule_op(
+            &vec![&env, admin.clone(), a2.clone()],
+            &Operation::SetFeeBps(MAX_FEE_BPS + 1)
+        ),
+        Err(Ok(Error::FeeTooHigh))
+    );
+    client.schedule_op(&vec![&env, admin.clone(), a2.clone()], &op);
+    assert_eq!(client.get_pending_ops().len(), 1);
+}
+
+#[test]
+fn test_guardian_can_only_pause() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let guardian = Address::generate(&env);
+    let (_, client) = setup_v2(&env, &admin);
+
+    assert_eq!(
+        client.try_guardian_pause(&guardian),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetGuardian(Some(guardian.clone())),
+    );
+    assert_eq!(client.get_guardian(), Some(guardian.clone()));
+
+    client.guardian_pause(&guardian);
+    assert!(client.is_paused());
+
+    // Unpausing remains an admin action.
+    client.unpause();
+    assert!(!client.is_paused());
+}
+
+// ── Ledger footprint simulation tests ───────────────────────────────────────
+
+#[test]
+fn test_creation_footprint_reflects_stream_settings() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &300_000_000);
+
+    let args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    let plain = v2_client.simulate_stream_creation(&args).footprint;
+    assert!(plain.persistent_bytes > 0);
+    assert!(plain.event_bytes > 0);
+    assert_eq!(plain.rent_ledgers, storage::STREAM_TTL_BUMP);
+    assert!(plain.rent_fee > 0);
+    // instance, 2 balances, stream, 3 position index entries, 2 first-seen users
+    assert_eq!(plain.estimated_writes, 9);
+
+    let mut split = args.clone();
+    split.split_address = Some(Address::generate(&env));
+    split.split_bps = 1_000;
+    let split_fp = v2_client.simulate_stream_creation(&split).footprint;
+    assert!(split_fp.persistent_bytes > plain.persistent_bytes);
+    assert!(split_fp.rent_fee >= plain.rent_fee);
+
+    // Once both parties are known, their flags are only read.
+    v2_client.create_stream(&args);
+    let repeat = v2_client.simulate_stream_creation(&args).footprint;
+    assert_eq!(repeat.estimated_writes, 7);
+    assert!(repeat.persistent_bytes < plain.persistent_bytes);
+}
+
+#[test]
+fn test_batch_and_withdraw_footprints() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &300_000_000);
+
+    let args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    let single = v2_client.simulate_stream_creation(&args).footprint;
+    let batch = v2_client.estimate_batch_footprint(&vec![&env, args.clone(), args.clone()]);
+    // An extra stream entry and position slot; the position count and user
+    // flags are shared.
+    assert_eq!(batch.estimated_writes, single.estimated_writes + 3);
+    assert!(batch.event_bytes > 2 * single.event_bytes);
+
+    let sid = v2_client.create_stream(&args);
+    let withdraw = v2_client.estimate_withdraw_footprint(&sid);
+    // instance, stream, contract and beneficiary balances
+    assert_eq!(withdraw.estimated_writes, 4);
+    assert!(withdraw.persistent_bytes > 0 && withdraw.rent_fee > 0);
+    assert_eq!(
+        v2_client.try_estimate_withdraw_footprint(&99),
+        Err(Ok(Error::StreamNotFound))
+    );
+}
+
+// ── Bridge-in receiver hook tests ───────────────────────────────────────────
+
+fn bridge_metadata(
+    env: &Env,
+    receiver: &Address,
+    duration: u64,
+    cliff: u64,
+    curve: u8,
+    memo: &[u8],
+) -> Bytes {
+    let receiver_xdr = receiver.clone().to_xdr(env);
+    let mut metadata = Bytes::from_slice(env, &[BRIDGE_METADATA_VERSION]);
+    metadata.extend_from_slice(&(receiver_xdr.len() as u16).to_be_bytes());
+    metadata.append(&receiver_xdr);
+    metadata.extend_from_slice(&duration.to_be_bytes());
+    metadata.extend_from_slice(&cliff.to_be_bytes());
+    metadata.extend_from_slice(&[curve, memo.len() as u8]);
+    metadata.extend_from_slice(memo);
+    metadata
+}
+
+#[test]
+fn test_bridge_in_creates_stream_from_metadata() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let bridge = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    asset_client.mint(&bridge, &1_000_000_000);
+
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+
+    let metadata = bridge_metadata(&env, &receiver, 1_000, 200, 1, b"order_42");
+    assert_eq!(
+        v2_client.try_on_token_receive(&bridge, &token_id, &100_000_000, &metadata),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetBridgeAllowed(bridge.clone(), true),
+    );
+    assert!(v2_client.is_bridge_allowed(&bridge));
+
+    let now = env.ledger().timestamp();
+    let receipt = v2_client.on_token_receive(&bridge, &token_id, &100_000_000, &metadata);
+    assert_eq!(receipt.deposit_id, None);
+    let stream = v2_client.get_stream(&receipt.stream_id.unwrap()).unwrap();
+    assert_eq!(stream.sender, bridge);
+    assert_eq!(stream.receiver, receiver);
+    assert_eq!(stream.cliff_time, now + 200);
+    assert_eq!(stream.end_time, now + 1_000);
+    assert_eq!(stream.curve_type, CurveType::Exponential);
+    assert_eq!(token_client.balance(&v2_id), 100_000_000);
+    assert_eq!(token_client.balance(&bridge), 900_000_000);
+}
+
+#[test]
+fn test_bridge_in_invalid_metadata_is_refundable() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let bridge = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let refund_to = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    asset_client.mint(&bridge, &1_000_000_000);
+
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetBridgeAllowed(bridge.clone(), true),
+    );
+
+    // Cliff past the end, a bad memo character and the legacy strkey layout
+    // are all held rather than streamed.
+    let bad = [
+        bridge_metadata(&env, &receiver, 100, 101, 0, b""),
+        bridge_metadata(&env, &receiver, 100, 0, 0, b"no-dash"),
+        Bytes::from_slice(&env, &[b'G'; 64]),
+    ];
+    for (i, metadata) in bad.iter().enumerate() {
+        let receipt = v2_client.on_token_receive(&bridge, &token_id, &10_000_000, &metadata);
+        assert_eq!(receipt.stream_id, None);
+        assert_eq!(receipt.deposit_id, Some(i as u64));
+    }
+    assert_eq!(token_client.balance(&v2_id), 30_000_000);
+    assert!(v2_client.get_stream(&0).is_none());
+
+    let deposit = v2_client.get_bridge_deposit(&1).unwrap();
+    assert_eq!(deposit.bridge, bridge);
+    assert_eq!(deposit.amount, 10_000_000);
+
+    assert_eq!(v2_client.refund_bridge_deposit(&1, &refund_to), 10_000_000);
+    assert_eq!(token_client.balance(&refund_to), 10_000_000);
+    assert_eq!(v2_client.get_bridge_deposit(&1), None);
+    assert_eq!(
+        v2_client.try_refund_bridge_deposit(&1, &refund_to),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+}
+
+// ── Treasury governance proposal tests ──────────────────────────────────────
+
+/// Deploy a DAO token, register it and escrow `locked` for each voter.
+fn setup_governance(
+    env: &Env,
+    client: &ContractClient,
+    admin: &Address,
+    voters: &[(&Address, i128)],
+) -> Address {
+    let dao_admin = Address::generate(env);
+    let (dao_token, _, dao_asset) = create_token(env, &dao_admin);
+    run_op(
+        env,
+        client,
+        admin,
+        Operation::SetDaoToken(dao_token.clone()),
+    );
+    for (voter, locked) in voters {
+        dao_asset.mint(voter, locked);
+        client.lock_voting_tokens(voter, locked);
+    }
+    env.ledger().with_mut(|li| li.timestamp += 1);
+    dao_token
+}
+
+#[test]
+fn test_treasury_proposal_passes_and_queues_split() {
+    let env = Env::default();
+    // The treasury authorizes the payout nested under execute_treasury_split.
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let admin = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let carol = Address::generate(&env);
+    let grantee = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &admin);
+    asset_client.mint(&treasury, &1_000);
+
+    let (_, client) = setup_v2(&env, &admin);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    run_op(&env, &client, &admin, Operation::SetVotingThreshold(100));
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetGovernanceConfig(GovernanceConfig {
+            quorum: 500,
+            voting_period: 1_000,
+        }),
+    );
+    setup_governance(
+        &env,
+        &client,
+        &admin,
+        &[(&alice, 300), (&bob, 200), (&carol, 50)],
+    );
+
+    // Below the proposal threshold.
+    assert_eq!(
+        client.try_propose_treasury_split(
+            &carol,
+            &token_id,
+            &vec![&env, grantee.clone()],
+            &vec![&env, 400i128]
+        ),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+
+    let id = client.propose_treasury_split(
+        &alice,
+        &token_id,
+        &vec![&env, grantee.clone()],
+        &vec![&env, 400i128],
+    );
+    assert_eq!(client.vote_on_proposal(&alice, &id, &VoteChoice::For), 300);
+    assert_eq!(
+        client.vote_on_proposal(&bob, &id, &VoteChoice::Against),
+        200
+    );
+    client.vote_on_proposal(&carol, &id, &VoteChoice::Abstain);
+    assert_eq!(
+        client.try_vote_on_proposal(&bob, &id, &VoteChoice::For),
+        Err(Ok(Error::AlreadyApproved))
+    );
+
+    // Votes keep the escrow locked until the proposal closes.
+    assert_eq!(
+        client.try_unlock_voting_tokens(&bob),
+        Err(Ok(Error::NotExecutionTime))
+    );
+    assert_eq!(
+        client.try_finalize_proposal(&id),
+        Err(Ok(Error::NotExecutionTime))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp += 1_000);
+    assert_eq!(client.finalize_proposal(&id), ProposalStatus::Queued);
+    assert_eq!(client.unlock_voting_tokens(&bob), 200);
+
+    let proposal = client.get_proposal(&id).unwrap();
+    assert_eq!(
+        (
+            proposal.for_votes,
+            proposal.against_votes,
+            proposal.abstain_votes
+        ),
+        (300, 200, 50)
+    );
+    let split_id = proposal.split_id.unwrap();
+    assert_eq!(
+        client.try_execute_treasury_split(&grantee, &split_id),
+        Err(Ok(Error::NotExecutionTime))
+    );
+    env.ledger()
+        .with_mut(|li| li.timestamp += storage::ADMIN_DELAY);
+    client.execute_treasury_split(&grantee, &split_id);
+    assert_eq!(token_client.balance(&grantee), 400);
+}
+
+#[test]
+fn test_treasury_proposal_snapshot_and_quorum() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let latecomer = Address::generate(&env);
+    let grantee = Address::generate(&env);
+    let (token_id, _, _) = create_token(&env, &admin);
+
+    let (_, client) = setup_v2(&env, &admin);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetGovernanceConfig(GovernanceConfig {
+            quorum: 1_000,
+            voting_period: 1_000,
+        }),
+    );
+    let dao_token = setup_governance(&env, &client, &admin, &[(&alice, 300)]);
+
+    let id = client.propose_treasury_split(
+        &alice,
+        &token_id,
+        &vec![&env, grantee.clone()],
+        &vec![&env, 400i128],
+    );
+
+    // Tokens locked at or after the snapshot carry no weight.
+    soroban_sdk::token::StellarAssetClient::new(&env, &dao_token).mint(&latecomer, &5_000);
+    client.lock_voting_tokens(&latecomer, &5_000);
+    assert_eq!(
+        client.try_vote_on_proposal(&latecomer, &id, &VoteChoice::For),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+
+    client.vote_on_proposal(&alice, &id, &VoteChoice::For);
+    env.ledger().with_mut(|li| li.timestamp += 1_000);
+    assert_eq!(
+        client.try_vote_on_proposal(&alice, &id, &VoteChoice::For),
+        Err(Ok(Error::NotExecutionTime))
+    );
+    assert_eq!(client.finalize_proposal(&id), ProposalStatus::Defeated);
+    assert_eq!(client.get_proposal(&id).unwrap().split_id, None);
+}
+
+// ── Treasury split veto tests ───────────────────────────────────────────────
+
+#[test]
+fn test_council_veto_cancels_treasury_split() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let c1 = Address::generate(&env);
+    let c2 = Address::generate(&env);
+    let grantee = Address::generate(&env);
+    let (token_id, _, _) = create_token(&env, &admin);
+
+    let (_, client) = setup_v2(&env, &admin);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetRecoveryCouncil(vec![&env, c1.clone(), c2.clone()], 2),
+    );
+    let split_id = client.initiate_treasury_split(
+        &admin,
+        &token_id,
+        &vec![&env, grantee.clone()],
+        &vec![&env, 100i128],
+    );
+
+    let tally = client.veto_treasury_split(&c1, &split_id);
+    assert_eq!(tally.council_votes, 1);
+    let pending = client.get_pending_treasury_splits(&0, &10);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending.get(0).unwrap().veto, tally);
+    assert_eq!(
+        client.try_veto_treasury_split(&c1, &split_id),
+        Err(Ok(Error::AlreadyApproved))
+    );
+
+    client.veto_treasury_split(&c2, &split_id);
+    assert!(client.get_pending_treasury_splits(&0, &10).is_empty());
+
+    env.ledger()
+        .with_mut(|li| li.timestamp += storage::ADMIN_DELAY);
+    assert_eq!(
+        client.try_execute_treasury_split(&admin, &split_id),
+        Err(Ok(Error::AlreadyCancelled))
+    );
+}
+
+#[test]
+fn test_token_holder_veto_threshold_and_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let grantee = Address::generate(&env);
+    let (token_id, _, _) = create_token(&env, &admin);
+
+    let (_, client) = setup_v2(&env, &admin);
+    run_op(&env, &client, &admin, Operation::SetVetoThreshold(250));
+    setup_governance(&env, &client, &admin, &[(&alice, 200), (&bob, 100)]);
+
+    let first = client.initiate_treasury_split(
+        &admin,
+        &token_id,
+        &vec![&env, grantee.clone()],
+        &vec![&env, 100i128],
+    );
+    let second = client.initiate_treasury_split(
+        &admin,
+        &token_id,
+        &vec![&env, grantee.clone()],
+        &vec![&env, 50i128],
+    );
+
+    assert_eq!(client.veto_treasury_split(&alice, &first).weight, 200);
+    assert_eq!(
+        client.try_veto_treasury_split(&outsider, &first),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+    assert_eq!(client.get_pending_treasury_splits(&0, &10).len(), 2);
+
+    client.veto_treasury_split(&bob, &first);
+    let pending = client.get_pending_treasury_splits(&0, &10);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending.get(0).unwrap().split_id, second);
+    assert_eq!(client.get_treasury_split_count(), 2);
+    assert!(client.get_pending_treasury_splits(&0, &1).is_empty());
+    assert_eq!(client.get_pending_treasury_splits(&1, &1), pending);
+
+    // Opposition after the window closes is too late.
+    env.ledger()
+        .with_mut(|li| li.timestamp += storage::ADMIN_DELAY);
+    assert_eq!(
+        client.try_veto_treasury_split(&alice, &second),
+        Err(Ok(Error::NotExecutionTime))
+    );
+}
+
+// ── Emergency recovery tests ────────────────────────────────────────────────
+
+#[test]
+fn test_recovery_accumulates_approvals_and_spares_stream_escrow() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let destination = Address::generate(&env);
+    let (c1, c2, c3) = (
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    );
+    let (token_id, token_client, asset_client) = create_token(&env, &admin);
+    asset_client.mint(&sender, &100_000_000);
+
+    let (v2_id, client) = setup_v2(&env, &admin);
+    whitelist(&env, &client, &admin, &token_id);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetRecoveryCouncil(vec![&env, c1.clone(), c2.clone(), c3.clone()], 2),
+    );
+    client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    // Stray tokens sent straight to the contract are the only recoverable funds.
+    asset_client.mint(&v2_id, &5_000);
+
+    client.init_recovery(&c1, &token_id, &destination);
+    assert_eq!(
+        client.try_recovery_split(&c3),
+        Err(Ok(Error::NotExecutionTime))
+    );
+    env.ledger()
+        .with_mut(|li| li.timestamp += storage::RECOVERY_GRACE_PERIOD);
+    assert_eq!(
+        client.try_recovery_split(&c3),
+        Err(Ok(Error::NotEnoughSigners))
+    );
+
+    assert_eq!(client.add_recovery_approval(&c2), 2);
+    assert_eq!(
+        client.try_add_recovery_approval(&c1),
+        Err(Ok(Error::AlreadyApproved))
+    );
+    assert_eq!(client.get_recovery_state().unwrap().approvals.len(), 2);
+
+    // The surplus comes from a completed, recent solvency pass.
+    assert_eq!(
+        client.try_recovery_split(&c3),
+        Err(Ok(Error::NotExecutionTime))
+    );
+    client.check_solvency(&token_id, &MAX_SOLVENCY_PAGE);
+    // Tokens arriving after the pass are left for the next one.
+    asset_client.mint(&v2_id, &1_000);
+
+    assert_eq!(client.recovery_split(&c3), 5_000);
+    assert_eq!(token_client.balance(&destination), 5_000);
+    assert_eq!(token_client.balance(&v2_id), 100_001_000);
+    assert_eq!(client.get_recovery_state(), None);
+}
+
+#[test]
+fn test_recovery_spares_pending_fees_and_voter_locks() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let voter = Address::generate(&env);
+    let destination = Address::generate(&env);
+    let council = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &admin);
+    asset_client.mint(&sender, &100_000_000);
+
+    let (v2_id, client) = setup_v2(&env, &admin);
+    whitelist(&env, &client, &admin, &token_id);
+    run_op(&env, &client, &admin, Operation::SetTreasury(treasury));
+    run_op(&env, &client, &admin, Operation::SetFeeBps(100u32));
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetRecoveryCouncil(vec![&env, council.clone()], 1),
+    );
+    client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    asset_client.mint(&v2_id, &5_000);
+    // 99M of stream escrow plus 1M of pending treasury fees.
+    assert_eq!(
+        client.check_balance_integrity(&token_id),
+        (100_005_000, 100_000_000)
+    );
+
+    client.init_recovery(&council, &token_id, &destination);
+    env.ledger()
+        .with_mut(|li| li.timestamp += storage::RECOVERY_GRACE_PERIOD);
+    client.check_solvency(&token_id, &MAX_SOLVENCY_PAGE);
+    assert_eq!(client.recovery_split(&council), 5_000);
+    assert_eq!(token_client.balance(&v2_id), 100_000_000);
+
+    // Escrowed voting tokens are not surplus either.
+    let dao_token = setup_governance(&env, &client, &admin, &[(&voter, 500)]);
+    let dao_client = TokenClient::new(&env, &dao_token);
+    soroban_sdk::token::StellarAssetClient::new(&env, &dao_token).mint(&v2_id, &300);
+    client.init_recovery(&council, &dao_token, &destination);
+    env.ledger()
+        .with_mut(|li| li.timestamp += storage::RECOVERY_GRACE_PERIOD);
+    client.check_solvency(&dao_token, &MAX_SOLVENCY_PAGE);
+    assert_eq!(client.recovery_split(&council), 300);
+    assert_eq!(dao_client.balance(&v2_id), 500);
+    assert_eq!(client.unlock_voting_tokens(&voter), 500);
+    assert_eq!(client.check_balance_integrity(&dao_token), (0, 0));
+}
+
+#[test]
+fn test_track_held_balance_counts_legacy_balances() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &admin);
+    let (v2_id, client) = setup_v2(&env, &admin);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetTreasury(treasury.clone()),
+    );
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetFeeToken(token_id.clone()),
+    );
+    asset_client.mint(&sender, &1_000_000);
+    client.deposit_gas_buffer(&sender, &1_000_000);
+
+    // Fees credited before held balances were tracked.
+    let credit_legacy_fees = || {
+        asset_client.mint(&v2_id, &2_000_000);
+        env.as_contract(&v2_id, || {
+            env.storage().instance().set(
+                &storage::DataKeyV2::PendingFees(treasury.clone(), token_id.clone()),
+                &2_000_000i128,
+            );
+        });
+    };
+    credit_legacy_fees();
+    assert_eq!(
+        client.check_balance_integrity(&token_id),
+        (3_000_000, 1_000_000)
+    );
+
+    // Paying them out leaves the tracked gas buffer counted.
+    assert_eq!(client.withdraw_treasury(&token_id), 2_000_000);
+    assert_eq!(
+        client.check_balance_integrity(&token_id),
+        (1_000_000, 1_000_000)
+    );
+
+    credit_legacy_fees();
+    let entry = HeldEntry::PendingFees(treasury.clone(), token_id.clone());
+    assert_eq!(client.track_held_balance(&entry), 2_000_000);
+    assert_eq!(
+        client.check_balance_integrity(&token_id),
+        (3_000_000, 3_000_000)
+    );
+    assert_eq!(
+        client.try_track_held_balance(&entry),
+        Err(Ok(Error::StreamNotMigratable))
+    );
+    assert_eq!(
+        client.try_track_held_balance(&HeldEntry::BridgeDeposit(7)),
+        Err(Ok(Error::StreamNotMigratable))
+    );
+}
+
+#[test]
+fn test_recovery_cancelled_by_council_majority_or_admin() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    let destination = Address::generate(&env);
+    let (c1, c2, c3) = (
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    );
+    let (token_id, _, _) = create_token(&env, &admin);
+
+    let (_, client) = setup_v2(&env, &admin);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetRecoveryCouncil(vec![&env, c1.clone(), c2.clone(), c3.clone()], 2),
+    );
+
+    client.init_recovery(&c1, &token_id, &destination);
+    assert_eq!(
+        client.try_cancel_recovery(&outsider),
+        Err(Ok(Error::NotCouncilMember))
+    );
+    assert!(!client.cancel_recovery(&c2));
+    assert_eq!(client.get_recovery_state().unwrap().cancel_votes.len(), 1);
+    assert!(client.cancel_recovery(&c3));
+    assert_eq!(client.get_recovery_state(), None);
+
+    // A fresh recovery starts with no cancel votes; the admin aborts alone.
+    client.init_recovery(&c2, &token_id, &destination);
+    assert!(client.get_recovery_state().unwrap().cancel_votes.is_empty());
+    assert!(client.cancel_recovery(&admin));
+    assert_eq!(
+        client.try_cancel_recovery(&admin),
+        Err(Ok(Error::RecoveryNotInitiated))
+    );
+}
+
+#[test]
+fn test_recovery_counts_only_current_council() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let destination = Address::generate(&env);
+    let (c1, c2, c3, c4) = (
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+        Address::generate(&env),
+    );
+    let (token_id, token_client, asset_client) = create_token(&env, &admin);
+    let (v2_id, client) = setup_v2(&env, &admin);
+    asset_client.mint(&v2_id, &5_000);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetRecoveryCouncil(vec![&env, c1.clone(), c2.clone(), c3.clone()], 2),
+    );
+
+    client.init_recovery(&c1, &token_id, &destination);
+    assert_eq!(client.add_recovery_approval(&c2), 2);
+    assert!(!client.cancel_recovery(&c2));
+
+    // c2 leaves; its approval and cancel vote no longer count.
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetRecoveryCouncil(vec![&env, c1.clone(), c3.clone(), c4.clone()], 2),
+    );
+    assert!(!client.cancel_recovery(&c3));
+    env.ledger()
+        .with_mut(|li| li.timestamp += storage::RECOVERY_GRACE_PERIOD);
+    assert_eq!(
+        client.try_recovery_split(&c4),
+        Err(Ok(Error::NotEnoughSigners))
+    );
+    assert_eq!(client.add_recovery_approval(&c4), 2);
+    client.check_solvency(&token_id, &MAX_SOLVENCY_PAGE);
+    assert_eq!(client.recovery_split(&c4), 5_000);
+    assert_eq!(token_client.balance(&destination), 5_000);
+}
+
+// ── Keeper incentive tests ──────────────────────────────────────────────────
+
+#[test]
+fn test_keeper_refill_and_ttl_bump_bounties() {
+    let env = Env::default();
+    env.mock_all_auths();
+    // Entries must outlive the jump to the next bump window below.
+    env.ledger().with_mut(|li| {
+        li.min_persistent_entry_ttl = 2 * storage::STREAM_TTL_BUMP;
+        li.max_entry_ttl = 4 * storage::STREAM_TTL_BUMP;
+    });
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &admin);
+    asset_client.mint(&sender, &1_000_000_000);
+
+    let (v2_id, client) = setup_v2(&env, &admin);
+    whitelist(&env, &client, &admin, &token_id);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetKeeperBounty(KeeperTask::Refill, token_id.clone(), 1_000),
+    );
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetKeeperBounty(KeeperTask::BumpTtl, token_id.clone(), 500),
+    );
+
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.is_recurrent = true;
+    args.cycle_duration = 100;
+    let id = client.create_stream(&args);
+    assert_eq!(client.fund_keeper_buffer(&sender, &id, &1_200), 1_200);
+    token_client.approve(&sender, &v2_id, &100_000_000, &1_000);
+
+    assert_eq!(
+        client.try_keeper_refill(&keeper, &id),
+        Err(Ok(Error::UnauthorizedSender))
+    );
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetKeeper(keeper.clone(), true),
+    );
+    assert_eq!(
+        client.try_keeper_refill(&keeper, &id),
+        Err(Ok(Error::NotExecutionTime))
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(client.keeper_refill(&keeper, &id), 1_000);
+    assert_eq!(client.get_stream(&id).unwrap().end_time, 200);
+    assert_eq!(client.get_keeper_buffer(&id), 200);
+
+    // Bumps only pay once the previous bump could have lapsed.
+    assert_eq!(
+        client.try_keeper_bump_ttl(&keeper, &id),
+        Err(Ok(Error::NotExecutionTime))
+    );
+    env.ledger().with_mut(|li| {
+        li.sequence_number = storage::STREAM_TTL_BUMP - storage::STREAM_TTL_THRESHOLD
+    });
+    assert_eq!(client.keeper_bump_ttl(&keeper, &id), 200); // capped by the buffer
+    assert_eq!(client.get_keeper_buffer(&id), 0);
+    assert_eq!(client.get_keeper_payouts(&keeper, &token_id), 1_200);
+    assert_eq!(token_client.balance(&keeper), 1_200);
+}
+
+#[test]
+fn test_keeper_archive_refunds_unused_buffer() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &admin);
+    asset_client.mint(&sender, &100_001_000);
+
+    let (_, client) = setup_v2(&env, &admin);
+    whitelist(&env, &client, &admin, &token_id);
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetKeeperBounty(KeeperTask::Archive, token_id.clone(), 300),
+    );
+    run_op(
+        &env,
+        &client,
+        &admin,
+        Operation::SetKeeper(keeper.clone(), true),
+    );
+
+    let id = client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    client.fund_keeper_buffer(&sender, &id, &1_000);
+    // The buffer counts as committed funds.
+    assert_eq!(
+        client.check_balance_integrity(&token_id),
+        (100_001_000, 100_001_000)
+    );
+
+    assert_eq!(
+        client.try_keeper_archive(&keeper, &id),
+        Err(Ok(Error::StreamNotFullyWithdrawn))
+    );
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    client.withdraw(&id, &receiver);
+
+    assert_eq!(client.keeper_archive(&keeper, &id), 300);
+    assert_eq!(token_client.balance(&keeper), 300);
+    assert_eq!(token_client.balance(&sender), 700);
+    assert_eq!(client.get_stream(&id), None);
+    assert_eq!(client.get_keeper_buffer(&id), 0);
+}
+
+// ── Streaming swap tests ────────────────────────────────────────────────────
+
+mod mock_dex {
+    use soroban_sdk::{contract, contractimpl, symbol_short, token::TokenClient, Address, Env};
+
+    /// Constant-price AMM trading with a single `trader`. Swaps pay
+    /// `amount_in * rate / 10^7`; the spot price is set independently so
+    /// tests can simulate price impact.
+    #[contract]
+    pub struct MockDex;
+
+    #[contractimpl]
+    impl MockDex {
+        pub fn set_prices(env: Env, trader: Address, rate: i128, spot: i128) {
+            env.storage()
+                .instance()
+                .set(&symbol_short!("trader"), &trader);
+            env.storage().instance().set(&symbol_short!("rate"), &rate);
+            env.storage().instance().set(&symbol_short!("spot"), &spot);
+        }
+
+        pub fn swap(
+            env: Env,
+            token_in: Address,
+            token_out: Address,
+            amount_in: i128,
+            min_amount_out: i128,
+            _deadline: u64,
+        ) -> i128 {
+            let dex = env.current_contract_address();
+            let trader: Address = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("trader"))
+                .unwrap();
+            let amount_out =
+                Self::get_amount_out(env.clone(), token_in.clone(), token_out.clone(), amount_in);
+            assert!(amount_out >= min_amount_out, "slippage");
+            TokenClient::new(&env, &token_in).transfer_from(&dex, &trader, &dex, &amount_in);
+            TokenClient::new(&env, &token_out).transfer(&dex, &trader, &amount_out);
+            amount_out
+        }
+
+        pub fn get_amount_out(
+            env: Env,
+            _token_in: Address,
+            _token_out: Address,
+            amount_in: i128,
+        ) -> i128 {
+            let rate: i128 = env
+                .storage()
+                .instance()
+                .get(&symbol_short!("rate"))
+                .unwrap();
+            amount_in * rate / 10_000_000
+        }
+
+        pub fn get_spot_price(env: Env, _token_in: Address, _token_out: Address) -> i128 {
+            env.storage()
+                .instance()
+                .get(&symbol_short!("spot"))
+                .unwrap()
+        }
+    }
+}
+
+use mock_dex::{MockDex, MockDexClient};
+
+/// Register the mock DEX, point the contract at it and enable swaps.
+fn setup_dex<'a>(
+    env: &Env,
+    client: &ContractClient,
+    admin: &Address,
+) -> (Address, MockDexClient<'a>) {
+    let dex_id = env.register(MockDex, ());
+    run_op(env, client, admin, Operation::SetDexAddress(dex_id.clone()));
+    run_op(env, client, admin, Operation::SetSwapEnabled(true));
+    (dex_id.clone(), MockDexClient::new(env, &dex_id))
+}
+
+fn swap_stream_args(
+    sender: &Address,
+    receiver: &Address,
+    asset_in: &Address,
+    asset_out: &Address,
+    amount_in: i128,
+) -> SwapStreamArgs {
+    SwapStreamArgs {
+        sender: sender.clone(),
+        receiver: receiver.clone(),
+        amount_in,
+        asset_in: asset_in.clone(),
+        asset_out: asset_out.clone(),
+        min_amount_out: 0,
+        slippage_tolerance_bps: 100,
+        swap_deadline: 0,
+        start_time: 0,
+        end_time: 100,
+        cliff_time: 0,
+        vault_address: None,
+        yield_enabled: false,
+        yield_recipient: 0,
+        split_address: None,
+        split_bps: 0,
+        cancellation_type: 0,
+        affiliate: None,
+        swap_on_withdraw: true,
+        max_price_impact_bps: 500,
+    }
+}
+
+#[test]
+fn test_swap_on_withdraw_swaps_each_slice_at_current_price() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (asset_in, in_client, in_asset) = create_token(&env, &token_admin);
+    let (asset_out, out_client, out_asset) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &asset_in);
+    whitelist(&env, &v2_client, &admin, &asset_out);
+    let (dex_id, dex) = setup_dex(&env, &v2_client, &admin);
+    in_asset.mint(&sender, &200_000_000);
+    out_asset.mint(&dex_id, &1_000_000_000);
+
+    dex.set_prices(&v2_id, &20_000_000, &20_000_000);
+    let args = swap_stream_args(&sender, &receiver, &asset_in, &asset_out, 200_000_000);
+    let sid = v2_client.create_swap_stream(&args);
+
+    // Nothing is swapped up front: the escrow stays in asset_in.
+    assert_eq!(v2_client.get_stream(&sid).unwrap().token, asset_in);
+    assert_eq!(in_client.balance(&v2_id), 200_000_000);
+    assert_eq!(
+        v2_client.get_swap_schedule(&sid).unwrap().asset_out,
+        asset_out
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 100_000_000);
+    assert_eq!(out_client.balance(&receiver), 200_000_000);
+
+    // The second slice trades at the new price.
+    dex.set_prices(&v2_id, &15_000_000, &15_000_000);
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 100_000_000);
+    assert_eq!(out_client.balance(&receiver), 350_000_000);
+    assert_eq!(in_client.balance(&v2_id), 0);
+    assert_eq!(in_client.balance(&receiver), 0);
+}
+
+#[test]
+fn test_swap_on_withdraw_enforces_max_price_impact() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (asset_in, _, in_asset) = create_token(&env, &token_admin);
+    let (asset_out, out_client, out_asset) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &asset_in);
+    whitelist(&env, &v2_client, &admin, &asset_out);
+    let (dex_id, dex) = setup_dex(&env, &v2_client, &admin);
+    in_asset.mint(&sender, &100_000_000);
+    out_asset.mint(&dex_id, &1_000_000_000);
+
+    let mut args = swap_stream_args(&sender, &receiver, &asset_in, &asset_out, 100_000_000);
+    args.max_price_impact_bps = 10_001;
+    assert_eq!(
+        v2_client.try_create_swap_stream(&args),
+        Err(Ok(Error::InvalidPenalty))
+    );
+    args.max_price_impact_bps = 500;
+    let sid = v2_client.create_swap_stream(&args);
+    env.ledger().with_mut(|li| li.timestamp = 100);
+
+    // Quote 10% below spot: refused, the slice stays in escrow.
+    dex.set_prices(&v2_id, &18_000_000, &20_000_000);
+    assert_eq!(
+        v2_client.try_withdraw(&sid, &receiver),
+        Err(Ok(Error::SwapSlippageExceeded))
+    );
+
+    // 2.5% impact is within the 5% bound.
+    dex.set_prices(&v2_id, &19_500_000, &20_000_000);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 100_000_000);
+    assert_eq!(out_client.balance(&receiver), 195_000_000);
+}
+
+#[test]
+fn test_withdraw_as_pays_split_in_payout_asset() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let split = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (usdc_id, usdc_client, usdc_asset) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    whitelist(&env, &v2_client, &admin, &usdc_id);
+    let (dex_id, dex) = setup_dex(&env, &v2_client, &admin);
+    asset_client.mint(&sender, &100_000_000);
+    usdc_asset.mint(&dex_id, &1_000_000_000);
+    dex.set_prices(&v2_id, &20_000_000, &20_000_000);
+
+    let mut args = stream_args(&sender, &receiver, &token_id, 100_000_000);
+    args.split_address = Some(split.clone());
+    args.split_bps = 2_000;
+    let sid = v2_client.create_stream(&args);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(
+        v2_client.withdraw_as(&sid, &receiver, &usdc_id, &190_000_000),
+        200_000_000
+    );
+    assert_eq!(usdc_client.balance(&split), 40_000_000);
+    assert_eq!(usdc_client.balance(&receiver), 160_000_000);
+    assert_eq!(token_client.balance(&receiver), 0);
+    assert_eq!(
+        v2_client.get_stream(&sid).unwrap().withdrawn_amount,
+        100_000_000
+    );
+}
+
+#[test]
+fn test_withdraw_as_guards() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, _, asset_client) = create_token(&env, &token_admin);
+    let (usdc_id, usdc_client, usdc_asset) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    let (dex_id, dex) = setup_dex(&env, &v2_client, &admin);
+    asset_client.mint(&sender, &100_000_000);
+    usdc_asset.mint(&dex_id, &1_000_000_000);
+    dex.set_prices(&v2_id, &10_000_000, &10_000_000);
+    let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    env.ledger().with_mut(|li| li.timestamp = 100);
+
+    assert_eq!(
+        v2_client.try_withdraw_as(&sid, &receiver, &usdc_id, &0),
+        Err(Ok(Error::AssetNotWhitelisted))
+    );
+    whitelist(&env, &v2_client, &admin, &usdc_id);
+    assert_eq!(
+        v2_client.try_withdraw_as(&sid, &receiver, &usdc_id, &100_000_001),
+        Err(Ok(Error::SwapSlippageExceeded))
+    );
+    run_op(&env, &v2_client, &admin, Operation::SetSwapEnabled(false));
+    assert_eq!(
+        v2_client.try_withdraw_as(&sid, &receiver, &usdc_id, &0),
+        Err(Ok(Error::DexNotConfigured))
+    );
+
+    // Paying out in the stream token itself needs no swap.
+    assert_eq!(
+        v2_client.withdraw_as(&sid, &receiver, &token_id, &100_000_000),
+        100_000_000
+    );
+    assert_eq!(usdc_client.balance(&receiver), 0);
+}
+
+/// Register a mock DEX as a routing adapter serving the `token_in`/`token_out`
+/// pool at a flat `rate`, funded with `token_out`.
+fn setup_pool<'a>(
+    env: &Env,
+    client: &ContractClient,
+    admin: &Address,
+    trader: &Address,
+    token_in: &Address,
+    token_out: &Address,
+    rate: i128,
+) -> (Address, MockDexClient<'a>) {
+    let dex_id = env.register(MockDex, ());
+    let dex = MockDexClient::new(env, &dex_id);
+    dex.set_prices(trader, &rate, &rate);
+    soroban_sdk::token::StellarAssetClient::new(env, token_out).mint(&dex_id, &1_000_000_000);
+    run_op(
+        env,
+        client,
+        admin,
+        Operation::SetDexAdapter(dex_id.clone(), true),
+    );
+    run_op(
+        env,
+        client,
+        admin,
+        Operation::SetDexPool(
+            token_in.clone(),
+            token_out.clone(),
+            DexPoolInfo {
+                dex_address: dex_id.clone(),
+                token_a: Some(token_in.clone()),
+                token_b: token_out.clone(),
+                fee_bps: 30,
+            },
+        ),
+    );
+    (dex_id, dex)
+}
+
+#[test]
+fn test_dex_pair_registration_is_capped() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let dex = Address::generate(&env);
+    let (_, client) = setup_v2(&env, &admin);
+    let pool = |token_in: &Address, token_out: &Address| {
+        Operation::SetDexPool(
+            token_in.clone(),
+            token_out.clone(),
+            DexPoolInfo {
+                dex_address: dex.clone(),
+                token_a: Some(token_in.clone()),
+                token_b: token_out.clone(),
+                fee_bps: 30,
+            },
+        )
+    };
+
+    let base = Address::generate(&env);
+    let mut first = None;
+    for _ in 0..MAX_DEX_PAIRS {
+        let token = Address::generate(&env);
+        run_op(&env, &client, &admin, pool(&base, &token));
+        first.get_or_insert(token);
+    }
+    assert_eq!(client.get_dex_pairs().len(), MAX_DEX_PAIRS);
+
+    let extra = pool(&base, &Address::generate(&env));
+    assert_eq!(
+        client.try_schedule_op(&vec![&env, admin.clone()], &extra),
+        Err(Ok(Error::BatchTooLarge))
+    );
+
+    // Reconfiguring a registered pair is still allowed at the cap.
+    run_op(&env, &client, &admin, pool(&base, &first.unwrap()));
+    assert_eq!(client.get_dex_pairs().len(), MAX_DEX_PAIRS);
+}
+
+#[test]
+fn test_multi_hop_route_beats_direct_swap() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_a, a_client, a_asset) = create_token(&env, &token_admin);
+    let (token_b, b_client, _) = create_token(&env, &token_admin);
+    let (token_c, c_client, c_asset) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_a);
+    whitelist(&env, &v2_client, &admin, &token_c);
+
+    // Direct A→C through the default DEX pays 3x; A→B→C pays 2x * 2x.
+    let (direct_id, direct) = setup_dex(&env, &v2_client, &admin);
+    direct.set_prices(&v2_id, &30_000_000, &30_000_000);
+    c_asset.mint(&direct_id, &1_000_000_000);
+    let (ab_id, _) = setup_pool(
+        &env, &v2_client, &admin, &v2_id, &token_a, &token_b, 20_000_000,
+    );
+    let (bc_id, _) = setup_pool(
+        &env, &v2_client, &admin, &v2_id, &token_b, &token_c, 20_000_000,
+    );
+
+    let routes = v2_client.get_swap_routes(&10_000_000, &token_a, &token_c);
+    assert_eq!(routes.len(), 2);
+    assert_eq!(routes.get(0).unwrap().amount_out, 30_000_000);
+    let best = routes.get(1).unwrap();
+    assert_eq!(best.amount_out, 40_000_000);
+    assert_eq!(best.hops.len(), 2);
+    assert_eq!(best.hops.get(0).unwrap().dex, ab_id);
+    assert_eq!(best.hops.get(1).unwrap().dex, bc_id);
+    assert_eq!(
+        v2_client
+            .get_swap_quote(&10_000_000, &token_a, &token_c)
+            .amount_out,
+        40_000_000
+    );
+
+    a_asset.mint(&sender, &100_000_000);
+    let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_a, 100_000_000));
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    assert_eq!(
+        v2_client.withdraw_as(&sid, &receiver, &token_c, &390_000_000),
+        400_000_000
+    );
+    assert_eq!(c_client.balance(&receiver), 400_000_000);
+    assert_eq!(a_client.balance(&v2_id), 0);
+    assert_eq!(b_client.balance(&v2_id), 0);
+    assert_eq!(c_client.balance(&direct_id), 1_000_000_000);
+}
+
+#[test]
+fn test_route_registry_respects_adapters_and_removed_pools() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_a, _, _) = create_token(&env, &token_admin);
+    let (token_b, _, _) = create_token(&env, &token_admin);
+    let (token_c, _, _) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    run_op(&env, &v2_client, &admin, Operation::SetSwapEnabled(true));
+
+    let (ab_id, _) = setup_pool(
+        &env, &v2_client, &admin, &v2_id, &token_a, &token_b, 20_000_000,
+    );
+    setup_pool(
+        &env, &v2_client, &admin, &v2_id, &token_b, &token_c, 20_000_000,
+    );
+    assert_eq!(v2_client.get_dex_pairs().len(), 2);
+    assert_eq!(
+        v2_client
+            .get_swap_routes(&10_000_000, &token_a, &token_c)
+            .len(),
+        1
+    );
+    // Pools are quoted in either direction.
+    assert_eq!(
+        v2_client
+            .get_swap_quote(&10_000_000, &token_c, &token_a)
+            .amount_out,
+        40_000_000
+    );
+
+    // Disabling the A/B adapter cuts the only route.
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetDexAdapter(ab_id.clone(), false),
+    );
+    assert_eq!(v2_client.get_dex_adapters().len(), 1);
+    assert_eq!(
+        v2_client
+            .get_swap_routes(&10_000_000, &token_a, &token_c)
+            .len(),
+        0
+    );
+    assert_eq!(
+        v2_client.try_get_swap_quote(&10_000_000, &token_a, &token_c),
+        Err(Ok(Error::DexNotConfigured))
+    );
+
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::SetDexAdapter(ab_id, true),
+    );
+    run_op(
+        &env,
+        &v2_client,
+        &admin,
+        Operation::RemoveDexPool(token_b.clone(), token_c.clone()),
+    );
+    assert_eq!(v2_client.get_dex_pairs().len(), 1);
+    assert_eq!(
+        v2_client
+            .get_swap_routes(&10_000_000, &token_a, &token_c)
+            .len(),
+        0
+    );
+    assert_eq!(
+        v2_client
+            .get_swap_quote(&10_000_000, &token_a, &token_b)
+            .amount_out,
+        20_000_000
+    );
+}
+
+// ── Allowance stream tests ──────────────────────────────────────────────────
+
+fn allowance_args(
+    sender: &Address,
+    receiver: &Address,
+    token: &Address,
+    grace_period: u64,
+) -> AllowanceStreamArgs {
+    AllowanceStreamArgs {
+        sender: sender.clone(),
+        receiver: receiver.clone(),
+        token: token.clone(),
+        total_amount: 100_000_000,
+        start_time: 0,
+        cliff_time: 0,
+        end_time: 100,
+        cancellation_type: 0,
+        curve_type: CurveType::Linear,
+        accrual_clock: 0,
+        affiliate: None,
+        memo: None,
+        grace_period,
+    }
+}
+
+#[test]
+fn test_allowance_stream_pulls_accrual_on_withdraw() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &30_000_000);
+    token_client.approve(&sender, &v2_id, &100_000_000, &1_000);
+
+    let sid = v2_client.create_allowance_stream(&allowance_args(&sender, &receiver, &token_id, 10));
+    assert_eq!(token_client.balance(&v2_id), 0);
+    assert_eq!(token_client.balance(&sender), 30_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 20);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 20_000_000);
+    let health = v2_client.get_stream_health(&sid);
+    assert!(!health.escrowed);
+    assert_eq!(health.accrued, 0);
+    assert_eq!(health.available, 10_000_000);
+    assert_eq!(health.runway, 10);
+
+    // A shortfall pays what the sender covers and starts the grace period.
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 10_000_000);
+    let health = v2_client.get_stream_health(&sid);
+    assert_eq!(health.accrued, 20_000_000);
+    assert_eq!((health.delinquent_since, health.grace_ends), (50, 60));
+
+    // Topping the balance back up within the grace period clears it.
+    asset_client.mint(&sender, &40_000_000);
+    env.ledger().with_mut(|li| li.timestamp = 55);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 25_000_000);
+    assert_eq!(v2_client.get_stream_health(&sid).delinquent_since, 0);
+    assert_eq!(token_client.balance(&receiver), 55_000_000);
+
+    // Cancelling pays the beneficiary its accrual; nothing is refunded.
+    env.ledger().with_mut(|li| li.timestamp = 60);
+    v2_client.cancel(&sid, &sender);
+    assert_eq!(token_client.balance(&receiver), 60_000_000);
+    assert_eq!(token_client.balance(&sender), 10_000_000);
+    assert_eq!(token_client.balance(&v2_id), 0);
+}
+
+#[test]
+fn test_allowance_stream_defaults_after_grace_period() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &10_000_000);
+    token_client.approve(&sender, &v2_id, &100_000_000, &1_000);
+    let sid = v2_client.create_allowance_stream(&allowance_args(&sender, &receiver, &token_id, 10));
+
+    env.ledger().with_mut(|li| li.timestamp = 20);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 10_000_000);
+    assert_eq!(v2_client.check_balance_integrity(&token_id), (0, 0));
+
+    // Still within the grace period: nothing to pay, the stream stays open.
+    env.ledger().with_mut(|li| li.timestamp = 25);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 0);
+    assert!(!v2_client.get_stream(&sid).unwrap().cancelled);
+
+    env.ledger().with_mut(|li| li.timestamp = 31);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 0);
+    let stream = v2_client.get_stream(&sid).unwrap();
+    assert!(stream.cancelled);
+    assert_eq!(stream.total_amount, stream.withdrawn_amount);
+    assert_eq!(v2_client.get_stream_health(&sid).runway, 0);
+    assert_eq!(
+        v2_client.try_withdraw(&sid, &receiver),
+        Err(Ok(Error::AlreadyCancelled))
+    );
+}
+
+#[test]
+fn test_allowance_cancel_keeps_unpaid_accrual_owed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &30_000_000);
+    token_client.approve(&sender, &v2_id, &100_000_000, &1_000);
+    let sid = v2_client.create_allowance_stream(&allowance_args(&sender, &receiver, &token_id, 10));
+
+    // 50 accrued but the sender covers 30: the other 20 stays owed.
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    v2_client.cancel(&sid, &sender);
+    assert_eq!(token_client.balance(&receiver), 30_000_000);
+    let stream = v2_client.get_stream(&sid).unwrap();
+    assert!(stream.cancelled);
+    assert_eq!(
+        (stream.total_amount, stream.withdrawn_amount),
+        (50_000_000, 30_000_000)
+    );
+    let health = v2_client.get_stream_health(&sid);
+    assert_eq!(health.status, FundingStatus::Delinquent);
+    assert_eq!(health.accrued, 20_000_000);
+    assert_eq!((health.delinquent_since, health.grace_ends), (50, 60));
+
+    // It accrues no further and is collected once the sender can pay.
+    asset_client.mint(&sender, &50_000_000);
+    env.ledger().with_mut(|li| li.timestamp = 55);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 20_000_000);
+    assert_eq!(token_client.balance(&receiver), 50_000_000);
+    assert_eq!(token_client.balance(&sender), 30_000_000);
+    assert_eq!(
+        v2_client.get_stream_health(&sid).status,
+        FundingStatus::Funded
+    );
+    assert_eq!(
+        v2_client.try_withdraw(&sid, &receiver),
+        Err(Ok(Error::AlreadyCancelled))
+    );
+}
+
+// ── Solvency tests ──────────────────────────────────────────────────────────
+
+#[test]
+fn test_solvency_pass_protects_earned_balances_first() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver1 = Address::generate(&env);
+    let receiver2 = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &1_000_000_000);
+    let mut args = stream_args(&sender, &receiver1, &token_id, 500_000_000);
+    args.end_time = 1_000;
+    let sid = v2_client.create_stream(&args);
+    args.receiver = receiver2.clone();
+    v2_client.create_stream(&args);
+
+    // Half-way through, 500 is earned and 500 unvested; a clawback leaves 600.
+    env.ledger().with_mut(|li| li.timestamp = 500);
+    token_client.transfer(&v2_id, &admin, &400_000_000);
+    assert_eq!(
+        v2_client.try_settle_insolvent_stream(&sid),
+        Err(Ok(Error::StreamNotInsolvent))
+    );
+    assert_eq!(
+        v2_client.try_check_solvency(&token_id, &(MAX_SOLVENCY_PAGE + 1)),
+        Err(Ok(Error::BatchTooLarge))
+    );
+
+    assert_eq!(v2_client.check_solvency(&token_id, &1).cursor, 1);
+    assert_eq!(
+        v2_client.get_stream_health(&sid).status,
+        FundingStatus::Funded
+    );
+    let report = v2_client.check_solvency(&token_id, &1);
+    assert_eq!(report.cursor, 0);
+    assert_eq!((report.earned, report.unvested), (500_000_000, 500_000_000));
+    assert_eq!(report.balance, 600_000_000);
+    assert_eq!((report.earned_bps, report.unvested_bps), (10_000, 2_000));
+    assert_eq!(
+        v2_client.get_stream_health(&sid).status,
+        FundingStatus::Insolvent
+    );
+
+    assert_eq!(v2_client.settle_insolvent_stream(&sid), 250_000_000);
+    assert_eq!(token_client.balance(&receiver1), 250_000_000);
+    assert_eq!(token_client.balance(&sender), 50_000_000);
+    assert_eq!(token_client.balance(&v2_id), 300_000_000);
+    let stream = v2_client.get_stream(&sid).unwrap();
+    assert!(stream.cancelled);
+    assert_eq!(stream.total_amount, stream.withdrawn_amount);
+    assert_eq!(
+        v2_client.try_settle_insolvent_stream(&sid),
+        Err(Ok(Error::AlreadyCancelled))
+    );
+}
+
+#[test]
+fn test_solvency_settles_only_short_balances_from_a_recent_pass() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let treasury = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    run_op(&env, &v2_client, &admin, Operation::SetTreasury(treasury));
+    run_op(&env, &v2_client, &admin, Operation::SetFeeBps(100u32));
+    asset_client.mint(&sender, &600_000_000);
+    let vested = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+    let mut args = stream_args(&sender, &receiver, &token_id, 500_000_000);
+    args.end_time = 1_000;
+    let vesting = v2_client.create_stream(&args);
+
+    // 99 + 247.5 earned plus 6 of pending fees; 247.5 unvested.
+    env.ledger().with_mut(|li| li.timestamp = 500);
+    token_client.transfer(&v2_id, &admin, &200_000_000);
+    let report = v2_client.check_solvency(&token_id, &10);
+    assert_eq!(report.earned, 352_500_000);
+    assert_eq!((report.earned_bps, report.unvested_bps), (10_000, 1_919));
+
+    // Only the stream holding unvested balances is short.
+    assert_eq!(
+        v2_client.get_stream_health(&vested).status,
+        FundingStatus::Funded
+    );
+    assert_eq!(
+        v2_client.try_settle_insolvent_stream(&vested),
+        Err(Ok(Error::StreamNotInsolvent))
+    );
+    assert_eq!(
+        v2_client.get_stream_health(&vesting).status,
+        FundingStatus::Insolvent
+    );
+
+    // A stale pass no longer justifies settling.
+    env.ledger()
+        .with_mut(|li| li.timestamp = 500 + MAX_SOLVENCY_AGE + 1);
+    assert_eq!(
+        v2_client.try_settle_insolvent_stream(&vesting),
+        Err(Ok(Error::StreamNotInsolvent))
+    );
+}
+
+#[test]
+fn test_settle_allowance_stream_after_grace_period() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let token_admin = Address::generate(&env);
+    let (token_id, token_client, asset_client) = create_token(&env, &token_admin);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &10_000_000);
+    token_client.approve(&sender, &v2_id, &100_000_000, &1_000);
+    let sid = v2_client.create_allowance_stream(&allowance_args(&sender, &receiver, &token_id, 10));
+    assert_eq!(
+        v2_client.get_stream_health(&sid).status,
+        FundingStatus::Underfunded
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 20);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 10_000_000);
+    assert_eq!(
+        v2_client.get_stream_health(&sid).status,
+        FundingStatus::Delinquent
+    );
+    assert_eq!(
+        v2_client.try_settle_insolvent_stream(&sid),
+        Err(Ok(Error::StreamNotInsolvent))
+    );
+
+    // Past the grace period, settling pays whatever the sender still covers.
+    asset_client.mint(&sender, &3_000_000);
+    env.ledger().with_mut(|li| li.timestamp = 31);
+    assert_eq!(
+        v2_client.get_stream_health(&sid).status,
+        FundingStatus::Insolvent
+    );
+    assert_eq!(v2_client.settle_insolvent_stream(&sid), 3_000_000);
+    assert_eq!(token_client.balance(&receiver), 13_000_000);
+    let stream = v2_client.get_stream(&sid).unwrap();
+    assert!(stream.cancelled);
+    assert_eq!(stream.total_amount, 13_000_000);
+    assert_eq!(stream.withdrawn_amount, 13_000_000);
+}
+
+// ── Classic asset tests ─────────────────────────────────────────────────────
+
+#[test]
+fn test_resolve_classic_asset_to_sac() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (_, v2_client) = setup_v2(&env, &admin);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let issuer = sac.issuer().address();
+
+    let asset = ClassicAsset::Credit(String::from_str(&env, "aaa"), issuer.clone());
+    assert_eq!(v2_client.resolve_asset(&asset), sac.address());
+    assert_ne!(
+        v2_client.resolve_asset(&ClassicAsset::Native),
+        sac.address()
+    );
+    assert_eq!(
+        v2_client.try_resolve_asset(&ClassicAsset::Credit(
+            String::from_str(&env, "TOOLONGASSETX"),
+            issuer.clone()
+        )),
+        Err(Ok(Error::AssetInterfaceNotSupported))
+    );
+    assert_eq!(
+        v2_client.try_resolve_asset(&ClassicAsset::Credit(
+            String::from_str(&env, "aaa"),
+            sender.clone()
+        )),
+        Err(Ok(Error::AssetInterfaceNotSupported))
+    );
+
+    whitelist(&env, &v2_client, &admin, &sac.address());
+    soroban_sdk::token::StellarAssetClient::new(&env, &sac.address()).mint(&sender, &100_000_000);
+    let placeholder = Address::generate(&env);
+    let sid = v2_client.create_classic_stream(
+        &asset,
+        &stream_args(&sender, &receiver, &placeholder, 100_000_000),
+    );
+    assert_eq!(v2_client.get_stream(&sid).unwrap().token, sac.address());
+}
+
+#[test]
+fn test_payouts_to_receiver_that_cannot_hold_become_claimable() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let (v2_id, v2_client) = setup_v2(&env, &admin);
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    sac.issuer()
+        .set_flag(soroban_sdk::testutils::IssuerFlags::RevocableFlag);
+    let token_id = sac.address();
+    let token_client = TokenClient::new(&env, &token_id);
+    let asset_client = soroban_sdk::token::StellarAssetClient::new(&env, &token_id);
+    whitelist(&env, &v2_client, &admin, &token_id);
+    asset_client.mint(&sender, &100_000_000);
+
+    asset_client.set_authorized(&receiver, &false);
+    let sid = v2_client.create_stream(&stream_args(&sender, &receiver, &token_id, 100_000_000));
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    assert_eq!(v2_client.withdraw(&sid, &receiver), 50_000_000);
+    assert_eq!(v2_client.get_claimable(&receiver, &token_id), 50_000_000);
+    assert_eq!(token_client.balance(&receiver), 0);
+    assert_eq!(
+        v2_client.check_balance_integrity(&token_id),
+        (100_000_000, 100_000_000)
+    );
+    assert!(v2_client.try_claim_balance(&receiver, &token_id).is_err());
+
+    // Once authorized, the receiver pulls its balance and is paid directly.
+    asset_client.set_authorized(&receiver, &true);
+    assert_eq!(v2_client.claim_balance(&receiver, &token_id), 50_000_000);
+    assert_eq!(
+        v2_client.try_claim_balance(&receiver, &token_id),
+        Err(Ok(Error::NothingToWithdraw))
+    );
+    env.ledger().with_mut(|li| li.timestamp = 60);
+    v2_client.withdraw(&sid, &receiver);
+    assert_eq!(token_client.balance(&receiver), 60_000_000);
+    assert_eq!(v2_client.get_claimable(&receiver, &token_id), 0);
+    assert_eq!(token_client.balance(&v2_id), 40_000_000);
+}
@@ -2,6 +2,41 @@ use soroban_sdk::{contracttype, Address, BytesN, String, Symbol, Val, Vec};
 
 pub const MAX_MEMO_LENGTH: u32 = 32;
 
+/// Most entries a single paginated view or sweep handles per call.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Maximum number of points in a piecewise-linear unlock schedule.
+pub const MAX_CURVE_POINTS: u32 = 16;
+
+/// One point of a piecewise-linear unlock schedule.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CurvePoint {
+    /// Offset from the stream start, in the stream's accrual clock units
+    pub offset: u64,
+    /// Cumulative share unlocked at `offset`, in basis points (0–10000)
+    pub bps: u32,
+}
+
+/// Shape of a stream's unlock curve between its start and end.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum CurveType {
+    /// unlocked = total * t
+    Linear,
+    /// Back-loaded: unlocked = total * t^2
+    Exponential,
+    /// Back-loaded: unlocked = total * t^3
+    Cubic,
+    /// Front-loaded: unlocked = total * sqrt(t)
+    SquareRoot,
+    /// Front-loaded: unlocked = total * log2(1 + 15t) / 4
+    Logarithmic,
+    /// Linear interpolation between (offset, cumulative bps) points, from an
+    /// implicit (0, 0) to a final point at the stream end with 10000 bps
+    Piecewise(Vec<CurvePoint>),
+}
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub struct StreamV2 {
@@ -38,8 +73,14 @@ pub struct StreamV2 {
     pub split_address: Option<Address>,
     /// Fraction of each withdrawal routed to split_address, in basis points 0–9999 (Issue #411)
     pub split_bps: u32,
-    /// Curve type: 0 = Linear, 1 = Exponential (back-loaded, unlocked = total * (elapsed/duration)^2)
-    pub curve_type: u32,
+    /// Unlock curve applied between start and end (ignored by step escalation)
+    pub curve_type: CurveType,
+    /// Non-transferable position: the beneficiary can never be changed (V1 soulbound parity)
+    pub is_soulbound: bool,
+    /// Accrual clock: 0 = Ledger timestamp (seconds), 1 = Ledger sequence.
+    /// With the ledger-sequence clock, start/cliff/end times and step and cycle
+    /// durations are expressed in ledgers, giving drift-free per-ledger accrual.
+    pub accrual_clock: u32,
 }
 
 #[contracttype]
@@ -63,7 +104,7 @@ pub struct StreamArgs {
     pub cycle_duration: u64,
     /// 0 = Unilateral, 1 = Mutual cancellation
     pub cancellation_type: u32,
-    /// Reserved for future routing extensions; protocol fees currently go to treasury only.
+    /// Approved affiliate credited with its share of the protocol fee
     pub affiliate: Option<Address>,
     /// Optional memo for external integrations (max 32 characters)
     pub memo: Option<Symbol>,
@@ -73,8 +114,14 @@ pub struct StreamArgs {
     pub split_address: Option<Address>,
     /// Fraction of each withdrawal routed to split_address, in basis points 0–9999 (Issue #411)
     pub split_bps: u32,
-    /// Curve type: 0 = Linear, 1 = Exponential (back-loaded, unlocked = total * (elapsed/duration)^2)
-    pub curve_type: u32,
+    /// Unlock curve applied between start and end (ignored by step escalation)
+    pub curve_type: CurveType,
+    /// Non-transferable position: the beneficiary can never be changed (V1 soulbound parity)
+    pub is_soulbound: bool,
+    /// Accrual clock: 0 = Ledger timestamp (seconds), 1 = Ledger sequence.
+    /// With the ledger-sequence clock, start/cliff/end times and step and cycle
+    /// durations are expressed in ledgers, giving drift-free per-ledger accrual.
+    pub accrual_clock: u32,
 }
 
 #[cfg(any(test, feature = "testutils"))]
@@ -104,15 +151,18 @@ impl Default for StreamArgs {
             yield_recipient: 0,
             split_address: None,
             split_bps: 0,
-            curve_type: 0,
+            curve_type: CurveType::Linear,
+            is_soulbound: false,
+            accrual_clock: 0,
         }
     }
 }
 
+/// Stream creation signed off-chain by `sender` (see `Permit`).
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub struct PermitArgs {
-    pub sender_pubkey: BytesN<32>,
+    pub sender: Address,
     pub receiver: Address,
     pub token: Address,
     pub total_amount: i128,
@@ -125,6 +175,94 @@ pub struct PermitArgs {
     pub multiplier_bps: i128,
     pub vault_address: Option<Address>,
     pub yield_enabled: bool,
+    /// Approved affiliate credited with its share of the protocol fee
+    pub affiliate: Option<Address>,
+}
+
+// ----------------------------------------------------------------
+// Typed Permits
+// ----------------------------------------------------------------
+
+/// Prefix of every signed permit message, as defined by SEP-53.
+pub const PERMIT_MESSAGE_PREFIX: &[u8] = b"Stellar Signed Message:\n";
+
+/// What a permit is bound to, so it cannot be replayed on another network,
+/// contract or entry point.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermitDomain {
+    /// SHA-256 of the network passphrase
+    pub network_id: BytesN<32>,
+    pub contract: Address,
+    /// Name of the entry point the permit authorizes
+    pub function: Symbol,
+    /// The permit's deadline, in the unit of the entry point's own deadline
+    /// (a ledger timestamp, or a ledger sequence for `create_via_signature`)
+    pub expiry: u64,
+}
+
+/// A typed permit. The signed message is `PERMIT_MESSAGE_PREFIX` followed by
+/// the XDR of this struct, and the signature covers its SHA-256 digest.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Permit {
+    pub domain: PermitDomain,
+    pub signer: Address,
+    /// The signer's current permit nonce
+    pub nonce: u64,
+    /// SHA-256 of the XDR of the entry point's typed arguments
+    pub args_hash: BytesN<32>,
+}
+
+/// How the signer of a permit authorizes it.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum PermitSignature {
+    /// (public key, signature) by a `G...` account's ed25519 key; the key
+    /// must be the signer's own
+    Ed25519(BytesN<32>, BytesN<64>),
+    /// The signer's own authorization over the permit digest, so smart
+    /// wallets apply their `__check_auth` policy
+    Auth,
+}
+
+/// The arguments of `withdraw_meta` covered by its permit.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetaWithdrawal {
+    pub stream_id: u64,
+    pub withdrawal_amount: i128,
+    pub relayer_fee: i128,
+    pub relayer: Address,
+}
+
+/// Cap on a `execute_meta` relayer fee, in basis points of the action's value.
+pub const MAX_META_FEE_BPS: u32 = 500;
+
+/// A stream action a relayer can submit on the signer's behalf.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum MetaAction {
+    /// `cancel` by the sender or beneficiary (unilateral streams only)
+    Cancel,
+    /// `accept_rate` by the receiver
+    AcceptRate,
+    /// `transfer_beneficiary` by the beneficiary
+    TransferBeneficiary(Address),
+    /// `split_stream` by the beneficiary: (split_address, split_bps)
+    SetSplit(Option<Address>, u32),
+    /// `top_up` by the sender, funded from its allowance to the contract
+    TopUp(i128),
+}
+
+/// The arguments of `execute_meta` covered by its permit.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetaIntent {
+    pub stream_id: u64,
+    pub action: MetaAction,
+    pub relayer: Address,
+    pub relayer_fee: i128,
 }
 
 // ----------------------------------------------------------------
@@ -137,8 +275,8 @@ pub struct PermitArgs {
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub struct StreamParams {
-    /// The sender's Ed25519 public key (32 bytes).
-    pub sender_pubkey: BytesN<32>,
+    /// The signing sender; its nonce is consumed (see `Permit`).
+    pub sender: Address,
     /// The intended receiver of the stream.
     pub receiver: Address,
     /// The token to stream.
@@ -151,7 +289,7 @@ pub struct StreamParams {
     pub cliff_time: u64,
     /// Stream end time (Unix timestamp).
     pub end_time: u64,
-    /// Replay-protection nonce (must match the stored nonce for sender_pubkey).
+    /// Replay-protection nonce (must match the sender's permit nonce).
     pub nonce: u64,
     /// Ledger number after which this signed intent is no longer valid.
     pub expiration_ledger: u32,
@@ -163,6 +301,8 @@ pub struct StreamParams {
     pub vault_address: Option<Address>,
     /// Whether to enable yield on the stream.
     pub yield_enabled: bool,
+    /// Approved affiliate credited with its share of the protocol fee.
+    pub affiliate: Option<Address>,
 }
 
 /// Event emitted when a stream is created via an off-chain signed intent.
@@ -170,7 +310,6 @@ pub struct StreamParams {
 #[derive(Clone, Debug)]
 pub struct SignatureStreamCreatedEvent {
     pub stream_id: u64,
-    pub sender_pubkey: BytesN<32>,
     pub receiver: Address,
     pub token: Address,
     pub total_amount: i128,
@@ -258,7 +397,6 @@ pub struct BatchStreamsCreatedEvent {
 #[derive(Clone, Debug)]
 pub struct PermitPayload {
     pub contract: Address,
-    pub sender_pubkey: soroban_sdk::BytesN<32>,
     pub receiver: Address,
     pub token: Address,
     pub total_amount: i128,
@@ -275,7 +413,6 @@ pub struct PermitPayload {
 #[derive(Clone, Debug)]
 pub struct PermitStreamCreatedEvent {
     pub stream_id: u64,
-    pub sender_pubkey: soroban_sdk::BytesN<32>,
     pub receiver: Address,
     pub token: Address,
     pub total_amount: i128,
@@ -413,6 +550,59 @@ pub enum Operation {
     SetAdmins(Vec<Address>, u32),
     TransferAdmin(Address),
     SetMinValue(Address, i128),
+    /// (asset, max_stream_amount, max_flow_rate)
+    SetTokenLimits(Address, i128, i128),
+    SetFeeBps(u32),
+    SetFeeTiers(Vec<crate::FeeTier>),
+    SetTreasury(Address),
+    SetComplianceOracle(Address),
+    SetOracleAddress(Address),
+    SetDexAddress(Address),
+    /// (token_in, token_out, pool)
+    SetDexPool(Address, Address, DexPoolInfo),
+    AddToWhitelist(Address),
+    SetAffiliateShareBps(u32),
+    SetDaoToken(Address),
+    SetVotingThreshold(i128),
+    SetFeeCollector(Address),
+    SetFeeToken(Address),
+    SetFeePerRecipient(i128),
+    /// (council, threshold)
+    SetRecoveryCouncil(Vec<Address>, u32),
+    /// Address allowed to pause without the timelock (None = no guardian)
+    SetGuardian(Option<Address>),
+    /// (bridge, allowed) — bridges permitted to call `on_token_receive`
+    SetBridgeAllowed(Address, bool),
+    SetGovernanceConfig(GovernanceConfig),
+    /// Escrowed DAO weight needed to veto a treasury split (0 = council only)
+    SetVetoThreshold(i128),
+    /// (task, token, bounty) — paid from the stream's keeper buffer
+    SetKeeperBounty(KeeperTask, Address, i128),
+    /// Treasury fee distribution table (empty = everything to the treasury)
+    SetFeeDistribution(Vec<FeeShare>),
+    /// (adapter, enabled) — DEX contracts whose pools may be routed through
+    SetDexAdapter(Address, bool),
+    /// (token_a, token_b) — drop a pool from the routing registry
+    RemoveDexPool(Address, Address),
+    /// (vault, config) — allow streams to deposit into a vault
+    SetVault(Address, VaultConfig),
+    /// Stop new deposits into a vault; open positions can still be redeemed
+    RemoveVault(Address),
+    /// Enable or disable swap streaming globally
+    SetSwapEnabled(bool),
+    /// (affiliate, approved) — registers the affiliate if needed; fees already
+    /// credited to a revoked affiliate remain withdrawable
+    SetAffiliateApproved(Address, bool),
+    /// (keeper, approved) — keepers allowed to claim stream bounties
+    SetKeeper(Address, bool),
+}
+
+/// An operation waiting in the timelock.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledOperation {
+    pub op: Operation,
+    pub execution_time: u64,
 }
 
 #[contracttype]
@@ -604,6 +794,27 @@ pub struct SwapStreamArgs {
     pub split_bps: u32,
     /// 0 = Unilateral, 1 = Mutual cancellation
     pub cancellation_type: u32,
+    /// Approved affiliate credited with its share of the protocol fee
+    pub affiliate: Option<Address>,
+    /// Keep the escrow in `asset_in` and swap each withdrawn slice at the
+    /// prevailing price instead of swapping everything up front. In this
+    /// mode `min_amount_out` and `swap_deadline` are not used.
+    pub swap_on_withdraw: bool,
+    /// Per-slice cap on price impact against the DEX spot price, in basis
+    /// points (only used with `swap_on_withdraw`)
+    pub max_price_impact_bps: u32,
+}
+
+/// Per-withdrawal swap settings of a `swap_on_withdraw` stream.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapSchedule {
+    /// Asset each unlocked slice is swapped into before payout
+    pub asset_out: Address,
+    /// Allowed shortfall against the live quote, in basis points
+    pub slippage_tolerance_bps: u32,
+    /// Allowed price impact against the spot price, in basis points
+    pub max_price_impact_bps: u32,
 }
 
 /// Result of a swap operation returned to the caller
@@ -647,6 +858,69 @@ pub struct DexPoolInfo {
     pub fee_bps: u32,
 }
 
+/// Maximum number of pools a swap route may pass through.
+pub const MAX_ROUTE_HOPS: u32 = 3;
+
+/// Maximum number of pool pairs registered for route discovery.
+pub const MAX_DEX_PAIRS: u32 = 32;
+
+/// Maximum number of pools route discovery follows out of one token.
+pub const MAX_ROUTE_BRANCHES: u32 = 8;
+
+/// Maximum number of candidate routes quoted per swap.
+pub const MAX_SWAP_ROUTES: u32 = 16;
+
+/// One swap of a route, executed against `dex`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteHop {
+    pub dex: Address,
+    pub token_in: Address,
+    pub token_out: Address,
+}
+
+/// A candidate swap route and its quoted end-to-end output.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapRoute {
+    pub hops: Vec<RouteHop>,
+    pub amount_out: i128,
+}
+
+/// Per-token stream caps, in the token's own base units.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenLimits {
+    /// Maximum `total_amount` of a single stream
+    pub max_stream_amount: i128,
+    /// Maximum flow rate (amount per second)
+    pub max_flow_rate: i128,
+}
+
+/// Reusable stream preset. The skeleton's `cliff_time` and `end_time` are
+/// read as offsets from its `start_time`; `sender`, `receiver` and
+/// `total_amount` are supplied when a stream is created from the template.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamTemplate {
+    pub id: u64,
+    /// Account allowed to publish new versions
+    pub owner: Address,
+    pub name: Symbol,
+    /// Starts at 1 and increases by one on every update
+    pub version: u32,
+    pub skeleton: StreamArgs,
+    pub updated_at: u64,
+}
+
+/// Template and version a stream was created from.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamTemplateRef {
+    pub template_id: u64,
+    pub version: u32,
+}
+
 // ----------------------------------------------------------------
 // Issue #377 — Push-Pull Rate Re-balancing
 // ----------------------------------------------------------------
@@ -668,6 +942,34 @@ pub struct PendingRateUpdate {
     pub original_total_amount: i128,
 }
 
+/// Sender-proposed changes to a live stream. `None` leaves a field untouched.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamAmendment {
+    /// Reduced total; the unvested difference is refunded to the sender
+    pub new_total_amount: Option<i128>,
+    /// New end time (may extend or shorten the stream)
+    pub new_end_time: Option<u64>,
+    /// New cliff time
+    pub new_cliff_time: Option<u64>,
+    /// New early-cancellation penalty in basis points (0–10000)
+    pub new_penalty_bps: Option<u32>,
+    /// New cancellation type: 0 = Unilateral, 1 = Mutual
+    pub new_cancellation_type: Option<u32>,
+}
+
+/// An amendment awaiting the receiver's acceptance.
+/// Expires after the same 7-day TTL as `PendingRateUpdate`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingAmendment {
+    pub amendment: StreamAmendment,
+    /// Unix timestamp when the proposal was created
+    pub proposed_at: u64,
+    /// Address that proposed the change (always the sender)
+    pub proposed_by: Address,
+}
+
 /// Event emitted when a rate update is proposed
 #[contracttype]
 #[derive(Clone, Debug)]
@@ -760,18 +1062,390 @@ pub struct SimulationCheck {
     pub error_message: String,
 }
 
-/// Estimated ledger footprint for a stream
+/// Estimated ledger footprint and rent of an invocation
 #[contracttype]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct LedgerFootprint {
-    /// Instance storage bytes
+    /// XDR bytes of the instance-storage entries the call writes
     pub instance_bytes: u32,
-    /// Persistent storage bytes
+    /// XDR bytes of the persistent entries the call writes
     pub persistent_bytes: u32,
-    /// Estimated read operations
+    /// Ledger entries in the read-only footprint (contract instances, oracles)
     pub estimated_reads: u32,
-    /// Estimated write operations
+    /// Ledger entries in the read-write footprint
     pub estimated_writes: u32,
-    /// Estimated emit events size
+    /// XDR bytes of the emitted events
     pub event_bytes: u32,
+    /// Ledgers of rent prepaid for the persistent entries (the stream TTL bump)
+    pub rent_ledgers: u32,
+    /// Rent for `persistent_bytes` over `rent_ledgers`, in stroops
+    pub rent_fee: i128,
+}
+
+// ----------------------------------------------------------------
+// Issue #407 — Bridge-In Receiver Hook
+// ----------------------------------------------------------------
+
+/// Version byte expected at the start of bridge instruction metadata
+pub const BRIDGE_METADATA_VERSION: u8 = 1;
+
+/// Outcome of a bridge-in delivery: either a stream was opened or the
+/// funds are held as a refundable deposit.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BridgeReceipt {
+    /// Stream created from valid instruction metadata
+    pub stream_id: Option<u64>,
+    /// Deposit held because the metadata could not be used
+    pub deposit_id: Option<u64>,
+}
+
+/// Bridged funds that arrived with unusable instruction metadata.
+/// The delivering bridge can refund them via `refund_bridge_deposit`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BridgeDeposit {
+    pub bridge: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub received_at: u64,
+}
+
+// ----------------------------------------------------------------
+// Treasury Governance Proposals
+// ----------------------------------------------------------------
+
+/// Default length of the voting period for treasury proposals (3 days).
+pub const DEFAULT_VOTING_PERIOD: u64 = 259_200;
+
+/// Quorum and voting period for treasury proposals.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GovernanceConfig {
+    /// Minimum total (for + against + abstain) voting power for a proposal to pass
+    pub quorum: i128,
+    /// Seconds a proposal stays open for voting
+    pub voting_period: u64,
+}
+
+/// DAO tokens escrowed by a holder to gain voting power.
+///
+/// Power only counts on proposals created after `locked_at`, so tokens
+/// borrowed within a single transaction can never vote.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoterLock {
+    pub amount: i128,
+    pub locked_at: u64,
+    /// Tokens stay locked until the last proposal voted on has closed
+    pub unlock_after: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VoteChoice {
+    For,
+    Against,
+    Abstain,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProposalStatus {
+    /// Voting is open (or closed but not yet finalized)
+    Active,
+    /// Passed and queued as a `PendingTreasurySplit` behind the veto window
+    Queued,
+    /// Failed quorum or majority
+    Defeated,
+}
+
+/// A community proposal to split treasury funds.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreasuryProposal {
+    pub id: u64,
+    pub proposer: Address,
+    pub token: Address,
+    pub recipients: Vec<Address>,
+    pub amounts: Vec<i128>,
+    /// Voting power snapshot point: only locks older than this count
+    pub created_at: u64,
+    pub voting_ends: u64,
+    /// Quorum in force when the proposal was created
+    pub quorum: i128,
+    pub for_votes: i128,
+    pub against_votes: i128,
+    pub abstain_votes: i128,
+    pub status: ProposalStatus,
+    /// Treasury split created once the proposal is queued
+    pub split_id: Option<u64>,
+}
+
+/// Opposition gathered against a pending treasury split.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitVetoTally {
+    /// Escrowed DAO weight of token-holder vetoes
+    pub weight: i128,
+    /// Recovery council members that vetoed
+    pub council_votes: u32,
+}
+
+/// A treasury split that has neither executed nor been vetoed.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingSplitInfo {
+    pub split_id: u64,
+    pub split: crate::storage::PendingTreasurySplit,
+    pub veto: SplitVetoTally,
+}
+
+// ----------------------------------------------------------------
+// Emergency Recovery
+// ----------------------------------------------------------------
+
+/// The in-flight emergency recovery, as returned by `get_recovery_state`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecoveryState {
+    pub token: Address,
+    pub destination: Address,
+    pub initiated_at: u64,
+    /// Earliest time `recovery_split` can run
+    pub executable_at: u64,
+    pub approvals: Vec<Address>,
+    pub cancel_votes: Vec<Address>,
+}
+
+// ----------------------------------------------------------------
+// Keeper Incentives
+// ----------------------------------------------------------------
+
+/// Maintenance a registered keeper can be paid for.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeeperTask {
+    /// `keeper_refill`: renew a recurrent stream's next cycle
+    Refill,
+    /// `keeper_bump_ttl`: extend a stream's storage TTL
+    BumpTtl,
+    /// `keeper_archive`: archive a fully withdrawn stream
+    Archive,
+}
+
+// ----------------------------------------------------------------
+// Treasury Fee Distribution
+// ----------------------------------------------------------------
+
+/// Maximum number of destinations in the fee distribution table.
+pub const MAX_FEE_SHARES: u32 = 8;
+
+/// One destination of the treasury fee distribution table.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeShare {
+    pub recipient: Address,
+    /// Share of each withdrawal in basis points; the table sums to 10000
+    pub bps: u32,
+    /// 0 = transferred; otherwise paid as a new stream over this many seconds
+    pub stream_duration: u64,
+}
+
+// ----------------------------------------------------------------
+// Yield Vaults
+// ----------------------------------------------------------------
+
+/// Interface a yield vault speaks.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VaultKind {
+    /// `VaultTrait`: principal is tracked 1:1 and interest is quoted separately
+    Principal,
+    /// `ShareVaultTrait`: deposits mint shares redeemed at the vault's rate
+    Shares,
+}
+
+/// Allowlist entry of a vault streams may deposit into.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VaultConfig {
+    pub kind: VaultKind,
+    /// Maximum principal held in the vault across all streams (0 = uncapped)
+    pub deposit_cap: i128,
+}
+
+/// A stream's holding in its vault.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VaultPosition {
+    pub kind: VaultKind,
+    /// Shares owned on behalf of the stream (equal to `principal` for
+    /// `Principal` vaults)
+    pub shares: i128,
+    /// Stream principal still held in the vault
+    pub principal: i128,
+}
+
+/// Outcome of `emergency_exit_vault`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VaultExitReport {
+    /// Streams moved out of the vault
+    pub exited: u32,
+    /// Streams whose redemption failed; they stay in the vault
+    pub failed: u32,
+    /// Principal the exited streams held in the vault
+    pub principal: i128,
+    /// Assets the vault returned for them
+    pub recovered: i128,
+    /// Principal the vault could not return, written off the affected streams
+    pub shortfall: i128,
+    /// Index in the vault's stream list the next page starts at
+    pub cursor: u32,
+    /// Streams still listed from `cursor` on
+    pub remaining: u32,
+}
+
+// ----------------------------------------------------------------
+// Allowance Streams
+// ----------------------------------------------------------------
+
+/// Creation arguments for a pay-as-you-go stream that pulls each
+/// withdrawal from the sender's allowance instead of escrowing it.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllowanceStreamArgs {
+    pub sender: Address,
+    pub receiver: Address,
+    pub token: Address,
+    /// Most the stream can ever pull; it accrues towards this over the schedule
+    pub total_amount: i128,
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    /// 0 = Unilateral, 1 = Mutual cancellation
+    pub cancellation_type: u32,
+    pub curve_type: CurveType,
+    /// Accrual clock: 0 = Ledger timestamp (seconds), 1 = Ledger sequence
+    pub accrual_clock: u32,
+    /// Approved affiliate credited with its share of the protocol fee on each pull
+    pub affiliate: Option<Address>,
+    pub memo: Option<Symbol>,
+    /// Seconds the sender may stay unable to cover a withdrawal before the stream defaults
+    pub grace_period: u64,
+}
+
+/// How an allowance stream is funded.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllowanceFunding {
+    pub grace_period: u64,
+    /// When a withdrawal first found the sender short; 0 while it is solvent
+    pub delinquent_since: u64,
+    pub affiliate: Option<Address>,
+}
+
+/// Whether a stream can pay what it owes.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FundingStatus {
+    /// Everything still owed over the stream's life is covered
+    Funded,
+    /// The accrual so far is covered, but not the rest of the schedule
+    Underfunded,
+    /// The accrual so far is not covered; an allowance stream is in (or
+    /// about to enter) its grace period
+    Delinquent,
+    /// Settleable with `settle_insolvent_stream`: an allowance stream past
+    /// its grace period, or an escrowed stream holding balances its token's
+    /// recent solvency pass found short
+    Insolvent,
+}
+
+/// Funding view of a stream (see `get_stream_health`).
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamHealth {
+    pub status: FundingStatus,
+    /// False for allowance streams
+    pub escrowed: bool,
+    /// Unlocked but not yet withdrawn
+    pub accrued: i128,
+    /// What can still be paid: the remaining escrow, or the lesser of the
+    /// sender's allowance and balance
+    pub available: i128,
+    /// Seconds until accrual outruns `available`, or until the stream ends
+    /// if it never does
+    pub runway: u64,
+    pub delinquent_since: u64,
+    /// When a delinquent allowance stream defaults; 0 while it is solvent
+    pub grace_ends: u64,
+}
+
+/// Most streams a single `check_solvency` call scans.
+pub const MAX_SOLVENCY_PAGE: u32 = 50;
+
+/// How long, in seconds, a completed solvency pass can justify settling
+/// escrowed streams.
+pub const MAX_SOLVENCY_AGE: u64 = 3_600;
+
+/// A token's escrow coverage, tallied over the stream book by
+/// `check_solvency` a page at a time.
+///
+/// Shortfalls are borne by unvested balances first: earned balances are
+/// covered in full as long as the balance allows.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SolvencyReport {
+    /// Next stream id the running pass scans; 0 when no pass is running
+    pub cursor: u64,
+    /// Unlocked but unwithdrawn escrow, plus prepaid keeper bounties and,
+    /// once complete, balances held for others (running tally while a pass
+    /// runs)
+    pub earned: i128,
+    /// Escrow not yet unlocked (running tally while a pass runs)
+    pub unvested: i128,
+    /// The contract's balance of the token when the last pass completed
+    pub balance: i128,
+    /// Share of earned balances the balance covered, in basis points
+    pub earned_bps: u32,
+    /// Share of unvested balances covered after earned ones, in basis points
+    pub unvested_bps: u32,
+    /// Streams the last completed pass covered (ids below this)
+    pub streams: u64,
+    /// When the last pass completed; 0 if none has
+    pub checked_at: u64,
+}
+
+/// A balance the contract holds for someone outside stream escrow, counted
+/// in the token's held balance (see `track_held_balance`).
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum HeldEntry {
+    /// Pending treasury fees: (recipient, token)
+    PendingFees(Address, Address),
+    /// Fees credited to an affiliate: (affiliate, token)
+    AffiliateFees(Address, Address),
+    /// A refundable bridge deposit
+    BridgeDeposit(u64),
+    /// DAO tokens locked by a voter
+    VoterLock(Address),
+    /// A sender's gas buffer: (sender, token)
+    GasBuffer(Address, Address),
+}
+
+// ----------------------------------------------------------------
+// Classic Assets
+// ----------------------------------------------------------------
+
+/// A Stellar classic asset, resolved on-chain to its Stellar Asset Contract
+/// (see `resolve_asset`).
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClassicAsset {
+    /// Native XLM
+    Native,
+    /// Asset code (1–12 characters) and issuing account
+    Credit(String, Address),
 }
@@ -313,7 +313,9 @@ fn stream_args(
         yield_recipient: 0,
         split_address: None,
         split_bps: 0,
-        curve_type: 0,
+        curve_type: CurveType::Linear,
+        is_soulbound: false,
+        accrual_clock: 0,
     }
 }
 
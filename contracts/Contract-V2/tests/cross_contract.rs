@@ -1,6 +1,9 @@
+use soroban_sdk::auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation};
+use soroban_sdk::testutils::{Address as _, Ledger};
 use soroban_sdk::token::{StellarAssetClient, TokenClient};
-use soroban_sdk::{contract, contractimpl, symbol_short, testutils::Address as _, Address, Env};
-use stellarstream_contracts_v2::types::StreamArgs;
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::{contract, contractimpl, symbol_short, vec, Address, Env, IntoVal};
+use stellarstream_contracts_v2::types::{CurveType, Operation, StreamArgs, VaultConfig, VaultKind};
 use stellarstream_contracts_v2::{Contract, ContractClient};
 
 // Mock Bridge contract
@@ -13,12 +16,25 @@ impl MockBridge {
     pub fn simulate_bridge_in(
         env: Env,
         nebula_id: Address,
-        from: Address,
+        token: Address,
         amount: i128,
         metadata: soroban_sdk::Bytes,
     ) {
+        // Nebula pulls the funds, so pre-authorize the nested token transfer.
+        env.authorize_as_current_contract(vec![
+            &env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: token.clone(),
+                    fn_name: symbol_short!("transfer"),
+                    args: (env.current_contract_address(), nebula_id.clone(), amount)
+                        .into_val(&env),
+                },
+                sub_invocations: vec![&env],
+            }),
+        ]);
         let nebula = ContractClient::new(&env, &nebula_id);
-        nebula.on_token_receive(&from, &amount, &metadata);
+        nebula.on_token_receive(&env.current_contract_address(), &token, &amount, &metadata);
     }
 }
 
@@ -55,6 +71,16 @@ fn create_token<'a>(
     )
 }
 
+/// Schedule `op`, wait out the 48h admin timelock and execute it, then restore
+/// the ledger clock.
+fn run_op(env: &Env, client: &ContractClient, admin: &Address, op: Operation) {
+    client.schedule_op(&vec![env, admin.clone()], &op);
+    let now = env.ledger().timestamp();
+    env.ledger().with_mut(|li| li.timestamp = now + 172_800);
+    client.execute_op(&op);
+    env.ledger().with_mut(|li| li.timestamp = now);
+}
+
 #[test]
 fn test_deep_space_cross_contract_flow() {
     let env = Env::default();
@@ -73,37 +99,63 @@ fn test_deep_space_cross_contract_flow() {
     let nebula_id = env.register(Contract, ());
     let nebula_client = ContractClient::new(&env, &nebula_id);
     nebula_client.init(&admin);
-    nebula_client.add_to_whitelist(&token_id);
-    nebula_client.add_to_whitelist(&sender);
+    run_op(
+        &env,
+        &nebula_client,
+        &admin,
+        Operation::AddToWhitelist(token_id.clone()),
+    );
 
     // Deploy mocks
     let bridge_id = env.register(MockBridge, ());
     let vault_id = env.register(MockVault, ());
+    asset_client.mint(&bridge_id, &100_000_000);
+    run_op(
+        &env,
+        &nebula_client,
+        &admin,
+        Operation::SetBridgeAllowed(bridge_id.clone(), true),
+    );
+    run_op(
+        &env,
+        &nebula_client,
+        &admin,
+        Operation::SetVault(
+            vault_id.clone(),
+            VaultConfig {
+                kind: VaultKind::Principal,
+                deposit_cap: 0,
+            },
+        ),
+    );
 
     // 1. Bridge In (calls on_token_receive -> create_stream)
-    let addr_str = receiver.to_string();
-    let mut buf = [0u8; 56];
-    addr_str.copy_into_slice(&mut buf);
-    let mut metadata = soroban_sdk::Bytes::from_slice(&env, &buf);
-    let duration: u64 = 100;
-    metadata.append(&soroban_sdk::Bytes::from_slice(
-        &env,
-        &duration.to_be_bytes(),
-    ));
+    let receiver_xdr = receiver.clone().to_xdr(&env);
+    let mut metadata = soroban_sdk::Bytes::from_slice(&env, &[1]);
+    metadata.extend_from_slice(&(receiver_xdr.len() as u16).to_be_bytes());
+    metadata.append(&receiver_xdr);
+    metadata.extend_from_slice(&100u64.to_be_bytes()); // duration
+    metadata.extend_from_slice(&0u64.to_be_bytes()); // cliff offset
+    metadata.extend_from_slice(&[0, 0]); // linear curve, no memo
     MockBridgeClient::new(&env, &bridge_id).simulate_bridge_in(
         &nebula_id,
-        &sender,
+        &token_id,
         &100_000_000,
         &metadata,
     );
 
     // Verify stream created (ID 0)
     let stream = nebula_client.get_stream(&0u64).unwrap();
-    assert_eq!(stream.sender, sender);
+    assert_eq!(stream.sender, bridge_id);
     assert_eq!(stream.receiver, receiver);
     assert_eq!(stream.total_amount, 100_000_000); // after fees
 
-    nebula_client.set_min_value(&token_id, &0);
+    run_op(
+        &env,
+        &nebula_client,
+        &admin,
+        Operation::SetMinValue(token_id.clone(), 0),
+    );
 
     // 2. Deposit stream to Vault (vault_address in stream)
     let stream_args = StreamArgs {
@@ -127,7 +179,9 @@ fn test_deep_space_cross_contract_flow() {
         yield_recipient: 0,
         split_address: None,
         split_bps: 0,
-        curve_type: 0,
+        curve_type: CurveType::Linear,
+        is_soulbound: false,
+        accrual_clock: 0,
     };
 
     // Update stream with vault
@@ -140,7 +194,7 @@ fn test_deep_space_cross_contract_flow() {
 
     // 3. Verify all 3 contracts in sync
     assert!(nebula_client.get_stream(&stream_id).is_some());
-    assert_eq!(token_client.balance(&nebula_id), 50_000_000); // held in Nebula
+    assert_eq!(token_client.balance(&nebula_id), 150_000_000); // bridged + held in Nebula
     assert_eq!(vault_client.balance(), 50_000_000); // deposited
 
     println!("✅ Deep Space cross-contract flow: Bridge -> Nebula -> Vault SUCCESS");